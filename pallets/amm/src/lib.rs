@@ -8,7 +8,11 @@ use frame_support::{
 	decl_error, decl_event, decl_module, decl_storage, dispatch, dispatch::DispatchResult, ensure, traits::Get,
 };
 use frame_system::{self as system, ensure_signed};
-use primitives::{fee, traits::AMM, AssetId, Balance, Price, MAX_IN_RATIO, MAX_OUT_RATIO};
+use primitives::{
+	fee,
+	traits::{IntentionPurger, AMM},
+	AssetId, Balance, Price, MAX_IN_RATIO, MAX_OUT_RATIO,
+};
 use sp_std::{marker::PhantomData, vec, vec::Vec};
 
 use frame_support::sp_runtime::app_crypto::sp_core::crypto::UncheckedFrom;
@@ -40,6 +44,10 @@ pub trait Config: frame_system::Config + pallet_asset_registry::Config {
 
 	/// Trading fee rate
 	type GetExchangeFee: Get<fee::Fee>;
+
+	/// Notified when a pool is destroyed by `remove_liquidity`, so it can clean up anything it
+	/// was holding against that pair. Deployments which don't need this can configure `()`.
+	type IntentionHandler: IntentionPurger<AssetId>;
 }
 
 pub trait AssetPairAccountIdFor<AssetId: Sized, AccountId: Sized> {
@@ -188,64 +196,7 @@ decl_module! {
 		) -> dispatch::DispatchResult {
 			let who = ensure_signed(origin)?;
 
-			ensure!(
-				!amount.is_zero(),
-				Error::<T>::CannotCreatePoolWithZeroLiquidity
-			);
-			ensure!(
-				!initial_price.is_zero(),
-				Error::<T>::CannotCreatePoolWithZeroInitialPrice
-			);
-
-			ensure!(
-				asset_a != asset_b,
-				Error::<T>::CannotCreatePoolWithSameAssets
-			);
-
-			ensure!(
-				!Self::exists(asset_a, asset_b),
-				Error::<T>::TokenPoolAlreadyExists
-			);
-
-			let asset_b_amount = initial_price.checked_mul_int(amount).ok_or(Error::<T>::CreatePoolAssetAmountInvalid)?;
-			let shares_added = if asset_a < asset_b { amount } else { asset_b_amount };
-
-			ensure!(
-				T::Currency::free_balance(asset_a, &who) >= amount,
-				Error::<T>::InsufficientAssetBalance
-			);
-
-			ensure!(
-				T::Currency::free_balance(asset_b, &who) >= asset_b_amount,
-				Error::<T>::InsufficientAssetBalance
-			);
-
-			// Create pool only if amounts don't overflow
-			let pair_account = Self::get_pair_id(&asset_a, &asset_b);
-
-			let share_token = match Self::exists(asset_a, asset_b) {
-				true => Self::share_token(&pair_account),
-				false => {
-					let token_name = Self::get_token_name(asset_a, asset_b);
-
-					let share_token = <pallet_asset_registry::Module<T>>::create_asset(token_name)?.into();
-
-					<ShareToken<T>>::insert(&pair_account, &share_token);
-					<PoolAssets<T>>::insert(&pair_account, (asset_a, asset_b));
-					share_token
-				}
-			};
-
-			T::Currency::transfer(asset_a, &who, &pair_account, amount)?;
-			T::Currency::transfer(asset_b, &who, &pair_account, asset_b_amount)?;
-
-			T::Currency::deposit(share_token, &who, shares_added)?;
-
-			<TotalLiquidity<T>>::insert(&pair_account, shares_added);
-
-			Self::deposit_event(RawEvent::CreatePool(who, asset_a, asset_b, shares_added));
-
-			Ok(())
+			Self::create_pool_for(who, asset_a, asset_b, amount, initial_price)
 		}
 
 		#[weight =  <T as Config>::WeightInfo::add_liquidity()]
@@ -403,6 +354,8 @@ decl_module! {
 				<ShareToken<T>>::remove(&pair_account);
 				<PoolAssets<T>>::remove(&pair_account);
 
+				T::IntentionHandler::purge_pair_intentions(asset_a, asset_b);
+
 				Self::deposit_event(RawEvent::PoolDestroyed(who, asset_a, asset_b));
 			}
 
@@ -490,6 +443,70 @@ impl<T: Config> Module<T> {
 		Some(balances)
 	}
 
+	/// Shared body of `create_pool` and the `AMMTrader` impl below - create a pool for
+	/// `asset_a`/`asset_b`, seeded with `amount` of `asset_a` and whatever `initial_price` implies
+	/// of `asset_b`, both debited from `who`, which receives the newly minted share token in
+	/// return.
+	pub fn create_pool_for(
+		who: T::AccountId,
+		asset_a: AssetId,
+		asset_b: AssetId,
+		amount: Balance,
+		initial_price: Price,
+	) -> DispatchResult {
+		ensure!(!amount.is_zero(), Error::<T>::CannotCreatePoolWithZeroLiquidity);
+		ensure!(
+			!initial_price.is_zero(),
+			Error::<T>::CannotCreatePoolWithZeroInitialPrice
+		);
+
+		ensure!(asset_a != asset_b, Error::<T>::CannotCreatePoolWithSameAssets);
+
+		ensure!(!Self::exists(asset_a, asset_b), Error::<T>::TokenPoolAlreadyExists);
+
+		let asset_b_amount = initial_price
+			.checked_mul_int(amount)
+			.ok_or(Error::<T>::CreatePoolAssetAmountInvalid)?;
+		let shares_added = if asset_a < asset_b { amount } else { asset_b_amount };
+
+		ensure!(
+			T::Currency::free_balance(asset_a, &who) >= amount,
+			Error::<T>::InsufficientAssetBalance
+		);
+
+		ensure!(
+			T::Currency::free_balance(asset_b, &who) >= asset_b_amount,
+			Error::<T>::InsufficientAssetBalance
+		);
+
+		// Create pool only if amounts don't overflow
+		let pair_account = Self::get_pair_id(&asset_a, &asset_b);
+
+		let share_token = match Self::exists(asset_a, asset_b) {
+			true => Self::share_token(&pair_account),
+			false => {
+				let token_name = Self::get_token_name(asset_a, asset_b);
+
+				let share_token = <pallet_asset_registry::Module<T>>::create_asset(token_name)?.into();
+
+				<ShareToken<T>>::insert(&pair_account, &share_token);
+				<PoolAssets<T>>::insert(&pair_account, (asset_a, asset_b));
+				share_token
+			}
+		};
+
+		T::Currency::transfer(asset_a, &who, &pair_account, amount)?;
+		T::Currency::transfer(asset_b, &who, &pair_account, asset_b_amount)?;
+
+		T::Currency::deposit(share_token, &who, shares_added)?;
+
+		<TotalLiquidity<T>>::insert(&pair_account, shares_added);
+
+		Self::deposit_event(RawEvent::CreatePool(who, asset_a, asset_b, shares_added));
+
+		Ok(())
+	}
+
 	fn calculate_fees(amount: Balance, discount: bool, hdx_fee: &mut Balance) -> Result<Balance, DispatchError> {
 		match discount {
 			true => {
@@ -765,3 +782,15 @@ impl<T: Config> AMM<T::AccountId, AssetId, Balance> for Module<T> {
 		Ok(())
 	}
 }
+
+impl<T: Config> primitives::traits::AMMTrader<T::AccountId, AssetId, Balance, Price> for Module<T> {
+	fn create_pool(
+		who: &T::AccountId,
+		asset_a: AssetId,
+		asset_b: AssetId,
+		amount: Balance,
+		initial_price: Price,
+	) -> DispatchResult {
+		Self::create_pool_for(who.clone(), asset_a, asset_b, amount, initial_price)
+	}
+}
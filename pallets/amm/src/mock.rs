@@ -121,6 +121,7 @@ impl Config for Test {
 	type HDXAssetId = HDXAssetId;
 	type WeightInfo = ();
 	type GetExchangeFee = ExchangeFeeRate;
+	type IntentionHandler = ();
 }
 pub type AMM = Module<Test>;
 pub type System = system::Module<Test>;
@@ -62,6 +62,12 @@ const SELL_INTENTION_LIMIT: Balance = 1;
 const BUY_INTENTION_AMOUNT: Balance = 1_000_000_000;
 const BUY_INTENTION_LIMIT: Balance = 2_000_000_000;
 
+// Dwarfs every tiny counterparty `resolve_pair` throws at it below, so the matcher never breaks
+// out of its counterparty loop early.
+const HUGE_SELL_INTENTION_AMOUNT: Balance = 1_000_000_000_000;
+const TINY_BUY_INTENTION_AMOUNT: Balance = 1_000;
+const TINY_BUY_INTENTION_LIMIT: Balance = 1;
+
 fn feed_intentions<T: Config>(asset_a: AssetId, asset_b: AssetId, number: u32) -> Result<(), DispatchError> {
 	for idx in 0..number / 2 {
 		let user = funded_account::<T>("user", idx + 100);
@@ -72,6 +78,10 @@ fn feed_intentions<T: Config>(asset_a: AssetId, asset_b: AssetId, number: u32) -
 			SELL_INTENTION_AMOUNT,
 			SELL_INTENTION_LIMIT,
 			false,
+			None,
+			None,
+			None,
+			true,
 		)?;
 	}
 
@@ -84,6 +94,10 @@ fn feed_intentions<T: Config>(asset_a: AssetId, asset_b: AssetId, number: u32) -
 			BUY_INTENTION_AMOUNT,
 			BUY_INTENTION_LIMIT,
 			false,
+			None,
+			None,
+			None,
+			true,
 		)?;
 	}
 
@@ -131,7 +145,7 @@ benchmarks! {
 
 		assert_eq!(pallet_exchange::Module::<T>::get_intentions_count((asset_a, asset_b)), 0);
 
-	}: {  Exchange::<T>::sell(RawOrigin::Signed(caller.clone()).into(), asset_a, asset_b, amount ,limit, false)? }
+	}: {  Exchange::<T>::sell(RawOrigin::Signed(caller.clone()).into(), asset_a, asset_b, amount ,limit, false, None, None, None, true)? }
 	verify{
 		assert_eq!(pallet_exchange::Module::<T>::get_intentions_count((asset_a, asset_b)), 1);
 	}
@@ -148,7 +162,7 @@ benchmarks! {
 
 		assert_eq!(pallet_exchange::Module::<T>::get_intentions_count((asset_a, asset_b)), 0);
 
-	}: {  Exchange::<T>::buy(RawOrigin::Signed(caller.clone()).into(), asset_a, asset_b, amount / 10 ,limit, false)? }
+	}: {  Exchange::<T>::buy(RawOrigin::Signed(caller.clone()).into(), asset_a, asset_b, amount / 10 ,limit, false, None, None, None, true)? }
 	verify{
 		assert_eq!(pallet_exchange::Module::<T>::get_intentions_count((asset_a, asset_b)), 1);
 	}
@@ -192,6 +206,10 @@ benchmarks! {
 				BUY_INTENTION_AMOUNT,
 				BUY_INTENTION_LIMIT,
 				false,
+				None,
+				None,
+				None,
+				true,
 			)?;
 		}
 
@@ -225,6 +243,10 @@ benchmarks! {
 				SELL_INTENTION_AMOUNT,
 				SELL_INTENTION_LIMIT,
 				false,
+				None,
+				None,
+				None,
+				true,
 			)?;
 		}
 
@@ -239,6 +261,56 @@ benchmarks! {
 		}
 	}
 
+	resolve_pair {
+		// Number of tiny counterparty intentions the one huge intention has to consume - each
+		// consumed via `Vec::remove` in `process_exchange_intentions`, so this drives the
+		// matcher's worst case, not the AMM fallback's.
+		let t in 0 .. 100;
+		let caller = funded_account::<T>("caller", 1);
+		let big_seller = funded_account::<T>("big_seller", 1);
+
+		let asset_a: AssetId = 1;
+		let asset_b: AssetId = 2;
+		let amount : Balance = 100_000_000_000_000;
+
+		initialize_pool::<T>(caller, asset_a, asset_b, amount, Price::from(1))?;
+
+		pallet_exchange::Module::<T>::sell(
+			RawOrigin::Signed(big_seller.clone()).into(),
+			asset_a,
+			asset_b,
+			HUGE_SELL_INTENTION_AMOUNT,
+			1,
+			false,
+			None,
+			None,
+			None,
+			true,
+		)?;
+
+		for idx in 0 .. t {
+			let user = funded_account::<T>("user", idx + 100);
+			pallet_exchange::Module::<T>::buy(
+				RawOrigin::Signed(user.clone()).into(),
+				asset_a,
+				asset_b,
+				TINY_BUY_INTENTION_AMOUNT,
+				TINY_BUY_INTENTION_LIMIT,
+				false,
+				None,
+				None,
+				None,
+				true,
+			)?;
+		}
+
+		assert_eq!(pallet_exchange::Module::<T>::get_intentions_count((asset_a, asset_b)), t + 1);
+
+	}: {  Exchange::<T>::on_finalize(1u32.into()); }
+	verify {
+		assert_eq!(pallet_exchange::Module::<T>::get_intentions_count((asset_a, asset_b)), 0);
+	}
+
 	sell_extrinsic {
 		let creator = funded_account::<T>("creator", 100);
 		let seller = funded_account::<T>("seller", 101);
@@ -275,6 +347,10 @@ benchmarks! {
 			SELL_INTENTION_AMOUNT,
 			SELL_INTENTION_LIMIT,
 			false,
+			None,
+			None,
+			None,
+			true,
 		)?;
 
 		assert_eq!(pallet_exchange::Module::<T>::get_intentions_count((asset_a, asset_b)), 1);
@@ -325,6 +401,10 @@ benchmarks! {
 			1_000_000_000,
 			max_sold,
 			false,
+			None,
+			None,
+			None,
+			true,
 		)?;
 
 		assert_eq!(pallet_exchange::Module::<T>::get_intentions_count((asset_a, asset_b)), 1);
@@ -352,6 +432,7 @@ mod tests {
 			assert_ok!(test_benchmark_on_finalize::<Test>());
 			assert_ok!(test_benchmark_on_finalize_buys_no_matches::<Test>());
 			assert_ok!(test_benchmark_on_finalize_sells_no_matches::<Test>());
+			assert_ok!(test_benchmark_resolve_pair::<Test>());
 			assert_ok!(test_benchmark_sell_extrinsic::<Test>());
 			assert_ok!(test_benchmark_on_finalize_for_one_sell_extrinsic::<Test>());
 			assert_ok!(test_benchmark_buy_extrinsic::<Test>());
@@ -43,6 +43,8 @@ parameter_types! {
 	pub const HDXAssetId: AssetId = HDX;
 
 	pub ExchangeFeeRate: fee::Fee = fee::Fee::default();
+
+	pub const MinimumPeriod: u64 = 1;
 }
 impl system::Config for Test {
 	type BaseCallFilter = ();
@@ -86,6 +88,13 @@ impl orml_tokens::Config for Test {
 
 pub type Currency = orml_tokens::Module<Test>;
 
+impl pallet_timestamp::Config for Test {
+	type Moment = u64;
+	type OnTimestampSet = ();
+	type MinimumPeriod = MinimumPeriod;
+	type WeightInfo = ();
+}
+
 pub struct AssetPairAccountIdTest();
 
 impl AssetPairAccountIdFor<AssetId, u64> for AssetPairAccountIdTest {
@@ -123,6 +132,7 @@ impl pallet_exchange::Config for Test {
 	type Currency = Currency;
 	type Resolver = pallet_exchange::Module<Test>;
 	type WeightInfo = ();
+	type PauseOrigin = frame_system::EnsureRoot<AccountId>;
 }
 
 pub struct ExtBuilder {
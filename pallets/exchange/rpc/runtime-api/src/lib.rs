@@ -0,0 +1,79 @@
+//! Runtime API definition for the exchange module.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+// The `too_many_arguments` warning originates from `decl_runtime_apis` macro.
+#![allow(clippy::too_many_arguments)]
+#![allow(clippy::unnecessary_mut_passed)]
+
+use codec::Codec;
+use primitives::{FillRecord, SettlementRecord};
+use sp_runtime::traits::{MaybeDisplay, MaybeFromStr};
+use sp_std::vec::Vec;
+
+sp_api::decl_runtime_apis! {
+	pub trait ExchangeApi<AccountId, AssetId, Balance, IntentionId, IntentionStatus, BlockNumber> where
+		AccountId: Codec,
+		AssetId: Codec,
+		Balance: Codec + MaybeDisplay + MaybeFromStr,
+		IntentionId: Codec,
+		IntentionStatus: Codec,
+		BlockNumber: Codec,
+	{
+		/// The deterministic account which holds `(asset_a, asset_b)`'s pool reserves and
+		/// receives its trading fees - the same account `on_finalize` direct-trades and AMM
+		/// fallbacks settle through.
+		fn pair_account(asset_a: AssetId, asset_b: AssetId) -> AccountId;
+
+		/// Cumulative direct-trade fees `(asset_a, asset_b)`'s pair account has collected so far.
+		fn collected_fees(asset_a: AssetId, asset_b: AssetId) -> Balance;
+
+		/// Total `asset` `who` currently has reserved across all of their open intentions selling
+		/// `asset`, in either direction of any pair.
+		fn reserved_balance(who: AccountId, asset: AssetId) -> Balance;
+
+		/// `intention_id`'s current lifecycle status - only known for the block it was
+		/// registered or settled in. `None` if `intention_id` was never registered, or was
+		/// settled or dropped in an earlier block.
+		fn intention_status(intention_id: IntentionId) -> Option<IntentionStatus>;
+
+		/// `(asset_a, asset_b)`'s current instantaneous marginal price, computed from the pair
+		/// account's live reserves. `None` if no pool exists for the pair, or it holds no
+		/// reserves to price against.
+		fn spot_price(asset_a: AssetId, asset_b: AssetId) -> Option<Balance>;
+
+		/// The amount of `asset_sell` that `amount_out` of `asset_buy` is worth at the pool's
+		/// current reserve ratio - the input-required counterpart to `spot_price`. A linear quote:
+		/// the pool's slippage and fee on an actual AMM buy are not included. `None` if no pool
+		/// exists for the pair.
+		fn quote_buy(asset_buy: AssetId, asset_sell: AssetId, amount_out: Balance) -> Option<Balance>;
+
+		/// `(asset_a, asset_b)`'s most recently settled price and the block it was recorded in -
+		/// `None` if the pair has never had a direct match or AMM trade settle.
+		fn last_price(asset_a: AssetId, asset_b: AssetId) -> Option<(Balance, BlockNumber)>;
+
+		/// The `IntentionId` a `sell`/`buy` call from `who` for `(asset_sell, asset_buy)` would be
+		/// assigned if it were submitted right now. Only valid until the next registration for the
+		/// pair or the next block, whichever comes first.
+		fn next_intention_id(who: AccountId, asset_sell: AssetId, asset_buy: AssetId) -> IntentionId;
+
+		/// Every pair with at least one intention queued right now, as `(asset_a, asset_b, count)` -
+		/// the set `on_finalize` is about to attempt settling this block. Cost scales with the
+		/// number of distinct pairs ever traded, not just the active ones - fine for an RPC query,
+		/// but not something to call from within a dispatchable.
+		fn active_pairs() -> Vec<(AssetId, AssetId, u32)>;
+
+		/// Every fill settled during the block this state was read from - one `FillRecord` per
+		/// direct-trade leg and one per AMM fill. Empty once the following block's `on_initialize`
+		/// has run, so this is only meaningful queried against the block that produced the fills.
+		fn last_block_fills() -> Vec<FillRecord<IntentionId, AssetId, Balance>>;
+
+		/// Cumulative volume of `asset` traded through the exchange, summed across every block
+		/// since genesis. `0` if `asset` has never traded.
+		fn asset_volume(asset: AssetId) -> Balance;
+
+		/// Every intention belonging to `who` settled during the block this state was read from -
+		/// one `SettlementRecord` per intention, direct or AMM. Empty once the following block's
+		/// `on_initialize` has run, same caveat as `last_block_fills`.
+		fn account_settlements(who: AccountId) -> Vec<SettlementRecord<IntentionId, Balance>>;
+	}
+}
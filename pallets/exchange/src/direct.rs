@@ -1,7 +1,8 @@
 use super::*;
+use frame_support::storage::{with_transaction, TransactionOutcome};
 use frame_support::traits::BalanceStatus;
 
-use primitives::fee::{Fee, WithFee};
+use primitives::fee::Fee;
 
 /// Hold info about each transfer which has to be made to resolve a direct trade.
 pub struct Transfer<'a, T: Config> {
@@ -20,6 +21,10 @@ pub struct DirectTradeData<'a, T: Config> {
 	pub amount_from_a: Balance,
 	pub amount_from_b: Balance,
 	pub transfers: Vec<Transfer<'a, T>>,
+	/// What's left of intention a's amount after this match settles - lets consumers of the
+	/// resolution event follow a partially matched intention's progress without recomputing it
+	/// from the sequence of prior events.
+	pub remaining_amount: Balance,
 }
 
 /// Direct trading implementaton
@@ -29,25 +34,41 @@ impl<'a, T: Config> DirectTradeData<'a, T> {
 	/// 2. Calculate fees
 	/// 3. Reserve amounts for each transfer ( including fee transfers )
 	pub fn prepare(&mut self, pool_account: &'a T::AccountId) -> bool {
-		if T::Currency::free_balance(self.intention_a.asset_sell, &self.intention_a.who) < self.amount_from_a {
-			Self::send_insufficient_balance_event(self.intention_a, self.intention_a.asset_sell);
-			return false;
+		let available_a = T::Currency::free_balance(self.intention_a.asset_sell, &self.intention_a.who);
+		if available_a < self.amount_from_a {
+			match Self::shortfall_amount(self.intention_a, self.intention_a.asset_sell, self.amount_from_a, available_a) {
+				Some(reduced) => self.amount_from_a = reduced,
+				None => return false,
+			}
 		}
-		if T::Currency::free_balance(self.intention_a.asset_buy, &self.intention_b.who) < self.amount_from_b {
-			Self::send_insufficient_balance_event(self.intention_b, self.intention_a.asset_buy);
-			return false;
+		let available_b = T::Currency::free_balance(self.intention_a.asset_buy, &self.intention_b.who);
+		if available_b < self.amount_from_b {
+			match Self::shortfall_amount(self.intention_b, self.intention_a.asset_buy, self.amount_from_b, available_b) {
+				Some(reduced) => self.amount_from_b = reduced,
+				None => return false,
+			}
 		}
 
-		if !Self::reserve_if_can(self.intention_a.asset_sell, &self.intention_a.who, self.amount_from_a) {
+		if !Self::reserve_and_notify(
+			self.intention_a.asset_sell,
+			&self.intention_a.who,
+			self.amount_from_a,
+			self.intention_a.intention_id,
+		) {
 			return false;
 		}
-		if !Self::reserve_if_can(self.intention_a.asset_buy, &self.intention_b.who, self.amount_from_b) {
+		if !Self::reserve_and_notify(
+			self.intention_a.asset_buy,
+			&self.intention_b.who,
+			self.amount_from_b,
+			self.intention_b.intention_id,
+		) {
 			return false;
 		}
 
 		let transfer = Transfer::<T> {
 			from: &self.intention_a.who,
-			to: &self.intention_b.who,
+			to: self.intention_b.beneficiary(),
 			asset: self.intention_a.asset_sell,
 			amount: self.amount_from_a,
 			fee_transfer: false,
@@ -55,7 +76,7 @@ impl<'a, T: Config> DirectTradeData<'a, T> {
 		self.transfers.push(transfer);
 		let transfer = Transfer::<T> {
 			from: &self.intention_b.who,
-			to: &self.intention_a.who,
+			to: self.intention_a.beneficiary(),
 			asset: self.intention_a.asset_buy,
 			amount: self.amount_from_b,
 			fee_transfer: false,
@@ -63,16 +84,16 @@ impl<'a, T: Config> DirectTradeData<'a, T> {
 		self.transfers.push(transfer);
 
 		// Let's handle the fees now for registered transfers.
-
-		let fee_a = self.amount_from_a.just_fee(Fee::default());
-		let fee_b = self.amount_from_b.just_fee(Fee::default());
-
-		if fee_a.is_none() || fee_b.is_none() {
-			return false;
-		}
-
-		let transfer_a_fee = fee_a.unwrap();
-		let transfer_b_fee = fee_b.unwrap();
+		//
+		// Each fee is collected as its own transfer on top of the two main ones above rather than
+		// netted out of them, so it's rounded up - truncating it down here would quietly leak the
+		// rounding remainder away from the pool on every single trade. Each fee is computed at
+		// the discounted rate when the intention actually paying it is flagged `discount`, not
+		// the intention whose amount it happens to be based on - see `direct_trade_fee`. The two
+		// sides are priced entirely independently of one another, so when they disagree - one
+		// `discount`, the other not - each simply pays what its own flag says: the discounted
+		// side never pays more because its counterparty isn't discounted, and the non-discounted
+		// side never pays less because its counterparty is.
 
 		// Work out where to a fee from.
 		// There are multiple possible scenarios to consider
@@ -83,108 +104,228 @@ impl<'a, T: Config> DirectTradeData<'a, T> {
 		// Each one is handled slightly different, hence the complicated match statement.
 		match (&self.intention_a.sell_or_buy, &self.intention_b.sell_or_buy) {
 			(IntentionType::SELL, IntentionType::SELL) => {
-				if !Self::reserve_if_can(self.intention_a.asset_buy, &self.intention_a.who, transfer_b_fee) {
-					return false;
-				}
-				if !Self::reserve_if_can(self.intention_b.asset_buy, &self.intention_b.who, transfer_a_fee) {
-					return false;
+				if !FeeExempt::<T>::get(&self.intention_a.who) {
+					let (fee, is_dust) = match Self::direct_trade_fee(self.amount_from_b, self.intention_a.discount) {
+						Some(result) => result,
+						None => return false,
+					};
+					if !Self::reserve_if_can(self.intention_a.asset_buy, &self.intention_a.who, fee) {
+						return false;
+					}
+
+					let transfer = Transfer::<T> {
+						from: &self.intention_a.who,
+						to: pool_account,
+						asset: self.intention_a.asset_buy,
+						amount: fee,
+						fee_transfer: true,
+					};
+					self.transfers.push(transfer);
+
+					if is_dust {
+						Module::<T>::deposit_event(RawEvent::DustToFee(
+							self.intention_a.who.clone(),
+							self.intention_a.asset_buy,
+							fee,
+							self.intention_a.intention_id,
+						));
+					}
 				}
+				if !FeeExempt::<T>::get(&self.intention_b.who) {
+					let (fee, is_dust) = match Self::direct_trade_fee(self.amount_from_a, self.intention_b.discount) {
+						Some(result) => result,
+						None => return false,
+					};
+					if !Self::reserve_if_can(self.intention_b.asset_buy, &self.intention_b.who, fee) {
+						return false;
+					}
 
-				let transfer = Transfer::<T> {
-					from: &self.intention_a.who,
-					to: pool_account,
-					asset: self.intention_a.asset_buy,
-					amount: transfer_b_fee,
-					fee_transfer: true,
-				};
-				self.transfers.push(transfer);
-
-				let transfer = Transfer::<T> {
-					from: &self.intention_b.who,
-					to: pool_account,
-					asset: self.intention_b.asset_buy,
-					amount: transfer_a_fee,
-					fee_transfer: true,
-				};
-				self.transfers.push(transfer);
+					let transfer = Transfer::<T> {
+						from: &self.intention_b.who,
+						to: pool_account,
+						asset: self.intention_b.asset_buy,
+						amount: fee,
+						fee_transfer: true,
+					};
+					self.transfers.push(transfer);
+
+					if is_dust {
+						Module::<T>::deposit_event(RawEvent::DustToFee(
+							self.intention_b.who.clone(),
+							self.intention_b.asset_buy,
+							fee,
+							self.intention_b.intention_id,
+						));
+					}
+				}
 			}
 			(IntentionType::BUY, IntentionType::BUY) => {
-				if !Self::reserve_if_can(self.intention_a.asset_sell, &self.intention_a.who, transfer_a_fee) {
-					return false;
-				}
-				if !Self::reserve_if_can(self.intention_b.asset_sell, &self.intention_b.who, transfer_b_fee) {
-					return false;
+				if !FeeExempt::<T>::get(&self.intention_a.who) {
+					let (fee, is_dust) = match Self::direct_trade_fee(self.amount_from_a, self.intention_a.discount) {
+						Some(result) => result,
+						None => return false,
+					};
+					if !Self::reserve_if_can(self.intention_a.asset_sell, &self.intention_a.who, fee) {
+						return false;
+					}
+
+					let transfer = Transfer::<T> {
+						from: &self.intention_a.who,
+						to: pool_account,
+						asset: self.intention_a.asset_sell,
+						amount: fee,
+						fee_transfer: true,
+					};
+					self.transfers.push(transfer);
+
+					if is_dust {
+						Module::<T>::deposit_event(RawEvent::DustToFee(
+							self.intention_a.who.clone(),
+							self.intention_a.asset_sell,
+							fee,
+							self.intention_a.intention_id,
+						));
+					}
 				}
+				if !FeeExempt::<T>::get(&self.intention_b.who) {
+					let (fee, is_dust) = match Self::direct_trade_fee(self.amount_from_b, self.intention_b.discount) {
+						Some(result) => result,
+						None => return false,
+					};
+					if !Self::reserve_if_can(self.intention_b.asset_sell, &self.intention_b.who, fee) {
+						return false;
+					}
+
+					let transfer = Transfer::<T> {
+						from: &self.intention_b.who,
+						to: pool_account,
+						asset: self.intention_b.asset_sell,
+						amount: fee,
+						fee_transfer: true,
+					};
+					self.transfers.push(transfer);
 
-				let transfer = Transfer::<T> {
-					from: &self.intention_a.who,
-					to: pool_account,
-					asset: self.intention_a.asset_sell,
-					amount: transfer_a_fee,
-					fee_transfer: true,
-				};
-				self.transfers.push(transfer);
-
-				let transfer = Transfer::<T> {
-					from: &self.intention_b.who,
-					to: pool_account,
-					asset: self.intention_b.asset_sell,
-					amount: transfer_b_fee,
-					fee_transfer: true,
-				};
-				self.transfers.push(transfer);
+					if is_dust {
+						Module::<T>::deposit_event(RawEvent::DustToFee(
+							self.intention_b.who.clone(),
+							self.intention_b.asset_sell,
+							fee,
+							self.intention_b.intention_id,
+						));
+					}
+				}
 			}
 			(IntentionType::BUY, IntentionType::SELL) => {
-				if !Self::reserve_if_can(self.intention_a.asset_sell, &self.intention_a.who, transfer_a_fee) {
-					return false;
-				}
-				if !Self::reserve_if_can(self.intention_b.asset_buy, &self.intention_b.who, transfer_b_fee) {
-					return false;
+				if !FeeExempt::<T>::get(&self.intention_a.who) {
+					let (fee, is_dust) = match Self::direct_trade_fee(self.amount_from_a, self.intention_a.discount) {
+						Some(result) => result,
+						None => return false,
+					};
+					if !Self::reserve_if_can(self.intention_a.asset_sell, &self.intention_a.who, fee) {
+						return false;
+					}
+
+					let transfer = Transfer::<T> {
+						from: &self.intention_a.who,
+						to: pool_account,
+						asset: self.intention_a.asset_sell,
+						amount: fee,
+						fee_transfer: true,
+					};
+					self.transfers.push(transfer);
+
+					if is_dust {
+						Module::<T>::deposit_event(RawEvent::DustToFee(
+							self.intention_a.who.clone(),
+							self.intention_a.asset_sell,
+							fee,
+							self.intention_a.intention_id,
+						));
+					}
 				}
+				if !FeeExempt::<T>::get(&self.intention_b.who) {
+					let (fee, is_dust) = match Self::direct_trade_fee(self.amount_from_b, self.intention_b.discount) {
+						Some(result) => result,
+						None => return false,
+					};
+					if !Self::reserve_if_can(self.intention_b.asset_buy, &self.intention_b.who, fee) {
+						return false;
+					}
+
+					let transfer = Transfer::<T> {
+						from: &self.intention_b.who,
+						to: pool_account,
+						asset: self.intention_b.asset_buy,
+						amount: fee,
+						fee_transfer: true,
+					};
+					self.transfers.push(transfer);
 
-				let transfer = Transfer::<T> {
-					from: &self.intention_a.who,
-					to: pool_account,
-					asset: self.intention_a.asset_sell,
-					amount: transfer_a_fee,
-					fee_transfer: true,
-				};
-				self.transfers.push(transfer);
-
-				let transfer = Transfer::<T> {
-					from: &self.intention_b.who,
-					to: pool_account,
-					asset: self.intention_b.asset_buy,
-					amount: transfer_b_fee,
-					fee_transfer: true,
-				};
-				self.transfers.push(transfer);
+					if is_dust {
+						Module::<T>::deposit_event(RawEvent::DustToFee(
+							self.intention_b.who.clone(),
+							self.intention_b.asset_buy,
+							fee,
+							self.intention_b.intention_id,
+						));
+					}
+				}
 			}
 			(IntentionType::SELL, IntentionType::BUY) => {
-				if !Self::reserve_if_can(self.intention_a.asset_buy, &self.intention_a.who, transfer_a_fee) {
-					return false;
-				}
-				if !Self::reserve_if_can(self.intention_b.asset_sell, &self.intention_b.who, transfer_b_fee) {
-					return false;
+				if !FeeExempt::<T>::get(&self.intention_a.who) {
+					let (fee, is_dust) = match Self::direct_trade_fee(self.amount_from_a, self.intention_a.discount) {
+						Some(result) => result,
+						None => return false,
+					};
+					if !Self::reserve_if_can(self.intention_a.asset_buy, &self.intention_a.who, fee) {
+						return false;
+					}
+
+					let transfer = Transfer::<T> {
+						from: &self.intention_a.who,
+						to: pool_account,
+						asset: self.intention_a.asset_buy,
+						amount: fee,
+						fee_transfer: true,
+					};
+					self.transfers.push(transfer);
+
+					if is_dust {
+						Module::<T>::deposit_event(RawEvent::DustToFee(
+							self.intention_a.who.clone(),
+							self.intention_a.asset_buy,
+							fee,
+							self.intention_a.intention_id,
+						));
+					}
 				}
+				if !FeeExempt::<T>::get(&self.intention_b.who) {
+					let (fee, is_dust) = match Self::direct_trade_fee(self.amount_from_b, self.intention_b.discount) {
+						Some(result) => result,
+						None => return false,
+					};
+					if !Self::reserve_if_can(self.intention_b.asset_sell, &self.intention_b.who, fee) {
+						return false;
+					}
 
-				let transfer = Transfer::<T> {
-					from: &self.intention_a.who,
-					to: pool_account,
-					asset: self.intention_a.asset_buy,
-					amount: transfer_a_fee,
-					fee_transfer: true,
-				};
-				self.transfers.push(transfer);
-
-				let transfer = Transfer::<T> {
-					from: &self.intention_b.who,
-					to: pool_account,
-					asset: self.intention_b.asset_sell,
-					amount: transfer_b_fee,
-					fee_transfer: true,
-				};
-				self.transfers.push(transfer);
+					let transfer = Transfer::<T> {
+						from: &self.intention_b.who,
+						to: pool_account,
+						asset: self.intention_b.asset_sell,
+						amount: fee,
+						fee_transfer: true,
+					};
+					self.transfers.push(transfer);
+
+					if is_dust {
+						Module::<T>::deposit_event(RawEvent::DustToFee(
+							self.intention_b.who.clone(),
+							self.intention_b.asset_sell,
+							fee,
+							self.intention_b.intention_id,
+						));
+					}
+				}
 			}
 		}
 
@@ -192,20 +333,83 @@ impl<'a, T: Config> DirectTradeData<'a, T> {
 	}
 
 	/// Execute direct trade.
-	/// Trade must be prepared first. Execute all transfers.
+	///
+	/// Trade must be prepared first. All transfers are repatriated inside a single storage
+	/// transaction, so a transfer failing part-way through (which shouldn't happen, since
+	/// `prepare` already reserved exactly what's repatriated here, but isn't otherwise provable)
+	/// rolls back every transfer already applied for this trade instead of leaving it
+	/// half-settled. If that happens, emits `DirectTransferFailed` for the failing transfer once
+	/// the rollback is done and returns `false` - the caller is responsible for calling `revert`
+	/// to release the still-held reservations and falling back to another resolution path.
 	pub fn execute(&self) -> bool {
+		let repatriated = with_transaction(|| {
+			for transfer in &self.transfers {
+				if let Err(error) = T::Currency::repatriate_reserved(
+					transfer.asset,
+					transfer.from,
+					transfer.to,
+					transfer.amount,
+					BalanceStatus::Free,
+				) {
+					return TransactionOutcome::Rollback(Err((transfer, error)));
+				}
+			}
+			TransactionOutcome::Commit(Ok(()))
+		});
+
+		// Deposited outside the transaction, so the rollback doesn't discard the event too.
+		if let Err((transfer, error)) = repatriated {
+			Self::send_direct_transfer_failed_event(transfer, error);
+			return false;
+		}
+
 		self.send_direct_trade_resolve_event();
+
+		Module::<T>::set_intention_status(self.intention_a.intention_id, crate::Status::Filled);
+		Module::<T>::set_intention_status(self.intention_b.intention_id, crate::Status::Filled);
+
+		Module::<T>::record_last_price(self.intention_a.asset_sell, self.intention_a.asset_buy);
+
+		Module::<T>::record_fill(
+			self.intention_a.intention_id,
+			self.intention_a.asset_sell,
+			self.intention_a.asset_buy,
+			self.intention_a.sell_or_buy.clone(),
+			self.amount_from_a,
+			self.amount_from_b,
+			true,
+		);
+		Module::<T>::record_fill(
+			self.intention_b.intention_id,
+			self.intention_b.asset_sell,
+			self.intention_b.asset_buy,
+			self.intention_b.sell_or_buy.clone(),
+			self.amount_from_b,
+			self.amount_from_a,
+			true,
+		);
+
+		T::OnTradeHandler::on_trade(
+			&self.intention_a.who,
+			self.intention_a.asset_sell,
+			self.intention_a.asset_buy,
+			self.amount_from_a,
+			self.amount_from_b,
+		);
+		T::OnTradeHandler::on_trade(
+			&self.intention_b.who,
+			self.intention_b.asset_sell,
+			self.intention_b.asset_buy,
+			self.amount_from_b,
+			self.amount_from_a,
+		);
+
 		for transfer in &self.transfers {
-			T::Currency::repatriate_reserved(
-				transfer.asset,
-				transfer.from,
-				transfer.to,
-				transfer.amount,
-				BalanceStatus::Free,
-			)
-			.expect("Cannot fail. Checks should have been done prior to this.");
-			if transfer.fee_transfer {
-				Self::send_trade_fee_event(transfer.from, transfer.to, transfer.asset, transfer.amount);
+			if !transfer.fee_transfer {
+				self.send_funds_unreserved_event(transfer.from, transfer.asset, transfer.amount);
+			} else {
+				Module::<T>::record_collected_fee(self.intention_a.asset_sell, self.intention_a.asset_buy, transfer.amount);
+				Self::collect_fee(transfer);
 			}
 		}
 		true
@@ -216,7 +420,41 @@ impl<'a, T: Config> DirectTradeData<'a, T> {
 	pub fn revert(&mut self) {
 		for transfer in &self.transfers {
 			T::Currency::unreserve(transfer.asset, transfer.from, transfer.amount);
+			if !transfer.fee_transfer {
+				self.send_funds_unreserved_event(transfer.from, transfer.asset, transfer.amount);
+			}
+		}
+	}
+
+	/// Look up which of the two intentions in this trade `who` belongs to.
+	fn intention_id_for(&self, who: &T::AccountId) -> IntentionId<T> {
+		if *who == self.intention_a.who {
+			self.intention_a.intention_id
+		} else {
+			self.intention_b.intention_id
+		}
+	}
+
+	/// Resolve a balance shortfall found while validating `prepare`'s two main transfer amounts.
+	/// If `T::AllowPartialOnShortfall` is set and `who` still holds something, reports a partial
+	/// fill and returns the amount that can actually be settled. Otherwise reports the ordinary
+	/// insufficient-balance failure and returns `None`, telling the caller to fail the whole trade.
+	fn shortfall_amount(intention: &Intention<T>, asset: AssetId, requested: Balance, available: Balance) -> Option<Balance> {
+		if !T::AllowPartialOnShortfall::get() || available.is_zero() {
+			Self::send_insufficient_balance_event(intention, asset);
+			return None;
 		}
+
+		Module::<T>::deposit_event(RawEvent::IntentionResolvedDirectTradePartialFill(
+			intention.who.clone(),
+			asset,
+			intention.sell_or_buy.clone(),
+			intention.intention_id,
+			requested,
+			available,
+		));
+
+		Some(available)
 	}
 
 	/// Send pallet event in case of insufficient balance.
@@ -230,6 +468,43 @@ impl<'a, T: Config> DirectTradeData<'a, T> {
 		));
 	}
 
+	/// Send event when a reserved-funds repatriation fails mid-trade, after the whole trade has
+	/// been rolled back - so operators can see exactly which transfer failed and why, since the
+	/// trade as a whole otherwise just silently reverts.
+	fn send_direct_transfer_failed_event(transfer: &Transfer<T>, error: dispatch::DispatchError) {
+		Module::<T>::deposit_event(RawEvent::DirectTransferFailed(
+			transfer.from.clone(),
+			transfer.to.clone(),
+			transfer.asset,
+			transfer.amount,
+			error,
+		));
+	}
+
+	/// Report a fee transfer that has just landed in `transfer.to` (the pool account) as
+	/// collected. If `CollectFeesInNative` is set and the fee isn't already in the native asset,
+	/// swap it for native via the AMM first, so it's the swapped-out amount that gets reported
+	/// and recorded as collected. Falls back to reporting the fee in its original asset if the
+	/// swap isn't possible (e.g. no pool between the fee asset and native) - the fee has already
+	/// safely landed at `transfer.to` either way, so there's nothing to roll back.
+	fn collect_fee(transfer: &Transfer<T>) {
+		let native = T::NativeAssetId::get();
+
+		if T::CollectFeesInNative::get() && transfer.asset != native {
+			let converted = T::AMMPool::validate_sell(transfer.to, transfer.asset, native, transfer.amount, 0, false)
+				.and_then(|amm_transfer| T::AMMPool::execute_sell(&amm_transfer).map(|_| amm_transfer.amount_out));
+
+			if let Ok(amount_out) = converted {
+				Self::send_trade_fee_event(transfer.from, transfer.to, native, amount_out);
+				Module::<T>::deposit_event(RawEvent::FeeCollected(native, amount_out, transfer.to.clone()));
+				return;
+			}
+		}
+
+		Self::send_trade_fee_event(transfer.from, transfer.to, transfer.asset, transfer.amount);
+		Module::<T>::deposit_event(RawEvent::FeeCollected(transfer.asset, transfer.amount, transfer.to.clone()));
+	}
+
 	/// Send pallet event after a free is transferred.
 	fn send_trade_fee_event(from: &T::AccountId, to: &T::AccountId, asset: AssetId, amount: Balance) {
 		Module::<T>::deposit_event(RawEvent::IntentionResolvedDirectTradeFees(
@@ -242,18 +517,69 @@ impl<'a, T: Config> DirectTradeData<'a, T> {
 
 	/// Send event after successful direct trade.
 	fn send_direct_trade_resolve_event(&self) {
-		Module::<T>::deposit_event(RawEvent::IntentionResolvedDirectTrade(
-			self.intention_a.who.clone(),
-			self.intention_b.who.clone(),
-			self.intention_a.intention_id,
-			self.intention_b.intention_id,
-			self.amount_from_a,
-			self.amount_from_b,
+		Module::<T>::deposit_resolution_event(
+			RawEvent::IntentionResolvedDirectTrade(
+				self.intention_a.who.clone(),
+				self.intention_b.who.clone(),
+				self.intention_a.intention_id,
+				self.intention_b.intention_id,
+				self.amount_from_a,
+				self.amount_from_b,
+				self.intention_a.reference,
+				self.intention_b.reference,
+				<system::Module<T>>::current_block_number(),
+				self.remaining_amount,
+			),
+			self.intention_a.asset_sell,
+			self.intention_a.asset_buy,
+			&[self.intention_a.intention_id, self.intention_b.intention_id],
+		);
+	}
+
+	/// Send event once reserved funds have been released, either by unreserving them directly or
+	/// by repatriating them to their new owner.
+	fn send_funds_unreserved_event(&self, who: &T::AccountId, asset: AssetId, amount: Balance) {
+		Module::<T>::deposit_event(RawEvent::FundsUnreserved(
+			who.clone(),
+			asset,
+			amount,
+			self.intention_id_for(who),
 		));
 	}
 
+	/// The direct-trade fee owed on `amount`, at the discounted rate when `discount` is set,
+	/// otherwise the standard rate - see `Module::calculate_fee` for the rounding and
+	/// `T::MinFee`/`T::MaxFee` clamping applied. `None` only on overflow. For a dust-sized
+	/// `amount`, `calculate_fee` could otherwise push the fee to or past `amount` itself - rather
+	/// than have the caller abort the whole trade over it, the fee is saturated to `amount` and
+	/// the second element of the tuple is set to flag it, so the caller can charge the full dust
+	/// amount as fee and emit `DustToFee` instead of the ordinary fee event.
+	fn direct_trade_fee(amount: Balance, discount: bool) -> Option<(Balance, bool)> {
+		let rate = if discount { Fee::discounted() } else { Fee::default() };
+		let fee = Module::<T>::calculate_fee(amount, rate)?;
+
+		if !amount.is_zero() && fee >= amount {
+			return Some((amount, true));
+		}
+
+		Some((fee, false))
+	}
+
 	/// Reserve amount.
 	fn reserve_if_can(asset: AssetId, who: &T::AccountId, amount: Balance) -> bool {
 		T::Currency::reserve(asset, who, amount).is_ok()
 	}
+
+	/// Reserve amount and, if successful, deposit a `FundsReserved` event for it.
+	///
+	/// Used for the two main transfer amounts only - fee reservations are an implementation
+	/// detail of settling the trade and don't get their own reservation event.
+	fn reserve_and_notify(asset: AssetId, who: &T::AccountId, amount: Balance, intention_id: IntentionId<T>) -> bool {
+		if !Self::reserve_if_can(asset, who, amount) {
+			return false;
+		}
+
+		Module::<T>::deposit_event(RawEvent::FundsReserved(who.clone(), asset, amount, intention_id));
+		true
+	}
 }
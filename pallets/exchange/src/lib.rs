@@ -1,18 +1,29 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 #![allow(clippy::comparison_chain)]
 
-use frame_support::{decl_error, decl_event, decl_module, decl_storage, dispatch, ensure, storage::IterableStorageMap};
-use frame_system::{self as system, ensure_signed};
+use frame_support::{
+	decl_error, decl_event, decl_module, decl_storage, dispatch, ensure,
+	dispatch::WithPostDispatchInfo,
+	storage::{with_transaction, IterableStorageMap, TransactionOutcome},
+	traits::{EnsureOrigin, Get},
+	unsigned::{TransactionValidity, ValidateUnsigned},
+};
+use frame_system::{
+	self as system, ensure_none, ensure_root, ensure_signed,
+	offchain::{SendTransactionTypes, SubmitTransaction},
+};
 
-use codec::Encode;
+use codec::{Decode, Encode};
 use sp_std::vec::Vec;
 
 use primitives::{
-	traits::{Resolver, AMM},
-	AssetId, Balance, ExchangeIntention, IntentionType,
+	fee::{Fee, WithFee},
+	traits::{AMMTrader, IntentionPurger, OnTradeHandler, PriceProvider, Resolver, AMM},
+	AssetId, Balance, ExchangeIntention, FillRecord, IntentionType, MatchPreview, Price, SettlementRecord,
 };
 use sp_std::borrow::ToOwned;
 use sp_std::cmp;
+use sp_std::convert::TryInto;
 
 use orml_traits::{MultiCurrency, MultiCurrencyExtended, MultiReservableCurrency};
 
@@ -21,7 +32,9 @@ use frame_support::weights::Weight;
 use primitives::traits::AMMTransfer;
 
 use frame_support::sp_runtime::offchain::storage_lock::BlockNumberProvider;
-use frame_support::sp_runtime::traits::Hash;
+use frame_support::sp_runtime::traits::{Hash, One, Zero};
+use frame_support::sp_runtime::transaction_validity::{InvalidTransaction, TransactionSource, ValidTransaction};
+use frame_support::sp_runtime::Permill;
 
 #[cfg(test)]
 mod mock;
@@ -31,22 +44,105 @@ pub mod weights;
 use weights::WeightInfo;
 
 mod direct;
+pub mod signed_extension;
 #[cfg(test)]
 mod tests;
 
+/// Lifecycle status of an intention, queryable via `ExchangeApi::intention_status` for the block
+/// it was last touched in - see `IntentionStatus`.
+#[derive(Debug, Encode, Decode, Clone, PartialEq, Eq)]
+pub enum Status {
+	/// Registered, not yet settled.
+	Pending,
+	/// Fully filled, either via a direct P2P match, an AMM trade, or `EnablePartialAMMFill`
+	/// eventually filling the whole amount over several matched legs.
+	Filled,
+	/// An `EnablePartialAMMFill` retry filled less than the intention's originally requested
+	/// amount.
+	PartiallyFilled,
+	/// Filled via `EnableRouting`'s intermediary-asset routing instead of a direct pool.
+	AMMRouted,
+	/// Removed by its owner before settlement, via `cancel_pair`.
+	Cancelled,
+	/// Failed to settle and was not carried forward - no retries left, or no viable trade found.
+	Failed,
+}
+
+impl Default for Status {
+	fn default() -> Self {
+		Status::Pending
+	}
+}
+
+/// Re-org-safe replay guard for a registered intention, deposited alongside `IntentionRegistered`
+/// via `IntentionReceiptIssued`.
+///
+/// `IntentionId` is derived from `(nonce, who, block *number*, asset pair)` (see
+/// `generate_intention_id`), and a `sell`/`buy` legitimately re-executed in a new block after its
+/// original was reverted by a re-org can land on the exact same block number, and so be assigned
+/// the exact same `IntentionId` - block number alone doesn't distinguish the two forks. An
+/// off-chain system keying on `IntentionId` alone would then see what looks like the same
+/// intention settle twice. `block_hash_prefix` is taken from the *parent* block's actual hash -
+/// unlike the new block's own hash, which isn't known yet while it's still executing, the parent
+/// hash is already fixed and genuinely differs between forks, so it disambiguates the two
+/// executions even though their `IntentionId`s collide.
+#[derive(Debug, Encode, Decode, Clone, PartialEq, Eq)]
+pub struct IntentionReceipt<IntentionID> {
+	/// First 4 bytes of the parent block's hash, at the point this intention was registered.
+	pub block_hash_prefix: [u8; 4],
+	pub intention_id: IntentionID,
+}
+
+/// Coarse classification of why `T::AMMPool::validate_sell`/`validate_buy` rejected an intention,
+/// attached to `AMMSellErrorEvent`/`AMMBuyErrorEvent` so consumers don't have to string-match the
+/// underlying `DispatchError` - see `Module::classify_amm_failure`.
+#[derive(Debug, Encode, Decode, Clone, PartialEq, Eq)]
+pub enum AMMFailureReason {
+	/// No pool exists for the intention's pair.
+	PoolMissing,
+	/// A pool exists and could otherwise fill the intention, but not within `trade_limit`.
+	TradeLimitExceeded,
+	/// Any other rejection (e.g. insufficient pool liquidity) - `T::AMMPool` is generic over any
+	/// implementation of the `AMM` trait, so its `DispatchError` can't be decomposed any further
+	/// than this without coupling to a specific implementor.
+	Other,
+}
+
 /// Intention alias
 type IntentionId<T> = <T as system::Config>::Hash;
-pub type Intention<T> = ExchangeIntention<<T as system::Config>::AccountId, AssetId, Balance, IntentionId<T>>;
+pub type Intention<T> = ExchangeIntention<
+	<T as system::Config>::AccountId,
+	AssetId,
+	Balance,
+	IntentionId<T>,
+	<T as system::Config>::BlockNumber,
+	<T as pallet_timestamp::Config>::Moment,
+>;
+
+/// One settled leg of a trade, as recorded in `LastBlockFills`. Alias for `FillRecord`.
+pub type Fill<T> = FillRecord<IntentionId<T>, AssetId, Balance>;
+
+/// Everything that happened to one intention during a block, as recorded in
+/// `LastBlockSettlements`. Alias for `SettlementRecord`.
+pub type Settlement<T> = SettlementRecord<IntentionId<T>, Balance>;
+
+/// The sorted `(min, max)` key an asset pair's storage - `ExchangeAssetsIntentionCount`,
+/// `ExchangeAssetsIntentions`, `CollectedFees`, `PairMaxSlippage`, `LastPrice` and more - is keyed
+/// under, regardless of which order a caller names the two assets in. Single source of truth for
+/// that ordering, so every call site agrees on it.
+fn canonical_pair(a: AssetId, b: AssetId) -> (AssetId, AssetId) {
+	(cmp::min(a, b), cmp::max(a, b))
+}
 
 /// The pallet's configuration trait.
-pub trait Config: system::Config {
+pub trait Config: system::Config + pallet_timestamp::Config + SendTransactionTypes<Call<Self>> {
 	type Event: From<Event<Self>> + Into<<Self as system::Config>::Event>;
 
 	/// AMM pool implementation
 	type AMMPool: AMM<Self::AccountId, AssetId, Balance>;
 
 	/// Intention resolver
-	type Resolver: Resolver<Self::AccountId, Intention<Self>, Error<Self>>;
+	type Resolver: Resolver<Self::AccountId, Intention<Self>, Error<Self>, Balance>;
 
 	/// Currecny for transfers
 	type Currency: MultiCurrencyExtended<Self::AccountId, CurrencyId = AssetId, Balance = Balance, Amount = i128>
@@ -54,6 +150,149 @@ pub trait Config: system::Config {
 
 	/// Weight information for the extrinsics.
 	type WeightInfo: WeightInfo;
+
+	/// Independent price reference used to sanity check AMM-derived prices.
+	/// Deployments which don't want this protection can configure `()`, which never reports a price.
+	type PriceOracle: PriceProvider<AssetId, Balance>;
+
+	/// Maximum allowed deviation of an AMM match's price from `PriceOracle`'s price.
+	/// Only enforced when `PriceOracle` reports a price for the pair.
+	type MaxPriceDeviation: Get<Permill>;
+
+	/// Maximum allowed price impact of an unmatched intention's AMM fallback trade, measured
+	/// against a linear projection of the pool's current marginal price. Matched P2P trades
+	/// never touch the pool, so this only applies to intentions routed through `T::AMMPool`.
+	type MaxPriceImpact: Get<Permill>;
+
+	/// Number of blocks an intention is retried in before it is dropped, if it can't be matched
+	/// or AMM-routed in the block it was submitted in.
+	type DefaultIntentionLifetime: Get<Self::BlockNumber>;
+
+	/// When an AMM fallback trade fails (e.g. insufficient pool liquidity for the full amount),
+	/// retry it with a progressively smaller amount instead of dropping the intention outright.
+	/// See `MinTradingLimit` for the smallest amount a retry is allowed to shrink down to.
+	type EnablePartialAMMFill: Get<bool>;
+
+	/// Smallest amount an `EnablePartialAMMFill` retry is allowed to shrink an intention down to
+	/// before giving up and falling back to the ordinary failure event.
+	type MinTradingLimit: Get<Balance>;
+
+	/// When a `SELL` intention has no direct `asset_sell`/`asset_buy` pool, route it through
+	/// `NativeAssetId` as an intermediary instead of giving up - `asset_sell` -> native ->
+	/// `asset_buy`, via two AMM trades. `BUY` intentions are never routed.
+	type EnableRouting: Get<bool>;
+
+	/// The intermediary asset `EnableRouting` routes unmatched `SELL` intentions through.
+	type NativeAssetId: Get<AssetId>;
+
+	/// Origin allowed to `pause`/`resume` the exchange - typically a faster-acting origin than
+	/// whatever gates `freeze_asset`/`set_min_pool_liquidity`, since a global kill switch is
+	/// meant for emergencies.
+	type PauseOrigin: EnsureOrigin<Self::Origin>;
+
+	/// Largest `amount_sell`/`amount_buy` discrepancy between two matched intentions that is
+	/// still treated as an exact match in `resolve_matched_intentions`. Without this, integer
+	/// rounding elsewhere (e.g. in `calculate_spot_price`) means two economically-equal orders
+	/// can differ by a few units and never hit the exact-match branch, instead dust-trading their
+	/// tiny leftover through the AMM. The dust itself is simply absorbed by whichever side's
+	/// amount was used to settle - it is not refunded or separately accounted for.
+	type MatchTolerance: Get<Balance>;
+
+	/// Smallest `amount_sell` a counterparty needs to be pulled into a match's `bvec` grouping in
+	/// `process_exchange_intentions`. Intentions below this size are left in `b_copy` for the AMM
+	/// fallback instead, trading a little P2P coverage for fewer, larger settlements - without it,
+	/// a single large intention can end up bin-packed against a long tail of tiny ones, each
+	/// adding its own transfers (and fee-rounding dust) to the block.
+	type MinMatchSize: Get<Balance>;
+
+	/// Largest number of counterparties a single intention's `bvec` grouping in
+	/// `process_exchange_intentions` may accumulate. Each counterparty in a group adds its own
+	/// set of transfers to `resolve_matched_intentions`, so an unbounded group size lets a single
+	/// intention matched against a long tail of small counterparties dominate a block's finalize
+	/// weight. Once reached, `match_intentions` stops pulling in more counterparties for that
+	/// intention and routes whatever remains through the AMM fallback instead, the same as it
+	/// already does for an intention with no counterparties left to match at all.
+	type MaxCounterpartiesPerIntention: Get<u32>;
+
+	/// Notified after each settled leg of a trade, whether matched directly against another
+	/// intention or filled via the AMM - defaults to `()` for deployments with nothing to notify.
+	type OnTradeHandler: OnTradeHandler<Self::AccountId, AssetId, Balance>;
+
+	/// Smallest reserve a pair's pool may hold, on either side, before its intentions are left
+	/// unsettled instead of matched or AMM-routed against it - see `PoolReservesTooLow`.
+	type MinPoolReserve: Get<Balance>;
+
+	/// If set, fees collected from a direct trade are swapped for `NativeAssetId` via the AMM
+	/// before being reported as collected, so all fees end up denominated in a single asset
+	/// regardless of what was traded. Falls back to reporting the fee in its original asset if
+	/// the swap isn't possible.
+	type CollectFeesInNative: Get<bool>;
+
+	/// If set, within each amount-sorted match bucket, counterparties are additionally ordered so
+	/// the ones whose own registered price is closest to the pair's current AMM spot price are
+	/// matched first - leaving less of a price gap for the AMM to bridge on whatever's left over.
+	type PriceProximityMatching: Get<bool>;
+
+	/// Largest number of per-intention resolution events (`IntentionResolvedDirectTrade`,
+	/// `IntentionResolvedAMMTrade`, `IntentionResolvedAMMTradePartialFill`) `on_finalize` will
+	/// emit in a single block. Once reached, further resolutions in that block still settle
+	/// normally - only the event is skipped, in favour of `BlockSettlementSummary`'s aggregate
+	/// totals - so a block with thousands of matches doesn't bloat storage with one event per
+	/// intention. Error events are never suppressed.
+	type MaxEventsPerBlock: Get<u32>;
+
+	/// Flat fee, denominated in the cancelled intention's `asset_sell`, charged by `cancel_pair`
+	/// on a caller's reserved amount and paid to the pair account - a deterrent against
+	/// place-and-cancel spam. Waived for an intention still in the block it was registered in
+	/// (a same-block cancellation is a caller catching their own mistake, not spam); charged once
+	/// it has survived into a later block via the carry-forward mechanism. Capped at the
+	/// intention's `amount_sell`, so cancelling never requires balance beyond what was reserved.
+	type CancellationFee: Get<Balance>;
+
+	/// If set, `cancel_pair` nets each asset's aggregate cancellation fee owed across every
+	/// intention it cancels for the caller into a single `update_balance` debit/credit pair,
+	/// instead of one `transfer` per cancelled intention. Reduces storage writes when a caller
+	/// cancels many queued intentions for the same pair at once; the fee charged per intention -
+	/// and the `CancellationFeeCharged` event for it - are unaffected either way.
+	type NetSettlementTransfers: Get<bool>;
+
+	/// If set, a direct trade whose counterparty's balance has dropped below what's needed for
+	/// their side of the match (e.g. spent elsewhere between registration and settlement) shrinks
+	/// that side down to whatever is actually available instead of failing the whole trade,
+	/// emitting `IntentionResolvedDirectTradePartialFill`. When unset (the default), any such
+	/// shortfall fails the trade exactly as before, via `InsufficientAssetBalanceEvent`.
+	type AllowPartialOnShortfall: Get<bool>;
+
+	/// Flat fee, denominated in the intention's own `asset_sell`, charged by
+	/// `set_intention_priority` when raising an intention's `priority` and paid to the pair
+	/// account - the same asset a caller with more to spare on the fee presumably has more to
+	/// spare on the trade too. Waived entirely when set to `0`.
+	type PriorityFee: Get<Balance>;
+
+	/// Bootstraps a pool on demand for `sell`/`buy`'s `create_if_missing` flag - see
+	/// `AllowPoolCreationOnDemand`.
+	type AMMTrader: primitives::traits::AMMTrader<Self::AccountId, AssetId, Balance, Price>;
+
+	/// Whether `sell`/`buy`'s `create_if_missing` flag is honoured at all. `false` makes the flag
+	/// a no-op, so a missing pool is always rejected with `TokenPoolNotFound` regardless of what
+	/// the caller passes - deployments have to opt in explicitly to letting an ordinary trade
+	/// extrinsic spend the caller's balance creating a pool as a side effect.
+	type AllowPoolCreationOnDemand: Get<bool>;
+
+	/// Floor a direct trade's fee is clamped up to, in `Module::calculate_fee` - without it, the
+	/// proportional fee rate rounds a small enough `amount` down to `0`, letting a trade slip
+	/// through fee-free. Never pushes the fee past the `amount` it's charged on.
+	type MinFee: Get<Balance>;
+
+	/// Ceiling a direct trade's fee is clamped down to, in `Module::calculate_fee` - without it,
+	/// the proportional fee rate charges an unbounded amount on a large enough trade.
+	type MaxFee: Get<Balance>;
+
+	/// Largest total encoded size, in bytes, `PendingIntentionsBytes` may reach before
+	/// `sell`/`buy` reject a new intention with `IntentionStorageBudgetExceeded` instead of
+	/// appending it - bounds this pallet's own contribution to a parachain block's proof size,
+	/// independent of how many intentions that comes out to.
+	type MaxIntentionsBytes: Get<u32>;
 }
 
 // This pallet's storage items.
@@ -63,9 +302,117 @@ decl_storage! {
 		/// Current intention count for current block
 		ExchangeAssetsIntentionCount get(fn get_intentions_count): map hasher(blake2_128_concat) (AssetId, AssetId) => u32;
 
+		/// Total number of intentions currently queued across every pair, kept in lockstep with
+		/// `ExchangeAssetsIntentionCount`. Read by `sell`/`buy`'s weight calculation so congestion
+		/// on any pair makes queuing another intention progressively more expensive.
+		///
+		/// `ExchangeAssetsIntentionCount` for the traded pair is also read there, since it is what
+		/// actually bounds `process_exchange_intentions`'s worst case for that pair - see
+		/// `WeightInfo::resolve_pair`.
+		TotalIntentions get(fn total_intentions): u32;
+
+		/// Running total of the encoded size, in bytes, of every intention appended to
+		/// `ExchangeAssetsIntentions` so far this block - both freshly registered ones and ones
+		/// carried forward past a settlement pass. Reset to `0` by `on_finalize` once it has
+		/// cleared and rebuilt `ExchangeAssetsIntentions` for the block; not otherwise
+		/// decremented as intentions are cancelled or expire mid-block, so it is a conservative
+		/// upper bound on what is actually in storage rather than a live-accurate count.
+		/// Compared against `T::MaxIntentionsBytes` in `sell`/`buy` to bound this pallet's own
+		/// contribution to a parachain block's proof size.
+		PendingIntentionsBytes get(fn pending_intentions_bytes): u32;
+
 		/// Registered intentions for current block
 		/// Always stored for ( asset_a, asset_b ) combination where asset_a < asset_B
 		ExchangeAssetsIntentions get(fn get_intentions): map hasher(blake2_128_concat) (AssetId, AssetId) => Vec<Intention<T>>;
+
+		/// Minimum required liquidity of either asset in a pair's account, below which `sell`/`buy`
+		/// against that pair are rejected. Settable per pair via `set_min_pool_liquidity`; a pair
+		/// with no entry defaults to `0`, i.e. no restriction. Always keyed by the sorted
+		/// `(min, max)` asset pair, regardless of trade direction.
+		PairMinLiquidity get(fn get_min_liquidity): map hasher(blake2_128_concat) (AssetId, AssetId) => Balance;
+
+		/// Default max slippage tolerance applied to a pair's `sell`/`buy` when the caller doesn't
+		/// provide their own `min_bought`/`max_sold`. Settable per pair via
+		/// `set_pair_max_slippage`; a pair with no entry defaults to `Permill::zero()`, i.e. no
+		/// default and the caller's own limit (or its absence) applies unchanged. Always keyed by
+		/// the sorted `(min, max)` asset pair, regardless of trade direction - see
+		/// `Module::effective_min_bought`/`Module::effective_max_sold` for how it combines with a
+		/// caller-provided limit.
+		PairMaxSlippage get(fn pair_max_slippage): map hasher(blake2_128_concat) (AssetId, AssetId) => Permill;
+
+		/// Per-asset override for the smallest `amount_sell` `sell` will accept for the sold
+		/// asset. Settable via `set_asset_min_trade_amount`; an asset with no entry (or
+		/// explicitly set to `0`) has no minimum enforced here. Exists because a single global
+		/// limit (`T::MinTradingLimit`) is unfair across assets with wildly different
+		/// decimals/values - see `Module::min_trade_amount` for the resolved effective minimum,
+		/// which does fall back to the global limit for informational purposes.
+		AssetMinTradeAmount get(fn asset_min_trade_amount): map hasher(blake2_128_concat) AssetId => Balance;
+
+		/// Assets currently frozen for trading by governance. `sell`/`buy` reject a trade if
+		/// either leg is frozen, and `on_finalize` skips settling any pair with a frozen leg,
+		/// carrying its registered intentions forward untouched until the asset is thawed.
+		FrozenAssets get(fn is_asset_frozen): map hasher(blake2_128_concat) AssetId => bool;
+
+		/// Global kill switch, settable via `pause`/`resume`. While `true`, `sell`/`buy` reject
+		/// every new intention - unlike `FrozenAssets`, which only blocks trading on specific
+		/// assets. Does NOT affect `on_finalize`, which keeps settling intentions already queued
+		/// before the pause - halting that too would strand funds reserved for trades mid-flight.
+		ExchangePaused get(fn is_exchange_paused): bool;
+
+		/// Accounts (e.g. protocol-owned liquidity) exempted by governance from direct-trade fees.
+		/// Settable via `set_fee_exempt`/`unset_fee_exempt`. Checked independently for each side of
+		/// a direct trade in `DirectTradeData::prepare` - an exempt account simply pays no fee for
+		/// its own side of the match, regardless of whether its counterparty is exempt too. Does
+		/// not affect AMM trading fees, which are charged by `T::AMMPool` itself and out of this
+		/// pallet's control.
+		FeeExempt get(fn is_fee_exempt): map hasher(blake2_128_concat) T::AccountId => bool;
+
+		/// Cumulative direct-trade fees paid to a pair's account so far, keyed by the sorted
+		/// `(min, max)` asset pair. Never reset - a pair with no entry has collected `0`.
+		CollectedFees get(fn get_collected_fees): map hasher(blake2_128_concat) (AssetId, AssetId) => Balance;
+
+		/// Last-settled price per pair, keyed by the sorted `(min, max)` asset pair, together with
+		/// the block it was recorded in. Updated whenever a direct match or an AMM trade settles
+		/// for the pair, from the AMM pool's live spot price at that moment - a cheap, always-
+		/// available price signal, not a TWAP. Use `Module::last_price` (or
+		/// `ExchangeApi::last_price`) rather than reading this directly, since a missing entry is
+		/// ambiguous between "never traded" and a genuine zero price.
+		LastPrice get(fn get_last_price_raw): map hasher(blake2_128_concat) (AssetId, AssetId) => (Balance, T::BlockNumber);
+
+		/// Lifecycle status of an intention - `Pending` from registration until settlement, then
+		/// its final status for exactly one more block. Use `Module::intention_status` (or
+		/// `ExchangeApi::intention_status`) rather than reading this directly, since a missing
+		/// entry is ambiguous between "never registered" and "cleaned up".
+		IntentionStatus get(fn get_intention_status_raw): map hasher(blake2_128_concat) IntentionId<T> => Status;
+
+		/// Ids given a non-`Pending` status during the current block - their `IntentionStatus`
+		/// entry is removed at the start of the next block's `on_finalize`, so a settled status
+		/// is only readable for the one block after it's set.
+		SettledIntentionIds get(fn get_settled_intention_ids): Vec<IntentionId<T>>;
+
+		/// Number of per-intention resolution events emitted so far during the current block's
+		/// `on_finalize` - reset to `0` at the start of each one. Compared against
+		/// `T::MaxEventsPerBlock` in `deposit_resolution_event`.
+		ResolutionEventsEmitted get(fn resolution_events_emitted): u32;
+
+		/// Every fill settled during the block's `on_finalize` - one `FillRecord` per direct-trade
+		/// leg and one per AMM fill. Cleared at the start of the following block's `on_initialize`,
+		/// so it stays readable against the block it was produced in for exactly one more block,
+		/// same as `SettledIntentionIds`. Exposed to external tools via
+		/// `ExchangeApi::last_block_fills`.
+		LastBlockFills get(fn get_last_block_fills): Vec<Fill<T>>;
+
+		/// Every intention settled during the block's `on_finalize`, keyed by the account whose
+		/// intention it was - one `SettlementRecord` per intention actually resolved, direct or
+		/// AMM. Cleared at the start of the following block's `on_initialize`, same as
+		/// `LastBlockFills`. Exposed to external tools via `ExchangeApi::account_settlements`.
+		LastBlockSettlements get(fn get_last_block_settlements): map hasher(blake2_128_concat) T::AccountId => Vec<Settlement<T>>;
+
+		/// Cumulative volume of an asset traded through this pallet, summed across every block
+		/// since genesis - never reset. Incremented in `record_fill` for both sides of every
+		/// settled leg, direct or AMM, so a single direct-trade match or AMM fill adds to both
+		/// assets' totals. An asset with no entry has traded `0`.
+		AssetVolume get(fn asset_volume): map hasher(blake2_128_concat) AssetId => Balance;
 	}
 }
 
@@ -75,20 +422,77 @@ decl_event!(
 	where
 		AccountId = <T as system::Config>::AccountId,
 		IntentionID = IntentionId<T>,
+		BlockNumber = <T as system::Config>::BlockNumber,
 	{
 		/// Intention registered event
-		/// who, asset a, asset b, amount, intention type, intention id
-		IntentionRegistered(AccountId, AssetId, AssetId, Balance, IntentionType, IntentionID),
+		/// who, asset a, asset b, amount, intention type, intention id, client reference, block
+		/// number - block number alone doesn't disambiguate a re-org replay (see
+		/// `IntentionReceiptIssued`, deposited alongside this event for that purpose), but is still
+		/// useful context for consumers that only care about ordering within a fork.
+		IntentionRegistered(AccountId, AssetId, AssetId, Balance, IntentionType, IntentionID, Option<[u8; 32]>, BlockNumber),
 
-		/// Intention resolved as AMM Trade
-		/// who, intention type, intention id, amount, amount sold/bought
-		IntentionResolvedAMMTrade(AccountId, IntentionType, IntentionID, Balance, Balance),
+		/// Deposited immediately after `IntentionRegistered`, for the same intention - see
+		/// `IntentionReceipt` for why this is needed in addition to it.
+		IntentionReceiptIssued(IntentionReceipt<IntentionID>),
 
-		IntentionResolvedDirectTrade(AccountId, AccountId, IntentionID, IntentionID, Balance, Balance),
+		/// A pending intention's amount was updated in place via `replace_intention`.
+		/// who, asset sell, asset buy, intention id, new amount
+		IntentionReplaced(AccountId, AssetId, AssetId, IntentionID, Balance),
+
+		/// A pending intention's matching priority was raised in place via
+		/// `set_intention_priority`.
+		/// who, asset sell, asset buy, intention id, new priority
+		IntentionPrioritySet(AccountId, AssetId, AssetId, IntentionID, u8),
+
+		/// A pending intention's `trade_limit` was updated in place via `amend_limit_price`.
+		/// who, asset sell, asset buy, intention id, new trade limit
+		IntentionLimitPriceAmended(AccountId, AssetId, AssetId, IntentionID, Balance),
+
+		/// Intention resolved as AMM Trade
+		/// who, intention type, intention id, amount, amount sold/bought, client reference, block
+		/// number - see `IntentionRegistered` for why the block number is included
+		IntentionResolvedAMMTrade(AccountId, IntentionType, IntentionID, Balance, Balance, Option<[u8; 32]>, BlockNumber),
+
+		/// intention a's who, intention b's who, intention a id, intention b id, amount from a,
+		/// amount from b, intention a's client reference, intention b's client reference, block
+		/// number, intention a's remaining amount after this match - see `IntentionRegistered` for
+		/// why the block number is included
+		IntentionResolvedDirectTrade(
+			AccountId,
+			AccountId,
+			IntentionID,
+			IntentionID,
+			Balance,
+			Balance,
+			Option<[u8; 32]>,
+			Option<[u8; 32]>,
+			BlockNumber,
+			Balance,
+		),
 		IntentionResolvedDirectTradeFees(AccountId, AccountId, AssetId, Balance),
 
+		/// A direct trade's reserved-funds repatriation failed part-way through, rolling the
+		/// whole trade back - the caller falls back to another resolution path.
+		/// from, to, asset, amount, error
+		DirectTransferFailed(AccountId, AccountId, AssetId, Balance, dispatch::DispatchError),
+
 		InsufficientAssetBalanceEvent(AccountId, AssetId, IntentionType, IntentionID, dispatch::DispatchError),
 
+		/// A direct trade's counterparty balance had dropped below what its side of the match
+		/// needed, and `T::AllowPartialOnShortfall` is set - the side was shrunk down to whatever
+		/// was available instead of failing the trade.
+		/// who, asset, intention type, intention id, originally requested amount, amount settled
+		IntentionResolvedDirectTradePartialFill(AccountId, AssetId, IntentionType, IntentionID, Balance, Balance),
+
+		/// The matcher paired an intention against another intention from the very same account -
+		/// direct-trading the two would just be a self-transfer that nets to a fee-only loss for
+		/// `who`, so the pairing was skipped instead, the same way an
+		/// `AssetBalanceLimitExceeded` match would be. The main intention (the first id) still
+		/// falls through to its AMM fallback, if any, exactly as if this counterparty hadn't been
+		/// offered at all.
+		/// who, main intention id, skipped counterparty's intention id
+		SelfMatchSkipped(AccountId, IntentionID, IntentionID),
+
 		//Note: This event can be used instead of AMMSellErrorEvent, AMMBuyErrorEvent
 		IntentionResolveErrorEvent(
 			AccountId,
@@ -99,6 +503,7 @@ decl_event!(
 			dispatch::DispatchError,
 		),
 
+		/// who, asset sell, asset buy, intention type, intention id, error, classified reason
 		AMMSellErrorEvent(
 			AccountId,
 			AssetId,
@@ -106,7 +511,9 @@ decl_event!(
 			IntentionType,
 			IntentionID,
 			dispatch::DispatchError,
+			AMMFailureReason,
 		),
+		/// who, asset buy, asset sell, intention type, intention id, error, classified reason
 		AMMBuyErrorEvent(
 			AccountId,
 			AssetId,
@@ -114,7 +521,134 @@ decl_event!(
 			IntentionType,
 			IntentionID,
 			dispatch::DispatchError,
+			AMMFailureReason,
 		),
+
+		/// Pool for a pair no longer exists at settlement time. Defensive fallback only - as long
+		/// as `T::AMMPool`'s pools are destroyed exclusively via a path that calls
+		/// `purge_pair_intentions` (as `pallet_amm`'s `remove_liquidity` does), queued intentions
+		/// for the pair are already gone by the time `on_finalize` would see this. When it does
+		/// trigger, every queued intention for the pair is unreserved and dropped `Failed` rather
+		/// than carried forward, since there's no pool left to eventually settle against.
+		/// asset a, asset b.
+		PoolRemovedBeforeSettlement(AssetId, AssetId),
+
+		/// Pool for a pair still exists but at least one side's reserve has dropped below
+		/// `T::MinPoolReserve` - close to empty enough that `calculate_spot_price` would return an
+		/// unstable price, so this pair's intentions are left unsettled this block rather than
+		/// matched or AMM-routed against it.
+		/// asset a, asset b - registered intentions for this pair are left unsettled.
+		PoolReservesTooLow(AssetId, AssetId),
+
+		/// One of a pair's assets lost its issuance in `T::Currency` between registration and
+		/// settlement (e.g. it was removed). Same treatment as `PoolRemovedBeforeSettlement` -
+		/// every queued intention for the pair is unreserved and dropped `Failed` rather than
+		/// carried forward, since there's nothing left to eventually settle against.
+		/// asset a, asset b.
+		AssetRemovedBeforeSettlement(AssetId, AssetId),
+
+		/// Funds were reserved to guarantee a direct trade transfer.
+		/// who, asset, amount, intention id
+		FundsReserved(AccountId, AssetId, Balance, IntentionID),
+
+		/// Previously reserved funds were released back to their owner, either because the
+		/// direct trade they were reserved for failed to prepare or because it settled.
+		/// who, asset, amount, intention id
+		FundsUnreserved(AccountId, AssetId, Balance, IntentionID),
+
+		/// An intention with `allow_amm_fallback` set to `false` had leftover amount after direct
+		/// matching and was dropped instead of being routed through the AMM - the leftover was
+		/// unreserved back to its owner.
+		/// who, asset, amount, intention id
+		IntentionUnmatched(AccountId, AssetId, Balance, IntentionID),
+
+		/// Two intentions found and matched by an off-chain worker were settled directly,
+		/// bypassing the next `on_finalize`'s own matching.
+		/// intention a id, intention b id
+		IntentionsSettledOffchain(IntentionID, IntentionID),
+
+		/// The minimum pool liquidity required to trade a pair was set by governance.
+		/// asset a, asset b, minimum liquidity
+		MinPoolLiquiditySet(AssetId, AssetId, Balance),
+
+		/// A pair's default max slippage tolerance was set by governance.
+		/// asset a, asset b, max slippage
+		PairMaxSlippageSet(AssetId, AssetId, Permill),
+
+		/// An AMM fallback trade failed at an intention's full amount and was retried at a
+		/// smaller amount by `EnablePartialAMMFill`, filling only part of the intention.
+		/// who, intention type, intention id, amount, amount sold/bought, block number - see
+		/// `IntentionRegistered` for why the block number is included
+		IntentionResolvedAMMTradePartialFill(AccountId, IntentionType, IntentionID, Balance, Balance, BlockNumber),
+
+		/// An asset was frozen for trading by governance. asset
+		AssetFrozen(AssetId),
+
+		/// A previously frozen asset was thawed by governance. asset
+		AssetThawed(AssetId),
+
+		/// A direct trade fee was repatriated to a pair's account. asset, fee amount, pair account
+		FeeCollected(AssetId, Balance, AccountId),
+
+		/// `direct_trade_fee` computed a fee that would have equalled or exceeded the small amount
+		/// it was based on - rather than aborting the whole direct trade over a dust-sized leg, the
+		/// entire amount was charged as fee instead. who, asset, amount, intention id
+		DustToFee(AccountId, AssetId, Balance, IntentionID),
+
+		/// An account was exempted from direct-trade fees by governance. who
+		FeeExemptionGranted(AccountId),
+
+		/// A previously exempted account's fee exemption was revoked by governance. who
+		FeeExemptionRevoked(AccountId),
+
+		/// Emitted from `on_finalize` and from `settle_pair`, summarizing how a settlement pass's
+		/// settled intentions were filled - skipped entirely if nothing settled.
+		/// matched volume (settled directly against another intention), amm volume (settled via
+		/// an AMM pool, either as a fallback or as the only possible route)
+		BlockSettlementSummary(Balance, Balance),
+
+		/// An intention wasn't matched or AMM-routed this block and survived into the next one
+		/// instead of being wiped - distinct from a partial fill, which reduces an intention's
+		/// amount but never carries it forward as-is. Not emitted for an intention resolved
+		/// (fully or partially) in the same block it was registered in.
+		/// intention id, remaining lifetime after this block
+		IntentionCarriedForward(IntentionID, BlockNumber),
+
+		/// `cancel_pair` removed this many of the caller's own queued intentions for a pair -
+		/// intentions belonging to other accounts on the same pair are left untouched.
+		/// who, asset sell, asset buy, number of intentions removed
+		IntentionsCancelledForPair(AccountId, AssetId, AssetId, u32),
+
+		/// `cancel_pair` charged `T::CancellationFee` on a cancelled intention that had already
+		/// survived into a later block - waived for same-block cancellations.
+		/// who, asset, amount, intention id
+		CancellationFeeCharged(AccountId, AssetId, Balance, IntentionID),
+
+		/// An asset's minimum trade amount override was set by governance. asset, minimum amount
+		AssetMinTradeAmountSet(AssetId, Balance),
+
+		/// The exchange was paused by governance - `sell`/`buy` reject every new intention until
+		/// `ExchangeResumed`. Already-queued intentions keep settling in `on_finalize`.
+		ExchangePaused,
+
+		/// A previous `ExchangePaused` was lifted by governance.
+		ExchangeResumed,
+
+		/// An intention with amount left over after direct matching - whether it was never
+		/// matched at all, only partially matched, or is the B side of a group that couldn't
+		/// absorb it all - had that leftover routed through the AMM instead. Emitted for both
+		/// sides alike, so neither is silently treated differently from the other.
+		/// intention id, intention type
+		LeftoverRoutedToAMM(IntentionID, IntentionType),
+
+		/// Among a pair's fills settled this pass, at least two intentions sold the exact same
+		/// `amount_sell` - reported so an observer can confirm the batch auction priced them the
+		/// same way rather than favouring one over the other by queue position. Skipped for a
+		/// group of one, since there's nothing to compare against.
+		/// asset sell, asset buy, the shared amount sold, average realized price across the
+		/// group, largest single deviation from that average - price and deviation both scaled by
+		/// `PRICE_PROXIMITY_PRECISION`, same as `FillRecord::price`.
+		FairnessReport(AssetId, AssetId, Balance, Balance, Balance),
 	}
 );
 
@@ -135,317 +669,2679 @@ decl_error! {
 
 		/// Limit exceeded
 		AssetBalanceLimitExceeded,
+
+		/// AMM trade price deviates from the price oracle by more than `MaxPriceDeviation`.
+		PriceDeviationTooLarge,
+
+		/// AMM fallback trade would move the pool price by more than `MaxPriceImpact`.
+		PriceImpactTooHigh,
+
+		/// Pair account holds less than `PairMinLiquidity` of one of the traded assets.
+		PoolLiquidityBelowMinimum,
+
+		/// An intention id passed to `settle_offchain_match` is not currently registered - it may
+		/// already have settled or never existed.
+		IntentionNotFound,
+
+		/// The two intentions passed to `settle_offchain_match` are not an exact opposite match.
+		IntentionsNotMatched,
+
+		/// One of the traded assets is currently frozen for trading by governance.
+		AssetFrozen,
+
+		/// `sell`/`buy` was called with an amount of `0`.
+		ZeroAmount,
+
+		/// An intention's `remaining_lifetime` reached `0` without being matched or AMM-routed.
+		IntentionExpired,
+
+		/// `replace_intention` was called by an account other than the intention's own `who`.
+		NotIntentionOwner,
+
+		/// `set_intention_priority` was called with a `priority` no higher than the intention
+		/// already has - priority can only be raised, never lowered or left unchanged.
+		PriorityNotIncreased,
+
+		/// `amount_sell` is below the sold asset's minimum trade amount - either
+		/// `AssetMinTradeAmount`'s override for it, or `MinTradingLimit` if it has none.
+		BelowMinTradeAmount,
+
+		/// `sell`/`buy` was called while `ExchangePaused` is set.
+		ExchangePaused,
+
+		/// `asset_sell` or `asset_buy` has no issuance in `T::Currency` - most likely because it
+		/// was never a real asset to begin with, or was removed since. `free_balance` and the
+		/// like silently treat such an asset as an empty-but-valid account instead of erroring,
+		/// so this is checked explicitly rather than left to fail confusingly downstream.
+		UnknownAsset,
+
+		/// Appending the new intention would push `PendingIntentionsBytes` past
+		/// `T::MaxIntentionsBytes`.
+		IntentionStorageBudgetExceeded,
 	}
 }
 
+/// Number of intentions read from storage per `get_intentions_page` call.
+const OFFCHAIN_MATCHER_PAGE_SIZE: u32 = 64;
+
+/// Fixed-point scale used to compare an intention's own registered price against the pair's
+/// current AMM spot price in `sort_by_price_proximity` - arbitrary beyond needing enough
+/// precision that truncation in the division doesn't dominate the comparison.
+const PRICE_PROXIMITY_PRECISION: Balance = 1_000_000_000_000;
+
 decl_module! {
 	/// The module declaration.
 	pub struct Module<T: Config> for enum Call where origin: T::Origin {
 
-		type Error = Error<T>;
+		type Error = Error<T>;
+
+		fn deposit_event() = default;
+
+		/// The smallest amount an intention (or a retry of one) is allowed to trade at - see
+		/// `T::MinTradingLimit`.
+		const MinTradingLimit: Balance = T::MinTradingLimit::get();
+
+		/// The asset routed through as an intermediary when a `SELL` has no direct pool for its
+		/// pair and `T::EnableRouting` is set - see `T::NativeAssetId`.
+		const NativeAssetId: AssetId = T::NativeAssetId::get();
+
+		/// The fee charged on a cancelled intention that had already survived into a later block -
+		/// see `T::CancellationFee`.
+		const CancellationFee: Balance = T::CancellationFee::get();
+
+		/// The most per-intention resolution events `on_finalize` will emit in a single block
+		/// before suppressing further ones in favour of `BlockSettlementSummary`'s aggregate
+		/// totals - see `T::MaxEventsPerBlock`.
+		const MaxEventsPerBlock: u32 = T::MaxEventsPerBlock::get();
+
+		/// Create sell intention
+		/// Calculate current spot price, create an intention and store in ```ExchangeAssetsIntentions```
+		/// `who` is always debited the sold asset. If `recipient` is set, the bought asset is
+		/// delivered to it instead of `who`. If `valid_until_timestamp` is set, the intention is
+		/// dropped once `pallet_timestamp::Now` reaches it, as an alternative to the block-number
+		/// based `remaining_lifetime` expiry. `reference` is an opaque client-supplied id echoed
+		/// back in this intention's registration and resolution events - no on-chain logic depends
+		/// on it. If `allow_amm_fallback` is `false`, any amount left over after direct P2P
+		/// matching is dropped and unreserved instead of being routed through the AMM. If
+		/// `create_if_missing` is set and `T::AllowPoolCreationOnDemand` allows it, a missing
+		/// `asset_sell`/`asset_buy` pool is created via `T::AMMTrader` - seeded with
+		/// `initial_liquidity` of `asset_sell` and whatever `initial_price` implies of `asset_buy`,
+		/// both debited from `who` - instead of rejecting with `TokenPoolNotFound`.
+		#[weight =  <T as Config>::WeightInfo::sell(TotalIntentions::get()) + <T as Config>::WeightInfo::resolve_pair(ExchangeAssetsIntentionCount::get(canonical_pair(asset_sell, asset_buy))) + <T as Config>::WeightInfo::on_finalize_for_one_sell_extrinsic() -  <T as Config>::WeightInfo::known_overhead_for_on_finalize()]
+		pub fn sell(
+			origin,
+			asset_sell: AssetId,
+			asset_buy: AssetId,
+			amount_sell: Balance,
+			min_bought: Balance,
+			discount: bool,
+			recipient: Option<T::AccountId>,
+			valid_until_timestamp: Option<T::Moment>,
+			reference: Option<[u8; 32]>,
+			allow_amm_fallback: bool,
+			create_if_missing: bool,
+			initial_liquidity: Balance,
+			initial_price: Price,
+		)  -> dispatch::DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+
+			if create_if_missing && T::AllowPoolCreationOnDemand::get() && !T::AMMPool::exists(asset_sell, asset_buy) {
+				T::AMMTrader::create_pool(&who, asset_sell, asset_buy, initial_liquidity, initial_price)?;
+			}
+
+			Self::register_sell_intention(who, asset_sell, asset_buy, amount_sell, min_bought, discount, recipient, valid_until_timestamp, reference, allow_amm_fallback)
+		}
+
+		/// Sell the caller's entire usable balance of `asset_sell`, i.e. its free balance less
+		/// whatever must be kept to stay above `asset_sell`'s existential deposit. Registers a
+		/// `SELL` intention for the remainder, same as `sell` with `min_bought` of `0`. Rejects
+		/// if the usable amount is below `MinTradingLimit`.
+		#[weight =  <T as Config>::WeightInfo::sell_all_intention() + <T as Config>::WeightInfo::on_finalize_for_one_sell_extrinsic() -  <T as Config>::WeightInfo::known_overhead_for_on_finalize()]
+		pub fn sell_all(
+			origin,
+			asset_sell: AssetId,
+			asset_buy: AssetId,
+			discount: bool,
+		) -> dispatch::DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+
+			let usable_amount =
+				T::Currency::free_balance(asset_sell, &who).saturating_sub(T::Currency::minimum_balance(asset_sell));
+
+			if usable_amount < T::MinTradingLimit::get() {
+				return Err(Error::<T>::InsufficientAssetBalance.with_weight(T::WeightInfo::reject_intention()));
+			}
+
+			Self::register_sell_intention(who, asset_sell, asset_buy, usable_amount, 0, discount, None, None, None, true)
+		}
+
+		/// Dry-run the preconditions `sell` would check, via the same `validate_sell_registration`
+		/// the real extrinsic uses, without registering an intention, mutating any storage, or
+		/// emitting `IntentionRegistered`. Lets integrators cheaply validate a trade before
+		/// submitting it, with no risk of the dry run passing something registration would reject.
+		#[weight = <T as Config>::WeightInfo::reject_intention()]
+		pub fn validate_sell(
+			origin,
+			asset_sell: AssetId,
+			asset_buy: AssetId,
+			amount: Balance,
+			_discount: bool,
+		) -> dispatch::DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			Self::validate_sell_registration(&who, asset_sell, asset_buy, amount, None)
+		}
+
+		/// Create buy intention
+		/// Calculate current spot price, create an intention and store in ```ExchangeAssetsIntentions```
+		/// `who` is always debited the sold asset. If `recipient` is set, the bought asset is
+		/// delivered to it instead of `who`. If `valid_until_timestamp` is set, the intention is
+		/// dropped once `pallet_timestamp::Now` reaches it, as an alternative to the block-number
+		/// based `remaining_lifetime` expiry. `reference` is an opaque client-supplied id echoed
+		/// back in this intention's registration and resolution events - no on-chain logic depends
+		/// on it. If `allow_amm_fallback` is `false`, any amount left over after direct P2P
+		/// matching is dropped and unreserved instead of being routed through the AMM. If
+		/// `create_if_missing` is set and `T::AllowPoolCreationOnDemand` allows it, a missing
+		/// `asset_sell`/`asset_buy` pool is created via `T::AMMTrader` - seeded with
+		/// `initial_liquidity` of `asset_sell` and whatever `initial_price` implies of `asset_buy`,
+		/// both debited from `who` - instead of rejecting with `TokenPoolNotFound`.
+		#[weight =  <T as Config>::WeightInfo::buy(TotalIntentions::get()) + <T as Config>::WeightInfo::resolve_pair(ExchangeAssetsIntentionCount::get(canonical_pair(asset_sell, asset_buy))) + <T as Config>::WeightInfo::on_finalize_for_one_buy_extrinsic() -  <T as Config>::WeightInfo::known_overhead_for_on_finalize()]
+		pub fn buy(
+			origin,
+			asset_buy: AssetId,
+			asset_sell: AssetId,
+			amount_buy: Balance,
+			max_sold: Balance,
+			discount: bool,
+			recipient: Option<T::AccountId>,
+			valid_until_timestamp: Option<T::Moment>,
+			reference: Option<[u8; 32]>,
+			allow_amm_fallback: bool,
+			create_if_missing: bool,
+			initial_liquidity: Balance,
+			initial_price: Price,
+		)  -> dispatch::DispatchResultWithPostInfo {
+			let who = ensure_signed(origin)?;
+
+			if create_if_missing && T::AllowPoolCreationOnDemand::get() && !T::AMMPool::exists(asset_sell, asset_buy) {
+				T::AMMTrader::create_pool(&who, asset_sell, asset_buy, initial_liquidity, initial_price)?;
+			}
+
+			Self::do_register_buy_intention(
+				who,
+				asset_buy,
+				asset_sell,
+				amount_buy,
+				max_sold,
+				discount,
+				recipient,
+				valid_until_timestamp,
+				reference,
+				allow_amm_fallback,
+			)
+			.map(|_| ().into())
+		}
+
+		/// Update a still-pending intention's amount in place, instead of cancelling and
+		/// resubmitting it - which would lose both its `intention_id` and its
+		/// `remaining_lifetime` countdown.
+		///
+		/// `new_amount` replaces `amount_sell` for a `SELL` intention or `amount_buy` for a `BUY`
+		/// intention - the same amount `sell`/`buy` would have taken. The paired estimate
+		/// (`amount_buy` for `SELL`, `amount_sell` for `BUY`) is recomputed from the current spot
+		/// price, exactly as registration does. Only the intention's own `who` may call this.
+		#[weight = <T as Config>::WeightInfo::replace_intention()]
+		pub fn replace_intention(
+			origin,
+			asset_sell: AssetId,
+			asset_buy: AssetId,
+			intention_id: IntentionId<T>,
+			new_amount: Balance,
+		) -> dispatch::DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			if new_amount.is_zero() {
+				return Err(Error::<T>::ZeroAmount.into());
+			}
+
+			let mut intentions = <ExchangeAssetsIntentions<T>>::get((asset_sell, asset_buy));
+
+			let intention = intentions
+				.iter_mut()
+				.find(|i| i.intention_id == intention_id)
+				.ok_or(Error::<T>::IntentionNotFound)?;
+
+			ensure!(intention.who == who, Error::<T>::NotIntentionOwner);
+
+			match intention.sell_or_buy {
+				IntentionType::SELL => {
+					ensure!(
+						T::Currency::free_balance(asset_sell, &who) >= new_amount,
+						Error::<T>::InsufficientAssetBalance
+					);
+
+					intention.amount_sell = new_amount;
+					intention.amount_buy = T::AMMPool::get_spot_price_unchecked(asset_sell, asset_buy, new_amount);
+				}
+				IntentionType::BUY => {
+					let required_sell = T::AMMPool::get_spot_price_unchecked(asset_buy, asset_sell, new_amount);
+
+					ensure!(
+						T::Currency::free_balance(asset_sell, &who) >= required_sell,
+						Error::<T>::InsufficientAssetBalance
+					);
+
+					intention.amount_buy = new_amount;
+					intention.amount_sell = required_sell;
+				}
+			}
+
+			<ExchangeAssetsIntentions<T>>::insert((asset_sell, asset_buy), intentions);
+
+			Self::deposit_event(RawEvent::IntentionReplaced(who, asset_sell, asset_buy, intention_id, new_amount));
+
+			Ok(())
+		}
+
+		/// Raise a still-pending intention's matching priority - higher goes first in
+		/// `process_exchange_intentions`. Charges `T::PriorityFee`, denominated in the intention's
+		/// own `asset_sell`, to the pair account. Priority can only be raised, never lowered, so a
+		/// caller can't cycle it up and down to jump the queue for free. Only the intention's own
+		/// `who` may call this.
+		#[weight = <T as Config>::WeightInfo::set_intention_priority()]
+		pub fn set_intention_priority(
+			origin,
+			asset_sell: AssetId,
+			asset_buy: AssetId,
+			intention_id: IntentionId<T>,
+			priority: u8,
+		) -> dispatch::DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let mut intentions = <ExchangeAssetsIntentions<T>>::get((asset_sell, asset_buy));
+
+			let intention = intentions
+				.iter_mut()
+				.find(|i| i.intention_id == intention_id)
+				.ok_or(Error::<T>::IntentionNotFound)?;
+
+			ensure!(intention.who == who, Error::<T>::NotIntentionOwner);
+			ensure!(priority > intention.priority, Error::<T>::PriorityNotIncreased);
+
+			let fee = T::PriorityFee::get();
+			if !fee.is_zero() {
+				let pair_account = T::AMMPool::get_pair_id(&asset_sell, &asset_buy);
+				T::Currency::transfer(asset_sell, &who, &pair_account, fee)?;
+			}
+
+			intention.priority = priority;
+
+			<ExchangeAssetsIntentions<T>>::insert((asset_sell, asset_buy), intentions);
+
+			Self::deposit_event(RawEvent::IntentionPrioritySet(who, asset_sell, asset_buy, intention_id, priority));
+
+			Ok(())
+		}
+
+		/// Update a still-pending intention's `trade_limit` in place, instead of cancelling and
+		/// resubmitting it. This pallet doesn't have a separate resting `LIMIT` order type -
+		/// every `SELL`/`BUY` intention already carries `trade_limit` as its own worst-acceptable
+		/// price, so this amends that price on whichever of the two the intention already is.
+		/// Touches neither the intention's reserved amount nor its position in the matching
+		/// queue. Only the intention's own `who` may call this.
+		#[weight = <T as Config>::WeightInfo::amend_limit_price()]
+		pub fn amend_limit_price(
+			origin,
+			asset_sell: AssetId,
+			asset_buy: AssetId,
+			intention_id: IntentionId<T>,
+			new_price: Balance,
+		) -> dispatch::DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			if new_price.is_zero() {
+				return Err(Error::<T>::ZeroAmount.into());
+			}
+
+			let mut intentions = <ExchangeAssetsIntentions<T>>::get((asset_sell, asset_buy));
+
+			let intention = intentions
+				.iter_mut()
+				.find(|i| i.intention_id == intention_id)
+				.ok_or(Error::<T>::IntentionNotFound)?;
+
+			ensure!(intention.who == who, Error::<T>::NotIntentionOwner);
+
+			intention.trade_limit = new_price;
+
+			<ExchangeAssetsIntentions<T>>::insert((asset_sell, asset_buy), intentions);
+
+			Self::deposit_event(RawEvent::IntentionLimitPriceAmended(who, asset_sell, asset_buy, intention_id, new_price));
+
+			Ok(())
+		}
+
+		/// Remove every intention the caller has queued for `(asset_sell, asset_buy)`, in either
+		/// direction, without touching their intentions for any other pair - unlike a hypothetical
+		/// cancel-everything extrinsic, which doesn't exist in this pallet. Meant for market makers
+		/// who want to pull their quotes on one pair to requote it, without cancelling and
+		/// resubmitting orders on every other pair they're quoting.
+		#[weight = <T as Config>::WeightInfo::cancel_pair(
+			<ExchangeAssetsIntentions<T>>::decode_len((asset_sell, asset_buy)).unwrap_or(0) as u32
+				+ <ExchangeAssetsIntentions<T>>::decode_len((asset_buy, asset_sell)).unwrap_or(0) as u32
+		)]
+		pub fn cancel_pair(origin, asset_sell: AssetId, asset_buy: AssetId) -> dispatch::DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let pair = canonical_pair(asset_sell, asset_buy);
+			let reverse = (pair.1, pair.0);
+			let pair_account = T::AMMPool::get_pair_id(&pair.0, &pair.1);
+
+			let mut removed = 0u32;
+
+			// Only populated when `T::NetSettlementTransfers` is enabled - the aggregate fee owed
+			// per asset across every cancelled intention selling it, along with the per-intention
+			// amounts needed to still emit one `CancellationFeeCharged` event each once the netted
+			// transfer lands.
+			let mut net_fees: Vec<(AssetId, Balance, Vec<(IntentionId<T>, Balance)>)> = Vec::new();
+
+			for key in [pair, reverse].iter() {
+				let (mine, others): (Vec<_>, Vec<_>) =
+					<ExchangeAssetsIntentions<T>>::get(*key).into_iter().partition(|i| i.who == who);
+
+				if mine.is_empty() {
+					continue;
+				}
+
+				removed = removed.saturating_add(mine.len() as u32);
+
+				if others.is_empty() {
+					<ExchangeAssetsIntentions<T>>::remove(*key);
+				} else {
+					<ExchangeAssetsIntentions<T>>::insert(*key, others);
+				}
+
+				for intention in mine {
+					T::Currency::unreserve(intention.asset_sell, &intention.who, intention.amount_sell);
+
+					// Waive the fee for a same-block cancellation - the caller is just catching
+					// their own mistake, not sitting on a stale order.
+					if intention.remaining_lifetime != T::DefaultIntentionLifetime::get() {
+						let fee = T::CancellationFee::get().min(intention.amount_sell);
+						if !fee.is_zero() {
+							if T::NetSettlementTransfers::get() {
+								match net_fees.iter_mut().find(|(asset, _, _)| *asset == intention.asset_sell) {
+									Some((_, total, charges)) => {
+										*total = total.saturating_add(fee);
+										charges.push((intention.intention_id, fee));
+									}
+									None => net_fees.push((intention.asset_sell, fee, vec![(intention.intention_id, fee)])),
+								}
+							} else if T::Currency::transfer(intention.asset_sell, &intention.who, &pair_account, fee).is_ok() {
+								Self::deposit_event(RawEvent::CancellationFeeCharged(
+									intention.who.clone(),
+									intention.asset_sell,
+									fee,
+									intention.intention_id,
+								));
+							}
+						}
+					}
+
+					Self::set_intention_status(intention.intention_id, Status::Cancelled);
+					Self::deposit_event(RawEvent::FundsUnreserved(
+						intention.who,
+						intention.asset_sell,
+						intention.amount_sell,
+						intention.intention_id,
+					));
+				}
+			}
+
+			// Apply each asset's netted fee total as a single debit/credit pair, rolled back
+			// together if either leg fails - the same all-or-nothing guarantee the per-intention
+			// `transfer` above gets for free.
+			for (asset, total, charges) in net_fees {
+				let net_applied = TryInto::<i128>::try_into(total).ok().map_or(false, |amount| {
+					with_transaction(|| {
+						if T::Currency::update_balance(asset, &who, amount.saturating_neg()).is_ok()
+							&& T::Currency::update_balance(asset, &pair_account, amount).is_ok()
+						{
+							TransactionOutcome::Commit(true)
+						} else {
+							TransactionOutcome::Rollback(false)
+						}
+					})
+				});
+
+				if net_applied {
+					for (intention_id, fee) in charges {
+						Self::deposit_event(RawEvent::CancellationFeeCharged(who.clone(), asset, fee, intention_id));
+					}
+				}
+			}
+
+			if removed > 0 {
+				ExchangeAssetsIntentionCount::mutate(pair, |count| *count = count.saturating_sub(removed));
+				TotalIntentions::mutate(|total| *total = total.saturating_sub(removed));
+			}
+
+			Self::deposit_event(RawEvent::IntentionsCancelledForPair(who, asset_sell, asset_buy, removed));
+
+			Ok(())
+		}
+
+		fn on_initialize() -> Weight {
+			// The previous block's fills are still readable up to this point - clear them only now
+			// that a new block has actually started, rather than at the end of the block that
+			// produced them.
+			LastBlockFills::<T>::kill();
+			LastBlockSettlements::<T>::remove_all();
+
+			T::WeightInfo::known_overhead_for_on_finalize()
+		}
+
+		/// Finalize and resolve all registered intentions.
+		/// Group/match intentions which can be directly traded.
+		fn on_finalize(){
+			// A settled status is only readable for the one block after it was set - clear
+			// whatever was settled last block before this block starts settling its own.
+			for id in SettledIntentionIds::<T>::take() {
+				IntentionStatus::<T>::remove(id);
+			}
+
+			ResolutionEventsEmitted::kill();
+
+			// Nothing was registered this block and nothing carried forward from the last one -
+			// `ExchangeAssetsIntentionCount` is empty, so skip iterating and clearing it entirely
+			// rather than paying for an empty `iter()` and two no-op `remove_all()`s every block.
+			// The byte budget is still reset, since intentions cancelled or expired mid-block
+			// leave their bytes counted.
+			if TotalIntentions::get() == 0u32 {
+				PendingIntentionsBytes::kill();
+				return;
+			}
+
+			// Intentions which couldn't be matched or AMM-routed this block but still have
+			// retries left - carried over to the next block's storage once it has been cleared.
+			let mut carry_forward: Vec<((AssetId, AssetId), Vec<Intention<T>>)> = Vec::new();
+
+			// `iter()`'s order is hash-dependent and non-deterministic across storage layouts -
+			// settle pairs in a fixed, deterministic order instead, so one pair's settlement
+			// affecting another via a shared asset doesn't depend on storage internals.
+			let mut pairs: Vec<((AssetId, AssetId), u32)> = ExchangeAssetsIntentionCount::iter().collect();
+			pairs.sort_by_key(|(pair, _)| *pair);
+
+			let mut matched_volume_total: Balance = Zero::zero();
+			let mut amm_volume_total: Balance = Zero::zero();
+
+			for ((asset_1, asset_2), count) in pairs {
+				// If no intention registered for asset1/2, move onto next one
+				if count == 0u32 {
+					continue;
+				}
+
+				let asset_a_sells = Self::intentions_selling(asset_2, asset_1);
+				let asset_b_sells = Self::intentions_selling(asset_1, asset_2);
+
+				if !T::AMMPool::exists(asset_1, asset_2) {
+					Self::deposit_event(RawEvent::PoolRemovedBeforeSettlement(asset_1, asset_2));
+					// The pool is gone, not just thin - there's nothing left to carry these
+					// forward to, so drop and unreserve them now instead of leaving their funds
+					// reserved forever once `ExchangeAssetsIntentions` is wiped below.
+					for intention in asset_a_sells.into_iter().chain(asset_b_sells) {
+						Self::unreserve_and_notify_unmatched(&intention);
+					}
+					continue;
+				}
+
+				if !Self::asset_known(asset_1) || !Self::asset_known(asset_2) {
+					Self::deposit_event(RawEvent::AssetRemovedBeforeSettlement(asset_1, asset_2));
+					// Registration already checked this, but an asset can still be removed from
+					// `T::Currency` in between - same treatment as the pool itself disappearing.
+					for intention in asset_a_sells.into_iter().chain(asset_b_sells) {
+						Self::unreserve_and_notify_unmatched(&intention);
+					}
+					continue;
+				}
+
+				if !Self::has_sufficient_pool_reserves(asset_1, asset_2) {
+					Self::deposit_event(RawEvent::PoolReservesTooLow(asset_1, asset_2));
+					let low_reserve_intentions = asset_a_sells.into_iter().chain(asset_b_sells).collect::<Vec<_>>();
+					if !low_reserve_intentions.is_empty() {
+						carry_forward.push(((asset_1, asset_2), low_reserve_intentions));
+					}
+					continue;
+				}
+
+				if FrozenAssets::get(asset_1) || FrozenAssets::get(asset_2) {
+					let frozen_intentions = asset_a_sells.into_iter().chain(asset_b_sells).collect::<Vec<_>>();
+					if !frozen_intentions.is_empty() {
+						carry_forward.push(((asset_1, asset_2), frozen_intentions));
+					}
+					continue;
+				}
+
+				let pair_account = T::AMMPool::get_pair_id(&asset_1, &asset_2);
+
+				//TODO: we can short circuit here if nothing in asset_b_sells and just resolve asset a sells.
+
+				#[cfg(debug_assertions)]
+				let pre_settlement_balances = (
+					Self::total_settlement_balance(asset_1, &pair_account, &asset_a_sells, &asset_b_sells),
+					Self::total_settlement_balance(asset_2, &pair_account, &asset_a_sells, &asset_b_sells),
+				);
+
+				let fills_before = LastBlockFills::<T>::decode_len().unwrap_or(0);
+				let (carried, matched, amm) = Self::process_exchange_intentions(&pair_account, &asset_a_sells, &asset_b_sells);
+				Self::emit_fairness_reports(fills_before);
+
+				#[cfg(debug_assertions)]
+				debug_assert_eq!(
+					pre_settlement_balances,
+					(
+						Self::total_settlement_balance(asset_1, &pair_account, &asset_a_sells, &asset_b_sells),
+						Self::total_settlement_balance(asset_2, &pair_account, &asset_a_sells, &asset_b_sells),
+					),
+					"settling ({:?}, {:?}) changed the total balance held by its participants and pair account - value was created or destroyed",
+					asset_1,
+					asset_2,
+				);
+
+				matched_volume_total = matched_volume_total.saturating_add(matched);
+				amm_volume_total = amm_volume_total.saturating_add(amm);
+				if !carried.is_empty() {
+					carry_forward.push(((asset_1, asset_2), carried));
+				}
+			}
+
+			ExchangeAssetsIntentionCount::remove_all();
+			ExchangeAssetsIntentions::<T>::remove_all();
+			TotalIntentions::kill();
+			PendingIntentionsBytes::kill();
+
+			for ((asset_1, asset_2), intentions) in carry_forward {
+				ExchangeAssetsIntentionCount::mutate((asset_1, asset_2), |total| {
+					*total = total.saturating_add(intentions.len() as u32)
+				});
+				TotalIntentions::mutate(|total| *total = total.saturating_add(intentions.len() as u32));
+
+				for intention in intentions {
+					Self::deposit_event(RawEvent::IntentionCarriedForward(
+						intention.intention_id,
+						intention.remaining_lifetime,
+					));
+					PendingIntentionsBytes::mutate(|total| *total = total.saturating_add(intention.encoded_size() as u32));
+					<ExchangeAssetsIntentions<T>>::append((intention.asset_sell, intention.asset_buy), intention);
+				}
+			}
+
+			if matched_volume_total > Zero::zero() || amm_volume_total > Zero::zero() {
+				Self::deposit_event(RawEvent::BlockSettlementSummary(matched_volume_total, amm_volume_total));
+			}
+		}
+
+		/// Spend any leftover block weight discovering intentions whose `remaining_lifetime` has
+		/// already reached `0`, so they don't sit around for another `on_finalize` pass just to be
+		/// dropped there.
+		fn on_idle(_n: T::BlockNumber, remaining_weight: Weight) -> Weight {
+			Self::clean_expired_intentions(remaining_weight)
+		}
+
+		/// Settle two intentions found and matched off-chain, by direct-trading them exactly as
+		/// `on_finalize` would.
+		///
+		/// `pair` is the sorted `(min, max)` asset pair - intentions for it are stored under both
+		/// the `(pair.0, pair.1)` and `(pair.1, pair.0)` keys, one per trade direction, so both are
+		/// searched for `intention_a` and `intention_b`.
+		///
+		/// Only callable as an unsigned transaction - see `ValidateUnsigned`, which re-checks that
+		/// `intention_a` and `intention_b` are still registered under `pair` and still an exact
+		/// opposite match before letting the transaction into the pool.
+		#[weight = <T as Config>::WeightInfo::known_overhead_for_on_finalize()]
+		pub fn settle_offchain_match(
+			origin,
+			pair: (AssetId, AssetId),
+			intention_a: IntentionId<T>,
+			intention_b: IntentionId<T>,
+		) -> dispatch::DispatchResult {
+			ensure_none(origin)?;
+
+			let forward = ExchangeAssetsIntentions::<T>::get((pair.0, pair.1));
+			let backward = ExchangeAssetsIntentions::<T>::get((pair.1, pair.0));
+			let candidates = forward.iter().chain(backward.iter());
+
+			let a = candidates
+				.clone()
+				.find(|i| i.intention_id == intention_a)
+				.ok_or(Error::<T>::IntentionNotFound)?;
+			let b = candidates
+				.clone()
+				.find(|i| i.intention_id == intention_b)
+				.ok_or(Error::<T>::IntentionNotFound)?;
+
+			ensure!(Self::is_exact_match(a, b), Error::<T>::IntentionsNotMatched);
+
+			let pair_account = T::AMMPool::get_pair_id(&pair.0, &pair.1);
+
+			let mut trade = DirectTradeData::<T> {
+				intention_a: a,
+				intention_b: b,
+				amount_from_a: a.amount_sell,
+				amount_from_b: b.amount_sell,
+				transfers: Vec::new(),
+				remaining_amount: Zero::zero(),
+			};
+
+			if !trade.prepare(&pair_account) || !trade.execute() {
+				trade.revert();
+				return Err(Error::<T>::IntentionsNotMatched.into());
+			}
+
+			Self::deposit_event(RawEvent::IntentionsSettledOffchain(intention_a, intention_b));
+			Self::remove_settled_intentions(pair, &[intention_a, intention_b]);
+
+			Ok(())
+		}
+
+		/// Settle `(asset_a, asset_b)` immediately, running the exact same matching and AMM-
+		/// fallback resolution `on_finalize` would for this pair, instead of waiting for block
+		/// finalization to reach it. Useful for tests and for integrators who want deterministic
+		/// settlement timing.
+		///
+		/// Permissionless - callable by any signed account, since it only settles intentions
+		/// their own owners already registered and reserved funds for; it cannot be used to
+		/// affect anyone else's funds beyond running matching they already opted into.
+		///
+		/// If this pair is subsequently reached by `on_finalize` in the same block, its
+		/// `ExchangeAssetsIntentionCount` is already `0` once fully settled here, so
+		/// `on_finalize`'s own `if count == 0u32 { continue; }` check skips it - settling a pair
+		/// manually and then finalizing the block is idempotent.
+		#[weight = <T as Config>::WeightInfo::resolve_pair(ExchangeAssetsIntentionCount::get(canonical_pair(asset_a, asset_b)))]
+		pub fn settle_pair(origin, asset_a: AssetId, asset_b: AssetId) -> dispatch::DispatchResult {
+			ensure_signed(origin)?;
+
+			ensure!(T::AMMPool::exists(asset_a, asset_b), Error::<T>::TokenPoolNotFound);
+
+			let pair = canonical_pair(asset_a, asset_b);
+
+			let asset_a_sells = Self::intentions_selling(pair.1, pair.0);
+			let asset_b_sells = Self::intentions_selling(pair.0, pair.1);
+
+			if !Self::has_sufficient_pool_reserves(pair.0, pair.1) {
+				Self::deposit_event(RawEvent::PoolReservesTooLow(pair.0, pair.1));
+				for intention in asset_a_sells.iter().chain(asset_b_sells.iter()) {
+					Self::deposit_event(RawEvent::IntentionCarriedForward(
+						intention.intention_id,
+						intention.remaining_lifetime,
+					));
+				}
+				return Ok(());
+			}
+
+			if FrozenAssets::get(pair.0) || FrozenAssets::get(pair.1) {
+				for intention in asset_a_sells.iter().chain(asset_b_sells.iter()) {
+					Self::deposit_event(RawEvent::IntentionCarriedForward(
+						intention.intention_id,
+						intention.remaining_lifetime,
+					));
+				}
+				return Ok(());
+			}
+
+			if asset_a_sells.is_empty() && asset_b_sells.is_empty() {
+				return Ok(());
+			}
+
+			let pair_account = T::AMMPool::get_pair_id(&pair.0, &pair.1);
+			let fills_before = LastBlockFills::<T>::decode_len().unwrap_or(0);
+			let (carried, matched, amm) = Self::process_exchange_intentions(&pair_account, &asset_a_sells, &asset_b_sells);
+			Self::emit_fairness_reports(fills_before);
+
+			ExchangeAssetsIntentionCount::remove(pair);
+			<ExchangeAssetsIntentions<T>>::remove(pair);
+			<ExchangeAssetsIntentions<T>>::remove((pair.1, pair.0));
+			TotalIntentions::mutate(|total| {
+				*total = total.saturating_sub((asset_a_sells.len() + asset_b_sells.len()) as u32)
+			});
+
+			if !carried.is_empty() {
+				ExchangeAssetsIntentionCount::mutate(pair, |total| *total = total.saturating_add(carried.len() as u32));
+				TotalIntentions::mutate(|total| *total = total.saturating_add(carried.len() as u32));
+
+				for intention in carried {
+					Self::deposit_event(RawEvent::IntentionCarriedForward(
+						intention.intention_id,
+						intention.remaining_lifetime,
+					));
+					PendingIntentionsBytes::mutate(|total| *total = total.saturating_add(intention.encoded_size() as u32));
+					<ExchangeAssetsIntentions<T>>::append((intention.asset_sell, intention.asset_buy), intention);
+				}
+			}
+
+			if matched > Zero::zero() || amm > Zero::zero() {
+				Self::deposit_event(RawEvent::BlockSettlementSummary(matched, amm));
+			}
+
+			Ok(())
+		}
+
+		/// Page through registered intentions looking for an exact opposite-side match and submit
+		/// a settlement for the first one found.
+		///
+		/// This is an optional fast path for deployments which want to move matching off-chain and
+		/// only settle on-chain - it runs independently of, and does not replace, `on_finalize`'s
+		/// own matching.
+		fn offchain_worker(_now: T::BlockNumber) {
+			Self::run_offchain_matcher();
+		}
+
+		/// Set the minimum liquidity `(asset_a, asset_b)`'s pair account must hold of either
+		/// asset for `sell`/`buy` to be allowed against it. Root-only; `0` (the default) means no
+		/// restriction.
+		#[weight = <T as Config>::WeightInfo::set_min_pool_liquidity()]
+		pub fn set_min_pool_liquidity(
+			origin,
+			asset_a: AssetId,
+			asset_b: AssetId,
+			min_liquidity: Balance,
+		) -> dispatch::DispatchResult {
+			ensure_root(origin)?;
+
+			let pair = canonical_pair(asset_a, asset_b);
+			PairMinLiquidity::insert(pair, min_liquidity);
+
+			Self::deposit_event(RawEvent::MinPoolLiquiditySet(pair.0, pair.1, min_liquidity));
+
+			Ok(())
+		}
+
+		/// Set `(asset_a, asset_b)`'s default max slippage tolerance, applied by `sell`/`buy`
+		/// when the caller doesn't provide their own `min_bought`/`max_sold`, and taken together
+		/// with a caller-provided limit as whichever of the two is stricter. Root-only;
+		/// `Permill::zero()` (the default) means no pair default.
+		#[weight = <T as Config>::WeightInfo::set_pair_max_slippage()]
+		pub fn set_pair_max_slippage(
+			origin,
+			asset_a: AssetId,
+			asset_b: AssetId,
+			max_slippage: Permill,
+		) -> dispatch::DispatchResult {
+			ensure_root(origin)?;
+
+			let pair = canonical_pair(asset_a, asset_b);
+			PairMaxSlippage::insert(pair, max_slippage);
+
+			Self::deposit_event(RawEvent::PairMaxSlippageSet(pair.0, pair.1, max_slippage));
+
+			Ok(())
+		}
+
+		/// Override the minimum `amount_sell` `sell` will accept for `asset`, instead of the
+		/// global `T::MinTradingLimit`. Root-only; `0` (the default) means no override.
+		#[weight = <T as Config>::WeightInfo::set_asset_min_trade_amount()]
+		pub fn set_asset_min_trade_amount(origin, asset: AssetId, min_amount: Balance) -> dispatch::DispatchResult {
+			ensure_root(origin)?;
+
+			AssetMinTradeAmount::insert(asset, min_amount);
+
+			Self::deposit_event(RawEvent::AssetMinTradeAmountSet(asset, min_amount));
+
+			Ok(())
+		}
+
+		/// Freeze `asset` for trading. Root-only. Existing pools are left untouched - `sell`/`buy`
+		/// reject any trade with `asset` on either leg, and `on_finalize` skips settling any pair
+		/// with `asset` on either leg, carrying its registered intentions forward until thawed.
+		#[weight = <T as Config>::WeightInfo::freeze_asset()]
+		pub fn freeze_asset(origin, asset: AssetId) -> dispatch::DispatchResult {
+			ensure_root(origin)?;
+
+			FrozenAssets::insert(asset, true);
+
+			Self::deposit_event(RawEvent::AssetFrozen(asset));
+
+			Ok(())
+		}
+
+		/// Thaw a previously `freeze_asset`-frozen asset, allowing `sell`/`buy` and `on_finalize`
+		/// settlement to resume for it. Root-only.
+		#[weight = <T as Config>::WeightInfo::thaw_asset()]
+		pub fn thaw_asset(origin, asset: AssetId) -> dispatch::DispatchResult {
+			ensure_root(origin)?;
+
+			FrozenAssets::remove(asset);
+
+			Self::deposit_event(RawEvent::AssetThawed(asset));
+
+			Ok(())
+		}
+
+		/// Exempt an account from direct-trade fees, e.g. for protocol-owned liquidity. Root-only.
+		#[weight = <T as Config>::WeightInfo::set_fee_exempt()]
+		pub fn set_fee_exempt(origin, who: T::AccountId) -> dispatch::DispatchResult {
+			ensure_root(origin)?;
+
+			FeeExempt::<T>::insert(&who, true);
+
+			Self::deposit_event(RawEvent::FeeExemptionGranted(who));
+
+			Ok(())
+		}
+
+		/// Revoke a previously granted `set_fee_exempt` exemption. Root-only.
+		#[weight = <T as Config>::WeightInfo::unset_fee_exempt()]
+		pub fn unset_fee_exempt(origin, who: T::AccountId) -> dispatch::DispatchResult {
+			ensure_root(origin)?;
+
+			FeeExempt::<T>::remove(&who);
+
+			Self::deposit_event(RawEvent::FeeExemptionRevoked(who));
+
+			Ok(())
+		}
+
+		/// Halt the whole exchange - `sell`/`buy` reject every new intention until `resume` is
+		/// called. Unlike `freeze_asset`, this isn't scoped to particular assets. Intentions
+		/// already queued before the pause are unaffected and keep settling in `on_finalize`,
+		/// since halting that too would strand funds already reserved for a trade mid-flight.
+		#[weight = <T as Config>::WeightInfo::pause()]
+		pub fn pause(origin) -> dispatch::DispatchResult {
+			T::PauseOrigin::ensure_origin(origin)?;
+
+			ExchangePaused::put(true);
+
+			Self::deposit_event(RawEvent::ExchangePaused);
+
+			Ok(())
+		}
+
+		/// Lift a previous `pause`, allowing `sell`/`buy` to register new intentions again.
+		#[weight = <T as Config>::WeightInfo::resume()]
+		pub fn resume(origin) -> dispatch::DispatchResult {
+			T::PauseOrigin::ensure_origin(origin)?;
+
+			ExchangePaused::put(false);
+
+			Self::deposit_event(RawEvent::ExchangeResumed);
+
+			Ok(())
+		}
+	}
+}
+
+// "Internal" functions, callable by code.
+impl<T: Config> Module<T> {
+	/// Process intentions and attempt to match them so they can be direct traded.
+	/// ```sell_a_intentions``` are considered 'main' intentions.
+	///
+	/// This algorithm is quite simple at the moment and it tries to match as many intentions from ```sell_b_intentions``` as possible while
+	/// satisfying  that sum( sell_b_intentions.amount_sell ) <= sell_a_intention.amount_sell
+	///
+	/// Intention A must be valid - that means that it is verified first by validating if it was possible to do AMM trade.
+	///
+	/// Note: this greedy bin-packing is inlined here rather than behind a pluggable strategy -
+	/// there is no separate `Matcher` trait/`T::IntentionMatcher` associated type in this pallet
+	/// to swap it out through. Introducing one (e.g. to offer a FIFO alternative) would be a
+	/// larger redesign than this pallet's other extension points (`T::AMMPool`, `T::Resolver`,
+	/// `T::PriceOracle`) suggest is warranted today.
+	///
+	/// Returns intentions which could not be matched or AMM-routed this block but still have
+	/// retries left in their `remaining_lifetime` - the caller is responsible for carrying these
+	/// over to the next block - alongside `(matched_volume, amm_volume)` settled for this pair.
+	fn process_exchange_intentions(
+		pair_account: &T::AccountId,
+		sell_a_intentions: &[Intention<T>],
+		sell_b_intentions: &[Intention<T>],
+	) -> (Vec<Intention<T>>, Balance, Balance) {
+		let mut a_copy = sell_a_intentions.to_owned();
+		let mut carry_forward = Vec::<Intention<T>>::new();
+		let mut matched_volume: Balance = Zero::zero();
+		let mut amm_volume: Balance = Zero::zero();
+
+		// Sorted up front, same as `match_intentions` sorts its own copy internally, so intentions
+		// that fail verification are carried forward (and, for the ones that can't be, reported)
+		// in the same order they would have been matched in.
+		a_copy.sort_by(Self::matching_order);
+
+		let mut verified_a = Vec::<Intention<T>>::new();
+		for intention in a_copy {
+			if Self::try_verify_or_carry_forward(&intention, &mut carry_forward) {
+				verified_a.push(intention);
+			}
+		}
+
+		let groups = Self::match_intentions(&verified_a, sell_b_intentions);
+
+		let mut matched_ids = Vec::<IntentionId<T>>::new();
+		for (_, bvec) in &groups {
+			matched_ids.extend(bvec.iter().map(|matched| matched.intention_id));
+		}
+
+		for (intention, bvec) in groups {
+			let (matched, amm) = T::Resolver::resolve_matched_intentions(pair_account, &intention, &bvec);
+			matched_volume = matched_volume.saturating_add(matched);
+			amm_volume = amm_volume.saturating_add(amm);
+		}
+
+		// Whatever `match_intentions` didn't group - either never reached or too small per
+		// `MinMatchSize` - is run through the AMM instead. Sorted the same way `match_intentions`
+		// sorts its own copy, so this reproduces the exact leftover order it would have consumed
+		// from, then filtered down to what it actually left behind.
+		let mut leftover = sell_b_intentions.to_owned();
+		leftover.sort_by(Self::matching_order);
+		leftover.retain(|b_intention| !matched_ids.contains(&b_intention.intention_id));
+
+		// If something left in sell_b_intentions, just run it throught AMM.
+		while let Some(b_intention) = leftover.pop() {
+			let (carried, amm) = Self::resolve_single_intention_or_carry_forward(&b_intention);
+			if let Some(carried) = carried {
+				carry_forward.push(carried);
+			}
+			amm_volume = amm_volume.saturating_add(amm);
+		}
+
+		(carry_forward, matched_volume, amm_volume)
+	}
+
+	/// Pure grouping step of intention matching, with no verification, resolution or settlement -
+	/// split out from `process_exchange_intentions` so grouping correctness can be asserted
+	/// directly in unit tests without needing a full mock runtime settlement.
+	///
+	/// `sell_a_intentions` is assumed already verified (see `try_verify_or_carry_forward`) - this
+	/// only decides which `sell_b_intentions` end up paired with which. Both sides are sorted by
+	/// `matching_order` before pairing, and each `b` is consumed into at most one group; a `b`
+	/// smaller than `T::MinMatchSize` is skipped and left ungrouped, and a group stops growing once
+	/// it reaches `T::MaxCounterpartiesPerIntention`, leaving the rest of `sell_b_intentions`
+	/// ungrouped too. Every `a` gets an entry, even if its group ends up empty.
+	pub(crate) fn match_intentions(
+		sell_a_intentions: &[Intention<T>],
+		sell_b_intentions: &[Intention<T>],
+	) -> Vec<(Intention<T>, Vec<Intention<T>>)> {
+		let mut b_copy = sell_b_intentions.to_owned();
+		let mut a_copy = sell_a_intentions.to_owned();
+
+		b_copy.sort_by(Self::matching_order);
+		a_copy.sort_by(Self::matching_order);
+
+		let mut groups = Vec::with_capacity(a_copy.len());
+
+		for intention in a_copy {
+			let mut bvec = Vec::<Intention<T>>::new();
+			let mut total: Balance = Zero::zero();
+			let mut idx: usize = 0;
+
+			while let Some(matched) = b_copy.get(idx) {
+				// Too small to be worth pulling into this group - leave it in `b_copy` for the
+				// AMM fallback pass to pick up instead of inflating this match's transfer count.
+				if matched.amount_sell < T::MinMatchSize::get() {
+					idx += 1;
+					continue;
+				}
+
+				// Group is already as large as `MaxCounterpartiesPerIntention` allows - stop here
+				// and let whatever's left of `intention` fall through to the AMM fallback, same as
+				// if there were no more counterparties left to match at all.
+				if bvec.len() as u32 >= T::MaxCounterpartiesPerIntention::get() {
+					break;
+				}
+
+				bvec.push(matched.clone());
+				// Saturate rather than overflow on a crafted set of large-amount intentions - once
+				// saturated, `total` is already `>= intention.amount_sell` for any real
+				// `amount_sell`, so the loop below stops taking on more counterparties.
+				total = total.saturating_add(matched.amount_sell);
+				b_copy.remove(idx);
+				idx += 1;
+
+				if total >= intention.amount_sell {
+					break;
+				}
+			}
+
+			if T::PriceProximityMatching::get() {
+				Self::sort_by_price_proximity(&mut bvec);
+			}
+
+			groups.push((intention, bvec));
+		}
+
+		groups
+	}
+
+	/// Pure, read-only preview of how `(asset_a, asset_b)` would settle if matching ran right now -
+	/// the read-only core the `simulate_matching` RPC and the off-chain worker both build on. Runs
+	/// the same `matching_order`/`match_intentions` grouping `process_exchange_intentions` uses,
+	/// but touches no storage and executes nothing: an intention that would currently fail
+	/// `validate_intention` is simply left out, exactly as `try_verify_or_carry_forward` would
+	/// exclude it before matching, but nothing is carried forward or reported as an error either.
+	///
+	/// Reflects storage as it currently stands, so it can go stale immediately - a registration,
+	/// cancellation or AMM price movement between this call and an actual settlement isn't
+	/// reflected. `amm_leftover`/`amm_preview_price` on each entry are themselves only a preview,
+	/// for the same reason.
+	pub fn compute_matches(pair: (AssetId, AssetId)) -> Vec<MatchPreview<T::AccountId, IntentionId<T>, Balance>> {
+		let mut sell_a_intentions = ExchangeAssetsIntentions::<T>::get(pair);
+		sell_a_intentions.retain(|intention| Self::validate_intention(intention).is_ok());
+		sell_a_intentions.sort_by(Self::matching_order);
+
+		let sell_b_intentions = ExchangeAssetsIntentions::<T>::get((pair.1, pair.0));
+
+		let groups = Self::match_intentions(&sell_a_intentions, &sell_b_intentions);
+
+		let mut matched_ids = Vec::<IntentionId<T>>::new();
+		for (_, bvec) in &groups {
+			matched_ids.extend(bvec.iter().map(|matched| matched.intention_id));
+		}
+
+		let mut plan: Vec<MatchPreview<T::AccountId, IntentionId<T>, Balance>> = groups
+			.into_iter()
+			.map(|(intention, matched)| {
+				let matched_amount: Balance = matched
+					.iter()
+					.fold(Zero::zero(), |total: Balance, b| total.saturating_add(b.amount_sell));
+				let amm_leftover = intention.amount_sell.saturating_sub(matched_amount);
+
+				MatchPreview {
+					intention_id: intention.intention_id,
+					who: intention.who.clone(),
+					matched_against: matched
+						.iter()
+						.map(|b| (b.intention_id, b.who.clone(), b.amount_sell))
+						.collect(),
+					amm_leftover,
+					amm_preview_price: Self::preview_amm_price(&intention, amm_leftover),
+				}
+			})
+			.collect();
+
+		plan.extend(
+			sell_b_intentions
+				.iter()
+				.filter(|b_intention| !matched_ids.contains(&b_intention.intention_id))
+				.map(|b_intention| MatchPreview {
+					intention_id: b_intention.intention_id,
+					who: b_intention.who.clone(),
+					matched_against: Vec::new(),
+					amm_leftover: b_intention.amount_sell,
+					amm_preview_price: Self::preview_amm_price(b_intention, b_intention.amount_sell),
+				}),
+		);
+
+		plan
+	}
+
+	/// `T::AMMPool`'s current spot price for trading `amount` of `intention.asset_sell` into
+	/// `intention.asset_buy` - `None` for a zero `amount`, since there's nothing left to preview.
+	fn preview_amm_price(intention: &Intention<T>, amount: Balance) -> Option<Balance> {
+		if amount.is_zero() {
+			return None;
+		}
+
+		Some(T::AMMPool::get_spot_price_unchecked(intention.asset_sell, intention.asset_buy, amount))
+	}
+
+	/// Order two intentions for `process_exchange_intentions`'s sort - higher `priority` first,
+	/// then larger `amount_sell` first, then `intention_id` as a final deterministic tie-break so
+	/// the outcome doesn't depend on storage iteration order for two intentions equal on both.
+	fn matching_order(a: &Intention<T>, b: &Intention<T>) -> cmp::Ordering {
+		b.priority
+			.cmp(&a.priority)
+			.then_with(|| b.amount_sell.cmp(&a.amount_sell))
+			.then_with(|| a.intention_id.cmp(&b.intention_id))
+	}
+
+	/// Re-order an already amount-selected match bucket so intentions whose own registered price
+	/// is closest to the pair's current AMM spot price come first - see `PriceProximityMatching`.
+	/// A no-op on an empty bucket. All of `bvec` shares the same `(asset_sell, asset_buy)`
+	/// direction, so the first entry's assets are representative of the whole bucket.
+	fn sort_by_price_proximity(bvec: &mut Vec<Intention<T>>) {
+		let reference = match bvec.first() {
+			Some(first) => T::AMMPool::get_spot_price_unchecked(first.asset_sell, first.asset_buy, PRICE_PROXIMITY_PRECISION),
+			None => return,
+		};
+
+		bvec.sort_by_key(|intention| {
+			let implied = intention
+				.amount_buy
+				.saturating_mul(PRICE_PROXIMITY_PRECISION)
+				.checked_div(intention.amount_sell)
+				.unwrap_or(Zero::zero());
+
+			implied.max(reference) - implied.min(reference)
+		});
+	}
+
+	/// Execute AMM trade.
+	///
+	/// This performs AMM trade with given transfer details. Proceeds are delivered to
+	/// `intention.recipient` if set, otherwise to `intention.who`.
+	///
+	/// Nothing needs to be unreserved first: unlike a direct trade, an intention settling here was
+	/// never reserved via `T::Currency::reserve` in the first place, so `T::AMMPool::execute_sell`/
+	/// `execute_buy` simply debit `intention.who`'s free balance directly.
+	fn execute_amm_transfer(
+		intention: &Intention<T>,
+		transfer: &AMMTransfer<T::AccountId, AssetId, Balance>,
+	) -> dispatch::DispatchResult {
+		Self::execute_amm_transfer_and_deposit_event(intention, transfer, false)
+	}
+
+	/// Execute an AMM trade which only fills part of `intention`'s originally requested amount -
+	/// see `try_partial_amm_fill`. Otherwise identical to `execute_amm_transfer`.
+	fn execute_partial_amm_transfer(
+		intention: &Intention<T>,
+		transfer: &AMMTransfer<T::AccountId, AssetId, Balance>,
+	) -> dispatch::DispatchResult {
+		Self::execute_amm_transfer_and_deposit_event(intention, transfer, true)
+	}
+
+	fn execute_amm_transfer_and_deposit_event(
+		intention: &Intention<T>,
+		transfer: &AMMTransfer<T::AccountId, AssetId, Balance>,
+		is_partial_fill: bool,
+	) -> dispatch::DispatchResult {
+		Self::ensure_price_within_oracle_bounds(transfer.asset_sell, transfer.asset_buy, transfer.amount, transfer.amount_out)?;
+		Self::ensure_price_impact_within_bounds(transfer.asset_sell, transfer.asset_buy, transfer.amount, transfer.amount_out)?;
+
+		match intention.sell_or_buy {
+			IntentionType::SELL => T::AMMPool::execute_sell(transfer)?,
+			IntentionType::BUY => T::AMMPool::execute_buy(transfer)?,
+		};
+
+		if is_partial_fill {
+			Self::deposit_resolution_event(
+				RawEvent::IntentionResolvedAMMTradePartialFill(
+					transfer.origin.clone(),
+					intention.sell_or_buy.clone(),
+					intention.intention_id,
+					transfer.amount,
+					transfer.amount_out,
+					<system::Module<T>>::current_block_number(),
+				),
+				transfer.asset_sell,
+				transfer.asset_buy,
+				&[intention.intention_id],
+			);
+		} else {
+			Self::deposit_resolution_event(
+				RawEvent::IntentionResolvedAMMTrade(
+					transfer.origin.clone(),
+					intention.sell_or_buy.clone(),
+					intention.intention_id,
+					transfer.amount,
+					transfer.amount_out,
+					intention.reference,
+					<system::Module<T>>::current_block_number(),
+				),
+				transfer.asset_sell,
+				transfer.asset_buy,
+				&[intention.intention_id],
+			);
+		}
+
+		Self::set_intention_status(
+			intention.intention_id,
+			if is_partial_fill { Status::PartiallyFilled } else { Status::Filled },
+		);
+
+		Self::record_last_price(transfer.asset_sell, transfer.asset_buy);
+
+		Self::record_fill(
+			intention.intention_id,
+			transfer.asset_sell,
+			transfer.asset_buy,
+			intention.sell_or_buy.clone(),
+			transfer.amount,
+			transfer.amount_out,
+			false,
+		);
+
+		T::OnTradeHandler::on_trade(&intention.who, transfer.asset_sell, transfer.asset_buy, transfer.amount, transfer.amount_out);
+
+		Self::forward_amm_proceeds_to_recipient(intention, transfer)?;
+
+		Ok(())
+	}
+
+	/// The AMM pool always credits the bought asset to `who`. If the intention names a different
+	/// recipient, forward the proceeds on to them.
+	fn forward_amm_proceeds_to_recipient(
+		intention: &Intention<T>,
+		transfer: &AMMTransfer<T::AccountId, AssetId, Balance>,
+	) -> dispatch::DispatchResult {
+		if let Some(recipient) = &intention.recipient {
+			if recipient != &intention.who {
+				T::Currency::transfer(transfer.asset_buy, &intention.who, recipient, transfer.amount_out)?;
+			}
+		}
+
+		Ok(())
+	}
+
+	/// Set `id`'s `IntentionStatus` to `status` and schedule its cleanup for the start of the
+	/// next block's `on_finalize` - `status` must not be `Status::Pending`, which is only ever
+	/// set at registration and lives until the intention next settles.
+	fn set_intention_status(id: IntentionId<T>, status: Status) {
+		IntentionStatus::<T>::insert(id, status);
+		SettledIntentionIds::<T>::append(id);
+	}
+
+	/// Deposit `event` indexed by `intention_id` and by the sorted `(asset_a, asset_b)` pair, so
+	/// light clients can subscribe to a specific intention or pair without scanning every event
+	/// this pallet emits. Used for `IntentionRegistered` and the resolution events
+	/// (`IntentionResolvedDirectTrade`, `IntentionResolvedAMMTrade`,
+	/// `IntentionResolvedAMMTradePartialFill`) - the events a subscriber is actually likely to
+	/// filter on.
+	fn deposit_indexed_event(event: Event<T>, asset_a: AssetId, asset_b: AssetId, intention_ids: &[IntentionId<T>]) {
+		let pair_topic = canonical_pair(asset_a, asset_b).using_encoded(T::Hashing::hash);
+		let mut topics = intention_ids.to_vec();
+		topics.push(pair_topic);
+		<system::Module<T>>::deposit_event_indexed(&topics, <T as Config>::Event::from(event));
+	}
+
+	/// Like `deposit_indexed_event`, but for a per-intention resolution event specifically -
+	/// counted against `T::MaxEventsPerBlock` and dropped once the current block has reached it.
+	/// `BlockSettlementSummary` still reports accurate totals either way, since matching and
+	/// settlement themselves are entirely unaffected by whether the event is emitted.
+	fn deposit_resolution_event(event: Event<T>, asset_a: AssetId, asset_b: AssetId, intention_ids: &[IntentionId<T>]) {
+		let emitted = ResolutionEventsEmitted::get();
+		if emitted >= T::MaxEventsPerBlock::get() {
+			return;
+		}
+
+		ResolutionEventsEmitted::put(emitted + 1);
+		Self::deposit_indexed_event(event, asset_a, asset_b, intention_ids);
+	}
+
+	/// Drop `intention`'s unmatched leftover amount instead of routing it through the AMM, since
+	/// `allow_amm_fallback` is `false` - unreserves whatever was held against it and marks it
+	/// `Failed`, the same terminal status a fully unmatchable intention gets.
+	fn unreserve_and_notify_unmatched(intention: &Intention<T>) {
+		T::Currency::unreserve(intention.asset_sell, &intention.who, intention.amount_sell);
+		Self::set_intention_status(intention.intention_id, Status::Failed);
+
+		Self::deposit_event(RawEvent::IntentionUnmatched(
+			intention.who.clone(),
+			intention.asset_sell,
+			intention.amount_sell,
+			intention.intention_id,
+		));
+	}
+
+	/// Send intention resolve error event.
+	///
+	/// Sends event with error detail for intention that failed.
+	fn send_intention_error_event(intention: &Intention<T>, error: dispatch::DispatchError) {
+		Self::set_intention_status(intention.intention_id, Status::Failed);
+
+		Self::deposit_event(RawEvent::IntentionResolveErrorEvent(
+			intention.who.clone(),
+			intention.asset_sell,
+			intention.asset_buy,
+			intention.sell_or_buy.clone(),
+			intention.intention_id,
+			error,
+		));
+	}
+
+	/// Perform AMM validate for given intention, without executing anything.
+	fn validate_intention(intention: &Intention<T>) -> dispatch::DispatchResult {
+		match intention.sell_or_buy {
+			IntentionType::SELL => T::AMMPool::validate_sell(
+				&intention.who,
+				intention.asset_sell,
+				intention.asset_buy,
+				intention.amount_sell,
+				intention.trade_limit,
+				intention.discount,
+			)
+			.map(|_| ()),
+			IntentionType::BUY => T::AMMPool::validate_buy(
+				&intention.who,
+				intention.asset_buy,
+				intention.asset_sell,
+				intention.amount_buy,
+				intention.trade_limit,
+				intention.discount,
+			)
+			.map(|_| ()),
+		}
+	}
+
+	/// Classify why `T::AMMPool::validate_sell`/`validate_buy` rejected `intention`.
+	///
+	/// `TradeLimitExceeded` is detected by re-running the same validation with `trade_limit`
+	/// relaxed as far as it goes - `Zero::zero()` as a sell's minimum bought, `Balance::MAX` as a
+	/// buy's maximum sold - since `validate_sell`/`validate_buy` are read-only checks (no
+	/// `execute_*` is ever called on the result), this is safe to call purely for classification
+	/// and works for any implementation of the `AMM` trait, not just a specific one.
+	fn classify_amm_failure(intention: &Intention<T>) -> AMMFailureReason {
+		if !T::AMMPool::exists(intention.asset_sell, intention.asset_buy) {
+			return AMMFailureReason::PoolMissing;
+		}
+
+		let unlimited = match intention.sell_or_buy {
+			IntentionType::SELL => T::AMMPool::validate_sell(
+				&intention.who,
+				intention.asset_sell,
+				intention.asset_buy,
+				intention.amount_sell,
+				Zero::zero(),
+				intention.discount,
+			)
+			.is_ok(),
+			IntentionType::BUY => T::AMMPool::validate_buy(
+				&intention.who,
+				intention.asset_buy,
+				intention.asset_sell,
+				intention.amount_buy,
+				Balance::MAX,
+				intention.discount,
+			)
+			.is_ok(),
+		};
+
+		if unlimited {
+			AMMFailureReason::TradeLimitExceeded
+		} else {
+			AMMFailureReason::Other
+		}
+	}
+
+	/// Send the AMM validation error event matching `intention`'s side.
+	fn send_amm_validation_error_event(intention: &Intention<T>, error: dispatch::DispatchError) {
+		Self::set_intention_status(intention.intention_id, Status::Failed);
+
+		let reason = Self::classify_amm_failure(intention);
+
+		match intention.sell_or_buy {
+			IntentionType::SELL => Self::deposit_event(RawEvent::AMMSellErrorEvent(
+				intention.who.clone(),
+				intention.asset_sell,
+				intention.asset_buy,
+				intention.sell_or_buy.clone(),
+				intention.intention_id,
+				error,
+				reason,
+			)),
+			IntentionType::BUY => Self::deposit_event(RawEvent::AMMBuyErrorEvent(
+				intention.who.clone(),
+				intention.asset_buy,
+				intention.asset_sell,
+				intention.sell_or_buy.clone(),
+				intention.intention_id,
+				error,
+				reason,
+			)),
+		};
+	}
+
+	/// Whether `intention` has run out of retries (`remaining_lifetime` reached `0`) or, as an
+	/// alternative wall-clock based expiry, `pallet_timestamp::Now` has reached its
+	/// `valid_until_timestamp`.
+	fn is_expired(intention: &Intention<T>) -> bool {
+		if intention.remaining_lifetime.is_zero() {
+			return true;
+		}
+
+		match intention.valid_until_timestamp {
+			Some(deadline) => pallet_timestamp::Module::<T>::get() >= deadline,
+			None => false,
+		}
+	}
+
+	/// Clone `intention` with its `remaining_lifetime` decremented, or `None` if it is expired -
+	/// see `is_expired`.
+	fn carry_forward_or_none(intention: &Intention<T>) -> Option<Intention<T>> {
+		if Self::is_expired(intention) {
+			return None;
+		}
+
+		let mut carried = intention.clone();
+		carried.remaining_lifetime -= One::one();
+		Some(carried)
+	}
+
+	/// Verify sell or buy intention.
+	///
+	/// If the AMM trade isn't currently possible and the intention still has retries left, it is
+	/// pushed onto `carry_forward` to be retried in a future block instead of being dropped.
+	fn try_verify_or_carry_forward(intention: &Intention<T>, carry_forward: &mut Vec<Intention<T>>) -> bool {
+		match Self::validate_intention(intention) {
+			Ok(_) => true,
+			Err(error) => {
+				match Self::carry_forward_or_none(intention) {
+					Some(carried) => carry_forward.push(carried),
+					None => Self::send_amm_validation_error_event(intention, error),
+				}
+				false
+			}
+		}
+	}
+
+	/// Resolve a single intention via AMM, unless it isn't currently feasible - in which case it
+	/// is carried forward to the next block while it still has retries left.
+	///
+	/// Returns the carried-forward intention (if any) alongside the amount settled via the AMM,
+	/// which is `0` whenever nothing was actually resolved this call.
+	fn resolve_single_intention_or_carry_forward(intention: &Intention<T>) -> (Option<Intention<T>>, Balance) {
+		if let Err(error) = Self::validate_intention(intention) {
+			if Self::try_resolve_via_routing(intention) {
+				return (None, Zero::zero());
+			}
+
+			return match Self::carry_forward_or_none(intention) {
+				Some(carried) => (Some(carried), Zero::zero()),
+				None => {
+					Self::send_intention_error_event(intention, error);
+					(None, Zero::zero())
+				}
+			};
+		}
+
+		let amm_volume = T::Resolver::resolve_single_intention(intention);
+		(None, amm_volume)
+	}
+
+	/// Lower bound for the first leg's native-asset output when routing a `SELL` intention through
+	/// `native` as an intermediary - the native amount that, at today's `native`/`asset_buy` spot
+	/// price, would be expected to yield at least `trade_limit` of `asset_buy` on the second leg.
+	/// `0` if no spot price is available yet, in which case the second leg's own validation
+	/// against `trade_limit` remains the only protection.
+	fn min_routed_first_leg_out(native: AssetId, asset_buy: AssetId, trade_limit: Balance) -> Balance {
+		let unit_price = T::AMMPool::get_spot_price_unchecked(native, asset_buy, 1);
+
+		if unit_price.is_zero() {
+			return Zero::zero();
+		}
+
+		let numerator = trade_limit.saturating_add(unit_price.saturating_sub(One::one()));
+		numerator / unit_price
+	}
+
+	/// Try to route a `SELL` intention which has no direct `asset_sell`/`asset_buy` pool through
+	/// `T::NativeAssetId` as an intermediary instead - `asset_sell` -> native -> `asset_buy`, via
+	/// two separate AMM trades. Returns `false` (without touching any storage) if `EnableRouting`
+	/// is off, `intention` is a `BUY`, either asset already is the native asset, or either leg
+	/// isn't fully validated up front - in all those cases the caller falls back to its ordinary
+	/// carry forward/drop handling, and neither leg has touched any balance. Both legs are
+	/// validated against `T::PriceOracle`/`T::MaxPriceImpact` and only executed once both are
+	/// confirmed possible, so a leg is never committed while the other one might fail - once both
+	/// executions are underway, a failure on the second leg is reported via the usual error event
+	/// instead, since the first leg can no longer be undone.
+	fn try_resolve_via_routing(intention: &Intention<T>) -> bool {
+		if !T::EnableRouting::get() || intention.sell_or_buy != IntentionType::SELL {
+			return false;
+		}
+
+		let native = T::NativeAssetId::get();
+		if intention.asset_sell == native || intention.asset_buy == native {
+			return false;
+		}
+
+		let min_first_leg_out = Self::min_routed_first_leg_out(native, intention.asset_buy, intention.trade_limit);
+
+		let first_leg = match T::AMMPool::validate_sell(
+			&intention.who,
+			intention.asset_sell,
+			native,
+			intention.amount_sell,
+			min_first_leg_out,
+			intention.discount,
+		) {
+			Ok(transfer) => transfer,
+			Err(_) => return false,
+		};
+
+		if Self::ensure_price_within_oracle_bounds(intention.asset_sell, native, first_leg.amount, first_leg.amount_out).is_err()
+			|| Self::ensure_price_impact_within_bounds(intention.asset_sell, native, first_leg.amount, first_leg.amount_out).is_err()
+		{
+			return false;
+		}
+
+		let second_leg = match T::AMMPool::validate_sell(
+			&intention.who,
+			native,
+			intention.asset_buy,
+			first_leg.amount_out,
+			intention.trade_limit,
+			intention.discount,
+		) {
+			Ok(transfer) => transfer,
+			Err(_) => return false,
+		};
+
+		if Self::ensure_price_within_oracle_bounds(native, intention.asset_buy, second_leg.amount, second_leg.amount_out).is_err()
+			|| Self::ensure_price_impact_within_bounds(native, intention.asset_buy, second_leg.amount, second_leg.amount_out).is_err()
+		{
+			return false;
+		}
+
+		if T::AMMPool::execute_sell(&first_leg).is_err() {
+			return false;
+		}
+
+		if let Err(error) = T::AMMPool::execute_sell(&second_leg) {
+			Self::send_intention_error_event(intention, error);
+			return true;
+		}
+
+		Self::deposit_resolution_event(
+			RawEvent::IntentionResolvedAMMTrade(
+				second_leg.origin.clone(),
+				intention.sell_or_buy.clone(),
+				intention.intention_id,
+				first_leg.amount,
+				second_leg.amount_out,
+				intention.reference,
+				<system::Module<T>>::current_block_number(),
+			),
+			intention.asset_sell,
+			intention.asset_buy,
+			&[intention.intention_id],
+		);
+
+		Self::set_intention_status(intention.intention_id, Status::AMMRouted);
+
+		if let Err(error) = Self::forward_amm_proceeds_to_recipient(intention, &second_leg) {
+			Self::send_intention_error_event(intention, error);
+		}
+
+		true
+	}
+
+	/// Compare the price implied by an AMM match against `T::PriceOracle` and reject it if it
+	/// deviates by more than `T::MaxPriceDeviation`. A no-op when no oracle price is available.
+	fn ensure_price_within_oracle_bounds(
+		asset_sell: AssetId,
+		asset_buy: AssetId,
+		amount_sell: Balance,
+		amount_buy: Balance,
+	) -> dispatch::DispatchResult {
+		if let Some(oracle_amount_buy) = T::PriceOracle::spot_price(asset_sell, asset_buy, amount_sell) {
+			let max_deviation = T::MaxPriceDeviation::get().mul_ceil(oracle_amount_buy);
+			let diff = if amount_buy >= oracle_amount_buy {
+				amount_buy - oracle_amount_buy
+			} else {
+				oracle_amount_buy - amount_buy
+			};
+
+			ensure!(diff <= max_deviation, Error::<T>::PriceDeviationTooLarge);
+		}
+
+		Ok(())
+	}
+
+	/// Compare the price a trade would actually execute at against a linear projection of the
+	/// pool's current marginal (unit) price and reject it if the trade would move the price by
+	/// more than `T::MaxPriceImpact`. Using the marginal price as the reference - rather than
+	/// the trade's own quote, which already prices in its slippage - is what makes this a
+	/// genuine impact check instead of a tautology.
+	fn ensure_price_impact_within_bounds(
+		asset_sell: AssetId,
+		asset_buy: AssetId,
+		amount_sell: Balance,
+		amount_buy: Balance,
+	) -> dispatch::DispatchResult {
+		let unit_price = T::AMMPool::get_spot_price_unchecked(asset_sell, asset_buy, 1);
+
+		if unit_price.is_zero() {
+			return Ok(());
+		}
+
+		let expected_amount_buy = unit_price.saturating_mul(amount_sell);
+		let max_impact = T::MaxPriceImpact::get().mul_ceil(expected_amount_buy);
+		let diff = expected_amount_buy.saturating_sub(amount_buy);
+
+		ensure!(diff <= max_impact, Error::<T>::PriceImpactTooHigh);
+
+		Ok(())
+	}
+
+	/// Preconditions a `SELL` intention for `amount_sell` of `asset_sell` against `asset_buy` must
+	/// pass before it is registered - pool exists, neither asset is frozen or unknown, pool
+	/// liquidity is above the configured minimum, `who` holds enough balance, and so on. Shared by
+	/// `do_register_sell_intention` (the real registration path) and `validate_sell` (its dry run),
+	/// so the dry run can never predict success where registration would actually reject, or vice
+	/// versa.
+	fn validate_sell_registration(
+		who: &T::AccountId,
+		asset_sell: AssetId,
+		asset_buy: AssetId,
+		amount_sell: Balance,
+		valid_until_timestamp: Option<T::Moment>,
+	) -> dispatch::DispatchResult {
+		if ExchangePaused::get() {
+			return Err(Error::<T>::ExchangePaused.into());
+		}
+
+		if amount_sell.is_zero() {
+			return Err(Error::<T>::ZeroAmount.into());
+		}
+
+		let min_trade_amount = AssetMinTradeAmount::get(asset_sell);
+		if !min_trade_amount.is_zero() && amount_sell < min_trade_amount {
+			return Err(Error::<T>::BelowMinTradeAmount.into());
+		}
+
+		if let Some(deadline) = valid_until_timestamp {
+			if deadline <= pallet_timestamp::Module::<T>::get() {
+				return Err(Error::<T>::IntentionExpired.into());
+			}
+		}
+
+		if !Self::asset_known(asset_sell) || !Self::asset_known(asset_buy) {
+			return Err(Error::<T>::UnknownAsset.into());
+		}
+
+		if !T::AMMPool::exists(asset_sell, asset_buy) {
+			return Err(Error::<T>::TokenPoolNotFound.into());
+		}
+
+		if FrozenAssets::get(asset_sell) || FrozenAssets::get(asset_buy) {
+			return Err(Error::<T>::AssetFrozen.into());
+		}
+
+		Self::ensure_pool_liquidity_above_minimum(asset_sell, asset_buy)?;
+
+		if T::Currency::free_balance(asset_sell, who) < amount_sell {
+			return Err(Error::<T>::InsufficientAssetBalance.into());
+		}
+
+		Ok(())
+	}
+
+	/// Shared body of `sell`, `sell_all` and `register_sell` - validate `who` can sell
+	/// `amount_sell` of `asset_sell` for `asset_buy`, register the resulting `SELL` intention and
+	/// return its assigned id.
+	fn do_register_sell_intention(
+		who: T::AccountId,
+		asset_sell: AssetId,
+		asset_buy: AssetId,
+		amount_sell: Balance,
+		min_bought: Balance,
+		discount: bool,
+		recipient: Option<T::AccountId>,
+		valid_until_timestamp: Option<T::Moment>,
+		reference: Option<[u8; 32]>,
+		allow_amm_fallback: bool,
+	) -> Result<IntentionId<T>, dispatch::DispatchErrorWithPostInfo<dispatch::PostDispatchInfo>> {
+		Self::validate_sell_registration(&who, asset_sell, asset_buy, amount_sell, valid_until_timestamp)
+			.map_err(|e| e.with_weight(T::WeightInfo::reject_intention()))?;
+
+		let amount_buy = T::AMMPool::get_spot_price_unchecked(asset_sell, asset_buy, amount_sell);
+
+		let (asset_1, asset_2) = canonical_pair(asset_sell, asset_buy);
+
+		let intention_count = ExchangeAssetsIntentionCount::get((asset_1, asset_2));
+
+		let new_intention_count = intention_count
+			.checked_add(1)
+			.ok_or_else(|| Error::<T>::StorageOverflow.with_weight(T::WeightInfo::reject_intention()))?;
+
+		let intention_id = Self::generate_intention_id(&who, intention_count, asset_1, asset_2);
+
+		let intention = Intention::<T> {
+			who: who.clone(),
+			asset_sell,
+			asset_buy,
+			amount_sell,
+			amount_buy,
+			discount,
+			sell_or_buy: IntentionType::SELL,
+			intention_id,
+			trade_limit: Self::effective_min_bought(asset_sell, asset_buy, amount_buy, min_bought),
+			remaining_lifetime: T::DefaultIntentionLifetime::get(),
+			recipient,
+			valid_until_timestamp,
+			reference,
+			allow_amm_fallback,
+			priority: 0,
+		};
+
+		Self::reserve_intention_bytes(&intention).map_err(|e| e.with_weight(T::WeightInfo::reject_intention()))?;
+
+		<ExchangeAssetsIntentions<T>>::append((intention.asset_sell, intention.asset_buy), intention.clone());
+
+		ExchangeAssetsIntentionCount::insert((asset_1, asset_2), new_intention_count);
+		TotalIntentions::mutate(|total| *total = total.saturating_add(1));
+
+		IntentionStatus::<T>::insert(intention.intention_id, Status::Pending);
+
+		Self::deposit_indexed_event(
+			RawEvent::IntentionRegistered(
+				who,
+				asset_sell,
+				asset_buy,
+				amount_sell,
+				IntentionType::SELL,
+				intention.intention_id,
+				reference,
+				<system::Module<T>>::current_block_number(),
+			),
+			asset_sell,
+			asset_buy,
+			&[intention.intention_id],
+		);
+
+		Self::deposit_event(RawEvent::IntentionReceiptIssued(Self::intention_receipt(
+			intention.intention_id,
+		)));
+
+		Ok(intention.intention_id)
+	}
+
+	/// Shared body of `buy` and `register_buy` - mirrors `do_register_sell_intention`, but for a
+	/// `BUY` intention, where `amount_buy` is the caller-given amount and `amount_sell` is derived
+	/// from the current spot price instead of the other way around.
+	fn do_register_buy_intention(
+		who: T::AccountId,
+		asset_buy: AssetId,
+		asset_sell: AssetId,
+		amount_buy: Balance,
+		max_sold: Balance,
+		discount: bool,
+		recipient: Option<T::AccountId>,
+		valid_until_timestamp: Option<T::Moment>,
+		reference: Option<[u8; 32]>,
+		allow_amm_fallback: bool,
+	) -> Result<IntentionId<T>, dispatch::DispatchErrorWithPostInfo<dispatch::PostDispatchInfo>> {
+		if ExchangePaused::get() {
+			return Err(Error::<T>::ExchangePaused.with_weight(T::WeightInfo::reject_intention()));
+		}
+
+		if amount_buy.is_zero() {
+			return Err(Error::<T>::ZeroAmount.with_weight(T::WeightInfo::reject_intention()));
+		}
+
+		if let Some(deadline) = valid_until_timestamp {
+			if deadline <= pallet_timestamp::Module::<T>::get() {
+				return Err(Error::<T>::IntentionExpired.with_weight(T::WeightInfo::reject_intention()));
+			}
+		}
+
+		if !Self::asset_known(asset_sell) || !Self::asset_known(asset_buy) {
+			return Err(Error::<T>::UnknownAsset.with_weight(T::WeightInfo::reject_intention()));
+		}
+
+		if !T::AMMPool::exists(asset_sell, asset_buy) {
+			return Err(Error::<T>::TokenPoolNotFound.with_weight(T::WeightInfo::reject_intention()));
+		}
+
+		if FrozenAssets::get(asset_sell) || FrozenAssets::get(asset_buy) {
+			return Err(Error::<T>::AssetFrozen.with_weight(T::WeightInfo::reject_intention()));
+		}
+
+		Self::ensure_pool_liquidity_above_minimum(asset_sell, asset_buy)?;
+
+		let amount_sell = Self::quote_buy(asset_buy, asset_sell, amount_buy)
+			.ok_or_else(|| Error::<T>::TokenPoolNotFound.with_weight(T::WeightInfo::reject_intention()))?;
+
+		if T::Currency::free_balance(asset_sell, &who) < amount_sell {
+			return Err(Error::<T>::InsufficientAssetBalance.with_weight(T::WeightInfo::reject_intention()));
+		}
+
+		let (asset_1, asset_2) = canonical_pair(asset_sell, asset_buy);
+
+		let intention_count = ExchangeAssetsIntentionCount::get((asset_1, asset_2));
+
+		let new_intention_count = intention_count
+			.checked_add(1)
+			.ok_or_else(|| Error::<T>::StorageOverflow.with_weight(T::WeightInfo::reject_intention()))?;
+
+		let intention_id = Self::generate_intention_id(&who, intention_count, asset_1, asset_2);
+
+		let intention = Intention::<T> {
+			who: who.clone(),
+			asset_sell,
+			asset_buy,
+			amount_sell,
+			amount_buy,
+			sell_or_buy: IntentionType::BUY,
+			discount,
+			intention_id,
+			trade_limit: Self::effective_max_sold(asset_sell, asset_buy, amount_sell, max_sold),
+			remaining_lifetime: T::DefaultIntentionLifetime::get(),
+			recipient,
+			valid_until_timestamp,
+			reference,
+			allow_amm_fallback,
+			priority: 0,
+		};
+
+		Self::reserve_intention_bytes(&intention).map_err(|e| e.with_weight(T::WeightInfo::reject_intention()))?;
+
+		<ExchangeAssetsIntentions<T>>::append((intention.asset_sell, intention.asset_buy), intention.clone());
+
+		ExchangeAssetsIntentionCount::insert((asset_1, asset_2), new_intention_count);
+		TotalIntentions::mutate(|total| *total = total.saturating_add(1));
+
+		IntentionStatus::<T>::insert(intention.intention_id, Status::Pending);
+
+		Self::deposit_indexed_event(
+			RawEvent::IntentionRegistered(
+				who,
+				asset_buy,
+				asset_sell,
+				amount_buy,
+				IntentionType::BUY,
+				intention.intention_id,
+				reference,
+				<system::Module<T>>::current_block_number(),
+			),
+			asset_sell,
+			asset_buy,
+			&[intention.intention_id],
+		);
+
+		Self::deposit_event(RawEvent::IntentionReceiptIssued(Self::intention_receipt(
+			intention.intention_id,
+		)));
+
+		Ok(intention.intention_id)
+	}
+
+	/// Shared body of `sell` and `sell_all` - validate `who` can sell `amount_sell` of
+	/// `asset_sell` for `asset_buy` and register the resulting `SELL` intention.
+	fn register_sell_intention(
+		who: T::AccountId,
+		asset_sell: AssetId,
+		asset_buy: AssetId,
+		amount_sell: Balance,
+		min_bought: Balance,
+		discount: bool,
+		recipient: Option<T::AccountId>,
+		valid_until_timestamp: Option<T::Moment>,
+		reference: Option<[u8; 32]>,
+		allow_amm_fallback: bool,
+	) -> dispatch::DispatchResultWithPostInfo {
+		Self::do_register_sell_intention(
+			who,
+			asset_sell,
+			asset_buy,
+			amount_sell,
+			min_bought,
+			discount,
+			recipient,
+			valid_until_timestamp,
+			reference,
+			allow_amm_fallback,
+		)
+		.map(|_| ().into())
+	}
+
+	/// Register a `SELL` intention on `who`'s behalf from within another pallet, returning the
+	/// assigned intention id immediately so the caller can correlate it with the deferred
+	/// settlement events `on_finalize` later deposits. `sell` is a thin wrapper around this same
+	/// logic for signed extrinsic callers.
+	pub fn register_sell(
+		who: T::AccountId,
+		asset_sell: AssetId,
+		asset_buy: AssetId,
+		amount_sell: Balance,
+		min_bought: Balance,
+		discount: bool,
+		recipient: Option<T::AccountId>,
+		valid_until_timestamp: Option<T::Moment>,
+		reference: Option<[u8; 32]>,
+		allow_amm_fallback: bool,
+	) -> Result<IntentionId<T>, dispatch::DispatchError> {
+		Self::do_register_sell_intention(
+			who,
+			asset_sell,
+			asset_buy,
+			amount_sell,
+			min_bought,
+			discount,
+			recipient,
+			valid_until_timestamp,
+			reference,
+			allow_amm_fallback,
+		)
+		.map_err(|e| e.error)
+	}
+
+	/// Register a `BUY` intention on `who`'s behalf from within another pallet - the `BUY`
+	/// counterpart to `register_sell`. `buy` is a thin wrapper around this same logic for signed
+	/// extrinsic callers.
+	pub fn register_buy(
+		who: T::AccountId,
+		asset_buy: AssetId,
+		asset_sell: AssetId,
+		amount_buy: Balance,
+		max_sold: Balance,
+		discount: bool,
+		recipient: Option<T::AccountId>,
+		valid_until_timestamp: Option<T::Moment>,
+		reference: Option<[u8; 32]>,
+		allow_amm_fallback: bool,
+	) -> Result<IntentionId<T>, dispatch::DispatchError> {
+		Self::do_register_buy_intention(
+			who,
+			asset_buy,
+			asset_sell,
+			amount_buy,
+			max_sold,
+			discount,
+			recipient,
+			valid_until_timestamp,
+			reference,
+			allow_amm_fallback,
+		)
+		.map_err(|e| e.error)
+	}
+
+	/// Register a `SELL` or `BUY` intention on `who`'s behalf, dispatching to `register_sell` or
+	/// `register_buy` as appropriate - a convenience entry point for callers (e.g. the AMM or a
+	/// router pallet) that don't need control over recipient, expiry, reference or AMM fallback,
+	/// and are happy to take the pair's own default slippage tolerance (see
+	/// `set_pair_max_slippage`) rather than giving an explicit trade limit. Reach for
+	/// `register_sell`/`register_buy` directly when any of that needs to be set explicitly.
+	pub fn submit_intention(
+		who: T::AccountId,
+		asset_sell: AssetId,
+		asset_buy: AssetId,
+		amount: Balance,
+		intention_type: IntentionType,
+		discount: bool,
+	) -> Result<IntentionId<T>, dispatch::DispatchError> {
+		match intention_type {
+			IntentionType::SELL => {
+				Self::register_sell(who, asset_sell, asset_buy, amount, 0, discount, None, None, None, true)
+			}
+			IntentionType::BUY => {
+				Self::register_buy(who, asset_buy, asset_sell, amount, 0, discount, None, None, None, true)
+			}
+		}
+	}
+
+	fn generate_intention_id(account: &T::AccountId, c: u32, a1: AssetId, a2: AssetId) -> IntentionId<T> {
+		let b = <system::Module<T>>::current_block_number();
+		(c, &account, b, a1, a2).using_encoded(T::Hashing::hash)
+	}
+
+	/// Build `intention_id`'s `IntentionReceipt` - see its doc comment for why this, and not
+	/// `IntentionId` alone, is what a re-org-aware off-chain system should key on.
+	fn intention_receipt(intention_id: IntentionId<T>) -> IntentionReceipt<IntentionId<T>> {
+		let parent_hash = <system::Module<T>>::parent_hash();
+		let mut block_hash_prefix = [0u8; 4];
+		block_hash_prefix.copy_from_slice(&parent_hash.as_ref()[..4]);
+
+		IntentionReceipt {
+			block_hash_prefix,
+			intention_id,
+		}
+	}
+
+	/// The `IntentionId` a `sell`/`buy` call from `who` for `(asset_sell, asset_buy)` would be
+	/// assigned if it were submitted right now - computed from the same
+	/// `ExchangeAssetsIntentionCount` counter and current block number `generate_intention_id`
+	/// itself uses. Only valid until the next registration for the pair (which advances the
+	/// counter) or the next block (which changes the hash input) - callers composing multiple
+	/// calls in the same extrinsic/block should re-check it if either happens in between.
+	/// Exposed to external tools via `ExchangeApi::next_intention_id`.
+	pub fn get_next_intention_id(who: &T::AccountId, asset_sell: AssetId, asset_buy: AssetId) -> IntentionId<T> {
+		let (asset_1, asset_2) = canonical_pair(asset_sell, asset_buy);
+		let intention_count = ExchangeAssetsIntentionCount::get((asset_1, asset_2));
+
+		Self::generate_intention_id(who, intention_count, asset_1, asset_2)
+	}
+
+	/// Intentions registered to sell `asset_sell` for `asset_buy` - the exact direction
+	/// `ExchangeAssetsIntentions` is keyed by, since `sell` and `buy` both append under the
+	/// intention's literal `(asset_sell, asset_buy)` regardless of which extrinsic or parameter
+	/// order was used to register it. Named explicitly so call sites like `on_finalize`, which
+	/// need both directions of a normalized `(asset_1, asset_2)` pair, can't accidentally pass the
+	/// two assets in the same order twice and silently starve one direction of matches.
+	fn intentions_selling(asset_sell: AssetId, asset_buy: AssetId) -> Vec<Intention<T>> {
+		<ExchangeAssetsIntentions<T>>::get((asset_sell, asset_buy))
+	}
+
+	/// Total `free + reserved` balance of `asset` held by `pair_account` and by every account
+	/// `on_finalize` could credit or debit while settling `asset_a_sells`/`asset_b_sells` for one
+	/// pair - each intention's `who` and `beneficiary`. Only used by the conservation-of-value
+	/// `debug_assert!` in `on_finalize`, so it's never compiled into a release build.
+	#[cfg(debug_assertions)]
+	fn total_settlement_balance(
+		asset: AssetId,
+		pair_account: &T::AccountId,
+		asset_a_sells: &[Intention<T>],
+		asset_b_sells: &[Intention<T>],
+	) -> Balance {
+		let mut total = T::Currency::free_balance(asset, pair_account)
+			.saturating_add(T::Currency::reserved_balance(asset, pair_account));
+
+		for intention in asset_a_sells.iter().chain(asset_b_sells.iter()) {
+			total = total.saturating_add(T::Currency::free_balance(asset, &intention.who));
+			total = total.saturating_add(T::Currency::reserved_balance(asset, &intention.who));
+
+			if let Some(recipient) = &intention.recipient {
+				total = total.saturating_add(T::Currency::free_balance(asset, recipient));
+				total = total.saturating_add(T::Currency::reserved_balance(asset, recipient));
+			}
+		}
+
+		total
+	}
+
+	/// The deterministic account which holds `(asset_a, asset_b)`'s pool reserves and receives
+	/// its trading fees - the same account `on_finalize` direct-trades and AMM fallbacks settle
+	/// through. Exposed to external tools via `ExchangeApi::pair_account`.
+	pub fn pair_account(asset_a: AssetId, asset_b: AssetId) -> T::AccountId {
+		T::AMMPool::get_pair_id(&asset_a, &asset_b)
+	}
+
+	/// Cumulative direct-trade fees `(asset_a, asset_b)`'s pair account has collected so far.
+	/// Exposed to external tools via `ExchangeApi::collected_fees`.
+	pub fn collected_fees(asset_a: AssetId, asset_b: AssetId) -> Balance {
+		CollectedFees::get(canonical_pair(asset_a, asset_b))
+	}
+
+	/// `(asset_a, asset_b)`'s most recently settled price and the block it was recorded in -
+	/// `None` if the pair has never had a direct match or AMM trade settle. Exposed to external
+	/// tools via `ExchangeApi::last_price`.
+	pub fn last_price(asset_a: AssetId, asset_b: AssetId) -> Option<(Balance, T::BlockNumber)> {
+		let pair = canonical_pair(asset_a, asset_b);
+		if LastPrice::<T>::contains_key(pair) {
+			Some(LastPrice::<T>::get(pair))
+		} else {
+			None
+		}
+	}
+
+	/// Record `(asset_a, asset_b)`'s current AMM spot price as its last-settled price, as of the
+	/// current block - called whenever a direct match or AMM trade settles for the pair.
+	pub(crate) fn record_last_price(asset_a: AssetId, asset_b: AssetId) {
+		let pair = canonical_pair(asset_a, asset_b);
+		let price = T::AMMPool::get_spot_price_unchecked(asset_a, asset_b, 1);
+		LastPrice::<T>::insert(pair, (price, <system::Module<T>>::current_block_number()));
+	}
+
+	/// Every fill settled during the block this state was read from - empty once `LastBlockFills`
+	/// is cleared by the following block's `on_initialize`. Exposed to external tools via
+	/// `ExchangeApi::last_block_fills`.
+	pub fn last_block_fills() -> Vec<Fill<T>> {
+		LastBlockFills::<T>::get()
+	}
+
+	/// Every intention belonging to `who` settled during the block this state was read from -
+	/// empty once the following block's `on_initialize` has run, same caveat as
+	/// `last_block_fills`. Exposed to external tools via `ExchangeApi::account_settlements`.
+	pub fn account_settlements(who: T::AccountId) -> Vec<Settlement<T>> {
+		LastBlockSettlements::<T>::get(who)
+	}
 
-		fn deposit_event() = default;
+	/// Total of `dt`'s fee transfers charged against `who`'s own side of the trade - `dt` must
+	/// already have been `execute`d, so its `transfers` reflect what was actually collected.
+	fn fee_paid_by(dt: &DirectTradeData<'_, T>, who: &T::AccountId) -> Balance {
+		dt.transfers
+			.iter()
+			.filter(|transfer| transfer.fee_transfer && transfer.from == who)
+			.fold(Zero::zero(), |total: Balance, transfer| total.saturating_add(transfer.amount))
+	}
 
-		/// Create sell intention
-		/// Calculate current spot price, create an intention and store in ```ExchangeAssetsIntentions```
-		#[weight =  <T as Config>::WeightInfo::sell_intention() + <T as Config>::WeightInfo::on_finalize_for_one_sell_extrinsic() -  <T as Config>::WeightInfo::known_overhead_for_on_finalize()]
-		pub fn sell(
-			origin,
-			asset_sell: AssetId,
-			asset_buy: AssetId,
-			amount_sell: Balance,
-			min_bought: Balance,
-			discount: bool,
-		)  -> dispatch::DispatchResult {
-			let who = ensure_signed(origin)?;
+	/// Append one resolved intention's settlement summary to `LastBlockSettlements`, keyed by
+	/// `who`.
+	pub(crate) fn record_settlement(
+		who: T::AccountId,
+		intention_id: IntentionId<T>,
+		filled_amount: Balance,
+		fee_paid: Balance,
+		counterparty_count: u32,
+		amm_portion: Balance,
+	) {
+		LastBlockSettlements::<T>::append(
+			who,
+			SettlementRecord {
+				intention_id,
+				filled_amount,
+				fee_paid,
+				counterparty_count,
+				amm_portion,
+			},
+		);
+	}
 
-			ensure!(
-				T::AMMPool::exists(asset_sell, asset_buy),
-				Error::<T>::TokenPoolNotFound
-			);
+	/// Append one settled leg of a trade to `LastBlockFills` and add to `AssetVolume` for both
+	/// sides - `amount` is what was sold on this leg, `price` is `PRICE_PROXIMITY_PRECISION`-scaled
+	/// `asset_buy` received per unit sold.
+	pub(crate) fn record_fill(
+		intention_id: IntentionId<T>,
+		asset_sell: AssetId,
+		asset_buy: AssetId,
+		direction: IntentionType,
+		amount: Balance,
+		amount_out: Balance,
+		direct: bool,
+	) {
+		let price = amount_out
+			.saturating_mul(PRICE_PROXIMITY_PRECISION)
+			.checked_div(amount)
+			.unwrap_or(Zero::zero());
+
+		AssetVolume::mutate(asset_sell, |total| *total = total.saturating_add(amount));
+		AssetVolume::mutate(asset_buy, |total| *total = total.saturating_add(amount_out));
+
+		LastBlockFills::<T>::append(FillRecord {
+			intention_id,
+			asset_sell,
+			asset_buy,
+			direction,
+			amount,
+			price,
+			direct,
+		});
+	}
 
-			ensure!(
-				T::Currency::free_balance(asset_sell, &who) >= amount_sell,
-				Error::<T>::InsufficientAssetBalance
-			);
+	/// Compare every fill settled since `fills_before` (an index into `LastBlockFills`, taken
+	/// right before the settlement pass that's about to run) for `(asset_sell, asset_buy, amount)`
+	/// groups of two or more identical legs, and deposit a `FairnessReport` for each one found -
+	/// see `FairnessReport` for what's reported. A no-op if nothing settled since `fills_before`,
+	/// or every leg's `(asset_sell, asset_buy, amount)` combination is unique.
+	fn emit_fairness_reports(fills_before: usize) {
+		let fills = LastBlockFills::<T>::get();
+		if fills.len() <= fills_before {
+			return;
+		}
 
-			let amount_buy = T::AMMPool::get_spot_price_unchecked(asset_sell, asset_buy, amount_sell);
+		let mut groups: Vec<(AssetId, AssetId, Balance, Vec<Balance>)> = Vec::new();
+		for fill in &fills[fills_before..] {
+			match groups
+				.iter_mut()
+				.find(|(asset_sell, asset_buy, amount, _)| *asset_sell == fill.asset_sell && *asset_buy == fill.asset_buy && *amount == fill.amount)
+			{
+				Some((_, _, _, prices)) => prices.push(fill.price),
+				None => groups.push((fill.asset_sell, fill.asset_buy, fill.amount, vec![fill.price])),
+			}
+		}
 
-			let asset_1 = cmp::min(asset_sell, asset_buy);
-			let asset_2 = cmp::max(asset_sell, asset_buy);
+		for (asset_sell, asset_buy, amount, prices) in groups {
+			if prices.len() < 2 {
+				continue;
+			}
 
-			let intention_count = ExchangeAssetsIntentionCount::get((asset_1, asset_2));
+			let count = prices.len() as Balance;
+			let sum: Balance = prices.iter().fold(Zero::zero(), |total: Balance, price| total.saturating_add(*price));
+			let average = sum.checked_div(count).unwrap_or(Zero::zero());
+			let max_deviation = prices
+				.iter()
+				.fold(Zero::zero(), |max: Balance, price| (*price).max(average).saturating_sub((*price).min(average)).max(max));
 
-			let intention_id = Self::generate_intention_id(&who, intention_count, asset_1, asset_2);
+			Self::deposit_event(RawEvent::FairnessReport(asset_sell, asset_buy, amount, average, max_deviation));
+		}
+	}
 
-			let intention = Intention::<T> {
-					who: who.clone(),
-					asset_sell,
-					asset_buy,
-					amount_sell,
-					amount_buy,
-					discount,
-					sell_or_buy : IntentionType::SELL,
-					intention_id,
-					trade_limit: min_bought
-			};
+	/// `intention_id`'s current `Status` - `None` if it was never registered, or was settled or
+	/// dropped in an earlier block. Exposed to external tools via `ExchangeApi::intention_status`.
+	pub fn intention_status(intention_id: IntentionId<T>) -> Option<Status> {
+		if IntentionStatus::<T>::contains_key(intention_id) {
+			Some(IntentionStatus::<T>::get(intention_id))
+		} else {
+			None
+		}
+	}
 
-			<ExchangeAssetsIntentions<T>>::append((intention.asset_sell, intention.asset_buy), intention.clone());
+	/// Whether `(asset_a, asset_b)`'s pool still holds at least `T::MinPoolReserve::get()` of
+	/// each asset - checked before matching or AMM-routing a pair's intentions, rather than
+	/// relying on whatever error `calculate_spot_price` or an AMM trade happens to produce once
+	/// reserves are already close to empty. See `PoolReservesTooLow`.
+	fn has_sufficient_pool_reserves(asset_a: AssetId, asset_b: AssetId) -> bool {
+		let pair_account = T::AMMPool::get_pair_id(&asset_a, &asset_b);
+		T::Currency::free_balance(asset_a, &pair_account) >= T::MinPoolReserve::get()
+			&& T::Currency::free_balance(asset_b, &pair_account) >= T::MinPoolReserve::get()
+	}
 
-			let asset_1 = cmp::min(intention.asset_sell, intention.asset_buy);
-			let asset_2 = cmp::max(intention.asset_sell, intention.asset_buy);
+	/// The total amount of `asset` `who` currently has reserved across all of their open
+	/// intentions selling `asset`, in either direction of any pair. Exposed to external tools via
+	/// `ExchangeApi::reserved_balance`.
+	///
+	/// Summed with `saturating_add` - an account's individual reservations are each already
+	/// bounded by its own free balance, but summing an unbounded number of them could otherwise
+	/// overflow `Balance`, and this is a read-only query with no funds at stake, so saturating is
+	/// preferable to panicking.
+	pub fn reserved_balance(who: T::AccountId, asset: AssetId) -> Balance {
+		<ExchangeAssetsIntentions<T>>::iter()
+			.flat_map(|(_, intentions)| intentions)
+			.filter(|intention| intention.who == who && intention.asset_sell == asset)
+			.fold(Zero::zero(), |total: Balance, intention| total.saturating_add(intention.amount_sell))
+	}
 
-			ExchangeAssetsIntentionCount::mutate((asset_1,asset_2), |total| *total += 1u32);
+	/// Every pair with at least one intention queued right now, together with how many - the set
+	/// `on_finalize` is about to attempt settling this block. Exposed to external tools via
+	/// `ExchangeApi::active_pairs`.
+	///
+	/// Iterates `ExchangeAssetsIntentionCount` in full, so its cost scales with the number of
+	/// distinct pairs ever traded, not just the active ones - acceptable for an off-chain RPC
+	/// query, but this is deliberately not called from any dispatchable or `on_finalize`/`on_idle`
+	/// hook, where an unbounded iteration would be a weight hazard.
+	pub fn active_pairs() -> Vec<(AssetId, AssetId, u32)> {
+		ExchangeAssetsIntentionCount::iter()
+			.filter(|(_, count)| *count > 0)
+			.map(|((asset_a, asset_b), count)| (asset_a, asset_b, count))
+			.collect()
+	}
 
-			Self::deposit_event(RawEvent::IntentionRegistered(who, asset_sell, asset_buy, amount_sell, IntentionType::SELL, intention.intention_id));
+	/// The pool's current instantaneous marginal price for `(asset_a, asset_b)`, i.e. the amount
+	/// of `asset_b` a vanishingly small trade of `asset_a` would fetch - as opposed to a specific
+	/// trade's quote, which already prices in that trade's own slippage. `None` if no pool exists
+	/// for the pair - a pool is never left registered with empty reserves, since removing all of
+	/// its liquidity tears it down entirely (see `T::AMMPool::exists`). Exposed to external tools
+	/// via `ExchangeApi::spot_price`.
+	pub fn spot_price(asset_a: AssetId, asset_b: AssetId) -> Option<Balance> {
+		if !T::AMMPool::exists(asset_a, asset_b) {
+			return None;
+		}
 
-			Ok(())
+		Some(T::AMMPool::get_spot_price_unchecked(asset_a, asset_b, 1))
+	}
+
+	/// The amount of `asset_sell` that `amount_out` of `asset_buy` is worth at the pool's current
+	/// reserve ratio - the same quote `do_register_buy_intention` computes `amount_sell` from when
+	/// registering a `BUY` intention. A linear quote: the pool's slippage and fee, which an actual
+	/// AMM buy of `amount_out` also pays, are not included. `None` if no pool exists for the
+	/// pair. Exposed to external tools via `ExchangeApi::quote_buy`.
+	pub fn quote_buy(asset_buy: AssetId, asset_sell: AssetId, amount_out: Balance) -> Option<Balance> {
+		if !T::AMMPool::exists(asset_sell, asset_buy) {
+			return None;
 		}
 
-		/// Create buy intention
-		/// Calculate current spot price, create an intention and store in ```ExchangeAssetsIntentions```
-		#[weight =  <T as Config>::WeightInfo::buy_intention() + <T as Config>::WeightInfo::on_finalize_for_one_buy_extrinsic() -  <T as Config>::WeightInfo::known_overhead_for_on_finalize()]
-		pub fn buy(
-			origin,
-			asset_buy: AssetId,
-			asset_sell: AssetId,
-			amount_buy: Balance,
-			max_sold: Balance,
-			discount: bool,
-		)  -> dispatch::DispatchResult {
-			let who = ensure_signed(origin)?;
+		Some(T::AMMPool::get_spot_price_unchecked(asset_buy, asset_sell, amount_out))
+	}
 
-			ensure!(
-				T::AMMPool::exists(asset_sell, asset_buy),
-				Error::<T>::TokenPoolNotFound
-			);
+	/// Remove intentions whose `remaining_lifetime` has already reached `0`, reporting each one the
+	/// same way `on_finalize` would once it got round to it. Called from `on_idle` to spend a
+	/// block's spare weight discovering intentions that would otherwise sit around for another
+	/// `on_finalize` pass just to be dropped there.
+	///
+	/// Processes whole pairs at a time, in ascending `(asset_sell, asset_buy)` order, and stops as
+	/// soon as the next pair's intentions wouldn't fit in `remaining_weight` - a pair skipped this
+	/// way is left completely untouched for a future `on_idle` call or `on_finalize` to handle.
+	/// Returns the weight actually consumed.
+	fn clean_expired_intentions(remaining_weight: Weight) -> Weight {
+		let mut consumed: Weight = 0;
+		let check_cost = T::WeightInfo::on_idle_intention_check();
+
+		let mut pairs: Vec<(AssetId, AssetId)> = <ExchangeAssetsIntentions<T>>::iter().map(|(pair, _)| pair).collect();
+		pairs.sort();
+
+		for pair in pairs {
+			let intentions = <ExchangeAssetsIntentions<T>>::get(pair);
+			let pair_cost = check_cost.saturating_mul(intentions.len() as Weight);
+
+			if consumed.saturating_add(pair_cost) > remaining_weight {
+				break;
+			}
+			consumed = consumed.saturating_add(pair_cost);
 
-			let amount_sell = T::AMMPool::get_spot_price_unchecked(asset_buy, asset_sell, amount_buy);
+			let (expired, retained): (Vec<_>, Vec<_>) = intentions.into_iter().partition(Self::is_expired);
 
-			ensure!(
-				T::Currency::free_balance(asset_sell, &who) >= amount_sell,
-				Error::<T>::InsufficientAssetBalance
-			);
+			if expired.is_empty() {
+				continue;
+			}
 
-			let asset_1 = cmp::min(asset_sell, asset_buy);
-			let asset_2 = cmp::max(asset_sell, asset_buy);
+			if retained.is_empty() {
+				<ExchangeAssetsIntentions<T>>::remove(pair);
+			} else {
+				<ExchangeAssetsIntentions<T>>::insert(pair, retained);
+			}
 
-			let intention_count = ExchangeAssetsIntentionCount::get((asset_1, asset_2));
+			let sorted_pair = canonical_pair(pair.0, pair.1);
+			ExchangeAssetsIntentionCount::mutate(sorted_pair, |count| {
+				*count = count.saturating_sub(expired.len() as u32)
+			});
+			TotalIntentions::mutate(|total| *total = total.saturating_sub(expired.len() as u32));
 
-			let intention_id = Self::generate_intention_id(&who, intention_count, asset_1, asset_2);
+			for intention in expired {
+				Self::send_amm_validation_error_event(&intention, Error::<T>::IntentionExpired.into());
+			}
+		}
 
-			let intention = Intention::<T> {
-					who: who.clone(),
-					asset_sell,
-					asset_buy,
-					amount_sell,
-					amount_buy,
-					sell_or_buy: IntentionType::BUY,
-					discount,
-					intention_id,
-					trade_limit: max_sold
-			};
+		consumed
+	}
 
-			<ExchangeAssetsIntentions<T>>::append((intention.asset_sell, intention.asset_buy), intention.clone());
+	/// Remove every intention queued for `(asset_a, asset_b)` in either direction, unreserving any
+	/// funds held against them and marking each as `Status::Failed`. Called via
+	/// `IntentionPurger::purge_pair_intentions` once `pallet_amm` has destroyed the pair's pool,
+	/// since intentions left queued against a nonexistent pool would otherwise never settle.
+	pub fn purge_pair_intentions(asset_a: AssetId, asset_b: AssetId) {
+		let pair = canonical_pair(asset_a, asset_b);
+		let reverse = (pair.1, pair.0);
+
+		let purged: Vec<_> = <ExchangeAssetsIntentions<T>>::take(pair)
+			.into_iter()
+			.chain(<ExchangeAssetsIntentions<T>>::take(reverse))
+			.collect();
+
+		ExchangeAssetsIntentionCount::remove(pair);
+		ExchangeAssetsIntentionCount::remove(reverse);
+		TotalIntentions::mutate(|total| *total = total.saturating_sub(purged.len() as u32));
+
+		for intention in purged {
+			T::Currency::unreserve(intention.asset_sell, &intention.who, intention.amount_sell);
+			Self::set_intention_status(intention.intention_id, Status::Failed);
+			Self::deposit_event(RawEvent::FundsUnreserved(
+				intention.who,
+				intention.asset_sell,
+				intention.amount_sell,
+				intention.intention_id,
+			));
+		}
+	}
 
-			ExchangeAssetsIntentionCount::mutate((asset_1,asset_2), |total| *total += 1u32);
+	/// The fee owed on `amount` at `rate`, rounded up same as every other fee collected as its own
+	/// transfer, then clamped to `[T::MinFee, T::MaxFee]` - without the floor, `rate` applied to a
+	/// small enough `amount` rounds down to `0` and the trade slips through fee-free; without the
+	/// ceiling, `rate` applied to a large enough `amount` is unbounded. Neither clamp can push the
+	/// fee past `amount` itself - a dust-sized `amount` below `T::MinFee` is capped at `amount`
+	/// rather than charged a fee larger than the trade. `None` only on overflow.
+	pub(crate) fn calculate_fee(amount: Balance, rate: Fee) -> Option<Balance> {
+		let fee = amount.just_fee_round_up(rate)?;
+		let clamped = fee.max(T::MinFee::get()).min(T::MaxFee::get());
+
+		Some(clamped.min(amount))
+	}
 
-			Self::deposit_event(RawEvent::IntentionRegistered(who, asset_buy, asset_sell, amount_buy, IntentionType::BUY, intention.intention_id));
+	/// Record `amount` of `asset` as fee collected by trading `(asset_a, asset_b)`.
+	pub(crate) fn record_collected_fee(asset_a: AssetId, asset_b: AssetId, amount: Balance) {
+		let pair = canonical_pair(asset_a, asset_b);
+		CollectedFees::mutate(pair, |total| *total = total.saturating_add(amount));
+	}
 
-			Ok(())
+	/// The smallest `amount_sell` `sell` will accept for `asset` - `AssetMinTradeAmount`'s
+	/// override for it if one is set, otherwise the global `T::MinTradingLimit` (which `sell`
+	/// itself does not otherwise enforce - only `sell_all` does, against the caller's usable
+	/// balance rather than a requested amount).
+	pub fn min_trade_amount(asset: AssetId) -> Balance {
+		let custom = AssetMinTradeAmount::get(asset);
+
+		if custom.is_zero() {
+			T::MinTradingLimit::get()
+		} else {
+			custom
 		}
+	}
 
-		fn on_initialize() -> Weight {
-			T::WeightInfo::known_overhead_for_on_finalize()
+	/// The effective `trade_limit` for a new SELL intention expecting `amount_buy` at today's
+	/// spot price, given the caller's own `min_bought` - the stricter (higher) of `min_bought`
+	/// and the floor implied by `PairMaxSlippage`'s default for `(asset_sell, asset_buy)`, if one
+	/// is set. A pair with no entry defaults to `Permill::zero()`, i.e. no default and
+	/// `min_bought` applies unchanged.
+	fn effective_min_bought(asset_sell: AssetId, asset_buy: AssetId, amount_buy: Balance, min_bought: Balance) -> Balance {
+		let pair = canonical_pair(asset_sell, asset_buy);
+		let max_slippage = PairMaxSlippage::get(pair);
+
+		if max_slippage.is_zero() {
+			return min_bought;
 		}
 
-		/// Finalize and resolve all registered intentions.
-		/// Group/match intentions which can be directly traded.
-		fn on_finalize(){
+		let default_min_bought = amount_buy.saturating_sub(max_slippage.mul_ceil(amount_buy));
+		cmp::max(min_bought, default_min_bought)
+	}
 
-			for ((asset_1,asset_2), count) in ExchangeAssetsIntentionCount::iter() {
-				// If no intention registered for asset1/2, move onto next one
-				if count == 0u32 {
-					continue;
-				}
+	/// The effective `trade_limit` for a new BUY intention expecting to sell `amount_sell` at
+	/// today's spot price, given the caller's own `max_sold` - the stricter (lower) of
+	/// `max_sold` and the ceiling implied by `PairMaxSlippage`'s default for `(asset_sell,
+	/// asset_buy)`, if one is set. A pair with no entry defaults to `Permill::zero()`, i.e. no
+	/// default and `max_sold` applies unchanged. `max_sold` of `0` means the caller gave no
+	/// limit of their own.
+	fn effective_max_sold(asset_sell: AssetId, asset_buy: AssetId, amount_sell: Balance, max_sold: Balance) -> Balance {
+		let pair = canonical_pair(asset_sell, asset_buy);
+		let max_slippage = PairMaxSlippage::get(pair);
+
+		if max_slippage.is_zero() {
+			return max_sold;
+		}
 
-				let pair_account = T::AMMPool::get_pair_id(&asset_1, &asset_2);
+		let default_max_sold = amount_sell.saturating_add(max_slippage.mul_ceil(amount_sell));
 
-				let asset_a_sells = <ExchangeAssetsIntentions<T>>::get((asset_2, asset_1));
-				let asset_b_sells = <ExchangeAssetsIntentions<T>>::get((asset_1, asset_2));
+		if max_sold.is_zero() {
+			default_max_sold
+		} else {
+			cmp::min(max_sold, default_max_sold)
+		}
+	}
 
-				//TODO: we can short circuit here if nothing in asset_b_sells and just resolve asset a sells.
+	/// Whether `asset` is a real, currently-issued asset as far as `T::Currency` is concerned -
+	/// an asset that was never registered, or has since been removed, reports `0` issuance the
+	/// same way an empty-but-valid one would, rather than erroring, so this is the only reliable
+	/// way to tell the two apart.
+	fn asset_known(asset: AssetId) -> bool {
+		!T::Currency::total_issuance(asset).is_zero()
+	}
 
-				Self::process_exchange_intentions(&pair_account, &asset_a_sells, &asset_b_sells);
-			}
+	/// Reject trading `(asset_a, asset_b)` if either asset's reserve in the pair account is below
+	/// the pair's configured `PairMinLiquidity` - a pair with no entry defaults to `0`, i.e. no
+	/// restriction.
+	fn ensure_pool_liquidity_above_minimum(asset_a: AssetId, asset_b: AssetId) -> dispatch::DispatchResult {
+		let pair = canonical_pair(asset_a, asset_b);
+		let min_liquidity = PairMinLiquidity::get(pair);
 
-			ExchangeAssetsIntentionCount::remove_all();
-			ExchangeAssetsIntentions::<T>::remove_all();
+		if min_liquidity.is_zero() {
+			return Ok(());
 		}
+
+		let pair_account = T::AMMPool::get_pair_id(&asset_a, &asset_b);
+
+		ensure!(
+			T::Currency::free_balance(asset_a, &pair_account) >= min_liquidity
+				&& T::Currency::free_balance(asset_b, &pair_account) >= min_liquidity,
+			Error::<T>::PoolLiquidityBelowMinimum
+		);
+
+		Ok(())
 	}
-}
 
-// "Internal" functions, callable by code.
-impl<T: Config> Module<T> {
-	/// Process intentions and attempt to match them so they can be direct traded.
-	/// ```sell_a_intentions``` are considered 'main' intentions.
-	///
-	/// This algorithm is quite simple at the moment and it tries to match as many intentions from ```sell_b_intentions``` as possible while
-	/// satisfying  that sum( sell_b_intentions.amount_sell ) <= sell_a_intention.amount_sell
-	///
-	/// Intention A must be valid - that means that it is verified first by validating if it was possible to do AMM trade.
-	fn process_exchange_intentions(
-		pair_account: &T::AccountId,
-		sell_a_intentions: &[Intention<T>],
-		sell_b_intentions: &[Intention<T>],
-	) {
-		let mut b_copy = sell_b_intentions.to_owned();
-		let mut a_copy = sell_a_intentions.to_owned();
+	/// Reserve `intention`'s own encoded size against `PendingIntentionsBytes`, rejecting it
+	/// with `IntentionStorageBudgetExceeded` if that would push the running total past
+	/// `T::MaxIntentionsBytes` - checked before `intention` is appended to
+	/// `ExchangeAssetsIntentions`, so a rejected intention never touches storage at all.
+	fn reserve_intention_bytes(intention: &Intention<T>) -> dispatch::DispatchResult {
+		let size = intention.encoded_size() as u32;
+		let new_total = PendingIntentionsBytes::get().saturating_add(size);
 
-		b_copy.sort_by(|a, b| b.amount_sell.cmp(&a.amount_sell));
-		a_copy.sort_by(|a, b| b.amount_sell.cmp(&a.amount_sell));
+		ensure!(new_total <= T::MaxIntentionsBytes::get(), Error::<T>::IntentionStorageBudgetExceeded);
 
-		for intention in a_copy {
-			if !Self::verify_intention(&intention) {
-				continue;
-			}
+		PendingIntentionsBytes::put(new_total);
+		Ok(())
+	}
 
-			let mut bvec = Vec::<Intention<T>>::new();
-			let mut total = 0;
-			let mut idx: usize = 0;
+	/// Read a page of currently-registered intentions for off-chain consumption.
+	///
+	/// Intentions are read directly out of `ExchangeAssetsIntentions` using its
+	/// `IterableStorageMap` iteration, in whatever order the underlying trie yields keys - callers
+	/// should not rely on a stable ordering across blocks, or even across calls within the same
+	/// block. `page` is zero-indexed; a `page_size` of `0` always returns nothing.
+	pub fn get_intentions_page(page: u32, page_size: u32) -> Vec<Intention<T>> {
+		if page_size.is_zero() {
+			return Vec::new();
+		}
 
-			while let Some(matched) = b_copy.get(idx) {
-				bvec.push(matched.clone());
-				total += matched.amount_sell;
-				b_copy.remove(idx);
-				idx += 1;
+		ExchangeAssetsIntentions::<T>::iter()
+			.flat_map(|(_pair, intentions)| intentions)
+			.skip(page as usize * page_size as usize)
+			.take(page_size as usize)
+			.collect()
+	}
 
-				if total >= intention.amount_sell {
-					break;
+	/// Two intentions are an exact opposite match if they trade the same pair in opposite
+	/// directions for exactly the same amounts, so they can be fully direct-traded against each
+	/// other with nothing left over.
+	fn is_exact_match(a: &Intention<T>, b: &Intention<T>) -> bool {
+		a.sell_or_buy != b.sell_or_buy
+			&& a.asset_sell == b.asset_buy
+			&& a.asset_buy == b.asset_sell
+			&& a.amount_sell == b.amount_buy
+			&& a.amount_buy == b.amount_sell
+	}
+
+	/// Find the first pair of exactly-matching intentions in `intentions`, if any.
+	fn find_exact_match(intentions: &[Intention<T>]) -> Option<(&Intention<T>, &Intention<T>)> {
+		for (i, a) in intentions.iter().enumerate() {
+			for b in intentions.iter().skip(i + 1) {
+				if Self::is_exact_match(a, b) {
+					return Some((a, b));
 				}
 			}
+		}
 
-			T::Resolver::resolve_matched_intentions(pair_account, &intention, &bvec);
+		None
+	}
+
+	/// Remove settled intentions from storage so `on_finalize` doesn't try to match them again.
+	///
+	/// `pair` is the sorted `(min, max)` asset pair - both of its direction-keyed storage entries
+	/// are checked, since a matched pair of intentions trade in opposite directions.
+	fn remove_settled_intentions(pair: (AssetId, AssetId), ids: &[IntentionId<T>]) {
+		let mut removed = 0u32;
+
+		for key in [(pair.0, pair.1), (pair.1, pair.0)].iter() {
+			ExchangeAssetsIntentions::<T>::mutate(key, |intentions| {
+				let before = intentions.len();
+				intentions.retain(|i| !ids.contains(&i.intention_id));
+				removed += (before - intentions.len()) as u32;
+			});
 		}
 
-		// If something left in sell_b_intentions, just run it throught AMM.
-		while let Some(b_intention) = b_copy.pop() {
-			T::Resolver::resolve_single_intention(&b_intention);
+		if removed > 0 {
+			ExchangeAssetsIntentionCount::mutate(pair, |count| *count = count.saturating_sub(removed));
+			TotalIntentions::mutate(|total| *total = total.saturating_sub(removed));
 		}
 	}
 
-	/// Execute AMM trade.
-	///
-	/// This performs AMM trade with given transfer details.
-	fn execute_amm_transfer(
-		amm_tranfer_type: IntentionType,
-		intention_id: IntentionId<T>,
-		transfer: &AMMTransfer<T::AccountId, AssetId, Balance>,
-	) -> dispatch::DispatchResult {
-		match amm_tranfer_type {
-			IntentionType::SELL => {
-				T::AMMPool::execute_sell(transfer)?;
+	/// Page through registered intentions looking for an exact opposite-side match and submit an
+	/// unsigned `settle_offchain_match` transaction for the first one found.
+	fn run_offchain_matcher() {
+		let mut page = 0u32;
 
-				Self::deposit_event(RawEvent::IntentionResolvedAMMTrade(
-					transfer.origin.clone(),
-					IntentionType::SELL,
-					intention_id,
-					transfer.amount,
-					transfer.amount_out,
-				));
+		loop {
+			let intentions = Self::get_intentions_page(page, OFFCHAIN_MATCHER_PAGE_SIZE);
+			if intentions.is_empty() {
+				break;
 			}
-			IntentionType::BUY => {
-				T::AMMPool::execute_buy(transfer)?;
 
-				Self::deposit_event(RawEvent::IntentionResolvedAMMTrade(
-					transfer.origin.clone(),
-					IntentionType::BUY,
-					intention_id,
-					transfer.amount,
-					transfer.amount_out,
-				));
+			if let Some((a, b)) = Self::find_exact_match(&intentions) {
+				let pair = canonical_pair(a.asset_sell, a.asset_buy);
+				let call = Call::settle_offchain_match(pair, a.intention_id, b.intention_id);
+
+				let _ = SubmitTransaction::<T, Call<T>>::submit_unsigned_transaction(call);
 			}
-		};
 
-		Ok(())
+			page += 1;
+		}
 	}
 
-	/// Send intention resolve error event.
+	/// When `T::EnablePartialAMMFill` is set, binary search for the largest amount, no smaller
+	/// than `T::MinTradingLimit`, that `intention` can be AMM-traded at - used to partially fill
+	/// an intention whose full amount the AMM fallback trade rejected (e.g. for insufficient pool
+	/// liquidity). `intention`'s `trade_limit` is scaled down proportionally to the reduced
+	/// amount for each attempt.
 	///
-	/// Sends event with error detail for intention that failed.
-	fn send_intention_error_event(intention: &Intention<T>, error: dispatch::DispatchError) {
-		Self::deposit_event(RawEvent::IntentionResolveErrorEvent(
-			intention.who.clone(),
-			intention.asset_sell,
-			intention.asset_buy,
-			intention.sell_or_buy.clone(),
-			intention.intention_id,
-			error,
-		));
-	}
+	/// Returns `None` if partial fills are disabled, or if no amount in
+	/// `[T::MinTradingLimit, intention's full amount)` validates.
+	fn try_partial_amm_fill(intention: &Intention<T>) -> Option<AMMTransfer<T::AccountId, AssetId, Balance>> {
+		if !T::EnablePartialAMMFill::get() {
+			return None;
+		}
 
-	/// Verify sell or buy intention.
-	/// Perform AMM validate for given intention.
-	fn verify_intention(intention: &Intention<T>) -> bool {
-		match intention.sell_or_buy {
-			IntentionType::SELL => {
-				match T::AMMPool::validate_sell(
-					&intention.who,
-					intention.asset_sell,
-					intention.asset_buy,
-					intention.amount_sell,
-					intention.trade_limit,
-					intention.discount,
-				) {
-					Err(error) => {
-						Self::deposit_event(RawEvent::AMMSellErrorEvent(
-							intention.who.clone(),
-							intention.asset_sell,
-							intention.asset_buy,
-							intention.sell_or_buy.clone(),
-							intention.intention_id,
-							error,
-						));
-						false
-					}
-					_ => true,
+		let full_amount = match intention.sell_or_buy {
+			IntentionType::SELL => intention.amount_sell,
+			IntentionType::BUY => intention.amount_buy,
+		};
+
+		let min_amount = T::MinTradingLimit::get();
+		if full_amount <= min_amount {
+			return None;
+		}
+
+		let mut low = min_amount;
+		let mut high = full_amount.saturating_sub(1);
+		let mut best = None;
+
+		while low <= high {
+			let mid = low + (high - low) / 2;
+
+			match Self::validate_reduced_intention(intention, full_amount, mid) {
+				Ok(transfer) => {
+					best = Some(transfer);
+					low = mid.saturating_add(1);
 				}
-			}
-			IntentionType::BUY => {
-				match T::AMMPool::validate_buy(
-					&intention.who,
-					intention.asset_buy,
-					intention.asset_sell,
-					intention.amount_buy,
-					intention.trade_limit,
-					intention.discount,
-				) {
-					Err(error) => {
-						Self::deposit_event(RawEvent::AMMBuyErrorEvent(
-							intention.who.clone(),
-							intention.asset_buy,
-							intention.asset_sell,
-							intention.sell_or_buy.clone(),
-							intention.intention_id,
-							error,
-						));
-						false
+				Err(_) => {
+					if mid.is_zero() {
+						break;
 					}
-					_ => true,
+					high = mid - 1;
 				}
 			}
 		}
+
+		best
 	}
 
-	fn generate_intention_id(account: &T::AccountId, c: u32, a1: AssetId, a2: AssetId) -> IntentionId<T> {
-		let b = <system::Module<T>>::current_block_number();
-		(c, &account, b, a1, a2).using_encoded(T::Hashing::hash)
+	/// Validate `intention` as if it only traded `reduced_amount` (out of its original
+	/// `full_amount`), scaling its `trade_limit` down by the same proportion.
+	fn validate_reduced_intention(
+		intention: &Intention<T>,
+		full_amount: Balance,
+		reduced_amount: Balance,
+	) -> Result<AMMTransfer<T::AccountId, AssetId, Balance>, dispatch::DispatchError> {
+		let reduced_limit = intention.trade_limit.saturating_mul(reduced_amount) / full_amount;
+
+		match intention.sell_or_buy {
+			IntentionType::SELL => T::AMMPool::validate_sell(
+				&intention.who,
+				intention.asset_sell,
+				intention.asset_buy,
+				reduced_amount,
+				reduced_limit,
+				intention.discount,
+			),
+			IntentionType::BUY => T::AMMPool::validate_buy(
+				&intention.who,
+				intention.asset_buy,
+				intention.asset_sell,
+				reduced_amount,
+				reduced_limit,
+				intention.discount,
+			),
+		}
+	}
+}
+
+impl<T: Config> ValidateUnsigned for Module<T> {
+	type Call = Call<T>;
+
+	/// Only `settle_offchain_match` may be submitted unsigned, and only when the intentions it
+	/// names are still registered under the given pair and still an exact opposite match - the
+	/// same check `settle_offchain_match` itself makes before executing the trade.
+	fn validate_unsigned(_source: TransactionSource, call: &Self::Call) -> TransactionValidity {
+		let (pair, intention_a, intention_b) = match call {
+			Call::settle_offchain_match(pair, intention_a, intention_b) => (pair, intention_a, intention_b),
+			_ => return InvalidTransaction::Call.into(),
+		};
+
+		let forward = ExchangeAssetsIntentions::<T>::get((pair.0, pair.1));
+		let backward = ExchangeAssetsIntentions::<T>::get((pair.1, pair.0));
+		let candidates = forward.iter().chain(backward.iter());
+
+		let a = candidates.clone().find(|i| &i.intention_id == intention_a);
+		let b = candidates.clone().find(|i| &i.intention_id == intention_b);
+
+		match (a, b) {
+			(Some(a), Some(b)) if Self::is_exact_match(a, b) => ValidTransaction::with_tag_prefix("ExchangeOffchainSettlement")
+				.and_provides((pair, intention_a, intention_b))
+				.longevity(3)
+				.propagate(true)
+				.build(),
+			_ => InvalidTransaction::Stale.into(),
+		}
 	}
 }
 
-impl<T: Config> Resolver<T::AccountId, Intention<T>, Error<T>> for Module<T> {
-	/// Resolve intention via AMM pool.
-	fn resolve_single_intention(intention: &Intention<T>) {
+impl<T: Config> Resolver<T::AccountId, Intention<T>, Error<T>, Balance> for Module<T> {
+	/// Resolve intention via AMM pool. Returns the amount actually settled - `0` if it couldn't
+	/// be filled at all.
+	fn resolve_single_intention(intention: &Intention<T>) -> Balance {
 		let amm_transfer = match intention.sell_or_buy {
 			IntentionType::SELL => T::AMMPool::validate_sell(
 				&intention.who,
@@ -466,26 +3362,88 @@ impl<T: Config> Resolver<T::AccountId, Intention<T>, Error<T>> for Module<T> {
 		};
 
 		match amm_transfer {
-			Ok(x) => match Self::execute_amm_transfer(intention.sell_or_buy.clone(), intention.intention_id, &x) {
-				Ok(_) => {}
+			Ok(x) => match Self::execute_amm_transfer(intention, &x) {
+				Ok(_) => {
+					Self::deposit_event(RawEvent::LeftoverRoutedToAMM(intention.intention_id, intention.sell_or_buy));
+					x.amount
+				}
 				Err(error) => {
 					Self::send_intention_error_event(&intention, error);
+					Zero::zero()
 				}
 			},
-			Err(error) => {
-				Self::send_intention_error_event(&intention, error);
-			}
-		};
+			Err(error) => match Self::try_partial_amm_fill(intention) {
+				Some(x) => {
+					if let Err(error) = Self::execute_partial_amm_transfer(intention, &x) {
+						Self::send_intention_error_event(&intention, error);
+						Zero::zero()
+					} else {
+						x.amount
+					}
+				}
+				None => {
+					Self::send_intention_error_event(&intention, error);
+					Zero::zero()
+				}
+			},
+		}
 	}
 
 	/// Resolve main intention and corresponding matched intention
 	///
 	/// For each matched intention - it works out how much can be traded directly and rest is AMM traded.
 	/// If there is anything left in the main intention - it is AMM traded.
-	fn resolve_matched_intentions(pair_account: &T::AccountId, intention: &Intention<T>, matched: &[Intention<T>]) {
+	///
+	/// Event ordering is deterministic and indexers may rely on it: direct-trade events for
+	/// `matched` are deposited one at a time, in the same order `matched` is iterated, and any
+	/// AMM fallback event for the main intention's remainder is always deposited last, since it can
+	/// only happen once every matched intention has already been resolved.
+	///
+	/// Every AMM quote used here (`T::AMMPool::validate_sell`/`validate_buy`) is fetched fresh at
+	/// the point it's needed, never cached across intentions - so a pair's reserves moved by an
+	/// earlier intention's AMM fill in the same block are already reflected in a later intention's
+	/// quote. There is no matched-loop-wide "stale price" to recompute. This also can't leak across
+	/// different pairs that happen to share an asset: `T::AMMPool` keys reserves by pair, not by
+	/// asset, so settling one pair's fallback never moves what a different pair's fallback is
+	/// quoted against.
+	///
+	/// `matched` may be empty (e.g. every counterparty was below `T::MinMatchSize`), in which case
+	/// `intention` is resolved via the AMM fallback with no direct-trade work attempted at all.
+	fn resolve_matched_intentions(
+		pair_account: &T::AccountId,
+		intention: &Intention<T>,
+		matched: &[Intention<T>],
+	) -> (Balance, Balance) {
+		// Nothing to direct-trade against - go straight to the AMM fallback below without
+		// cloning `intention` or entering a loop that would do nothing anyway.
+		if matched.is_empty() {
+			return if intention.allow_amm_fallback {
+				let amm_volume = Self::resolve_single_intention(intention);
+				if !amm_volume.is_zero() {
+					Self::record_settlement(intention.who.clone(), intention.intention_id, amm_volume, Zero::zero(), 0, amm_volume);
+				}
+				(Zero::zero(), amm_volume)
+			} else {
+				Self::unreserve_and_notify_unmatched(intention);
+				(Zero::zero(), Zero::zero())
+			};
+		}
+
 		let mut intention_copy = intention.clone();
+		let mut matched_volume: Balance = Zero::zero();
+		let mut amm_volume: Balance = Zero::zero();
+		let mut fee_total_a: Balance = Zero::zero();
 
 		for matched_intention in matched.iter() {
+			if intention.who == matched_intention.who {
+				Self::deposit_event(RawEvent::SelfMatchSkipped(
+					intention.who.clone(),
+					intention.intention_id,
+					matched_intention.intention_id,
+				));
+				continue;
+			}
+
 			let amount_a_sell = intention_copy.amount_sell;
 			let amount_a_buy = intention_copy.amount_buy;
 			let amount_b_sell = matched_intention.amount_sell;
@@ -494,9 +3452,11 @@ impl<T: Config> Resolver<T::AccountId, Intention<T>, Error<T>> for Module<T> {
 			// There are multiple scenarios to handle
 			// !. Main intention amount left > matched intention amount
 			// 2. Main intention amount left < matched intention amount
-			// 3. Main intention amount left = matched intention amount
+			// 3. Main intention amount left = matched intention amount, within `MatchTolerance`
 
-			if amount_a_sell > amount_b_buy {
+			let amount_diff = amount_a_sell.max(amount_b_buy) - amount_a_sell.min(amount_b_buy);
+
+			if amount_diff > T::MatchTolerance::get() && amount_a_sell > amount_b_buy {
 				// Scenario 1: Matched intention can be completely directly traded
 				//
 				// 1. Prepare direct trade details - during preparation, direct amounts are reserved.
@@ -508,6 +3468,7 @@ impl<T: Config> Resolver<T::AccountId, Intention<T>, Error<T>> for Module<T> {
 					amount_from_a: amount_b_buy,
 					amount_from_b: amount_b_sell,
 					transfers: Vec::<Transfer<T>>::new(),
+					remaining_amount: amount_a_sell - amount_b_buy,
 				};
 
 				// As we direct trading the total matched intention amount - we need to check the trade limit for the matched intention
@@ -533,8 +3494,17 @@ impl<T: Config> Resolver<T::AccountId, Intention<T>, Error<T>> for Module<T> {
 				};
 
 				match dt.prepare(pair_account) {
-					true => {
-						dt.execute();
+					true if dt.execute() => {
+						matched_volume = matched_volume.saturating_add(dt.amount_from_a);
+						fee_total_a = fee_total_a.saturating_add(Self::fee_paid_by(&dt, &intention_copy.who));
+						Self::record_settlement(
+							matched_intention.who.clone(),
+							matched_intention.intention_id,
+							dt.amount_from_b,
+							Self::fee_paid_by(&dt, &matched_intention.who),
+							1,
+							Zero::zero(),
+						);
 
 						intention_copy.amount_sell = amount_a_sell - amount_b_buy;
 						intention_copy.amount_buy = amount_a_buy - amount_b_sell;
@@ -544,12 +3514,12 @@ impl<T: Config> Resolver<T::AccountId, Intention<T>, Error<T>> for Module<T> {
 							IntentionType::BUY => intention_copy.trade_limit - amount_b_sell,
 						};
 					}
-					false => {
+					_ => {
 						dt.revert();
 						continue;
 					}
 				}
-			} else if amount_a_sell < amount_b_buy {
+			} else if amount_diff > T::MatchTolerance::get() && amount_a_sell < amount_b_buy {
 				// Scenario 2: Matched intention CANNOT be completely directly traded
 				//
 				// 1. Work out rest amount and rest trade limits for direct trades.
@@ -571,6 +3541,7 @@ impl<T: Config> Resolver<T::AccountId, Intention<T>, Error<T>> for Module<T> {
 					amount_from_a: amount_a_sell,
 					amount_from_b: amount_b_sell - rest_sell_amount,
 					transfers: Vec::<Transfer<T>>::new(),
+					remaining_amount: Zero::zero(),
 				};
 
 				let amm_transfer_result = match matched_intention.sell_or_buy {
@@ -623,15 +3594,25 @@ impl<T: Config> Resolver<T::AccountId, Intention<T>, Error<T>> for Module<T> {
 
 				match dt.prepare(pair_account) {
 					true => {
-						match Self::execute_amm_transfer(
-							matched_intention.sell_or_buy.clone(),
-							matched_intention.intention_id,
-							&amm_transfer,
-						) {
-							Ok(_) => {
-								dt.execute();
+						match Self::execute_amm_transfer(matched_intention, &amm_transfer) {
+							Ok(_) if dt.execute() => {
+								matched_volume = matched_volume.saturating_add(dt.amount_from_a);
+								amm_volume = amm_volume.saturating_add(amm_transfer.amount);
+								fee_total_a = fee_total_a.saturating_add(Self::fee_paid_by(&dt, &intention_copy.who));
+								Self::record_settlement(
+									matched_intention.who.clone(),
+									matched_intention.intention_id,
+									dt.amount_from_b.saturating_add(amm_transfer.amount),
+									Self::fee_paid_by(&dt, &matched_intention.who),
+									1,
+									amm_transfer.amount,
+								);
 								intention_copy.amount_sell = 0;
 							}
+							Ok(_) => {
+								dt.revert();
+								continue;
+							}
 							Err(error) => {
 								Self::send_intention_error_event(&matched_intention, error);
 								dt.revert();
@@ -645,7 +3626,10 @@ impl<T: Config> Resolver<T::AccountId, Intention<T>, Error<T>> for Module<T> {
 					}
 				}
 			} else {
-				// Scenario 3: Exact match
+				// Scenario 3: Exact match (or within `MatchTolerance` of one) - either way, the
+				// full `amount_a_sell`/`amount_b_sell` is direct-traded and any dust discrepancy
+				// between `amount_a_sell` and `amount_b_buy` is simply absorbed rather than
+				// carried forward.
 				//
 				// 1. Prepare direct trade
 				// 2. Verify and execute
@@ -656,6 +3640,7 @@ impl<T: Config> Resolver<T::AccountId, Intention<T>, Error<T>> for Module<T> {
 					amount_from_a: amount_a_sell,
 					amount_from_b: amount_b_sell,
 					transfers: Vec::<Transfer<T>>::new(),
+					remaining_amount: Zero::zero(),
 				};
 
 				// As we direct trading the total matched intention amount - we need to check the trade limit for the matched intention
@@ -696,11 +3681,20 @@ impl<T: Config> Resolver<T::AccountId, Intention<T>, Error<T>> for Module<T> {
 				};
 
 				match dt.prepare(pair_account) {
-					true => {
-						dt.execute();
+					true if dt.execute() => {
+						matched_volume = matched_volume.saturating_add(dt.amount_from_a);
+						fee_total_a = fee_total_a.saturating_add(Self::fee_paid_by(&dt, &intention_copy.who));
+						Self::record_settlement(
+							matched_intention.who.clone(),
+							matched_intention.intention_id,
+							dt.amount_from_b,
+							Self::fee_paid_by(&dt, &matched_intention.who),
+							1,
+							Zero::zero(),
+						);
 						intention_copy.amount_sell = 0;
 					}
-					false => {
+					_ => {
 						dt.revert();
 						continue;
 					}
@@ -708,9 +3702,34 @@ impl<T: Config> Resolver<T::AccountId, Intention<T>, Error<T>> for Module<T> {
 			}
 		}
 
-		// If there is something left, just resolve as single intention
+		// If there is something left, just resolve as single intention - unless the intention has
+		// opted out of the AMM fallback, in which case the leftover is dropped instead.
 		if intention_copy.amount_sell > 0 {
-			Self::resolve_single_intention(&intention_copy);
+			if intention_copy.allow_amm_fallback {
+				amm_volume = amm_volume.saturating_add(Self::resolve_single_intention(&intention_copy));
+			} else {
+				Self::unreserve_and_notify_unmatched(&intention_copy);
+			}
+		}
+
+		let filled = matched_volume.saturating_add(amm_volume);
+		if !filled.is_zero() {
+			Self::record_settlement(
+				intention.who.clone(),
+				intention.intention_id,
+				filled,
+				fee_total_a,
+				matched.len() as u32,
+				amm_volume,
+			);
 		}
+
+		(matched_volume, amm_volume)
+	}
+}
+
+impl<T: Config> IntentionPurger<AssetId> for Module<T> {
+	fn purge_pair_intentions(asset_a: AssetId, asset_b: AssetId) {
+		Self::purge_pair_intentions(asset_a, asset_b);
 	}
 }
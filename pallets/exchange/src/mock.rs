@@ -1,19 +1,26 @@
 // Creating mock runtime here
 
-use crate::{Config, Module};
-use frame_support::{impl_outer_event, impl_outer_origin, parameter_types};
+use crate::{Call, Config, Module};
+use frame_support::{impl_outer_dispatch, impl_outer_event, impl_outer_origin, parameter_types, traits::Get};
 use frame_system as system;
+use frame_system::offchain::SendTransactionTypes;
 use orml_traits::parameter_type_with_key;
 use sp_core::H256;
 use sp_runtime::{
-	testing::Header,
+	testing::{Header, TestXt},
 	traits::{BlakeTwo256, IdentityLookup, Zero},
 };
 
 use pallet_amm as amm;
 
 use pallet_amm::AssetPairAccountIdFor;
-use primitives::{fee, AssetId, Balance};
+use primitives::{
+	fee,
+	traits::{OnTradeHandler, PriceProvider},
+	AssetId, Balance,
+};
+use sp_runtime::Permill;
+use std::cell::RefCell;
 
 pub type Amount = i128;
 pub type AccountId = u64;
@@ -33,6 +40,45 @@ mod exchange {
 	pub use super::super::*;
 }
 
+/// A minimal stand-in for a pallet composing on top of the exchange - e.g. a router - with a
+/// single dispatchable that registers an intention on the caller's behalf via
+/// `pallet_exchange::Module::submit_intention` and records the assigned id, so tests can drive
+/// `submit_intention` the way an external pallet would rather than calling it directly.
+pub mod mock_caller {
+	use crate::IntentionId;
+	use frame_support::{decl_module, decl_storage, dispatch};
+	use frame_system::ensure_signed;
+	use primitives::{AssetId, Balance, IntentionType};
+
+	pub trait Config: crate::Config {}
+
+	decl_storage! {
+		trait Store for Module<T: Config> as MockCaller {
+			pub LastIntentionId get(fn last_intention_id): Option<IntentionId<T>>;
+		}
+	}
+
+	decl_module! {
+		pub struct Module<T: Config> for enum Call where origin: T::Origin {
+			#[weight = 10_000]
+			pub fn submit(
+				origin,
+				asset_sell: AssetId,
+				asset_buy: AssetId,
+				amount: Balance,
+				intention_type: IntentionType,
+				discount: bool,
+			) -> dispatch::DispatchResult {
+				let who = ensure_signed(origin)?;
+				let intention_id =
+					crate::Module::<T>::submit_intention(who, asset_sell, asset_buy, amount, intention_type, discount)?;
+				LastIntentionId::<T>::put(intention_id);
+				Ok(())
+			}
+		}
+	}
+}
+
 impl_outer_event! {
 	pub enum TestEvent for Test{
 		system<T>,
@@ -46,6 +92,15 @@ impl_outer_origin! {
 	pub enum Origin for Test {}
 }
 
+// Only `System` and `Exchange` need to be dispatchable through the outer `Call` - nothing else in
+// this mock exercises `SignedExtension`/`ValidateUnsigned` against a call built from the rest.
+impl_outer_dispatch! {
+	pub enum TestCall for Test where origin: Origin {
+		system::System,
+		exchange::Exchange,
+	}
+}
+
 // For testing the pallet, we construct most of a mock runtime. This means
 // first constructing a configuration type (`Test`) which `impl`s each of the
 // configuration traits of pallets we want to use.
@@ -57,13 +112,362 @@ parameter_types! {
 	pub const HDXAssetId: AssetId = HDX;
 
 	pub ExchangeFeeRate: fee::Fee = fee::Fee::default();
+
+	pub const MaxPriceDeviation: Permill = Permill::from_percent(10);
+
+	pub const MaxPriceImpact: Permill = Permill::from_percent(10);
+
+	pub const DefaultIntentionLifetime: u64 = 3;
+
+	pub const MinTradingLimit: Balance = 1_000;
+
+	pub const NativeAssetId: AssetId = HDX;
+
+	pub const MinimumPeriod: u64 = 1;
+}
+
+thread_local! {
+	static ORACLE_PRICE: RefCell<Option<Balance>> = RefCell::new(None);
+	static ENABLE_PARTIAL_AMM_FILL: RefCell<bool> = RefCell::new(false);
+	static ENABLE_ROUTING: RefCell<bool> = RefCell::new(false);
+	static MATCH_TOLERANCE: RefCell<Balance> = RefCell::new(0);
+	static MIN_MATCH_SIZE: RefCell<Balance> = RefCell::new(0);
+	static ON_TRADE_CALLS: RefCell<Vec<(AccountId, AssetId, AssetId, Balance, Balance)>> = RefCell::new(Vec::new());
+	static MIN_POOL_RESERVE: RefCell<Balance> = RefCell::new(0);
+	static COLLECT_FEES_IN_NATIVE: RefCell<bool> = RefCell::new(false);
+	static PRICE_PROXIMITY_MATCHING: RefCell<bool> = RefCell::new(false);
+	static MAX_EVENTS_PER_BLOCK: RefCell<u32> = RefCell::new(u32::MAX);
+	static CANCELLATION_FEE: RefCell<Balance> = RefCell::new(0);
+	static NET_SETTLEMENT_TRANSFERS: RefCell<bool> = RefCell::new(false);
+	static ALLOW_PARTIAL_ON_SHORTFALL: RefCell<bool> = RefCell::new(false);
+	static PRIORITY_FEE: RefCell<Balance> = RefCell::new(0);
+	static ALLOW_POOL_CREATION_ON_DEMAND: RefCell<bool> = RefCell::new(false);
+	static MAX_COUNTERPARTIES_PER_INTENTION: RefCell<u32> = RefCell::new(u32::MAX);
+	static MIN_FEE: RefCell<Balance> = RefCell::new(0);
+	static MAX_FEE: RefCell<Balance> = RefCell::new(Balance::MAX);
+	static MAX_INTENTIONS_BYTES: RefCell<u32> = RefCell::new(u32::MAX);
+}
+
+/// Test-only `CollectFeesInNative` whose value can be set per-test via `set` - defaults to
+/// `false`, i.e. fees stay in whatever asset they were paid in, so existing tests keep their
+/// current fee behaviour unless they opt in.
+pub struct CollectFeesInNativeMock;
+
+impl CollectFeesInNativeMock {
+	pub fn set(enabled: bool) {
+		COLLECT_FEES_IN_NATIVE.with(|v| *v.borrow_mut() = enabled);
+	}
+}
+
+impl Get<bool> for CollectFeesInNativeMock {
+	fn get() -> bool {
+		COLLECT_FEES_IN_NATIVE.with(|v| *v.borrow())
+	}
+}
+
+/// Test-only `PriceProximityMatching` whose value can be set per-test via `set` - defaults to
+/// `false`, i.e. match buckets keep their amount-sorted order, so existing tests aren't affected
+/// unless they opt in.
+pub struct PriceProximityMatchingMock;
+
+impl PriceProximityMatchingMock {
+	pub fn set(enabled: bool) {
+		PRICE_PROXIMITY_MATCHING.with(|v| *v.borrow_mut() = enabled);
+	}
+}
+
+impl Get<bool> for PriceProximityMatchingMock {
+	fn get() -> bool {
+		PRICE_PROXIMITY_MATCHING.with(|v| *v.borrow())
+	}
+}
+
+/// Test-only `MaxEventsPerBlock` whose value can be set per-test via `set` - defaults to
+/// `u32::MAX`, i.e. no resolution event is ever suppressed, so existing tests keep asserting on
+/// them unless they opt in.
+pub struct MaxEventsPerBlockMock;
+
+impl MaxEventsPerBlockMock {
+	pub fn set(max_events: u32) {
+		MAX_EVENTS_PER_BLOCK.with(|v| *v.borrow_mut() = max_events);
+	}
+}
+
+impl Get<u32> for MaxEventsPerBlockMock {
+	fn get() -> u32 {
+		MAX_EVENTS_PER_BLOCK.with(|v| *v.borrow())
+	}
+}
+
+/// Test-only `MaxCounterpartiesPerIntention` whose value can be set per-test via `set` - defaults
+/// to `u32::MAX`, i.e. a group's counterparty count is never capped, so existing tests keep their
+/// current matching behaviour unless they opt in.
+pub struct MaxCounterpartiesPerIntentionMock;
+
+impl MaxCounterpartiesPerIntentionMock {
+	pub fn set(max_counterparties: u32) {
+		MAX_COUNTERPARTIES_PER_INTENTION.with(|v| *v.borrow_mut() = max_counterparties);
+	}
+}
+
+impl Get<u32> for MaxCounterpartiesPerIntentionMock {
+	fn get() -> u32 {
+		MAX_COUNTERPARTIES_PER_INTENTION.with(|v| *v.borrow())
+	}
+}
+
+/// Test-only `MinFee` whose value can be set per-test via `set` - defaults to `0`, i.e. no floor,
+/// so existing tests keep their current unclamped fee behaviour unless they opt in.
+pub struct MinFeeMock;
+
+impl MinFeeMock {
+	pub fn set(fee: Balance) {
+		MIN_FEE.with(|v| *v.borrow_mut() = fee);
+	}
+}
+
+impl Get<Balance> for MinFeeMock {
+	fn get() -> Balance {
+		MIN_FEE.with(|v| *v.borrow())
+	}
+}
+
+/// Test-only `MaxFee` whose value can be set per-test via `set` - defaults to `Balance::MAX`, i.e.
+/// no ceiling, so existing tests keep their current unclamped fee behaviour unless they opt in.
+pub struct MaxFeeMock;
+
+impl MaxFeeMock {
+	pub fn set(fee: Balance) {
+		MAX_FEE.with(|v| *v.borrow_mut() = fee);
+	}
+}
+
+impl Get<Balance> for MaxFeeMock {
+	fn get() -> Balance {
+		MAX_FEE.with(|v| *v.borrow())
+	}
+}
+
+/// Test-only `MaxIntentionsBytes` whose value can be set per-test via `set` - defaults to
+/// `u32::MAX`, i.e. `PendingIntentionsBytes` never blocks a registration, so existing tests keep
+/// their current behaviour unless they opt in.
+pub struct MaxIntentionsBytesMock;
+
+impl MaxIntentionsBytesMock {
+	pub fn set(max_bytes: u32) {
+		MAX_INTENTIONS_BYTES.with(|v| *v.borrow_mut() = max_bytes);
+	}
+}
+
+impl Get<u32> for MaxIntentionsBytesMock {
+	fn get() -> u32 {
+		MAX_INTENTIONS_BYTES.with(|v| *v.borrow())
+	}
+}
+
+/// Test-only `CancellationFee` whose value can be set per-test via `set` - defaults to `0`, i.e.
+/// cancelling never costs anything, so existing tests keep their current behaviour unless they
+/// opt in.
+pub struct CancellationFeeMock;
+
+impl CancellationFeeMock {
+	pub fn set(fee: Balance) {
+		CANCELLATION_FEE.with(|v| *v.borrow_mut() = fee);
+	}
+}
+
+impl Get<Balance> for CancellationFeeMock {
+	fn get() -> Balance {
+		CANCELLATION_FEE.with(|v| *v.borrow())
+	}
+}
+
+/// Test-only `NetSettlementTransfers` whose value can be set per-test via `set` - defaults to
+/// `false`, i.e. `cancel_pair` transfers each intention's fee individually, so existing tests
+/// keep their current per-intention transfer behaviour unless they opt in.
+pub struct NetSettlementTransfersMock;
+
+impl NetSettlementTransfersMock {
+	pub fn set(enabled: bool) {
+		NET_SETTLEMENT_TRANSFERS.with(|v| *v.borrow_mut() = enabled);
+	}
+}
+
+impl Get<bool> for NetSettlementTransfersMock {
+	fn get() -> bool {
+		NET_SETTLEMENT_TRANSFERS.with(|v| *v.borrow())
+	}
+}
+
+/// Test-only `AllowPartialOnShortfall` whose value can be set per-test via `set` - defaults to
+/// `false`, i.e. a direct trade fails outright on a balance shortfall, so existing tests keep
+/// their current all-or-nothing behaviour unless they opt in.
+pub struct AllowPartialOnShortfallMock;
+
+impl AllowPartialOnShortfallMock {
+	pub fn set(enabled: bool) {
+		ALLOW_PARTIAL_ON_SHORTFALL.with(|v| *v.borrow_mut() = enabled);
+	}
+}
+
+impl Get<bool> for AllowPartialOnShortfallMock {
+	fn get() -> bool {
+		ALLOW_PARTIAL_ON_SHORTFALL.with(|v| *v.borrow())
+	}
+}
+
+/// Test-only `PriorityFee` whose value can be set per-test via `set` - defaults to `0`, i.e.
+/// raising an intention's priority never costs anything, so existing tests keep their current
+/// behaviour unless they opt in.
+pub struct PriorityFeeMock;
+
+impl PriorityFeeMock {
+	pub fn set(fee: Balance) {
+		PRIORITY_FEE.with(|v| *v.borrow_mut() = fee);
+	}
+}
+
+impl Get<Balance> for PriorityFeeMock {
+	fn get() -> Balance {
+		PRIORITY_FEE.with(|v| *v.borrow())
+	}
+}
+
+/// Test-only `MinPoolReserve` whose value can be set per-test via `set` - defaults to `0`, i.e.
+/// no pool is ever considered too low on reserves, so existing tests aren't affected unless they
+/// opt in.
+pub struct MinPoolReserveMock;
+
+impl MinPoolReserveMock {
+	pub fn set(min_pool_reserve: Balance) {
+		MIN_POOL_RESERVE.with(|v| *v.borrow_mut() = min_pool_reserve);
+	}
+}
+
+impl Get<Balance> for MinPoolReserveMock {
+	fn get() -> Balance {
+		MIN_POOL_RESERVE.with(|v| *v.borrow())
+	}
+}
+
+/// Test-only `OnTradeHandler` which records every call it receives, so tests can assert it fires
+/// for each settled leg without needing a real downstream pallet to observe.
+pub struct OnTradeHandlerMock;
+
+impl OnTradeHandlerMock {
+	pub fn calls() -> Vec<(AccountId, AssetId, AssetId, Balance, Balance)> {
+		ON_TRADE_CALLS.with(|v| v.borrow().clone())
+	}
+
+	pub fn reset() {
+		ON_TRADE_CALLS.with(|v| v.borrow_mut().clear());
+	}
+}
+
+impl OnTradeHandler<AccountId, AssetId, Balance> for OnTradeHandlerMock {
+	fn on_trade(who: &AccountId, asset_in: AssetId, asset_out: AssetId, amount_in: Balance, amount_out: Balance) {
+		ON_TRADE_CALLS.with(|v| v.borrow_mut().push((*who, asset_in, asset_out, amount_in, amount_out)));
+	}
+}
+
+/// Test-only `MinMatchSize` whose value can be set per-test via `set` - defaults to `0`, i.e. no
+/// counterparty is too small to match, so existing tests keep their current matching behaviour
+/// unless they opt in.
+pub struct MinMatchSizeMock;
+
+impl MinMatchSizeMock {
+	pub fn set(min_match_size: Balance) {
+		MIN_MATCH_SIZE.with(|v| *v.borrow_mut() = min_match_size);
+	}
+}
+
+impl Get<Balance> for MinMatchSizeMock {
+	fn get() -> Balance {
+		MIN_MATCH_SIZE.with(|v| *v.borrow())
+	}
+}
+
+/// Test-only `MatchTolerance` whose value can be set per-test via `set` - defaults to `0`, i.e.
+/// no tolerance, so existing tests keep requiring an exact match unless they opt in.
+pub struct MatchToleranceMock;
+
+impl MatchToleranceMock {
+	pub fn set(tolerance: Balance) {
+		MATCH_TOLERANCE.with(|v| *v.borrow_mut() = tolerance);
+	}
+}
+
+impl Get<Balance> for MatchToleranceMock {
+	fn get() -> Balance {
+		MATCH_TOLERANCE.with(|v| *v.borrow())
+	}
+}
+
+pub struct EnablePartialAMMFillMock;
+
+impl EnablePartialAMMFillMock {
+	pub fn set(enabled: bool) {
+		ENABLE_PARTIAL_AMM_FILL.with(|v| *v.borrow_mut() = enabled);
+	}
+}
+
+impl Get<bool> for EnablePartialAMMFillMock {
+	fn get() -> bool {
+		ENABLE_PARTIAL_AMM_FILL.with(|v| *v.borrow())
+	}
+}
+
+/// Test-only `AllowPoolCreationOnDemand` whose value can be set per-test via `set` - defaults to
+/// `false`, i.e. `create_if_missing` is a no-op and a missing pool is always rejected with
+/// `TokenPoolNotFound`, so existing tests keep their current behaviour unless they opt in.
+pub struct AllowPoolCreationOnDemandMock;
+
+impl AllowPoolCreationOnDemandMock {
+	pub fn set(enabled: bool) {
+		ALLOW_POOL_CREATION_ON_DEMAND.with(|v| *v.borrow_mut() = enabled);
+	}
+}
+
+impl Get<bool> for AllowPoolCreationOnDemandMock {
+	fn get() -> bool {
+		ALLOW_POOL_CREATION_ON_DEMAND.with(|v| *v.borrow())
+	}
+}
+
+pub struct EnableRoutingMock;
+
+impl EnableRoutingMock {
+	pub fn set(enabled: bool) {
+		ENABLE_ROUTING.with(|v| *v.borrow_mut() = enabled);
+	}
+}
+
+impl Get<bool> for EnableRoutingMock {
+	fn get() -> bool {
+		ENABLE_ROUTING.with(|v| *v.borrow())
+	}
+}
+
+/// Test-only price oracle whose reported price can be set per-test via `set_price`.
+pub struct PriceOracleMock;
+
+impl PriceOracleMock {
+	pub fn set_price(price: Option<Balance>) {
+		ORACLE_PRICE.with(|v| *v.borrow_mut() = price);
+	}
+}
+
+impl PriceProvider<AssetId, Balance> for PriceOracleMock {
+	fn spot_price(_asset_a: AssetId, _asset_b: AssetId, _amount: Balance) -> Option<Balance> {
+		ORACLE_PRICE.with(|v| *v.borrow())
+	}
 }
 impl system::Config for Test {
 	type BaseCallFilter = ();
 	type BlockWeights = ();
 	type BlockLength = ();
 	type Origin = Origin;
-	type Call = ();
+	type Call = TestCall;
 	type Index = u64;
 	type BlockNumber = u64;
 	type Hash = H256;
@@ -100,6 +504,15 @@ impl orml_tokens::Config for Test {
 
 pub type Currency = orml_tokens::Module<Test>;
 
+impl pallet_timestamp::Config for Test {
+	type Moment = u64;
+	type OnTimestampSet = ();
+	type MinimumPeriod = MinimumPeriod;
+	type WeightInfo = ();
+}
+
+pub type Timestamp = pallet_timestamp::Module<Test>;
+
 impl pallet_asset_registry::Config for Test {
 	type AssetId = AssetId;
 }
@@ -126,20 +539,57 @@ impl amm::Config for Test {
 	type HDXAssetId = HDXAssetId;
 	type WeightInfo = ();
 	type GetExchangeFee = ExchangeFeeRate;
+	type IntentionHandler = Exchange;
 }
 
 pub type AMMModule = amm::Module<Test>;
 pub type System = system::Module<Test>;
 
+type TestExtrinsic = TestXt<Call<Test>, ()>;
+
+impl SendTransactionTypes<Call<Test>> for Test {
+	type OverarchingCall = Call<Test>;
+	type Extrinsic = TestExtrinsic;
+}
+
 impl Config for Test {
 	type Event = TestEvent;
 	type AMMPool = AMMModule;
+	type AMMTrader = AMMModule;
 	type Currency = Currency;
 	type Resolver = exchange::Module<Test>;
 	type WeightInfo = ();
+	type PriceOracle = PriceOracleMock;
+	type MaxPriceDeviation = MaxPriceDeviation;
+	type MaxPriceImpact = MaxPriceImpact;
+	type DefaultIntentionLifetime = DefaultIntentionLifetime;
+	type EnablePartialAMMFill = EnablePartialAMMFillMock;
+	type MinTradingLimit = MinTradingLimit;
+	type MatchTolerance = MatchToleranceMock;
+	type MinMatchSize = MinMatchSizeMock;
+	type MaxCounterpartiesPerIntention = MaxCounterpartiesPerIntentionMock;
+	type OnTradeHandler = OnTradeHandlerMock;
+	type MinPoolReserve = MinPoolReserveMock;
+	type CollectFeesInNative = CollectFeesInNativeMock;
+	type PriceProximityMatching = PriceProximityMatchingMock;
+	type MaxEventsPerBlock = MaxEventsPerBlockMock;
+	type CancellationFee = CancellationFeeMock;
+	type NetSettlementTransfers = NetSettlementTransfersMock;
+	type AllowPartialOnShortfall = AllowPartialOnShortfallMock;
+	type PriorityFee = PriorityFeeMock;
+	type AllowPoolCreationOnDemand = AllowPoolCreationOnDemandMock;
+	type MinFee = MinFeeMock;
+	type MaxFee = MaxFeeMock;
+	type MaxIntentionsBytes = MaxIntentionsBytesMock;
+	type EnableRouting = EnableRoutingMock;
+	type NativeAssetId = NativeAssetId;
+	type PauseOrigin = frame_system::EnsureRoot<AccountId>;
 }
 pub type Exchange = Module<Test>;
 
+impl mock_caller::Config for Test {}
+pub type MockCaller = mock_caller::Module<Test>;
+
 pub struct ExtBuilder {
 	endowed_accounts: Vec<(AccountId, AssetId, Balance)>,
 }
@@ -0,0 +1,103 @@
+//! `SignedExtension` that pre-rejects a `sell`/`buy` transaction during `validate_transaction`,
+//! before it is ever gossiped or included in a block, if it would fail on dispatch for a reason
+//! cheap enough to check up front. Keeping these out of the pool means a spammer can't fill it
+//! with transactions that were never going to settle.
+//!
+//! Only the cheapest, always-true-or-false preconditions are checked here - a zero `amount`, or
+//! a pair with no pool when the call isn't asking to create one. Anything that can change between
+//! validation and inclusion (balance, frozen assets, pool liquidity, expiry, ...) is left to the
+//! full checks `validate_sell_registration`/`do_register_buy_intention` make at dispatch time.
+
+use crate::{Call, Config};
+use codec::{Decode, Encode};
+use frame_support::traits::IsSubType;
+use primitives::traits::AMM;
+use sp_runtime::traits::{DispatchInfoOf, SignedExtension, Zero};
+use sp_runtime::transaction_validity::{
+	InvalidTransaction, TransactionValidity, TransactionValidityError, ValidTransaction,
+};
+use sp_std::marker::PhantomData;
+
+/// `InvalidTransaction::Custom` code for a `sell`/`buy` whose `amount` is `0`.
+const INVALID_ZERO_AMOUNT: u8 = 1;
+/// `InvalidTransaction::Custom` code for a `sell`/`buy` naming a pair with no pool, submitted
+/// without `create_if_missing`.
+const INVALID_NO_POOL: u8 = 2;
+
+/// See the module documentation.
+#[derive(Encode, Decode, Clone, Eq, PartialEq)]
+pub struct RejectObviouslyInvalidExchangeCall<T: Config + Send + Sync>(PhantomData<T>);
+
+impl<T: Config + Send + Sync> RejectObviouslyInvalidExchangeCall<T> {
+	pub fn new() -> Self {
+		Self(PhantomData)
+	}
+}
+
+impl<T: Config + Send + Sync> Default for RejectObviouslyInvalidExchangeCall<T> {
+	fn default() -> Self {
+		Self::new()
+	}
+}
+
+impl<T: Config + Send + Sync> sp_std::fmt::Debug for RejectObviouslyInvalidExchangeCall<T> {
+	#[cfg(feature = "std")]
+	fn fmt(&self, f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+		write!(f, "RejectObviouslyInvalidExchangeCall")
+	}
+
+	#[cfg(not(feature = "std"))]
+	fn fmt(&self, _f: &mut sp_std::fmt::Formatter) -> sp_std::fmt::Result {
+		Ok(())
+	}
+}
+
+impl<T: Config + Send + Sync> SignedExtension for RejectObviouslyInvalidExchangeCall<T>
+where
+	<T as frame_system::Config>::Call: IsSubType<Call<T>>,
+{
+	const IDENTIFIER: &'static str = "RejectObviouslyInvalidExchangeCall";
+	type AccountId = T::AccountId;
+	type Call = <T as frame_system::Config>::Call;
+	type AdditionalSigned = ();
+	type Pre = ();
+
+	fn additional_signed(&self) -> Result<Self::AdditionalSigned, TransactionValidityError> {
+		Ok(())
+	}
+
+	fn validate(
+		&self,
+		_who: &Self::AccountId,
+		call: &Self::Call,
+		_info: &DispatchInfoOf<Self::Call>,
+		_len: usize,
+	) -> TransactionValidity {
+		// `create_if_missing` is at the same argument position in both `sell` and `buy` - see the
+		// extrinsics in `lib.rs`.
+		let checked = match call.is_sub_type() {
+			Some(Call::sell(asset_sell, asset_buy, amount_sell, _, _, _, _, _, _, create_if_missing, _, _)) => {
+				Some((*asset_sell, *asset_buy, *amount_sell, *create_if_missing))
+			}
+			Some(Call::buy(asset_buy, asset_sell, amount_buy, _, _, _, _, _, _, create_if_missing, _, _)) => {
+				Some((*asset_sell, *asset_buy, *amount_buy, *create_if_missing))
+			}
+			_ => None,
+		};
+
+		let (asset_sell, asset_buy, amount, create_if_missing) = match checked {
+			Some(v) => v,
+			None => return Ok(ValidTransaction::default()),
+		};
+
+		if amount.is_zero() {
+			return Err(InvalidTransaction::Custom(INVALID_ZERO_AMOUNT).into());
+		}
+
+		if !create_if_missing && !T::AMMPool::exists(asset_sell, asset_buy) {
+			return Err(InvalidTransaction::Custom(INVALID_NO_POOL).into());
+		}
+
+		Ok(ValidTransaction::default())
+	}
+}
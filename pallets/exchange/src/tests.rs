@@ -1,12 +1,24 @@
 use super::*;
 use crate::mock::*;
+use frame_support::metadata::DecodeDifferent;
 use frame_support::sp_runtime::traits::Hash;
-use frame_support::traits::OnFinalize;
+use frame_support::traits::{OnFinalize, OnInitialize};
 use frame_support::{assert_noop, assert_ok};
 use frame_system::InitKind;
+use primitives::fee::{Fee, WithFee};
 use primitives::Price;
-use sp_runtime::{DispatchError, FixedPointNumber};
-
+use sp_runtime::{
+	traits::SignedExtension,
+	transaction_validity::InvalidTransaction,
+	DispatchError, FixedPointNumber,
+};
+
+use crate::signed_extension::RejectObviouslyInvalidExchangeCall;
+
+use crate::mock::{
+	CollectFeesInNativeMock, EnablePartialAMMFillMock, EnableRoutingMock, MatchToleranceMock, MinMatchSizeMock,
+	MinPoolReserveMock, OnTradeHandlerMock, PriceOracleMock,
+};
 use pallet_amm as amm;
 
 const ENDOWED_AMOUNT: u128 = 1_000_000_000_000_000;
@@ -40,8 +52,18 @@ fn expect_events(e: Vec<TestEvent>) {
 }
 
 fn generate_intention_id(account: &<Test as system::Config>::AccountId, c: u32) -> crate::IntentionId<Test> {
+	generate_intention_id_for_pair(account, c, DOT, ETH)
+}
+
+fn generate_intention_id_for_pair(
+	account: &<Test as system::Config>::AccountId,
+	c: u32,
+	asset_a: u32,
+	asset_b: u32,
+) -> crate::IntentionId<Test> {
 	let b = <system::Module<Test>>::current_block_number();
-	(c, &account, b, DOT, ETH).using_encoded(<Test as system::Config>::Hashing::hash)
+	let (a1, a2) = (cmp::min(asset_a, asset_b), cmp::max(asset_a, asset_b));
+	(c, &account, b, a1, a2).using_encoded(<Test as system::Config>::Hashing::hash)
 }
 
 /// HELPER FOR INITIALIZING POOLS
@@ -90,6 +112,39 @@ fn initialize_pool(asset_a: u32, asset_b: u32, user: u64, amount: u128, price: P
 	);
 }
 
+/// Sum `asset`'s free balance across every account in `accounts` - a `MultiCurrency`-agnostic
+/// stand-in for "total value in the system", since it doesn't care which of `accounts` ends up
+/// holding what, only that nothing is created or destroyed.
+fn total_free_balance(asset: u32, accounts: &[u64]) -> u128 {
+	accounts
+		.iter()
+		.map(|account| Currency::free_balance(asset, account))
+		.sum()
+}
+
+/// Snapshot every `asset` in `assets` summed across `accounts`, to be compared against
+/// `assert_value_conserved` after settlement runs. Pass the pair account among `accounts` so its
+/// collected fees are counted as moved rather than leaked.
+fn snapshot_balances(accounts: &[u64], assets: &[u32]) -> Vec<(u32, u128)> {
+	assets
+		.iter()
+		.map(|&asset| (asset, total_free_balance(asset, accounts)))
+		.collect()
+}
+
+/// Assert that `accounts`' summed balances for every asset in `snapshot` are unchanged - i.e.
+/// settlement only moved value between `accounts`, never leaking or minting any of it.
+fn assert_value_conserved(snapshot: &[(u32, u128)], accounts: &[u64]) {
+	for &(asset, before) in snapshot {
+		assert_eq!(
+			total_free_balance(asset, accounts),
+			before,
+			"value was not conserved for asset {}",
+			asset
+		);
+	}
+}
+
 #[test]
 fn sell_test_pool_finalization_states() {
 	new_test_ext().execute_with(|| {
@@ -112,6 +167,13 @@ fn sell_test_pool_finalization_states() {
 			2_000_000_000_000,
 			20000000000,
 			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
 		));
 
 		let user_2_sell_intention_id = generate_intention_id(&user_2, 0);
@@ -123,6 +185,13 @@ fn sell_test_pool_finalization_states() {
 			1_000_000_000_000,
 			4_000_000_000_000,
 			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
 		));
 
 		let user_3_sell_intention_id = generate_intention_id(&user_3, 1);
@@ -138,6 +207,8 @@ fn sell_test_pool_finalization_states() {
 
 		assert_eq!(Currency::free_balance(asset_a, &pair_account), 100_000_000_000_000);
 
+		let value_before = snapshot_balances(&[user_1, user_2, user_3, pair_account], &[asset_a, asset_b]);
+
 		// Finalize block
 		<Exchange as OnFinalize<u64>>::on_finalize(9);
 
@@ -149,6 +220,8 @@ fn sell_test_pool_finalization_states() {
 				2_000_000_000_000,
 				IntentionType::SELL,
 				user_2_sell_intention_id,
+				None,
+				1,
 			)
 			.into(),
 			RawEvent::IntentionRegistered(
@@ -158,8 +231,12 @@ fn sell_test_pool_finalization_states() {
 				1_000_000_000_000,
 				IntentionType::BUY,
 				user_3_sell_intention_id,
+				None,
+				1,
 			)
 			.into(),
+			RawEvent::FundsReserved(user_2, asset_a, 1000000000000, user_2_sell_intention_id).into(),
+			RawEvent::FundsReserved(user_3, asset_b, 2000000000000, user_3_sell_intention_id).into(),
 			RawEvent::IntentionResolvedDirectTrade(
 				user_2,
 				user_3,
@@ -167,8 +244,14 @@ fn sell_test_pool_finalization_states() {
 				user_3_sell_intention_id,
 				1000000000000,
 				2000000000000,
+				None,
+				None,
+				1,
+				1000000000000,
 			)
 			.into(),
+			RawEvent::FundsUnreserved(user_2, asset_a, 1000000000000, user_2_sell_intention_id).into(),
+			RawEvent::FundsUnreserved(user_3, asset_b, 2000000000000, user_3_sell_intention_id).into(),
 			RawEvent::IntentionResolvedDirectTradeFees(user_2, pair_account, asset_b, 2000000000).into(),
 			RawEvent::IntentionResolvedDirectTradeFees(user_3, pair_account, asset_b, 4000000000).into(),
 			TestEvent::amm(amm::RawEvent::Sell(user_2, 3000, 2000, 1000000000000, 1976336046259)),
@@ -178,6 +261,8 @@ fn sell_test_pool_finalization_states() {
 				user_2_sell_intention_id,
 				1000000000000,
 				1976336046259,
+				None,
+				1,
 			)
 			.into(),
 		]);
@@ -194,6 +279,8 @@ fn sell_test_pool_finalization_states() {
 		assert_eq!(Currency::free_balance(asset_a, &pair_account), 101000000000000);
 		assert_eq!(Currency::free_balance(asset_b, &pair_account), 198029663953741);
 
+		assert_value_conserved(&value_before, &[user_1, user_2, user_3, pair_account]);
+
 		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 0);
 	});
 }
@@ -220,6 +307,13 @@ fn sell_test_standard() {
 			2_000_000_000_000,
 			300_000_000_000,
 			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
 		));
 		let user_2_sell_intention_id = generate_intention_id(&user_2, 0);
 
@@ -230,12 +324,21 @@ fn sell_test_standard() {
 			1_000_000_000_000,
 			4_000_000_000_000,
 			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
 		));
 
 		let user_3_sell_intention_id = generate_intention_id(&user_3, 1);
 
 		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 2);
 
+		let value_before = snapshot_balances(&[user_1, user_2, user_3, pair_account], &[asset_a, asset_b]);
+
 		// Finalize block
 		<Exchange as OnFinalize<u64>>::on_finalize(9);
 
@@ -250,8 +353,9 @@ fn sell_test_standard() {
 		assert_eq!(Currency::free_balance(asset_a, &pair_account), 101000000000000);
 		assert_eq!(Currency::free_balance(asset_b, &pair_account), 198029663953741);
 
-		// TODO: check if final transferred balances add up to initial balance
-		// No tokens should be created or lost
+		// No tokens should be created or lost.
+		assert_value_conserved(&value_before, &[user_1, user_2, user_3, pair_account]);
+
 		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 0);
 
 		expect_events(vec![
@@ -262,6 +366,8 @@ fn sell_test_standard() {
 				2_000_000_000_000,
 				IntentionType::SELL,
 				user_2_sell_intention_id,
+				None,
+				1,
 			)
 			.into(),
 			RawEvent::IntentionRegistered(
@@ -271,8 +377,12 @@ fn sell_test_standard() {
 				1_000_000_000_000,
 				IntentionType::BUY,
 				user_3_sell_intention_id,
+				None,
+				1,
 			)
 			.into(),
+			RawEvent::FundsReserved(user_2, asset_a, 1000000000000, user_2_sell_intention_id).into(),
+			RawEvent::FundsReserved(user_3, asset_b, 2000000000000, user_3_sell_intention_id).into(),
 			RawEvent::IntentionResolvedDirectTrade(
 				user_2,
 				user_3,
@@ -280,8 +390,14 @@ fn sell_test_standard() {
 				user_3_sell_intention_id,
 				1000000000000,
 				2000000000000,
+				None,
+				None,
+				1,
+				1000000000000,
 			)
 			.into(),
+			RawEvent::FundsUnreserved(user_2, asset_a, 1000000000000, user_2_sell_intention_id).into(),
+			RawEvent::FundsUnreserved(user_3, asset_b, 2000000000000, user_3_sell_intention_id).into(),
 			RawEvent::IntentionResolvedDirectTradeFees(user_2, pair_account, asset_b, 2000000000).into(),
 			RawEvent::IntentionResolvedDirectTradeFees(user_3, pair_account, asset_b, 4000000000).into(),
 			TestEvent::amm(amm::RawEvent::Sell(user_2, 3000, 2000, 1000000000000, 1976336046259)),
@@ -291,6 +407,8 @@ fn sell_test_standard() {
 				user_2_sell_intention_id,
 				1000000000000,
 				1976336046259,
+				None,
+				1,
 			)
 			.into(),
 		]);
@@ -298,7 +416,10 @@ fn sell_test_standard() {
 }
 
 #[test]
-fn sell_test_inverse_standard() {
+fn sell_with_amm_fallback_disabled_should_drop_the_unmatched_remainder_instead_of_hitting_the_amm() {
+	// Same scenario as `sell_test_standard`, but user_2 opts out of the AMM fallback. Only the
+	// half matched directly against user_3's buy should settle - the other half must be dropped
+	// and unreserved instead of being routed through the AMM.
 	new_test_ext().execute_with(|| {
 		let user_1 = ALICE;
 		let user_2 = BOB;
@@ -316,90 +437,74 @@ fn sell_test_inverse_standard() {
 			Origin::signed(user_2),
 			asset_a,
 			asset_b,
-			1_000_000_000_000,
-			100_000_000_000,
+			2_000_000_000_000,
+			300_000_000_000,
 			false,
+			None,
+			None,
+			None,
+			false,
+			false,
+			0,
+			Price::from(1),
 		));
-
 		let user_2_sell_intention_id = generate_intention_id(&user_2, 0);
 
-		assert_ok!(Exchange::sell(
+		assert_ok!(Exchange::buy(
 			Origin::signed(user_3),
-			asset_b,
 			asset_a,
-			4_000_000_000_000,
+			asset_b,
 			1_000_000_000_000,
+			4_000_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
 			false,
+			0,
+			Price::from(1),
 		));
 
 		let user_3_sell_intention_id = generate_intention_id(&user_3, 1);
 
-		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 2);
+		let value_before = snapshot_balances(&[user_1, user_2, user_3, pair_account], &[asset_a, asset_b]);
 
 		// Finalize block
 		<Exchange as OnFinalize<u64>>::on_finalize(9);
 
-		// Check final account balances  -> SEEMS LEGIT
-		assert_eq!(Currency::free_balance(asset_a, &user_2), 999_000_000_000_000);
-		assert_eq!(Currency::free_balance(asset_b, &user_2), 1001996000000000);
+		// No AMM trade should have been emitted for user_2's leftover amount.
+		assert!(!system::Module::<Test>::events().into_iter().any(|record| matches!(
+			record.event,
+			TestEvent::exchange(RawEvent::IntentionResolvedAMMTrade(..))
+		)));
 
-		assert_eq!(Currency::free_balance(asset_a, &user_3), 1_001_986_138_378_978);
-		assert_eq!(Currency::free_balance(asset_b, &user_3), 996_000_000_000_000);
+		expect_event(RawEvent::IntentionUnmatched(
+			user_2,
+			asset_a,
+			1000000000000,
+			user_2_sell_intention_id,
+		));
 
-		// Check final pool balances  -> SEEMS LEGIT
-		assert_eq!(Currency::free_balance(asset_a, &pair_account), 99_013_861_621_022);
-		assert_eq!(Currency::free_balance(asset_b, &pair_account), 202004000000000);
+		// user_2 only ever parted with the amount that was actually matched directly.
+		assert_eq!(Currency::free_balance(asset_a, &user_2), 999_000_000_000_000);
+		assert_eq!(Currency::free_balance(asset_b, &user_2), 1_001_998_000_000_000);
 
-		// TODO: check if final transferred balances add up to initial balance
-		// No tokens should be created or lost
+		assert_eq!(Currency::free_balance(asset_a, &user_3), 1_001_000_000_000_000);
+		assert_eq!(Currency::free_balance(asset_b, &user_3), 997996000000000);
 
-		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 0);
+		assert_value_conserved(&value_before, &[user_1, user_2, user_3, pair_account]);
 
-		expect_events(vec![
-			RawEvent::IntentionRegistered(
-				user_2,
-				asset_a,
-				asset_b,
-				1_000_000_000_000,
-				IntentionType::SELL,
-				user_2_sell_intention_id,
-			)
-			.into(),
-			RawEvent::IntentionRegistered(
-				user_3,
-				asset_b,
-				asset_a,
-				4_000_000_000_000,
-				IntentionType::SELL,
-				user_3_sell_intention_id,
-			)
-			.into(),
-			TestEvent::amm(amm::RawEvent::Sell(3, 2000, 3000, 2000000000000, 988138378978)),
-			RawEvent::IntentionResolvedAMMTrade(
-				user_3,
-				IntentionType::SELL,
-				user_3_sell_intention_id,
-				2000000000000,
-				988138378978,
-			)
-			.into(),
-			RawEvent::IntentionResolvedDirectTrade(
-				user_2,
-				user_3,
-				user_2_sell_intention_id,
-				user_3_sell_intention_id,
-				1000000000000,
-				2000000000000,
-			)
-			.into(),
-			RawEvent::IntentionResolvedDirectTradeFees(user_2, pair_account, asset_b, 4000000000).into(),
-			RawEvent::IntentionResolvedDirectTradeFees(user_3, pair_account, asset_a, 2000000000).into(),
-		]);
+		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 0);
 	});
 }
 
 #[test]
-fn sell_test_exact_match() {
+fn partial_match_then_amm_fallback_should_emit_events_in_deterministic_order() {
+	// Same scenario as `sell_test_standard`: user_2's sell is only half matched by user_3's buy,
+	// so the rest of user_2's intention falls through to an AMM trade. This test exists purely to
+	// pin down the event ordering guarantee documented on `resolve_matched_intentions` - the direct
+	// trade settles first, and the AMM fallback for the unmatched remainder always comes last.
 	new_test_ext().execute_with(|| {
 		let user_1 = ALICE;
 		let user_2 = BOB;
@@ -409,86 +514,88 @@ fn sell_test_exact_match() {
 		let pool_amount = 100_000_000_000_000;
 		let initial_price = Price::from(2);
 
-		let pair_account = AMMModule::get_pair_id(&asset_a, &asset_b);
-
 		initialize_pool(asset_a, asset_b, user_1, pool_amount, initial_price);
 
 		assert_ok!(Exchange::sell(
 			Origin::signed(user_2),
 			asset_a,
 			asset_b,
-			1_000_000_000_000,
-			1_500_000_000_000,
+			2_000_000_000_000,
+			300_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
 			false,
+			0,
+			Price::from(1),
 		));
-
 		let user_2_sell_intention_id = generate_intention_id(&user_2, 0);
 
-		assert_ok!(Exchange::sell(
+		assert_ok!(Exchange::buy(
 			Origin::signed(user_3),
-			asset_b,
 			asset_a,
-			2_000_000_000_000,
-			200_000_000_000,
+			asset_b,
+			1_000_000_000_000,
+			4_000_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
 			false,
+			0,
+			Price::from(1),
 		));
-
 		let user_3_sell_intention_id = generate_intention_id(&user_3, 1);
 
-		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 2);
-
-		// Finalize block
 		<Exchange as OnFinalize<u64>>::on_finalize(9);
 
-		// Check final account balances -> SEEMS LEGIT
-		assert_eq!(Currency::free_balance(asset_a, &user_2), 999_000_000_000_000);
-		assert_eq!(Currency::free_balance(asset_b, &user_2), 1_001_996_000_000_000);
-
-		assert_eq!(Currency::free_balance(asset_a, &user_3), 1_000_998_000_000_000);
-		assert_eq!(Currency::free_balance(asset_b, &user_3), 998_000_000_000_000);
-
-		// Check final pool balances -> SEEMS LEGIT
-		assert_eq!(Currency::free_balance(asset_a, &pair_account), 100002000000000);
-		assert_eq!(Currency::free_balance(asset_b, &pair_account), 200004000000000);
-
-		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 0);
-
-		expect_events(vec![
-			RawEvent::IntentionRegistered(
-				user_2,
-				asset_a,
-				asset_b,
-				1_000_000_000_000,
-				IntentionType::SELL,
-				user_2_sell_intention_id,
-			)
-			.into(),
-			RawEvent::IntentionRegistered(
-				user_3,
-				asset_b,
-				asset_a,
-				2_000_000_000_000,
-				IntentionType::SELL,
-				user_3_sell_intention_id,
-			)
-			.into(),
-			RawEvent::IntentionResolvedDirectTrade(
-				user_2,
-				user_3,
-				user_2_sell_intention_id,
-				user_3_sell_intention_id,
-				1000000000000,
-				2000000000000,
-			)
-			.into(),
-			RawEvent::IntentionResolvedDirectTradeFees(user_2, pair_account, asset_b, 4000000000).into(),
-			RawEvent::IntentionResolvedDirectTradeFees(user_3, pair_account, asset_a, 2000000000).into(),
-		]);
+		let direct_trade_index = last_events(10)
+			.iter()
+			.position(|e| {
+				*e == TestEvent::exchange(RawEvent::IntentionResolvedDirectTrade(
+					user_2,
+					user_3,
+					user_2_sell_intention_id,
+					user_3_sell_intention_id,
+					1000000000000,
+					2000000000000,
+					None,
+					None,
+					1,
+					1000000000000,
+				))
+			})
+			.expect("direct trade event expected");
+
+		let amm_fallback_index = last_events(10)
+			.iter()
+			.position(|e| {
+				*e == TestEvent::exchange(RawEvent::IntentionResolvedAMMTrade(
+					user_2,
+					IntentionType::SELL,
+					user_2_sell_intention_id,
+					1000000000000,
+					1976336046259,
+					None,
+					1,
+				))
+			})
+			.expect("AMM fallback event expected");
+
+		assert!(
+			direct_trade_index < amm_fallback_index,
+			"direct trade event must be deposited before the AMM fallback for the same intention's remainder"
+		);
 	});
 }
 
 #[test]
-fn sell_test_single_eth_sells() {
+fn leftover_routed_to_amm_event_should_be_emitted_for_a_partially_matched_intention() {
+	// Same setup as `partial_match_then_amm_fallback_should_emit_events_in_deterministic_order`:
+	// user_2's sell is only half matched by user_3's buy, so the rest falls through to the AMM.
 	new_test_ext().execute_with(|| {
 		let user_1 = ALICE;
 		let user_2 = BOB;
@@ -498,710 +605,867 @@ fn sell_test_single_eth_sells() {
 		let pool_amount = 100_000_000_000_000;
 		let initial_price = Price::from(2);
 
-		let pair_account = AMMModule::get_pair_id(&asset_a, &asset_b);
-
 		initialize_pool(asset_a, asset_b, user_1, pool_amount, initial_price);
 
 		assert_ok!(Exchange::sell(
 			Origin::signed(user_2),
 			asset_a,
 			asset_b,
-			1_000_000_000_000,
-			100_000_000_000,
+			2_000_000_000_000,
+			300_000_000_000,
 			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
 		));
 		let user_2_sell_intention_id = generate_intention_id(&user_2, 0);
-		assert_ok!(Exchange::sell(
+
+		assert_ok!(Exchange::buy(
 			Origin::signed(user_3),
 			asset_a,
 			asset_b,
-			2_000_000_000_000,
-			200_000_000_000,
+			1_000_000_000_000,
+			4_000_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
 			false,
+			0,
+			Price::from(1),
 		));
 
-		let user_3_sell_intention_id = generate_intention_id(&user_3, 1);
-
-		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 2);
-
-		// Finalize block
 		<Exchange as OnFinalize<u64>>::on_finalize(9);
 
-		// Check final account balances -> SEEMS LEGIT
-		assert_eq!(Currency::free_balance(asset_a, &user_2), 999_000_000_000_000);
-		assert_eq!(Currency::free_balance(asset_b, &user_2), 1_001_899_978_143_094);
-
-		assert_eq!(Currency::free_balance(asset_a, &user_3), 998_000_000_000_000);
-		assert_eq!(Currency::free_balance(asset_b, &user_3), 1003913878975647);
-
-		// Check final pool balances -> SEEMS LEGIT
-		assert_eq!(Currency::free_balance(asset_a, &pair_account), 103_000_000_000_000);
-		assert_eq!(Currency::free_balance(asset_b, &pair_account), 194_186_142_881_259);
-
-		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 0);
-
-		expect_events(vec![
-			RawEvent::IntentionRegistered(
-				user_2,
-				asset_a,
-				asset_b,
-				1_000_000_000_000,
-				IntentionType::SELL,
-				user_2_sell_intention_id,
-			)
-			.into(),
-			RawEvent::IntentionRegistered(
-				user_3,
-				asset_a,
-				asset_b,
-				2_000_000_000_000,
-				IntentionType::SELL,
-				user_3_sell_intention_id,
-			)
-			.into(),
-			TestEvent::amm(amm::RawEvent::Sell(
-				user_3,
-				asset_a,
-				asset_b,
-				2000000000000,
-				3913878975647,
-			)),
-			RawEvent::IntentionResolvedAMMTrade(
-				user_3,
-				IntentionType::SELL,
-				user_3_sell_intention_id,
-				2000000000000,
-				3913878975647,
-			)
-			.into(),
-			TestEvent::amm(amm::RawEvent::Sell(
-				user_2,
-				asset_a,
-				asset_b,
-				1000000000000,
-				1899978143094,
-			)),
-			RawEvent::IntentionResolvedAMMTrade(
-				user_2,
-				IntentionType::SELL,
-				user_2_sell_intention_id,
-				1000000000000,
-				1899978143094,
-			)
-			.into(),
-		]);
+		assert!(last_events(10).iter().any(|e| *e
+			== TestEvent::exchange(RawEvent::LeftoverRoutedToAMM(user_2_sell_intention_id, IntentionType::SELL))));
 	});
 }
 
 #[test]
-fn sell_test_single_dot_sells() {
+fn leftover_routed_to_amm_event_should_be_emitted_for_an_unmatched_intention() {
+	// A lone BUY intention with no direct counterparty at all - the whole thing is leftover and
+	// is routed through the AMM, same as `single_buy_intention_test`.
 	new_test_ext().execute_with(|| {
 		let user_1 = ALICE;
 		let user_2 = BOB;
-		let user_3 = CHARLIE;
 		let asset_a = ETH;
 		let asset_b = DOT;
 		let pool_amount = 100_000_000_000_000;
 		let initial_price = Price::from(2);
 
-		let pair_account = AMMModule::get_pair_id(&asset_a, &asset_b);
-
 		initialize_pool(asset_a, asset_b, user_1, pool_amount, initial_price);
 
-		assert_ok!(Exchange::sell(
+		assert_ok!(Exchange::buy(
 			Origin::signed(user_2),
+			asset_a,
 			asset_b,
+			2_000_000_000_000,
+			15_000_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		let user_2_intention_id = generate_intention_id(&user_2, 0);
+
+		<Exchange as OnFinalize<u64>>::on_finalize(9);
+
+		assert!(last_events(10).iter().any(|e| *e
+			== TestEvent::exchange(RawEvent::LeftoverRoutedToAMM(user_2_intention_id, IntentionType::BUY))));
+	});
+}
+
+#[test]
+fn dust_left_by_rounding_should_amm_route_without_tolerance_but_direct_match_with_it() {
+	// Two SELL intentions on opposite legs of the same pair. user_3's odd 101 DOT sell makes
+	// `calculate_spot_price` round its implied `amount_buy` down to 50 ETH, one short of user_2's
+	// 51 ETH sell. Without `MatchTolerance`, that single leftover unit of ETH is dust-traded
+	// through the AMM instead of being absorbed into a full direct match.
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let user_3 = CHARLIE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+
+		initialize_pool(asset_a, asset_b, user_1, 100_000_000_000_000, Price::from(2));
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_2),
 			asset_a,
-			1_000_000_000_000,
-			100_000_000_000,
+			asset_b,
+			51,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
 			false,
+			0,
+			Price::from(1),
 		));
 
-		let user_2_sell_intention_id = generate_intention_id(&user_2, 0);
 		assert_ok!(Exchange::sell(
 			Origin::signed(user_3),
 			asset_b,
 			asset_a,
-			2_000_000_000_000,
-			200_000_000_000,
+			101,
+			0,
 			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
 		));
 
-		let user_3_sell_intention_id = generate_intention_id(&user_3, 1);
+		<Exchange as OnFinalize<u64>>::on_finalize(9);
 
-		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 2);
+		// With no tolerance, the one-unit dust left over after matching falls through to the AMM.
+		assert!(system::Module::<Test>::events().into_iter().any(|record| matches!(
+			record.event,
+			TestEvent::exchange(RawEvent::IntentionResolvedAMMTrade(..))
+		)));
+	});
+}
 
-		// Finalize block
-		<Exchange as OnFinalize<u64>>::on_finalize(9);
+#[test]
+fn dust_left_by_rounding_should_direct_match_fully_once_within_match_tolerance() {
+	// Same setup as `dust_left_by_rounding_should_amm_route_without_tolerance_but_direct_match_with_it`,
+	// but with `MatchTolerance` covering the one-unit rounding dust - the whole trade should settle
+	// as a single direct match, with no AMM fallback and no leftover carried forward.
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let user_3 = CHARLIE;
+		let asset_a = ETH;
+		let asset_b = DOT;
 
-		// Check final account balances -> SEEMS LEGIT
-		assert_eq!(Currency::free_balance(asset_a, &user_2), 1_000_496_522_353_457);
-		assert_eq!(Currency::free_balance(asset_b, &user_2), 999_000_000_000_000);
+		MatchToleranceMock::set(1);
 
-		assert_eq!(Currency::free_balance(asset_a, &user_3), 1_000_978_388_447_963);
-		assert_eq!(Currency::free_balance(asset_b, &user_3), 998_000_000_000_000);
+		initialize_pool(asset_a, asset_b, user_1, 100_000_000_000_000, Price::from(2));
 
-		// Check final pool balances -> SEEMS LEGIT
-		assert_eq!(Currency::free_balance(asset_a, &pair_account), 98_525_089_198_580);
-		assert_eq!(Currency::free_balance(asset_b, &pair_account), 203_000_000_000_000);
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_2),
+			asset_a,
+			asset_b,
+			51,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_3),
+			asset_b,
+			asset_a,
+			101,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		<Exchange as OnFinalize<u64>>::on_finalize(9);
+
+		assert!(!system::Module::<Test>::events().into_iter().any(|record| matches!(
+			record.event,
+			TestEvent::exchange(RawEvent::IntentionResolvedAMMTrade(..))
+		)));
 
 		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 0);
-		expect_events(vec![
-			RawEvent::IntentionRegistered(
-				user_2,
-				asset_b,
-				asset_a,
-				1_000_000_000_000,
-				IntentionType::SELL,
-				user_2_sell_intention_id,
-			)
-			.into(),
-			RawEvent::IntentionRegistered(
-				user_3,
-				asset_b,
-				asset_a,
-				2_000_000_000_000,
-				IntentionType::SELL,
-				user_3_sell_intention_id,
-			)
-			.into(),
-			TestEvent::amm(amm::RawEvent::Sell(
-				user_2,
-				asset_b,
-				asset_a,
-				1000000000000,
-				496522353457,
-			)),
-			RawEvent::IntentionResolvedAMMTrade(
-				user_2,
-				IntentionType::SELL,
-				user_2_sell_intention_id,
-				1000000000000,
-				496522353457,
-			)
-			.into(),
-			TestEvent::amm(amm::RawEvent::Sell(
-				user_3,
-				asset_b,
-				asset_a,
-				2000000000000,
-				978388447963,
-			)),
-			RawEvent::IntentionResolvedAMMTrade(
-				user_3,
-				IntentionType::SELL,
-				user_3_sell_intention_id,
-				2000000000000,
-				978388447963,
-			)
-			.into(),
-		]);
+
+		// user_2 sold all 51 ETH and received all 101 of user_3's DOT - the 1-unit dust between
+		// the two sides' price-implied amounts was simply absorbed, not refunded to either party.
+		assert_eq!(Currency::free_balance(asset_a, &user_2), ENDOWED_AMOUNT - 51);
+		assert_eq!(Currency::free_balance(asset_b, &user_2), ENDOWED_AMOUNT + 101);
+
+		MatchToleranceMock::set(0);
 	});
 }
 
 #[test]
-fn sell_test_single_multiple_sells() {
+fn block_settlement_summary_should_report_volumes_summing_to_the_total_traded_amount() {
+	// Same mixed scenario as `sell_test_standard`: half of user_2's sell is matched directly
+	// against user_3's buy, the other half falls through to an AMM trade. The emitted
+	// `BlockSettlementSummary` must attribute exactly one half to each route, and the two must
+	// sum to the total amount user_2 actually sold.
 	new_test_ext().execute_with(|| {
 		let user_1 = ALICE;
 		let user_2 = BOB;
 		let user_3 = CHARLIE;
-		let user_4 = DAVE;
-		let user_5 = FERDIE;
-		let user_6 = GEORGE;
 		let asset_a = ETH;
 		let asset_b = DOT;
-
 		let pool_amount = 100_000_000_000_000;
 		let initial_price = Price::from(2);
 
-		let pair_account = AMMModule::get_pair_id(&asset_a, &asset_b);
-
 		initialize_pool(asset_a, asset_b, user_1, pool_amount, initial_price);
 
+		let total_sell_amount = 2_000_000_000_000;
+
 		assert_ok!(Exchange::sell(
 			Origin::signed(user_2),
 			asset_a,
 			asset_b,
-			1_000_000_000_000,
-			100_000_000_000,
+			total_sell_amount,
+			300_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
 			false,
+			0,
+			Price::from(1),
 		));
-		let user_2_sell_intention_id = generate_intention_id(&user_2, 0);
-		assert_ok!(Exchange::sell(
+
+		assert_ok!(Exchange::buy(
 			Origin::signed(user_3),
-			asset_b,
 			asset_a,
+			asset_b,
 			1_000_000_000_000,
-			100_000_000_000,
+			4_000_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
 			false,
+			0,
+			Price::from(1),
 		));
-		let user_3_sell_intention_id = generate_intention_id(&user_3, 1);
+
+		<Exchange as OnFinalize<u64>>::on_finalize(9);
+
+		let summary = last_events(10)
+			.into_iter()
+			.find_map(|e| match e {
+				TestEvent::exchange(RawEvent::BlockSettlementSummary(matched, amm)) => Some((matched, amm)),
+				_ => None,
+			})
+			.expect("BlockSettlementSummary event expected");
+
+		assert_eq!(summary.0 + summary.1, total_sell_amount);
+	});
+}
+
+#[test]
+fn last_price_should_be_recorded_after_settlement_and_match_the_final_spot_price() {
+	// Same mixed scenario as `sell_test_standard`. The AMM fallback trade is the last thing to
+	// settle in the block, so the recorded `last_price` should match the pool's spot price once
+	// `on_finalize` is done.
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let user_3 = CHARLIE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000_000_000;
+		let initial_price = Price::from(2);
+
+		initialize_pool(asset_a, asset_b, user_1, pool_amount, initial_price);
+
+		assert!(Exchange::last_price(asset_a, asset_b).is_none());
+
 		assert_ok!(Exchange::sell(
-			Origin::signed(user_4),
+			Origin::signed(user_2),
 			asset_a,
 			asset_b,
-			1_000_000_000_000,
-			100_000_000_000,
+			2_000_000_000_000,
+			300_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
 			false,
+			0,
+			Price::from(1),
 		));
-		let user_4_sell_intention_id = generate_intention_id(&user_4, 2);
-		assert_ok!(Exchange::sell(
-			Origin::signed(user_5),
-			asset_b,
+
+		assert_ok!(Exchange::buy(
+			Origin::signed(user_3),
 			asset_a,
+			asset_b,
 			1_000_000_000_000,
-			100_000_000_000,
+			4_000_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
 			false,
+			0,
+			Price::from(1),
 		));
-		let user_5_sell_intention_id = generate_intention_id(&user_5, 3);
+
+		<Exchange as OnFinalize<u64>>::on_finalize(9);
+
+		let (recorded_price, recorded_block) = Exchange::last_price(asset_a, asset_b).expect("a trade settled");
+
+		assert_eq!(recorded_price, Exchange::spot_price(asset_a, asset_b).unwrap());
+		assert_eq!(recorded_block, System::block_number());
+	});
+}
+
+#[test]
+fn settle_pair_should_settle_intentions_immediately_without_waiting_for_finalize() {
+	// Same mixed scenario as `sell_test_standard`, but settled early via `settle_pair` instead of
+	// waiting for `on_finalize` - the resulting balances should match exactly.
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let user_3 = CHARLIE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000_000_000;
+		let initial_price = Price::from(2);
+
+		let pair_account = AMMModule::get_pair_id(&asset_a, &asset_b);
+
+		initialize_pool(asset_a, asset_b, user_1, pool_amount, initial_price);
+
 		assert_ok!(Exchange::sell(
-			Origin::signed(user_6),
-			asset_b,
+			Origin::signed(user_2),
 			asset_a,
+			asset_b,
 			2_000_000_000_000,
-			200_000_000_000,
+			300_000_000_000,
 			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
 		));
-		let user_6_sell_intention_id = generate_intention_id(&user_6, 4);
 
-		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 5);
+		assert_ok!(Exchange::buy(
+			Origin::signed(user_3),
+			asset_a,
+			asset_b,
+			1_000_000_000_000,
+			4_000_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
 
-		// Finalize block
-		<Exchange as OnFinalize<u64>>::on_finalize(9);
+		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 2);
 
-		// Check final account balances
-		assert_eq!(Currency::free_balance(asset_a, &user_2), 999000000000000);
-		assert_eq!(Currency::free_balance(asset_b, &user_2), 1001996000000000);
+		let value_before = snapshot_balances(&[user_1, user_2, user_3, pair_account], &[asset_a, asset_b]);
 
-		assert_eq!(Currency::free_balance(asset_a, &user_3), 1000499000000000);
-		assert_eq!(Currency::free_balance(asset_b, &user_3), 999000000000000);
+		assert_ok!(Exchange::settle_pair(Origin::signed(user_1), asset_a, asset_b));
 
-		assert_eq!(Currency::free_balance(asset_a, &user_4), 999000000000000);
-		assert_eq!(Currency::free_balance(asset_b, &user_4), 1001991044854829);
+		assert_eq!(Currency::free_balance(asset_a, &user_2), 998_000_000_000_000);
+		assert_eq!(Currency::free_balance(asset_b, &user_2), 1003974336046259);
 
-		// Check final pool balances
-		assert_eq!(Currency::free_balance(asset_a, &pair_account), 100001517499067);
-		assert_eq!(Currency::free_balance(asset_b, &pair_account), 200012955145171);
+		assert_eq!(Currency::free_balance(asset_a, &user_3), 1001000000000000);
+		assert_eq!(Currency::free_balance(asset_b, &user_3), 997996000000000);
 
-		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 0);
+		assert_eq!(Currency::free_balance(asset_a, &pair_account), 101000000000000);
+		assert_eq!(Currency::free_balance(asset_b, &pair_account), 198029663953741);
 
-		expect_events(vec![
-			RawEvent::IntentionRegistered(
-				user_2,
-				asset_a,
-				asset_b,
-				1_000_000_000_000,
-				IntentionType::SELL,
-				user_2_sell_intention_id,
-			)
-			.into(),
-			RawEvent::IntentionRegistered(
-				user_3,
-				asset_b,
-				asset_a,
-				1_000_000_000_000,
-				IntentionType::SELL,
-				user_3_sell_intention_id,
-			)
-			.into(),
-			RawEvent::IntentionRegistered(
-				user_4,
-				asset_a,
-				asset_b,
-				1_000_000_000_000,
-				IntentionType::SELL,
-				user_4_sell_intention_id,
-			)
-			.into(),
-			RawEvent::IntentionRegistered(
-				user_5,
-				asset_b,
-				asset_a,
-				1_000_000_000_000,
-				IntentionType::SELL,
-				user_5_sell_intention_id,
-			)
-			.into(),
-			RawEvent::IntentionRegistered(
-				user_6,
-				asset_b,
-				asset_a,
-				2_000_000_000_000,
-				IntentionType::SELL,
-				user_6_sell_intention_id,
-			)
-			.into(),
-			RawEvent::IntentionResolvedDirectTrade(
-				user_2,
-				user_6,
-				user_2_sell_intention_id,
-				user_6_sell_intention_id,
-				1000000000000,
-				2000000000000,
-			)
-			.into(),
-			RawEvent::IntentionResolvedDirectTradeFees(user_2, pair_account, asset_b, 4000000000).into(),
-			RawEvent::IntentionResolvedDirectTradeFees(user_6, pair_account, asset_a, 2000000000).into(),
-			RawEvent::IntentionResolvedDirectTrade(
-				user_4,
-				user_3,
-				user_4_sell_intention_id,
-				user_3_sell_intention_id,
-				500000000000,
-				1000000000000,
-			)
-			.into(),
-			RawEvent::IntentionResolvedDirectTradeFees(user_4, pair_account, asset_b, 2000000000).into(),
-			RawEvent::IntentionResolvedDirectTradeFees(user_3, pair_account, asset_a, 1000000000).into(),
-			TestEvent::amm(amm::RawEvent::Sell(
-				user_4,
-				asset_a,
-				asset_b,
-				500000000000,
-				993044854829,
-			)),
-			RawEvent::IntentionResolvedAMMTrade(
-				user_4,
-				IntentionType::SELL,
-				user_4_sell_intention_id,
-				5_000_000_000_00,
-				993044854829,
-			)
-			.into(),
-			TestEvent::amm(amm::RawEvent::Sell(
-				user_5,
-				asset_b,
-				asset_a,
-				1000000000000,
-				501482500933,
-			)),
-			RawEvent::IntentionResolvedAMMTrade(
-				user_5,
-				IntentionType::SELL,
-				user_5_sell_intention_id,
-				1000000000000,
-				501482500933,
-			)
-			.into(),
-		]);
+		assert_value_conserved(&value_before, &[user_1, user_2, user_3, pair_account]);
+
+		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 0);
 	});
 }
 
 #[test]
-fn sell_test_group_sells() {
+fn settle_pair_followed_by_finalize_should_be_idempotent_and_still_settle_other_pairs() {
+	// `settle_pair` fully settles (asset_a, asset_b) ahead of `on_finalize`. The subsequent
+	// `on_finalize` call in the same block must not re-process it (no second
+	// `BlockSettlementSummary` volume for it), while an untouched (asset_a, asset_c) pair queued
+	// in the same block is still settled normally.
 	new_test_ext().execute_with(|| {
 		let user_1 = ALICE;
 		let user_2 = BOB;
 		let user_3 = CHARLIE;
-		let user_4 = DAVE;
 		let asset_a = ETH;
 		let asset_b = DOT;
-
+		let asset_c = HDX;
 		let pool_amount = 100_000_000_000_000;
 		let initial_price = Price::from(2);
 
-		let pair_account = AMMModule::get_pair_id(&asset_a, &asset_b);
-
 		initialize_pool(asset_a, asset_b, user_1, pool_amount, initial_price);
+		initialize_pool(asset_a, asset_c, user_1, pool_amount, initial_price);
 
 		assert_ok!(Exchange::sell(
 			Origin::signed(user_2),
-			asset_b,
 			asset_a,
-			5_000_000_000_000,
-			200_000_000_000,
+			asset_b,
+			2_000_000_000_000,
+			300_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
 			false,
+			0,
+			Price::from(1),
 		));
-		let user_2_sell_intention_id = generate_intention_id(&user_2, 0);
-		assert_ok!(Exchange::sell(
+		assert_ok!(Exchange::buy(
 			Origin::signed(user_3),
-			asset_b,
 			asset_a,
-			3_000_000_000_000,
-			200_000_000_000,
+			asset_b,
+			1_000_000_000_000,
+			4_000_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
 			false,
+			0,
+			Price::from(1),
 		));
-		let user_3_sell_intention_id = generate_intention_id(&user_3, 1);
+
 		assert_ok!(Exchange::sell(
-			Origin::signed(user_4),
+			Origin::signed(user_2),
 			asset_a,
-			asset_b,
-			10_000_000_000_000,
-			200_000_000_000,
+			asset_c,
+			1_000_000_000_000,
+			100_000_000_000,
 			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
 		));
-		let user_4_sell_intention_id = generate_intention_id(&user_4, 2);
-
-		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 3);
-
-		// Finalize block
-		<Exchange as OnFinalize<u64>>::on_finalize(9);
 
-		// Check final account balances
-		assert_eq!(Currency::free_balance(asset_a, &user_2), 1002495000000000);
-		assert_eq!(Currency::free_balance(asset_b, &user_2), 995000000000000);
+		assert_ok!(Exchange::settle_pair(Origin::signed(user_1), asset_a, asset_b));
 
-		assert_eq!(Currency::free_balance(asset_a, &user_3), 1001702327336909);
-		assert_eq!(Currency::free_balance(asset_b, &user_3), 997000000000000);
+		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 0);
+		assert_eq!(Exchange::get_intentions_count((asset_c, asset_a)), 1);
 
-		assert_eq!(Currency::free_balance(asset_a, &user_4), 990000000000000);
-		assert_eq!(Currency::free_balance(asset_b, &user_4), 1018917573262630);
+		let balance_a_after_manual_settle = Currency::free_balance(asset_a, &user_2);
+		let balance_b_after_manual_settle = Currency::free_balance(asset_b, &user_2);
 
-		// Check final pool balances
-		assert_eq!(Currency::free_balance(asset_a, &pair_account), 105802672663091);
-		assert_eq!(Currency::free_balance(asset_b, &pair_account), 189082426737370);
+		<Exchange as OnFinalize<u64>>::on_finalize(9);
 
-		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 0);
+		// (asset_a, asset_b) was already fully settled - finalizing the block must not touch
+		// user_2's balances for it a second time.
+		assert_eq!(Currency::free_balance(asset_a, &user_2), balance_a_after_manual_settle);
+		assert_eq!(Currency::free_balance(asset_b, &user_2), balance_b_after_manual_settle);
 
-		expect_events(vec![
-			RawEvent::IntentionRegistered(
-				user_2,
-				asset_b,
-				asset_a,
-				5_000_000_000_000,
-				IntentionType::SELL,
-				user_2_sell_intention_id,
-			)
-			.into(),
-			RawEvent::IntentionRegistered(
-				user_3,
-				asset_b,
-				asset_a,
-				3_000_000_000_000,
-				IntentionType::SELL,
-				user_3_sell_intention_id,
-			)
-			.into(),
-			RawEvent::IntentionRegistered(
-				user_4,
-				asset_a,
-				asset_b,
-				10_000_000_000_000,
-				IntentionType::SELL,
-				user_4_sell_intention_id,
-			)
-			.into(),
-			RawEvent::IntentionResolvedDirectTrade(
-				user_4,
-				user_2,
-				user_4_sell_intention_id,
-				user_2_sell_intention_id,
-				2500000000000,
-				5000000000000,
-			)
-			.into(),
-			RawEvent::IntentionResolvedDirectTradeFees(user_4, pair_account, asset_b, 10000000000).into(),
-			RawEvent::IntentionResolvedDirectTradeFees(user_2, pair_account, asset_a, 5000000000).into(),
-			TestEvent::amm(amm::RawEvent::Sell(
-				user_4,
-				asset_a,
-				asset_b,
-				7500000000000,
-				13927573262630,
-			)),
-			RawEvent::IntentionResolvedAMMTrade(
-				user_4,
-				IntentionType::SELL,
-				user_4_sell_intention_id,
-				7500000000000,
-				13927573262630,
-			)
-			.into(),
-			TestEvent::amm(amm::RawEvent::Sell(
-				user_3,
-				asset_b,
-				asset_a,
-				3000000000000,
-				1702327336909,
-			)),
-			RawEvent::IntentionResolvedAMMTrade(
-				user_3,
-				IntentionType::SELL,
-				user_3_sell_intention_id,
-				3000000000000,
-				1702327336909,
-			)
-			.into(),
-		]);
-	});
-}
-#[test]
-fn sell_without_pool_should_not_work() {
-	new_test_ext().execute_with(|| {
-		assert_noop!(
-			Exchange::sell(Origin::signed(ALICE), HDX, ETH, 100, 200, false),
-			Error::<Test>::TokenPoolNotFound
-		);
+		// (asset_a, asset_c) was never manually settled - `on_finalize` still resolves it.
+		assert_eq!(Exchange::get_intentions_count((asset_c, asset_a)), 0);
 	});
 }
 
 #[test]
-fn sell_more_than_owner_should_not_work() {
+fn settle_pair_should_fail_when_pool_does_not_exist() {
 	new_test_ext().execute_with(|| {
-		assert_ok!(AMMModule::create_pool(
-			Origin::signed(ALICE),
-			HDX,
-			ETH,
-			200_000,
-			Price::from(2)
-		));
-
 		assert_noop!(
-			Exchange::sell(Origin::signed(ALICE), HDX, ETH, 1000_000_000_000_000u128, 1, false),
-			Error::<Test>::InsufficientAssetBalance
+			Exchange::settle_pair(Origin::signed(ALICE), HDX, ETH),
+			Error::<Test>::TokenPoolNotFound
 		);
 	});
 }
 
 #[test]
-fn sell_test_mixed_buy_sells() {
+fn amm_fallback_should_reprice_against_reserves_moved_by_an_earlier_match_in_the_same_block() {
+	// Two independent sell/buy pairs, both hitting the same partial-match-then-AMM-fallback shape
+	// as `partial_match_then_amm_fallback_should_emit_events_in_deterministic_order`, settled back
+	// to back in the same `on_finalize`. The second pair's AMM fallback must be quoted against the
+	// reserves left behind by the first pair's fallback, not a quote taken before either settled.
 	new_test_ext().execute_with(|| {
-		let user_1 = ALICE;
-		let user_2 = BOB;
-		let user_3 = CHARLIE;
-		let user_4 = DAVE;
+		let pool_owner = ALICE;
 		let asset_a = ETH;
 		let asset_b = DOT;
-
 		let pool_amount = 100_000_000_000_000;
 		let initial_price = Price::from(2);
 
-		let pair_account = AMMModule::get_pair_id(&asset_a, &asset_b);
-
-		initialize_pool(asset_a, asset_b, user_1, pool_amount, initial_price);
+		initialize_pool(asset_a, asset_b, pool_owner, pool_amount, initial_price);
 
-		assert_ok!(Exchange::buy(
-			Origin::signed(user_2),
+		// First pair: BOB sells, CHARLIE only buys half - the rest of BOB's sell falls through to
+		// the AMM at the pool's initial reserves.
+		assert_ok!(Exchange::sell(
+			Origin::signed(BOB),
+			asset_a,
 			asset_b,
+			2_000_000_000_000,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		let bob_intention_id = generate_intention_id_for_pair(&BOB, 0, asset_a, asset_b);
+
+		assert_ok!(Exchange::buy(
+			Origin::signed(CHARLIE),
 			asset_a,
-			5_000_000_000_000,
-			20_000_000_000_000,
+			asset_b,
+			1_000_000_000_000,
+			4_000_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
 			false,
+			0,
+			Price::from(1),
 		));
-		let user_2_sell_intention_id = generate_intention_id(&user_2, 0);
+
+		// Second pair: same shape, same amounts, but its AMM fallback only runs once the first
+		// pair's direct trade and AMM fallback have already moved the pool's reserves.
 		assert_ok!(Exchange::sell(
-			Origin::signed(user_3),
+			Origin::signed(DAVE),
+			asset_a,
 			asset_b,
+			2_000_000_000_000,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		let dave_intention_id = generate_intention_id_for_pair(&DAVE, 2, asset_a, asset_b);
+
+		assert_ok!(Exchange::buy(
+			Origin::signed(FERDIE),
 			asset_a,
-			3_000_000_000_000,
-			1400_000_000_000,
+			asset_b,
+			1_000_000_000_000,
+			4_000_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
 			false,
+			0,
+			Price::from(1),
 		));
-		let user_3_sell_intention_id = generate_intention_id(&user_3, 1);
+
+		<Exchange as OnFinalize<u64>>::on_finalize(1);
+
+		let amm_fallback_amount = |intention_id: crate::IntentionId<Test>| {
+			system::Module::<Test>::events()
+				.into_iter()
+				.find_map(|e| match e.event {
+					TestEvent::exchange(RawEvent::IntentionResolvedAMMTrade(_, _, id, _, amount_out, _, _))
+						if id == intention_id =>
+					{
+						Some(amount_out)
+					}
+					_ => None,
+				})
+				.expect("AMM fallback event expected")
+		};
+
+		let bob_amm_fallback = amm_fallback_amount(bob_intention_id);
+		let dave_amm_fallback = amm_fallback_amount(dave_intention_id);
+
+		// BOB's fallback trades against the pool at its initial reserves; DAVE's identical-sized
+		// remainder trades against the pool after BOB's fallback already shifted the ratio, so the
+		// two payouts must differ.
+		assert_ne!(
+			bob_amm_fallback, dave_amm_fallback,
+			"second pair's AMM fallback must be quoted against reserves moved by the first pair's fallback"
+		);
+	});
+}
+
+#[test]
+fn amm_fallback_on_one_pair_should_not_be_priced_off_a_different_pair_sharing_an_asset() {
+	// HDX/ETH and HDX/DOT both quote HDX, but each pool keeps its reserves in its own dedicated
+	// `pair_account` from `AMMModule::get_pair_id` - settling an AMM fallback on one must not move
+	// what the other's fallback is quoted against, even though a naive shared-reserve cache keyed
+	// only on the asset id would conflate the two.
+	let control_amount = new_test_ext().execute_with(|| {
+		let pool_owner = ALICE;
+		let pool_amount = 100_000_000_000_000;
+		let initial_price = Price::from(2);
+
+		initialize_pool(HDX, DOT, pool_owner, pool_amount, initial_price);
+
 		assert_ok!(Exchange::sell(
-			Origin::signed(user_4),
+			Origin::signed(BOB),
+			HDX,
+			DOT,
+			2_000_000_000_000,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		let bob_intention_id = generate_intention_id_for_pair(&BOB, 0, HDX, DOT);
+
+		<Exchange as OnFinalize<u64>>::on_finalize(1);
+
+		let amm_fallback_amount = |intention_id: crate::IntentionId<Test>| {
+			system::Module::<Test>::events()
+				.into_iter()
+				.find_map(|e| match e.event {
+					TestEvent::exchange(RawEvent::IntentionResolvedAMMTrade(_, _, id, _, amount_out, _, _))
+						if id == intention_id =>
+					{
+						Some(amount_out)
+					}
+					_ => None,
+				})
+				.expect("AMM fallback event expected")
+		};
+
+		amm_fallback_amount(bob_intention_id)
+	});
+
+	let actual_amount = new_test_ext().execute_with(|| {
+		let pool_owner = ALICE;
+		let pool_amount = 100_000_000_000_000;
+		let initial_price = Price::from(2);
+
+		initialize_pool(HDX, ETH, pool_owner, pool_amount, initial_price);
+		initialize_pool(HDX, DOT, pool_owner, pool_amount, initial_price);
+
+		// Settle an unrelated HDX/ETH fallback first, moving HDX/ETH's own pool reserves - but not
+		// HDX/DOT's, since the two pairs don't share a `pair_account`.
+		assert_ok!(Exchange::sell(
+			Origin::signed(CHARLIE),
+			HDX,
+			ETH,
+			2_000_000_000_000,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(BOB),
+			HDX,
+			DOT,
+			2_000_000_000_000,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		let bob_intention_id = generate_intention_id_for_pair(&BOB, 1, HDX, DOT);
+
+		<Exchange as OnFinalize<u64>>::on_finalize(1);
+
+		let amm_fallback_amount = |intention_id: crate::IntentionId<Test>| {
+			system::Module::<Test>::events()
+				.into_iter()
+				.find_map(|e| match e.event {
+					TestEvent::exchange(RawEvent::IntentionResolvedAMMTrade(_, _, id, _, amount_out, _, _))
+						if id == intention_id =>
+					{
+						Some(amount_out)
+					}
+					_ => None,
+				})
+				.expect("AMM fallback event expected")
+		};
+
+		amm_fallback_amount(bob_intention_id)
+	});
+
+	assert_eq!(
+		actual_amount, control_amount,
+		"settling HDX/ETH's own AMM fallback must not change what HDX/DOT is quoted against"
+	);
+}
+
+#[test]
+fn sell_test_inverse_standard() {
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let user_3 = CHARLIE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000_000_000;
+		let initial_price = Price::from(2);
+
+		let pair_account = AMMModule::get_pair_id(&asset_a, &asset_b);
+
+		initialize_pool(asset_a, asset_b, user_1, pool_amount, initial_price);
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_2),
 			asset_a,
 			asset_b,
-			10_000_000_000_000,
-			2000_000_000_000,
+			1_000_000_000_000,
+			100_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
 			false,
+			0,
+			Price::from(1),
 		));
-		let user_4_sell_intention_id = generate_intention_id(&user_4, 2);
 
-		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 3);
+		let user_2_sell_intention_id = generate_intention_id(&user_2, 0);
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_3),
+			asset_b,
+			asset_a,
+			4_000_000_000_000,
+			1_000_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		let user_3_sell_intention_id = generate_intention_id(&user_3, 1);
+
+		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 2);
 
 		// Finalize block
 		<Exchange as OnFinalize<u64>>::on_finalize(9);
 
-		// Check final account balances
-		assert_eq!(Currency::free_balance(asset_a, &user_2), 996969167073281);
-		assert_eq!(Currency::free_balance(asset_b, &user_2), 1005000000000000);
+		// Check final account balances  -> SEEMS LEGIT
+		assert_eq!(Currency::free_balance(asset_a, &user_2), 999_000_000_000_000);
+		assert_eq!(Currency::free_balance(asset_b, &user_2), 1001996000000000);
 
-		assert_eq!(Currency::free_balance(asset_a, &user_3), 1001497000000000);
-		assert_eq!(Currency::free_balance(asset_b, &user_3), 997000000000000);
+		assert_eq!(Currency::free_balance(asset_a, &user_3), 1_001_986_138_378_978);
+		assert_eq!(Currency::free_balance(asset_b, &user_3), 996_000_000_000_000);
 
-		assert_eq!(Currency::free_balance(asset_a, &user_4), 990000000000000);
-		assert_eq!(Currency::free_balance(asset_b, &user_4), 1018633353446528);
+		// Check final pool balances  -> SEEMS LEGIT
+		assert_eq!(Currency::free_balance(asset_a, &pair_account), 99_013_861_621_022);
+		assert_eq!(Currency::free_balance(asset_b, &pair_account), 202004000000000);
 
-		// Check final pool balances
-		assert_eq!(Currency::free_balance(asset_a, &pair_account), 111533832926719);
-		assert_eq!(Currency::free_balance(asset_b, &pair_account), 179366646553472);
+		// TODO: check if final transferred balances add up to initial balance
+		// No tokens should be created or lost
 
 		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 0);
 
 		expect_events(vec![
 			RawEvent::IntentionRegistered(
 				user_2,
-				asset_b,
 				asset_a,
-				5_000_000_000_000,
-				IntentionType::BUY,
+				asset_b,
+				1_000_000_000_000,
+				IntentionType::SELL,
 				user_2_sell_intention_id,
+				None,
+				1,
 			)
 			.into(),
 			RawEvent::IntentionRegistered(
 				user_3,
 				asset_b,
 				asset_a,
-				3_000_000_000_000,
-				IntentionType::SELL,
-				user_3_sell_intention_id,
-			)
-			.into(),
-			RawEvent::IntentionRegistered(
-				user_4,
-				asset_a,
-				asset_b,
-				10_000_000_000_000,
+				4_000_000_000_000,
 				IntentionType::SELL,
-				user_4_sell_intention_id,
-			)
-			.into(),
-			RawEvent::IntentionResolvedDirectTrade(
-				user_4,
-				user_3,
-				user_4_sell_intention_id,
 				user_3_sell_intention_id,
-				1500000000000,
-				3000000000000,
+				None,
+				1,
 			)
 			.into(),
-			RawEvent::IntentionResolvedDirectTradeFees(user_4, pair_account, asset_b, 6000000000).into(),
-			RawEvent::IntentionResolvedDirectTradeFees(user_3, pair_account, asset_a, 3000000000).into(),
-			TestEvent::amm(amm::RawEvent::Sell(
-				user_4,
-				asset_a,
-				asset_b,
-				8500000000000,
-				15639353446528,
-			)),
+			TestEvent::amm(amm::RawEvent::Sell(3, 2000, 3000, 2000000000000, 988138378978)),
 			RawEvent::IntentionResolvedAMMTrade(
-				user_4,
+				user_3,
 				IntentionType::SELL,
-				user_4_sell_intention_id,
-				8500000000000,
-				15639353446528,
+				user_3_sell_intention_id,
+				2000000000000,
+				988138378978,
+				None,
+				1,
 			)
 			.into(),
-			TestEvent::amm(amm::RawEvent::Buy(
-				user_2,
-				asset_b,
-				asset_a,
-				5000000000000,
-				3030832926719,
-			)),
-			RawEvent::IntentionResolvedAMMTrade(
+			RawEvent::FundsReserved(user_2, asset_a, 1000000000000, user_2_sell_intention_id).into(),
+			RawEvent::FundsReserved(user_3, asset_b, 2000000000000, user_3_sell_intention_id).into(),
+			RawEvent::IntentionResolvedDirectTrade(
 				user_2,
-				IntentionType::BUY,
+				user_3,
 				user_2_sell_intention_id,
-				5000000000000,
-				3030832926719,
+				user_3_sell_intention_id,
+				1000000000000,
+				2000000000000,
+				None,
+				None,
+				1,
+				0,
 			)
 			.into(),
+			RawEvent::FundsUnreserved(user_2, asset_a, 1000000000000, user_2_sell_intention_id).into(),
+			RawEvent::FundsUnreserved(user_3, asset_b, 2000000000000, user_3_sell_intention_id).into(),
+			RawEvent::IntentionResolvedDirectTradeFees(user_2, pair_account, asset_b, 4000000000).into(),
+			RawEvent::IntentionResolvedDirectTradeFees(user_3, pair_account, asset_a, 2000000000).into(),
 		]);
 	});
 }
 
 #[test]
-fn discount_tests_no_discount() {
+fn sell_test_exact_match() {
 	new_test_ext().execute_with(|| {
 		let user_1 = ALICE;
 		let user_2 = BOB;
 		let user_3 = CHARLIE;
-		let user_4 = DAVE;
 		let asset_a = ETH;
 		let asset_b = DOT;
-
 		let pool_amount = 100_000_000_000_000;
 		let initial_price = Price::from(2);
 
@@ -1209,267 +1473,228 @@ fn discount_tests_no_discount() {
 
 		initialize_pool(asset_a, asset_b, user_1, pool_amount, initial_price);
 
-		assert_ok!(Exchange::buy(
+		assert_ok!(Exchange::sell(
 			Origin::signed(user_2),
-			asset_b,
 			asset_a,
-			5_000_000_000_000,
-			20_000_000_000_000,
+			asset_b,
+			1_000_000_000_000,
+			1_500_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
 			false,
+			0,
+			Price::from(1),
 		));
+
 		let user_2_sell_intention_id = generate_intention_id(&user_2, 0);
+
 		assert_ok!(Exchange::sell(
 			Origin::signed(user_3),
 			asset_b,
 			asset_a,
-			3_000_000_000_000,
-			1400_000_000_000,
+			2_000_000_000_000,
+			200_000_000_000,
 			false,
-		));
-		let user_3_sell_intention_id = generate_intention_id(&user_3, 1);
-		assert_ok!(Exchange::sell(
-			Origin::signed(user_4),
-			asset_a,
-			asset_b,
-			10_000_000_000_000,
-			2000_000_000_000,
+			None,
+			None,
+			None,
+			true,
 			false,
+			0,
+			Price::from(1),
 		));
-		let user_4_sell_intention_id = generate_intention_id(&user_4, 2);
 
-		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 3);
+		let user_3_sell_intention_id = generate_intention_id(&user_3, 1);
+
+		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 2);
 
 		// Finalize block
 		<Exchange as OnFinalize<u64>>::on_finalize(9);
 
-		// Check final account balances
-		assert_eq!(Currency::free_balance(asset_a, &user_2), 996969167073281);
-		assert_eq!(Currency::free_balance(asset_b, &user_2), 1005000000000000);
-
-		assert_eq!(Currency::free_balance(asset_a, &user_3), 1001497000000000);
-		assert_eq!(Currency::free_balance(asset_b, &user_3), 997000000000000);
+		// Check final account balances -> SEEMS LEGIT
+		assert_eq!(Currency::free_balance(asset_a, &user_2), 999_000_000_000_000);
+		assert_eq!(Currency::free_balance(asset_b, &user_2), 1_001_996_000_000_000);
 
-		assert_eq!(Currency::free_balance(asset_a, &user_4), 990000000000000);
-		assert_eq!(Currency::free_balance(asset_b, &user_4), 1018633353446528);
+		assert_eq!(Currency::free_balance(asset_a, &user_3), 1_000_998_000_000_000);
+		assert_eq!(Currency::free_balance(asset_b, &user_3), 998_000_000_000_000);
 
-		// Check final pool balances
-		assert_eq!(Currency::free_balance(asset_a, &pair_account), 111533832926719);
-		assert_eq!(Currency::free_balance(asset_b, &pair_account), 179366646553472);
+		// Check final pool balances -> SEEMS LEGIT
+		assert_eq!(Currency::free_balance(asset_a, &pair_account), 100002000000000);
+		assert_eq!(Currency::free_balance(asset_b, &pair_account), 200004000000000);
 
 		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 0);
 
 		expect_events(vec![
 			RawEvent::IntentionRegistered(
 				user_2,
-				asset_b,
 				asset_a,
-				5_000_000_000_000,
-				IntentionType::BUY,
+				asset_b,
+				1_000_000_000_000,
+				IntentionType::SELL,
 				user_2_sell_intention_id,
+				None,
+				1,
 			)
 			.into(),
 			RawEvent::IntentionRegistered(
 				user_3,
 				asset_b,
 				asset_a,
-				3_000_000_000_000,
+				2_000_000_000_000,
 				IntentionType::SELL,
 				user_3_sell_intention_id,
+				None,
+				1,
 			)
 			.into(),
-			RawEvent::IntentionRegistered(
-				user_4,
-				asset_a,
-				asset_b,
-				10_000_000_000_000,
-				IntentionType::SELL,
-				user_4_sell_intention_id,
-			)
-			.into(),
+			RawEvent::FundsReserved(user_2, asset_a, 1000000000000, user_2_sell_intention_id).into(),
+			RawEvent::FundsReserved(user_3, asset_b, 2000000000000, user_3_sell_intention_id).into(),
 			RawEvent::IntentionResolvedDirectTrade(
-				user_4,
-				user_3,
-				user_4_sell_intention_id,
-				user_3_sell_intention_id,
-				1500000000000,
-				3000000000000,
-			)
-			.into(),
-			RawEvent::IntentionResolvedDirectTradeFees(user_4, pair_account, asset_b, 6000000000).into(),
-			RawEvent::IntentionResolvedDirectTradeFees(user_3, pair_account, asset_a, 3000000000).into(),
-			TestEvent::amm(amm::RawEvent::Sell(
-				user_4,
-				asset_a,
-				asset_b,
-				8500000000000,
-				15639353446528,
-			)),
-			RawEvent::IntentionResolvedAMMTrade(
-				user_4,
-				IntentionType::SELL,
-				user_4_sell_intention_id,
-				8500000000000,
-				15639353446528,
-			)
-			.into(),
-			TestEvent::amm(amm::RawEvent::Buy(
-				user_2,
-				asset_b,
-				asset_a,
-				5000000000000,
-				3030832926719,
-			)),
-			RawEvent::IntentionResolvedAMMTrade(
 				user_2,
-				IntentionType::BUY,
+				user_3,
 				user_2_sell_intention_id,
-				5000000000000,
-				3030832926719,
+				user_3_sell_intention_id,
+				1000000000000,
+				2000000000000,
+				None,
+				None,
+				1,
+				0,
 			)
 			.into(),
+			RawEvent::FundsUnreserved(user_2, asset_a, 1000000000000, user_2_sell_intention_id).into(),
+			RawEvent::FundsUnreserved(user_3, asset_b, 2000000000000, user_3_sell_intention_id).into(),
+			RawEvent::IntentionResolvedDirectTradeFees(user_2, pair_account, asset_b, 4000000000).into(),
+			RawEvent::IntentionResolvedDirectTradeFees(user_3, pair_account, asset_a, 2000000000).into(),
 		]);
 	});
 }
 
 #[test]
-fn discount_tests_with_discount() {
+fn sell_test_single_eth_sells() {
 	new_test_ext().execute_with(|| {
 		let user_1 = ALICE;
 		let user_2 = BOB;
 		let user_3 = CHARLIE;
-		let user_4 = DAVE;
 		let asset_a = ETH;
 		let asset_b = DOT;
-
 		let pool_amount = 100_000_000_000_000;
 		let initial_price = Price::from(2);
 
 		let pair_account = AMMModule::get_pair_id(&asset_a, &asset_b);
 
 		initialize_pool(asset_a, asset_b, user_1, pool_amount, initial_price);
-		initialize_pool(asset_a, HDX, user_2, pool_amount, initial_price);
-		initialize_pool(asset_b, HDX, user_3, pool_amount, initial_price);
 
-		assert_ok!(Exchange::buy(
+		assert_ok!(Exchange::sell(
 			Origin::signed(user_2),
-			asset_b,
 			asset_a,
-			5_000_000_000_000,
-			20_000_000_000_000,
+			asset_b,
+			1_000_000_000_000,
+			100_000_000_000,
+			false,
+			None,
+			None,
+			None,
 			true,
+			false,
+			0,
+			Price::from(1),
 		));
 		let user_2_sell_intention_id = generate_intention_id(&user_2, 0);
 		assert_ok!(Exchange::sell(
 			Origin::signed(user_3),
-			asset_b,
-			asset_a,
-			3_000_000_000_000,
-			1400_000_000_000,
-			true,
-		));
-		let user_3_sell_intention_id = generate_intention_id(&user_3, 1);
-		assert_ok!(Exchange::sell(
-			Origin::signed(user_4),
 			asset_a,
 			asset_b,
-			10_000_000_000_000,
-			2000_000_000_000,
+			2_000_000_000_000,
+			200_000_000_000,
+			false,
+			None,
+			None,
+			None,
 			true,
+			false,
+			0,
+			Price::from(1),
 		));
-		let user_4_sell_intention_id = generate_intention_id(&user_4, 2);
 
-		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 3);
+		let user_3_sell_intention_id = generate_intention_id(&user_3, 1);
+
+		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 2);
 
 		// Finalize block
 		<Exchange as OnFinalize<u64>>::on_finalize(9);
 
-		// Check final account balances
-		assert_eq!(Currency::free_balance(asset_a, &user_2), 896972892085116);
-		assert_eq!(Currency::free_balance(asset_b, &user_2), 1005000000000000);
-
-		assert_eq!(Currency::free_balance(asset_a, &user_3), 1001497000000000);
-		assert_eq!(Currency::free_balance(asset_b, &user_3), 897000000000000);
-
-		assert_eq!(Currency::free_balance(asset_a, &user_4), 990000000000000);
-		assert_eq!(Currency::free_balance(asset_b, &user_4), 1018652130468064);
+		// Check final account balances -> SEEMS LEGIT
+		assert_eq!(Currency::free_balance(asset_a, &user_2), 999_000_000_000_000);
+		assert_eq!(Currency::free_balance(asset_b, &user_2), 1_001_899_978_143_094);
 
-		// Check final pool balances
-		assert_eq!(Currency::free_balance(asset_a, &pair_account), 111530107914884);
-		assert_eq!(Currency::free_balance(asset_b, &pair_account), 179347869531936);
+		assert_eq!(Currency::free_balance(asset_a, &user_3), 998_000_000_000_000);
+		assert_eq!(Currency::free_balance(asset_b, &user_3), 1003913878975647);
 
-		assert_eq!(Currency::free_balance(HDX, &user_4), 999988100000000);
-		assert_eq!(Currency::free_balance(HDX, &user_2), 799993000000000);
-		assert_eq!(Currency::free_balance(HDX, &user_3), 800000000000000);
+		// Check final pool balances -> SEEMS LEGIT
+		assert_eq!(Currency::free_balance(asset_a, &pair_account), 103_000_000_000_000);
+		assert_eq!(Currency::free_balance(asset_b, &pair_account), 194_186_142_881_259);
 
 		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 0);
 
 		expect_events(vec![
 			RawEvent::IntentionRegistered(
 				user_2,
-				asset_b,
 				asset_a,
-				5_000_000_000_000,
-				IntentionType::BUY,
-				user_2_sell_intention_id,
-			)
-			.into(),
-			RawEvent::IntentionRegistered(
-				user_3,
 				asset_b,
-				asset_a,
-				3_000_000_000_000,
+				1_000_000_000_000,
 				IntentionType::SELL,
-				user_3_sell_intention_id,
+				user_2_sell_intention_id,
+				None,
+				1,
 			)
 			.into(),
 			RawEvent::IntentionRegistered(
-				user_4,
+				user_3,
 				asset_a,
 				asset_b,
-				10_000_000_000_000,
+				2_000_000_000_000,
 				IntentionType::SELL,
-				user_4_sell_intention_id,
-			)
-			.into(),
-			RawEvent::IntentionResolvedDirectTrade(
-				user_4,
-				user_3,
-				user_4_sell_intention_id,
 				user_3_sell_intention_id,
-				1500000000000,
-				3000000000000,
+				None,
+				1,
 			)
 			.into(),
-			RawEvent::IntentionResolvedDirectTradeFees(user_4, pair_account, asset_b, 6000000000).into(),
-			RawEvent::IntentionResolvedDirectTradeFees(user_3, pair_account, asset_a, 3000000000).into(),
 			TestEvent::amm(amm::RawEvent::Sell(
-				user_4,
+				user_3,
 				asset_a,
 				asset_b,
-				8500000000000,
-				15658130468064,
+				2000000000000,
+				3913878975647,
 			)),
 			RawEvent::IntentionResolvedAMMTrade(
-				user_4,
+				user_3,
 				IntentionType::SELL,
-				user_4_sell_intention_id,
-				8500000000000,
-				15658130468064,
+				user_3_sell_intention_id,
+				2000000000000,
+				3913878975647,
+				None,
+				1,
 			)
 			.into(),
-			TestEvent::amm(amm::RawEvent::Buy(
+			TestEvent::amm(amm::RawEvent::Sell(
 				user_2,
-				asset_b,
 				asset_a,
-				5000000000000,
-				3027107914884,
+				asset_b,
+				1000000000000,
+				1899978143094,
 			)),
 			RawEvent::IntentionResolvedAMMTrade(
 				user_2,
-				IntentionType::BUY,
+				IntentionType::SELL,
 				user_2_sell_intention_id,
-				5000000000000,
-				3027107914884,
+				1000000000000,
+				1899978143094,
+				None,
+				1,
 			)
 			.into(),
 		]);
@@ -1477,7 +1702,7 @@ fn discount_tests_with_discount() {
 }
 
 #[test]
-fn buy_test_exact_match() {
+fn sell_test_single_dot_sells() {
 	new_test_ext().execute_with(|| {
 		let user_1 = ALICE;
 		let user_2 = BOB;
@@ -1491,23 +1716,39 @@ fn buy_test_exact_match() {
 
 		initialize_pool(asset_a, asset_b, user_1, pool_amount, initial_price);
 
-		assert_ok!(Exchange::buy(
+		assert_ok!(Exchange::sell(
 			Origin::signed(user_2),
-			asset_a,
 			asset_b,
+			asset_a,
 			1_000_000_000_000,
-			4_000_000_000_000,
+			100_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
 			false,
+			0,
+			Price::from(1),
 		));
+
 		let user_2_sell_intention_id = generate_intention_id(&user_2, 0);
-		assert_ok!(Exchange::buy(
+		assert_ok!(Exchange::sell(
 			Origin::signed(user_3),
 			asset_b,
 			asset_a,
 			2_000_000_000_000,
-			4_000_000_000_000,
+			200_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
 			false,
+			0,
+			Price::from(1),
 		));
+
 		let user_3_sell_intention_id = generate_intention_id(&user_3, 1);
 
 		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 2);
@@ -1516,26 +1757,27 @@ fn buy_test_exact_match() {
 		<Exchange as OnFinalize<u64>>::on_finalize(9);
 
 		// Check final account balances -> SEEMS LEGIT
-		assert_eq!(Currency::free_balance(asset_a, &user_2), 1001000000000000);
-		assert_eq!(Currency::free_balance(asset_b, &user_2), 997996000000000);
+		assert_eq!(Currency::free_balance(asset_a, &user_2), 1_000_496_522_353_457);
+		assert_eq!(Currency::free_balance(asset_b, &user_2), 999_000_000_000_000);
 
-		assert_eq!(Currency::free_balance(asset_a, &user_3), 998998000000000);
-		assert_eq!(Currency::free_balance(asset_b, &user_3), 1002000000000000);
+		assert_eq!(Currency::free_balance(asset_a, &user_3), 1_000_978_388_447_963);
+		assert_eq!(Currency::free_balance(asset_b, &user_3), 998_000_000_000_000);
 
 		// Check final pool balances -> SEEMS LEGIT
-		assert_eq!(Currency::free_balance(asset_a, &pair_account), 100002000000000);
-		assert_eq!(Currency::free_balance(asset_b, &pair_account), 200004000000000);
+		assert_eq!(Currency::free_balance(asset_a, &pair_account), 98_525_089_198_580);
+		assert_eq!(Currency::free_balance(asset_b, &pair_account), 203_000_000_000_000);
 
 		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 0);
-
 		expect_events(vec![
 			RawEvent::IntentionRegistered(
 				user_2,
-				asset_a,
 				asset_b,
+				asset_a,
 				1_000_000_000_000,
-				IntentionType::BUY,
+				IntentionType::SELL,
 				user_2_sell_intention_id,
+				None,
+				1,
 			)
 			.into(),
 			RawEvent::IntentionRegistered(
@@ -1543,32 +1785,59 @@ fn buy_test_exact_match() {
 				asset_b,
 				asset_a,
 				2_000_000_000_000,
-				IntentionType::BUY,
+				IntentionType::SELL,
 				user_3_sell_intention_id,
+				None,
+				1,
 			)
 			.into(),
-			RawEvent::IntentionResolvedDirectTrade(
-				user_3,
+			TestEvent::amm(amm::RawEvent::Sell(
 				user_2,
-				user_3_sell_intention_id,
+				asset_b,
+				asset_a,
+				1000000000000,
+				496522353457,
+			)),
+			RawEvent::IntentionResolvedAMMTrade(
+				user_2,
+				IntentionType::SELL,
 				user_2_sell_intention_id,
 				1000000000000,
+				496522353457,
+				None,
+				1,
+			)
+			.into(),
+			TestEvent::amm(amm::RawEvent::Sell(
+				user_3,
+				asset_b,
+				asset_a,
+				2000000000000,
+				978388447963,
+			)),
+			RawEvent::IntentionResolvedAMMTrade(
+				user_3,
+				IntentionType::SELL,
+				user_3_sell_intention_id,
 				2000000000000,
+				978388447963,
+				None,
+				1,
 			)
 			.into(),
-			RawEvent::IntentionResolvedDirectTradeFees(user_3, pair_account, asset_a, 2000000000).into(),
-			RawEvent::IntentionResolvedDirectTradeFees(user_2, pair_account, asset_b, 4000000000).into(),
 		]);
 	});
 }
 
 #[test]
-fn buy_test_group_buys() {
+fn sell_test_single_multiple_sells() {
 	new_test_ext().execute_with(|| {
 		let user_1 = ALICE;
 		let user_2 = BOB;
 		let user_3 = CHARLIE;
 		let user_4 = DAVE;
+		let user_5 = FERDIE;
+		let user_6 = GEORGE;
 		let asset_a = ETH;
 		let asset_b = DOT;
 
@@ -1579,122 +1848,234 @@ fn buy_test_group_buys() {
 
 		initialize_pool(asset_a, asset_b, user_1, pool_amount, initial_price);
 
-		assert_ok!(Exchange::buy(
+		assert_ok!(Exchange::sell(
 			Origin::signed(user_2),
-			asset_b,
 			asset_a,
-			5_000_000_000_000,
-			20_000_000_000_000,
+			asset_b,
+			1_000_000_000_000,
+			100_000_000_000,
 			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
 		));
 		let user_2_sell_intention_id = generate_intention_id(&user_2, 0);
-		assert_ok!(Exchange::buy(
+		assert_ok!(Exchange::sell(
 			Origin::signed(user_3),
 			asset_b,
 			asset_a,
-			3_000_000_000_000,
-			20_000_000_000_000,
+			1_000_000_000_000,
+			100_000_000_000,
 			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
 		));
 		let user_3_sell_intention_id = generate_intention_id(&user_3, 1);
-		assert_ok!(Exchange::buy(
+		assert_ok!(Exchange::sell(
 			Origin::signed(user_4),
 			asset_a,
 			asset_b,
-			10_000_000_000_000,
-			22_000_000_000_000,
+			1_000_000_000_000,
+			100_000_000_000,
 			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
 		));
 		let user_4_sell_intention_id = generate_intention_id(&user_4, 2);
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_5),
+			asset_b,
+			asset_a,
+			1_000_000_000_000,
+			100_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		let user_5_sell_intention_id = generate_intention_id(&user_5, 3);
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_6),
+			asset_b,
+			asset_a,
+			2_000_000_000_000,
+			200_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		let user_6_sell_intention_id = generate_intention_id(&user_6, 4);
 
-		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 3);
+		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 5);
 
 		// Finalize block
 		<Exchange as OnFinalize<u64>>::on_finalize(9);
 
 		// Check final account balances
-		assert_eq!(Currency::free_balance(asset_a, &user_2), 997495000000000);
-		assert_eq!(Currency::free_balance(asset_b, &user_2), 1005000000000000);
+		assert_eq!(Currency::free_balance(asset_a, &user_2), 999000000000000);
+		assert_eq!(Currency::free_balance(asset_b, &user_2), 1001996000000000);
 
-		assert_eq!(Currency::free_balance(asset_a, &user_3), 998696069683270);
-		assert_eq!(Currency::free_balance(asset_b, &user_3), 1003000000000000);
+		assert_eq!(Currency::free_balance(asset_a, &user_3), 1000499000000000);
+		assert_eq!(Currency::free_balance(asset_b, &user_3), 999000000000000);
 
-		assert_eq!(Currency::free_balance(asset_a, &user_4), 1010000000000000);
-		assert_eq!(Currency::free_balance(asset_b, &user_4), 978738716008001);
+		assert_eq!(Currency::free_balance(asset_a, &user_4), 999000000000000);
+		assert_eq!(Currency::free_balance(asset_b, &user_4), 1001991044854829);
 
 		// Check final pool balances
-		assert_eq!(Currency::free_balance(asset_a, &pair_account), 93808930316730);
-		assert_eq!(Currency::free_balance(asset_b, &pair_account), 213261283991999);
+		assert_eq!(Currency::free_balance(asset_a, &pair_account), 100001517499067);
+		assert_eq!(Currency::free_balance(asset_b, &pair_account), 200012955145171);
 
 		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 0);
 
 		expect_events(vec![
 			RawEvent::IntentionRegistered(
 				user_2,
-				asset_b,
 				asset_a,
-				5_000_000_000_000,
-				IntentionType::BUY,
+				asset_b,
+				1_000_000_000_000,
+				IntentionType::SELL,
 				user_2_sell_intention_id,
+				None,
+				1,
 			)
 			.into(),
 			RawEvent::IntentionRegistered(
 				user_3,
 				asset_b,
 				asset_a,
-				3_000_000_000_000,
-				IntentionType::BUY,
+				1_000_000_000_000,
+				IntentionType::SELL,
 				user_3_sell_intention_id,
+				None,
+				1,
 			)
 			.into(),
 			RawEvent::IntentionRegistered(
 				user_4,
 				asset_a,
 				asset_b,
-				10_000_000_000_000,
-				IntentionType::BUY,
+				1_000_000_000_000,
+				IntentionType::SELL,
 				user_4_sell_intention_id,
+				None,
+				1,
 			)
 			.into(),
-			TestEvent::amm(amm::RawEvent::Buy(
-				user_4,
+			RawEvent::IntentionRegistered(
+				user_5,
+				asset_b,
 				asset_a,
+				1_000_000_000_000,
+				IntentionType::SELL,
+				user_5_sell_intention_id,
+				None,
+				1,
+			)
+			.into(),
+			RawEvent::IntentionRegistered(
+				user_6,
 				asset_b,
-				7500000000000,
-				16251283991999,
-			)),
-			RawEvent::IntentionResolvedAMMTrade(
-				user_4,
-				IntentionType::BUY,
-				user_4_sell_intention_id,
-				7500000000000,
-				16251283991999,
+				asset_a,
+				2_000_000_000_000,
+				IntentionType::SELL,
+				user_6_sell_intention_id,
+				None,
+				1,
 			)
 			.into(),
+			RawEvent::FundsReserved(user_2, asset_a, 1000000000000, user_2_sell_intention_id).into(),
+			RawEvent::FundsReserved(user_6, asset_b, 2000000000000, user_6_sell_intention_id).into(),
 			RawEvent::IntentionResolvedDirectTrade(
 				user_2,
-				user_4,
+				user_6,
 				user_2_sell_intention_id,
-				user_4_sell_intention_id,
-				2500000000000,
-				5000000000000,
+				user_6_sell_intention_id,
+				1000000000000,
+				2000000000000,
+				None,
+				None,
+				1,
+				0,
 			)
 			.into(),
-			RawEvent::IntentionResolvedDirectTradeFees(user_2, pair_account, asset_a, 5000000000).into(),
-			RawEvent::IntentionResolvedDirectTradeFees(user_4, pair_account, asset_b, 10000000000).into(),
-			TestEvent::amm(amm::RawEvent::Buy(
+			RawEvent::FundsUnreserved(user_2, asset_a, 1000000000000, user_2_sell_intention_id).into(),
+			RawEvent::FundsUnreserved(user_6, asset_b, 2000000000000, user_6_sell_intention_id).into(),
+			RawEvent::IntentionResolvedDirectTradeFees(user_2, pair_account, asset_b, 4000000000).into(),
+			RawEvent::IntentionResolvedDirectTradeFees(user_6, pair_account, asset_a, 2000000000).into(),
+			RawEvent::FundsReserved(user_4, asset_a, 500000000000, user_4_sell_intention_id).into(),
+			RawEvent::FundsReserved(user_3, asset_b, 1000000000000, user_3_sell_intention_id).into(),
+			RawEvent::IntentionResolvedDirectTrade(
+				user_4,
 				user_3,
+				user_4_sell_intention_id,
+				user_3_sell_intention_id,
+				500000000000,
+				1000000000000,
+				None,
+				None,
+				1,
+				500000000000,
+			)
+			.into(),
+			RawEvent::FundsUnreserved(user_4, asset_a, 500000000000, user_4_sell_intention_id).into(),
+			RawEvent::FundsUnreserved(user_3, asset_b, 1000000000000, user_3_sell_intention_id).into(),
+			RawEvent::IntentionResolvedDirectTradeFees(user_4, pair_account, asset_b, 2000000000).into(),
+			RawEvent::IntentionResolvedDirectTradeFees(user_3, pair_account, asset_a, 1000000000).into(),
+			TestEvent::amm(amm::RawEvent::Sell(
+				user_4,
+				asset_a,
+				asset_b,
+				500000000000,
+				993044854829,
+			)),
+			RawEvent::IntentionResolvedAMMTrade(
+				user_4,
+				IntentionType::SELL,
+				user_4_sell_intention_id,
+				5_000_000_000_00,
+				993044854829,
+				None,
+				1,
+			)
+			.into(),
+			TestEvent::amm(amm::RawEvent::Sell(
+				user_5,
 				asset_b,
 				asset_a,
-				3000000000000,
-				1303930316730,
+				1000000000000,
+				501482500933,
 			)),
 			RawEvent::IntentionResolvedAMMTrade(
-				user_3,
-				IntentionType::BUY,
-				user_3_sell_intention_id,
-				3000000000000,
-				1303930316730,
+				user_5,
+				IntentionType::SELL,
+				user_5_sell_intention_id,
+				1000000000000,
+				501482500933,
+				None,
+				1,
 			)
 			.into(),
 		]);
@@ -1702,7 +2083,7 @@ fn buy_test_group_buys() {
 }
 
 #[test]
-fn discount_tests_with_error() {
+fn sell_test_group_sells() {
 	new_test_ext().execute_with(|| {
 		let user_1 = ALICE;
 		let user_2 = BOB;
@@ -1718,32 +2099,53 @@ fn discount_tests_with_error() {
 
 		initialize_pool(asset_a, asset_b, user_1, pool_amount, initial_price);
 
-		assert_ok!(Exchange::buy(
+		assert_ok!(Exchange::sell(
 			Origin::signed(user_2),
 			asset_b,
 			asset_a,
 			5_000_000_000_000,
-			20_000_000_000_000,
+			200_000_000_000,
+			false,
+			None,
+			None,
+			None,
 			true,
+			false,
+			0,
+			Price::from(1),
 		));
+		let user_2_sell_intention_id = generate_intention_id(&user_2, 0);
 		assert_ok!(Exchange::sell(
 			Origin::signed(user_3),
 			asset_b,
 			asset_a,
 			3_000_000_000_000,
-			20_000_000_000_000,
+			200_000_000_000,
+			false,
+			None,
+			None,
+			None,
 			true,
+			false,
+			0,
+			Price::from(1),
 		));
+		let user_3_sell_intention_id = generate_intention_id(&user_3, 1);
 		assert_ok!(Exchange::sell(
 			Origin::signed(user_4),
 			asset_a,
 			asset_b,
 			10_000_000_000_000,
-			20_000_000_000_000,
+			200_000_000_000,
+			false,
+			None,
+			None,
+			None,
 			true,
+			false,
+			0,
+			Price::from(1),
 		));
-		let user_2_sell_intention_id = generate_intention_id(&user_2, 0);
-		let user_3_sell_intention_id = generate_intention_id(&user_3, 1);
 		let user_4_sell_intention_id = generate_intention_id(&user_4, 2);
 
 		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 3);
@@ -1752,22 +2154,18 @@ fn discount_tests_with_error() {
 		<Exchange as OnFinalize<u64>>::on_finalize(9);
 
 		// Check final account balances
-		assert_eq!(Currency::free_balance(asset_a, &user_2), 1000000000000000);
-		assert_eq!(Currency::free_balance(asset_b, &user_2), 1000000000000000);
+		assert_eq!(Currency::free_balance(asset_a, &user_2), 1002495000000000);
+		assert_eq!(Currency::free_balance(asset_b, &user_2), 995000000000000);
 
-		assert_eq!(Currency::free_balance(asset_a, &user_3), 1000000000000000);
-		assert_eq!(Currency::free_balance(asset_b, &user_3), 1000000000000000);
+		assert_eq!(Currency::free_balance(asset_a, &user_3), 1001702327336909);
+		assert_eq!(Currency::free_balance(asset_b, &user_3), 997000000000000);
 
-		assert_eq!(Currency::free_balance(asset_a, &user_4), 1000000000000000);
-		assert_eq!(Currency::free_balance(asset_b, &user_4), 1000000000000000);
+		assert_eq!(Currency::free_balance(asset_a, &user_4), 990000000000000);
+		assert_eq!(Currency::free_balance(asset_b, &user_4), 1018917573262630);
 
 		// Check final pool balances
-		assert_eq!(Currency::free_balance(asset_a, &pair_account), 100000000000000);
-		assert_eq!(Currency::free_balance(asset_b, &pair_account), 200000000000000);
-
-		assert_eq!(Currency::free_balance(HDX, &user_4), 1000000000000000);
-		assert_eq!(Currency::free_balance(HDX, &user_2), 1000000000000000);
-		assert_eq!(Currency::free_balance(HDX, &user_3), 1000000000000000);
+		assert_eq!(Currency::free_balance(asset_a, &pair_account), 105802672663091);
+		assert_eq!(Currency::free_balance(asset_b, &pair_account), 189082426737370);
 
 		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 0);
 
@@ -1777,8 +2175,10 @@ fn discount_tests_with_error() {
 				asset_b,
 				asset_a,
 				5_000_000_000_000,
-				IntentionType::BUY,
+				IntentionType::SELL,
 				user_2_sell_intention_id,
+				None,
+				1,
 			)
 			.into(),
 			RawEvent::IntentionRegistered(
@@ -1788,6 +2188,8 @@ fn discount_tests_with_error() {
 				3_000_000_000_000,
 				IntentionType::SELL,
 				user_3_sell_intention_id,
+				None,
+				1,
 			)
 			.into(),
 			RawEvent::IntentionRegistered(
@@ -1797,614 +2199,9070 @@ fn discount_tests_with_error() {
 				10_000_000_000_000,
 				IntentionType::SELL,
 				user_4_sell_intention_id,
+				None,
+				1,
 			)
 			.into(),
-			RawEvent::AMMSellErrorEvent(
+			RawEvent::FundsReserved(user_4, asset_a, 2500000000000, user_4_sell_intention_id).into(),
+			RawEvent::FundsReserved(user_2, asset_b, 5000000000000, user_2_sell_intention_id).into(),
+			RawEvent::IntentionResolvedDirectTrade(
 				user_4,
-				asset_a,
-				asset_b,
-				IntentionType::SELL,
+				user_2,
 				user_4_sell_intention_id,
-				DispatchError::Module {
-					index: 0,
-					error: 23,
-					message: None,
-				},
+				user_2_sell_intention_id,
+				2500000000000,
+				5000000000000,
+				None,
+				None,
+				1,
+				7500000000000,
 			)
 			.into(),
-			RawEvent::AMMBuyErrorEvent(
-				user_2,
-				asset_b,
+			RawEvent::FundsUnreserved(user_4, asset_a, 2500000000000, user_4_sell_intention_id).into(),
+			RawEvent::FundsUnreserved(user_2, asset_b, 5000000000000, user_2_sell_intention_id).into(),
+			RawEvent::IntentionResolvedDirectTradeFees(user_4, pair_account, asset_b, 10000000000).into(),
+			RawEvent::IntentionResolvedDirectTradeFees(user_2, pair_account, asset_a, 5000000000).into(),
+			TestEvent::amm(amm::RawEvent::Sell(
+				user_4,
 				asset_a,
-				IntentionType::BUY,
-				user_2_sell_intention_id,
-				DispatchError::Module {
-					index: 0,
-					error: 23,
-					message: None,
-				},
+				asset_b,
+				7500000000000,
+				13927573262630,
+			)),
+			RawEvent::IntentionResolvedAMMTrade(
+				user_4,
+				IntentionType::SELL,
+				user_4_sell_intention_id,
+				7500000000000,
+				13927573262630,
+				None,
+				1,
 			)
 			.into(),
-			RawEvent::IntentionResolveErrorEvent(
+			TestEvent::amm(amm::RawEvent::Sell(
 				user_3,
 				asset_b,
 				asset_a,
+				3000000000000,
+				1702327336909,
+			)),
+			RawEvent::IntentionResolvedAMMTrade(
+				user_3,
 				IntentionType::SELL,
 				user_3_sell_intention_id,
-				DispatchError::Module {
-					index: 0,
-					error: 23,
-					message: None,
-				},
+				3000000000000,
+				1702327336909,
+				None,
+				1,
 			)
 			.into(),
 		]);
 	});
 }
+#[test]
+fn sell_without_pool_should_not_work() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			Exchange::sell(
+				Origin::signed(ALICE),
+				HDX,
+				ETH,
+				100,
+				200,
+				false,
+				None,
+				None,
+				None,
+				true,
+				false,
+				0,
+				Price::from(1),
+			),
+			Error::<Test>::TokenPoolNotFound.with_weight(<Test as Config>::WeightInfo::reject_intention())
+		);
+	});
+}
 
 #[test]
-fn simple_sell_sell() {
+fn sell_with_zero_amount_should_not_work() {
 	new_test_ext().execute_with(|| {
-		let user_1 = ALICE;
-		let user_2 = BOB;
-		let user_3 = CHARLIE;
-		let asset_a = ETH;
-		let asset_b = DOT;
+		assert_ok!(AMMModule::create_pool(
+			Origin::signed(ALICE),
+			HDX,
+			ETH,
+			200_000,
+			Price::from(2)
+		));
+
+		assert_noop!(
+			Exchange::sell(
+				Origin::signed(ALICE),
+				HDX,
+				ETH,
+				0,
+				0,
+				false,
+				None,
+				None,
+				None,
+				true,
+				false,
+				0,
+				Price::from(1),
+			),
+			Error::<Test>::ZeroAmount.with_weight(<Test as Config>::WeightInfo::reject_intention())
+		);
+
+		assert_eq!(Exchange::get_intentions_count((HDX, ETH)), 0);
+		assert!(Exchange::get_intentions((HDX, ETH)).is_empty());
+	});
+}
+
+#[test]
+fn buy_with_zero_amount_should_not_work() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(AMMModule::create_pool(
+			Origin::signed(ALICE),
+			HDX,
+			ETH,
+			200_000,
+			Price::from(2)
+		));
+
+		assert_noop!(
+			Exchange::buy(
+				Origin::signed(ALICE),
+				HDX,
+				ETH,
+				0,
+				1_000_000_000_000,
+				false,
+				None,
+				None,
+				None,
+				true,
+				false,
+				0,
+				Price::from(1),
+			),
+			Error::<Test>::ZeroAmount.with_weight(<Test as Config>::WeightInfo::reject_intention())
+		);
+
+		assert_eq!(Exchange::get_intentions_count((HDX, ETH)), 0);
+		assert!(Exchange::get_intentions((HDX, ETH)).is_empty());
+	});
+}
+
+#[test]
+fn sell_more_than_owner_should_not_work() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(AMMModule::create_pool(
+			Origin::signed(ALICE),
+			HDX,
+			ETH,
+			200_000,
+			Price::from(2)
+		));
+
+		assert_noop!(
+			Exchange::sell(
+				Origin::signed(ALICE),
+				HDX,
+				ETH,
+				1000_000_000_000_000u128,
+				1,
+				false,
+				None,
+				None,
+				None,
+				true,
+				false,
+				0,
+				Price::from(1),
+			),
+			Error::<Test>::InsufficientAssetBalance.with_weight(<Test as Config>::WeightInfo::reject_intention())
+		);
+	});
+}
+
+#[test]
+fn sell_should_fail_when_intention_count_is_at_u32_max() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(AMMModule::create_pool(
+			Origin::signed(ALICE),
+			HDX,
+			ETH,
+			200_000,
+			Price::from(2)
+		));
+
+		ExchangeAssetsIntentionCount::insert((HDX, ETH), u32::MAX);
+
+		assert_noop!(
+			Exchange::sell(
+				Origin::signed(ALICE),
+				HDX,
+				ETH,
+				100,
+				1,
+				false,
+				None,
+				None,
+				None,
+				true,
+				false,
+				0,
+				Price::from(1),
+			),
+			Error::<Test>::StorageOverflow.with_weight(<Test as Config>::WeightInfo::reject_intention())
+		);
+	});
+}
+
+#[test]
+fn sell_should_charge_reduced_weight_when_rejected_before_pool_lookup() {
+	new_test_ext().execute_with(|| {
+		let result = Exchange::sell(
+			Origin::signed(ALICE),
+			HDX,
+			ETH,
+			100,
+			200,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		);
+
+		assert_eq!(
+			result.unwrap_err().post_info.actual_weight,
+			Some(<Test as Config>::WeightInfo::reject_intention())
+		);
+	});
+}
+
+#[test]
+fn sell_should_charge_full_weight_when_accepted() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(AMMModule::create_pool(
+			Origin::signed(ALICE),
+			HDX,
+			ETH,
+			200_000,
+			Price::from(2)
+		));
+
+		let result = Exchange::sell(
+			Origin::signed(ALICE),
+			HDX,
+			ETH,
+			100,
+			1,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		);
+
+		// A registered intention pays the full extrinsic weight - `actual_weight` is left
+		// unset so the pre-dispatch weight from `#[weight]` stands.
+		assert_eq!(result.unwrap().actual_weight, None);
+	});
+}
+
+#[test]
+fn sell_pre_dispatch_weight_should_grow_with_the_number_of_already_queued_intentions() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(AMMModule::create_pool(
+			Origin::signed(ALICE),
+			HDX,
+			ETH,
+			200_000,
+			Price::from(2)
+		));
+
+		let weight_when_empty = <Test as Config>::WeightInfo::sell(TotalIntentions::get());
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(ALICE),
+			HDX,
+			ETH,
+			100,
+			1,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		assert_ok!(Exchange::sell(
+			Origin::signed(ALICE),
+			HDX,
+			ETH,
+			100,
+			1,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		let weight_after_two_queued = <Test as Config>::WeightInfo::sell(TotalIntentions::get());
+
+		assert!(
+			weight_after_two_queued > weight_when_empty,
+			"an intention submitted later in a congested block must cost more weight than the first one"
+		);
+	});
+}
+
+#[test]
+fn sell_all_should_register_intention_for_entire_free_balance() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(AMMModule::create_pool(
+			Origin::signed(ALICE),
+			HDX,
+			ETH,
+			200_000,
+			Price::from(2)
+		));
+
+		let usable_amount = Currency::free_balance(HDX, &BOB);
+
+		assert_ok!(Exchange::sell_all(Origin::signed(BOB), HDX, ETH, false));
+
+		match last_event() {
+			TestEvent::exchange(RawEvent::IntentionRegistered(
+				who,
+				asset_sell,
+				asset_buy,
+				amount_sell,
+				kind,
+				_,
+				_,
+				_,
+			)) => {
+				assert_eq!(who, BOB);
+				assert_eq!(asset_sell, HDX);
+				assert_eq!(asset_buy, ETH);
+				assert_eq!(amount_sell, usable_amount);
+				assert_eq!(kind, IntentionType::SELL);
+			}
+			other => panic!("expected an intention to be registered, got {:?}", other),
+		}
+
+		// The whole free balance was earmarked for the intention - `ExistentialDeposits` is
+		// zero for every asset in this mock, so nothing is held back.
+		assert_eq!(Currency::free_balance(HDX, &BOB), usable_amount);
+	});
+}
+
+#[test]
+fn sell_all_should_work_with_balance_barely_above_existential_deposit() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(AMMModule::create_pool(
+			Origin::signed(ALICE),
+			HDX,
+			ETH,
+			200_000,
+			Price::from(2)
+		));
+
+		// Leave BOB with just above the minimum tradable amount - with `ExistentialDeposits`
+		// at zero, this is also just above the existential deposit.
+		let remaining = MinTradingLimit::get() + 1;
+		let to_withdraw = Currency::free_balance(HDX, &BOB) - remaining;
+		assert_ok!(Currency::withdraw(HDX, &BOB, to_withdraw));
+
+		assert_ok!(Exchange::sell_all(Origin::signed(BOB), HDX, ETH, false));
+
+		match last_event() {
+			TestEvent::exchange(RawEvent::IntentionRegistered(
+				who,
+				asset_sell,
+				asset_buy,
+				amount_sell,
+				kind,
+				_,
+				_,
+				_,
+			)) => {
+				assert_eq!(who, BOB);
+				assert_eq!(asset_sell, HDX);
+				assert_eq!(asset_buy, ETH);
+				assert_eq!(amount_sell, remaining);
+				assert_eq!(kind, IntentionType::SELL);
+			}
+			other => panic!("expected an intention to be registered, got {:?}", other),
+		}
+
+		assert_eq!(Currency::free_balance(HDX, &BOB), remaining);
+	});
+}
+
+#[test]
+fn sell_all_should_fail_when_usable_balance_is_below_min_trading_limit() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(AMMModule::create_pool(
+			Origin::signed(ALICE),
+			HDX,
+			ETH,
+			200_000,
+			Price::from(2)
+		));
+
+		let remaining = MinTradingLimit::get() - 1;
+		let to_withdraw = Currency::free_balance(HDX, &BOB) - remaining;
+		assert_ok!(Currency::withdraw(HDX, &BOB, to_withdraw));
+
+		assert_noop!(
+			Exchange::sell_all(Origin::signed(BOB), HDX, ETH, false),
+			Error::<Test>::InsufficientAssetBalance.with_weight(<Test as Config>::WeightInfo::reject_intention())
+		);
+	});
+}
+
+#[test]
+fn validate_sell_should_pass_without_registering_an_intention() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(AMMModule::create_pool(
+			Origin::signed(ALICE),
+			HDX,
+			ETH,
+			200_000,
+			Price::from(2)
+		));
+
+		assert_ok!(Exchange::validate_sell(Origin::signed(ALICE), HDX, ETH, 100, false));
+
+		assert_eq!(Exchange::get_intentions_count((HDX, ETH)), 0);
+		assert!(Exchange::get_intentions((HDX, ETH)).is_empty());
+	});
+}
+
+#[test]
+fn validate_sell_should_fail_when_pool_does_not_exist() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			Exchange::validate_sell(Origin::signed(ALICE), HDX, ETH, 100, false),
+			Error::<Test>::TokenPoolNotFound
+		);
+	});
+}
+
+#[test]
+fn sell_should_reject_missing_pool_by_default_even_with_create_if_missing_set() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			Exchange::sell(
+				Origin::signed(ALICE),
+				HDX,
+				ETH,
+				100_000_000_000,
+				0,
+				false,
+				None,
+				None,
+				None,
+				true,
+				true,
+				200_000_000_000,
+				Price::from(1),
+			),
+			Error::<Test>::TokenPoolNotFound.with_weight(<Test as Config>::WeightInfo::reject_intention())
+		);
+
+		assert!(!AMMModule::exists(HDX, ETH));
+	});
+}
+
+#[test]
+fn sell_should_create_pool_on_demand_when_missing_and_allowed() {
+	new_test_ext().execute_with(|| {
+		AllowPoolCreationOnDemandMock::set(true);
+
+		let initial_liquidity = 200_000_000_000;
+		let amount_sell = 100_000_000_000;
+
+		assert!(!AMMModule::exists(HDX, ETH));
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(ALICE),
+			HDX,
+			ETH,
+			amount_sell,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			true,
+			initial_liquidity,
+			Price::from(1),
+		));
+
+		assert!(AMMModule::exists(HDX, ETH));
+		assert_eq!(Exchange::get_intentions_count((HDX, ETH)), 1);
+
+		assert_eq!(
+			Currency::free_balance(HDX, &ALICE),
+			ENDOWED_AMOUNT - initial_liquidity - amount_sell
+		);
+	});
+}
+
+#[test]
+fn validate_sell_should_fail_when_sold_asset_is_frozen() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(AMMModule::create_pool(
+			Origin::signed(ALICE),
+			HDX,
+			ETH,
+			200_000,
+			Price::from(2)
+		));
+
+		assert_ok!(Exchange::freeze_asset(frame_system::RawOrigin::Root.into(), HDX));
+
+		assert_noop!(
+			Exchange::validate_sell(Origin::signed(ALICE), HDX, ETH, 100, false),
+			Error::<Test>::AssetFrozen
+		);
+	});
+}
+
+#[test]
+fn validate_sell_should_fail_when_pool_liquidity_is_below_configured_minimum() {
+	new_test_ext().execute_with(|| {
+		let pool_amount = 200_000;
+
+		assert_ok!(AMMModule::create_pool(
+			Origin::signed(ALICE),
+			HDX,
+			ETH,
+			pool_amount,
+			Price::from(2)
+		));
+
+		assert_ok!(Exchange::set_min_pool_liquidity(
+			frame_system::RawOrigin::Root.into(),
+			HDX,
+			ETH,
+			pool_amount + 1,
+		));
+
+		assert_noop!(
+			Exchange::validate_sell(Origin::signed(BOB), HDX, ETH, 100, false),
+			Error::<Test>::PoolLiquidityBelowMinimum
+		);
+	});
+}
+
+#[test]
+fn validate_sell_should_fail_when_balance_is_insufficient() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(AMMModule::create_pool(
+			Origin::signed(ALICE),
+			HDX,
+			ETH,
+			200_000,
+			Price::from(2)
+		));
+
+		assert_noop!(
+			Exchange::validate_sell(Origin::signed(ALICE), HDX, ETH, 1_000_000_000_000_000u128, false),
+			Error::<Test>::InsufficientAssetBalance
+		);
+	});
+}
+
+#[test]
+fn sell_test_mixed_buy_sells() {
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let user_3 = CHARLIE;
+		let user_4 = DAVE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+
+		let pool_amount = 100_000_000_000_000;
+		let initial_price = Price::from(2);
+
+		let pair_account = AMMModule::get_pair_id(&asset_a, &asset_b);
+
+		initialize_pool(asset_a, asset_b, user_1, pool_amount, initial_price);
+
+		assert_ok!(Exchange::buy(
+			Origin::signed(user_2),
+			asset_b,
+			asset_a,
+			5_000_000_000_000,
+			20_000_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		let user_2_sell_intention_id = generate_intention_id(&user_2, 0);
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_3),
+			asset_b,
+			asset_a,
+			3_000_000_000_000,
+			1400_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		let user_3_sell_intention_id = generate_intention_id(&user_3, 1);
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_4),
+			asset_a,
+			asset_b,
+			10_000_000_000_000,
+			2000_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		let user_4_sell_intention_id = generate_intention_id(&user_4, 2);
+
+		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 3);
+
+		// Finalize block
+		<Exchange as OnFinalize<u64>>::on_finalize(9);
+
+		// Check final account balances
+		assert_eq!(Currency::free_balance(asset_a, &user_2), 996969167073281);
+		assert_eq!(Currency::free_balance(asset_b, &user_2), 1005000000000000);
+
+		assert_eq!(Currency::free_balance(asset_a, &user_3), 1001497000000000);
+		assert_eq!(Currency::free_balance(asset_b, &user_3), 997000000000000);
+
+		assert_eq!(Currency::free_balance(asset_a, &user_4), 990000000000000);
+		assert_eq!(Currency::free_balance(asset_b, &user_4), 1018633353446528);
+
+		// Check final pool balances
+		assert_eq!(Currency::free_balance(asset_a, &pair_account), 111533832926719);
+		assert_eq!(Currency::free_balance(asset_b, &pair_account), 179366646553472);
+
+		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 0);
+
+		expect_events(vec![
+			RawEvent::IntentionRegistered(
+				user_2,
+				asset_b,
+				asset_a,
+				5_000_000_000_000,
+				IntentionType::BUY,
+				user_2_sell_intention_id,
+				None,
+				1,
+			)
+			.into(),
+			RawEvent::IntentionRegistered(
+				user_3,
+				asset_b,
+				asset_a,
+				3_000_000_000_000,
+				IntentionType::SELL,
+				user_3_sell_intention_id,
+				None,
+				1,
+			)
+			.into(),
+			RawEvent::IntentionRegistered(
+				user_4,
+				asset_a,
+				asset_b,
+				10_000_000_000_000,
+				IntentionType::SELL,
+				user_4_sell_intention_id,
+				None,
+				1,
+			)
+			.into(),
+			RawEvent::FundsReserved(user_4, asset_a, 1500000000000, user_4_sell_intention_id).into(),
+			RawEvent::FundsReserved(user_3, asset_b, 3000000000000, user_3_sell_intention_id).into(),
+			RawEvent::IntentionResolvedDirectTrade(
+				user_4,
+				user_3,
+				user_4_sell_intention_id,
+				user_3_sell_intention_id,
+				1500000000000,
+				3000000000000,
+				None,
+				None,
+				1,
+				8500000000000,
+			)
+			.into(),
+			RawEvent::FundsUnreserved(user_4, asset_a, 1500000000000, user_4_sell_intention_id).into(),
+			RawEvent::FundsUnreserved(user_3, asset_b, 3000000000000, user_3_sell_intention_id).into(),
+			RawEvent::IntentionResolvedDirectTradeFees(user_4, pair_account, asset_b, 6000000000).into(),
+			RawEvent::IntentionResolvedDirectTradeFees(user_3, pair_account, asset_a, 3000000000).into(),
+			TestEvent::amm(amm::RawEvent::Sell(
+				user_4,
+				asset_a,
+				asset_b,
+				8500000000000,
+				15639353446528,
+			)),
+			RawEvent::IntentionResolvedAMMTrade(
+				user_4,
+				IntentionType::SELL,
+				user_4_sell_intention_id,
+				8500000000000,
+				15639353446528,
+				None,
+				1,
+			)
+			.into(),
+			TestEvent::amm(amm::RawEvent::Buy(
+				user_2,
+				asset_b,
+				asset_a,
+				5000000000000,
+				3030832926719,
+			)),
+			RawEvent::IntentionResolvedAMMTrade(
+				user_2,
+				IntentionType::BUY,
+				user_2_sell_intention_id,
+				5000000000000,
+				3030832926719,
+				None,
+				1,
+			)
+			.into(),
+		]);
+	});
+}
+
+#[test]
+fn discount_tests_no_discount() {
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let user_3 = CHARLIE;
+		let user_4 = DAVE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+
+		let pool_amount = 100_000_000_000_000;
+		let initial_price = Price::from(2);
+
+		let pair_account = AMMModule::get_pair_id(&asset_a, &asset_b);
+
+		initialize_pool(asset_a, asset_b, user_1, pool_amount, initial_price);
+
+		assert_ok!(Exchange::buy(
+			Origin::signed(user_2),
+			asset_b,
+			asset_a,
+			5_000_000_000_000,
+			20_000_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		let user_2_sell_intention_id = generate_intention_id(&user_2, 0);
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_3),
+			asset_b,
+			asset_a,
+			3_000_000_000_000,
+			1400_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		let user_3_sell_intention_id = generate_intention_id(&user_3, 1);
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_4),
+			asset_a,
+			asset_b,
+			10_000_000_000_000,
+			2000_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		let user_4_sell_intention_id = generate_intention_id(&user_4, 2);
+
+		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 3);
+
+		// Finalize block
+		<Exchange as OnFinalize<u64>>::on_finalize(9);
+
+		// Check final account balances
+		assert_eq!(Currency::free_balance(asset_a, &user_2), 996969167073281);
+		assert_eq!(Currency::free_balance(asset_b, &user_2), 1005000000000000);
+
+		assert_eq!(Currency::free_balance(asset_a, &user_3), 1001497000000000);
+		assert_eq!(Currency::free_balance(asset_b, &user_3), 997000000000000);
+
+		assert_eq!(Currency::free_balance(asset_a, &user_4), 990000000000000);
+		assert_eq!(Currency::free_balance(asset_b, &user_4), 1018633353446528);
+
+		// Check final pool balances
+		assert_eq!(Currency::free_balance(asset_a, &pair_account), 111533832926719);
+		assert_eq!(Currency::free_balance(asset_b, &pair_account), 179366646553472);
+
+		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 0);
+
+		expect_events(vec![
+			RawEvent::IntentionRegistered(
+				user_2,
+				asset_b,
+				asset_a,
+				5_000_000_000_000,
+				IntentionType::BUY,
+				user_2_sell_intention_id,
+				None,
+				1,
+			)
+			.into(),
+			RawEvent::IntentionRegistered(
+				user_3,
+				asset_b,
+				asset_a,
+				3_000_000_000_000,
+				IntentionType::SELL,
+				user_3_sell_intention_id,
+				None,
+				1,
+			)
+			.into(),
+			RawEvent::IntentionRegistered(
+				user_4,
+				asset_a,
+				asset_b,
+				10_000_000_000_000,
+				IntentionType::SELL,
+				user_4_sell_intention_id,
+				None,
+				1,
+			)
+			.into(),
+			RawEvent::FundsReserved(user_4, asset_a, 1500000000000, user_4_sell_intention_id).into(),
+			RawEvent::FundsReserved(user_3, asset_b, 3000000000000, user_3_sell_intention_id).into(),
+			RawEvent::IntentionResolvedDirectTrade(
+				user_4,
+				user_3,
+				user_4_sell_intention_id,
+				user_3_sell_intention_id,
+				1500000000000,
+				3000000000000,
+				None,
+				None,
+				1,
+				8500000000000,
+			)
+			.into(),
+			RawEvent::FundsUnreserved(user_4, asset_a, 1500000000000, user_4_sell_intention_id).into(),
+			RawEvent::FundsUnreserved(user_3, asset_b, 3000000000000, user_3_sell_intention_id).into(),
+			RawEvent::IntentionResolvedDirectTradeFees(user_4, pair_account, asset_b, 6000000000).into(),
+			RawEvent::IntentionResolvedDirectTradeFees(user_3, pair_account, asset_a, 3000000000).into(),
+			TestEvent::amm(amm::RawEvent::Sell(
+				user_4,
+				asset_a,
+				asset_b,
+				8500000000000,
+				15639353446528,
+			)),
+			RawEvent::IntentionResolvedAMMTrade(
+				user_4,
+				IntentionType::SELL,
+				user_4_sell_intention_id,
+				8500000000000,
+				15639353446528,
+				None,
+				1,
+			)
+			.into(),
+			TestEvent::amm(amm::RawEvent::Buy(
+				user_2,
+				asset_b,
+				asset_a,
+				5000000000000,
+				3030832926719,
+			)),
+			RawEvent::IntentionResolvedAMMTrade(
+				user_2,
+				IntentionType::BUY,
+				user_2_sell_intention_id,
+				5000000000000,
+				3030832926719,
+				None,
+				1,
+			)
+			.into(),
+		]);
+	});
+}
+
+#[test]
+fn discount_tests_with_discount() {
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let user_3 = CHARLIE;
+		let user_4 = DAVE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+
+		let pool_amount = 100_000_000_000_000;
+		let initial_price = Price::from(2);
+
+		let pair_account = AMMModule::get_pair_id(&asset_a, &asset_b);
+
+		initialize_pool(asset_a, asset_b, user_1, pool_amount, initial_price);
+		initialize_pool(asset_a, HDX, user_2, pool_amount, initial_price);
+		initialize_pool(asset_b, HDX, user_3, pool_amount, initial_price);
+
+		assert_ok!(Exchange::buy(
+			Origin::signed(user_2),
+			asset_b,
+			asset_a,
+			5_000_000_000_000,
+			20_000_000_000_000,
+			true,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		let user_2_sell_intention_id = generate_intention_id(&user_2, 0);
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_3),
+			asset_b,
+			asset_a,
+			3_000_000_000_000,
+			1400_000_000_000,
+			true,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		let user_3_sell_intention_id = generate_intention_id(&user_3, 1);
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_4),
+			asset_a,
+			asset_b,
+			10_000_000_000_000,
+			2000_000_000_000,
+			true,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		let user_4_sell_intention_id = generate_intention_id(&user_4, 2);
+
+		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 3);
+
+		// Finalize block
+		<Exchange as OnFinalize<u64>>::on_finalize(9);
+
+		// Check final account balances
+		assert_eq!(Currency::free_balance(asset_a, &user_2), 896972892085116);
+		assert_eq!(Currency::free_balance(asset_b, &user_2), 1005000000000000);
+
+		assert_eq!(Currency::free_balance(asset_a, &user_3), 1001497000000000);
+		assert_eq!(Currency::free_balance(asset_b, &user_3), 897000000000000);
+
+		assert_eq!(Currency::free_balance(asset_a, &user_4), 990000000000000);
+		assert_eq!(Currency::free_balance(asset_b, &user_4), 1018652130468064);
+
+		// Check final pool balances
+		assert_eq!(Currency::free_balance(asset_a, &pair_account), 111530107914884);
+		assert_eq!(Currency::free_balance(asset_b, &pair_account), 179347869531936);
+
+		assert_eq!(Currency::free_balance(HDX, &user_4), 999988100000000);
+		assert_eq!(Currency::free_balance(HDX, &user_2), 799993000000000);
+		assert_eq!(Currency::free_balance(HDX, &user_3), 800000000000000);
+
+		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 0);
+
+		expect_events(vec![
+			RawEvent::IntentionRegistered(
+				user_2,
+				asset_b,
+				asset_a,
+				5_000_000_000_000,
+				IntentionType::BUY,
+				user_2_sell_intention_id,
+				None,
+				1,
+			)
+			.into(),
+			RawEvent::IntentionRegistered(
+				user_3,
+				asset_b,
+				asset_a,
+				3_000_000_000_000,
+				IntentionType::SELL,
+				user_3_sell_intention_id,
+				None,
+				1,
+			)
+			.into(),
+			RawEvent::IntentionRegistered(
+				user_4,
+				asset_a,
+				asset_b,
+				10_000_000_000_000,
+				IntentionType::SELL,
+				user_4_sell_intention_id,
+				None,
+				1,
+			)
+			.into(),
+			RawEvent::FundsReserved(user_4, asset_a, 1500000000000, user_4_sell_intention_id).into(),
+			RawEvent::FundsReserved(user_3, asset_b, 3000000000000, user_3_sell_intention_id).into(),
+			RawEvent::IntentionResolvedDirectTrade(
+				user_4,
+				user_3,
+				user_4_sell_intention_id,
+				user_3_sell_intention_id,
+				1500000000000,
+				3000000000000,
+				None,
+				None,
+				1,
+				8500000000000,
+			)
+			.into(),
+			RawEvent::FundsUnreserved(user_4, asset_a, 1500000000000, user_4_sell_intention_id).into(),
+			RawEvent::FundsUnreserved(user_3, asset_b, 3000000000000, user_3_sell_intention_id).into(),
+			RawEvent::IntentionResolvedDirectTradeFees(user_4, pair_account, asset_b, 6000000000).into(),
+			RawEvent::IntentionResolvedDirectTradeFees(user_3, pair_account, asset_a, 3000000000).into(),
+			TestEvent::amm(amm::RawEvent::Sell(
+				user_4,
+				asset_a,
+				asset_b,
+				8500000000000,
+				15658130468064,
+			)),
+			RawEvent::IntentionResolvedAMMTrade(
+				user_4,
+				IntentionType::SELL,
+				user_4_sell_intention_id,
+				8500000000000,
+				15658130468064,
+				None,
+				1,
+			)
+			.into(),
+			TestEvent::amm(amm::RawEvent::Buy(
+				user_2,
+				asset_b,
+				asset_a,
+				5000000000000,
+				3027107914884,
+			)),
+			RawEvent::IntentionResolvedAMMTrade(
+				user_2,
+				IntentionType::BUY,
+				user_2_sell_intention_id,
+				5000000000000,
+				3027107914884,
+				None,
+				1,
+			)
+			.into(),
+		]);
+	});
+}
+
+#[test]
+fn buy_test_exact_match() {
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let user_3 = CHARLIE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000_000_000;
+		let initial_price = Price::from(2);
+
+		let pair_account = AMMModule::get_pair_id(&asset_a, &asset_b);
+
+		initialize_pool(asset_a, asset_b, user_1, pool_amount, initial_price);
+
+		assert_ok!(Exchange::buy(
+			Origin::signed(user_2),
+			asset_a,
+			asset_b,
+			1_000_000_000_000,
+			4_000_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		let user_2_sell_intention_id = generate_intention_id(&user_2, 0);
+		assert_ok!(Exchange::buy(
+			Origin::signed(user_3),
+			asset_b,
+			asset_a,
+			2_000_000_000_000,
+			4_000_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		let user_3_sell_intention_id = generate_intention_id(&user_3, 1);
+
+		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 2);
+
+		// Finalize block
+		<Exchange as OnFinalize<u64>>::on_finalize(9);
+
+		// Check final account balances -> SEEMS LEGIT
+		assert_eq!(Currency::free_balance(asset_a, &user_2), 1001000000000000);
+		assert_eq!(Currency::free_balance(asset_b, &user_2), 997996000000000);
+
+		assert_eq!(Currency::free_balance(asset_a, &user_3), 998998000000000);
+		assert_eq!(Currency::free_balance(asset_b, &user_3), 1002000000000000);
+
+		// Check final pool balances -> SEEMS LEGIT
+		assert_eq!(Currency::free_balance(asset_a, &pair_account), 100002000000000);
+		assert_eq!(Currency::free_balance(asset_b, &pair_account), 200004000000000);
+
+		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 0);
+
+		expect_events(vec![
+			RawEvent::IntentionRegistered(
+				user_2,
+				asset_a,
+				asset_b,
+				1_000_000_000_000,
+				IntentionType::BUY,
+				user_2_sell_intention_id,
+				None,
+				1,
+			)
+			.into(),
+			RawEvent::IntentionRegistered(
+				user_3,
+				asset_b,
+				asset_a,
+				2_000_000_000_000,
+				IntentionType::BUY,
+				user_3_sell_intention_id,
+				None,
+				1,
+			)
+			.into(),
+			RawEvent::FundsReserved(user_3, asset_a, 1000000000000, user_3_sell_intention_id).into(),
+			RawEvent::FundsReserved(user_2, asset_b, 2000000000000, user_2_sell_intention_id).into(),
+			RawEvent::IntentionResolvedDirectTrade(
+				user_3,
+				user_2,
+				user_3_sell_intention_id,
+				user_2_sell_intention_id,
+				1000000000000,
+				2000000000000,
+				None,
+				None,
+				1,
+				0,
+			)
+			.into(),
+			RawEvent::FundsUnreserved(user_3, asset_a, 1000000000000, user_3_sell_intention_id).into(),
+			RawEvent::FundsUnreserved(user_2, asset_b, 2000000000000, user_2_sell_intention_id).into(),
+			RawEvent::IntentionResolvedDirectTradeFees(user_3, pair_account, asset_a, 2000000000).into(),
+			RawEvent::IntentionResolvedDirectTradeFees(user_2, pair_account, asset_b, 4000000000).into(),
+		]);
+	});
+}
+
+#[test]
+fn buy_test_group_buys() {
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let user_3 = CHARLIE;
+		let user_4 = DAVE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+
+		let pool_amount = 100_000_000_000_000;
+		let initial_price = Price::from(2);
+
+		let pair_account = AMMModule::get_pair_id(&asset_a, &asset_b);
+
+		initialize_pool(asset_a, asset_b, user_1, pool_amount, initial_price);
+
+		assert_ok!(Exchange::buy(
+			Origin::signed(user_2),
+			asset_b,
+			asset_a,
+			5_000_000_000_000,
+			20_000_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		let user_2_sell_intention_id = generate_intention_id(&user_2, 0);
+		assert_ok!(Exchange::buy(
+			Origin::signed(user_3),
+			asset_b,
+			asset_a,
+			3_000_000_000_000,
+			20_000_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		let user_3_sell_intention_id = generate_intention_id(&user_3, 1);
+		assert_ok!(Exchange::buy(
+			Origin::signed(user_4),
+			asset_a,
+			asset_b,
+			10_000_000_000_000,
+			22_000_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		let user_4_sell_intention_id = generate_intention_id(&user_4, 2);
+
+		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 3);
+
+		// Finalize block
+		<Exchange as OnFinalize<u64>>::on_finalize(9);
+
+		// Check final account balances
+		assert_eq!(Currency::free_balance(asset_a, &user_2), 997495000000000);
+		assert_eq!(Currency::free_balance(asset_b, &user_2), 1005000000000000);
+
+		assert_eq!(Currency::free_balance(asset_a, &user_3), 998696069683270);
+		assert_eq!(Currency::free_balance(asset_b, &user_3), 1003000000000000);
+
+		assert_eq!(Currency::free_balance(asset_a, &user_4), 1010000000000000);
+		assert_eq!(Currency::free_balance(asset_b, &user_4), 978738716008001);
+
+		// Check final pool balances
+		assert_eq!(Currency::free_balance(asset_a, &pair_account), 93808930316730);
+		assert_eq!(Currency::free_balance(asset_b, &pair_account), 213261283991999);
+
+		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 0);
+
+		expect_events(vec![
+			RawEvent::IntentionRegistered(
+				user_2,
+				asset_b,
+				asset_a,
+				5_000_000_000_000,
+				IntentionType::BUY,
+				user_2_sell_intention_id,
+				None,
+				1,
+			)
+			.into(),
+			RawEvent::IntentionRegistered(
+				user_3,
+				asset_b,
+				asset_a,
+				3_000_000_000_000,
+				IntentionType::BUY,
+				user_3_sell_intention_id,
+				None,
+				1,
+			)
+			.into(),
+			RawEvent::IntentionRegistered(
+				user_4,
+				asset_a,
+				asset_b,
+				10_000_000_000_000,
+				IntentionType::BUY,
+				user_4_sell_intention_id,
+				None,
+				1,
+			)
+			.into(),
+			TestEvent::amm(amm::RawEvent::Buy(
+				user_4,
+				asset_a,
+				asset_b,
+				7500000000000,
+				16251283991999,
+			)),
+			RawEvent::IntentionResolvedAMMTrade(
+				user_4,
+				IntentionType::BUY,
+				user_4_sell_intention_id,
+				7500000000000,
+				16251283991999,
+				None,
+				1,
+			)
+			.into(),
+			RawEvent::FundsReserved(user_2, asset_a, 2500000000000, user_2_sell_intention_id).into(),
+			RawEvent::FundsReserved(user_4, asset_b, 5000000000000, user_4_sell_intention_id).into(),
+			RawEvent::IntentionResolvedDirectTrade(
+				user_2,
+				user_4,
+				user_2_sell_intention_id,
+				user_4_sell_intention_id,
+				2500000000000,
+				5000000000000,
+				None,
+				None,
+				1,
+				0,
+			)
+			.into(),
+			RawEvent::FundsUnreserved(user_2, asset_a, 2500000000000, user_2_sell_intention_id).into(),
+			RawEvent::FundsUnreserved(user_4, asset_b, 5000000000000, user_4_sell_intention_id).into(),
+			RawEvent::IntentionResolvedDirectTradeFees(user_2, pair_account, asset_a, 5000000000).into(),
+			RawEvent::IntentionResolvedDirectTradeFees(user_4, pair_account, asset_b, 10000000000).into(),
+			TestEvent::amm(amm::RawEvent::Buy(
+				user_3,
+				asset_b,
+				asset_a,
+				3000000000000,
+				1303930316730,
+			)),
+			RawEvent::IntentionResolvedAMMTrade(
+				user_3,
+				IntentionType::BUY,
+				user_3_sell_intention_id,
+				3000000000000,
+				1303930316730,
+				None,
+				1,
+			)
+			.into(),
+		]);
+	});
+}
+
+#[test]
+fn discount_tests_with_error() {
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let user_3 = CHARLIE;
+		let user_4 = DAVE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+
+		let pool_amount = 100_000_000_000_000;
+		let initial_price = Price::from(2);
+
+		let pair_account = AMMModule::get_pair_id(&asset_a, &asset_b);
+
+		initialize_pool(asset_a, asset_b, user_1, pool_amount, initial_price);
+
+		assert_ok!(Exchange::buy(
+			Origin::signed(user_2),
+			asset_b,
+			asset_a,
+			5_000_000_000_000,
+			20_000_000_000_000,
+			true,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_3),
+			asset_b,
+			asset_a,
+			3_000_000_000_000,
+			20_000_000_000_000,
+			true,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_4),
+			asset_a,
+			asset_b,
+			10_000_000_000_000,
+			20_000_000_000_000,
+			true,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		let user_2_sell_intention_id = generate_intention_id(&user_2, 0);
+		let user_3_sell_intention_id = generate_intention_id(&user_3, 1);
+		let user_4_sell_intention_id = generate_intention_id(&user_4, 2);
+
+		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 3);
+
+		// Finalize block
+		<Exchange as OnFinalize<u64>>::on_finalize(9);
+
+		// Check final account balances
+		assert_eq!(Currency::free_balance(asset_a, &user_2), 1000000000000000);
+		assert_eq!(Currency::free_balance(asset_b, &user_2), 1000000000000000);
+
+		assert_eq!(Currency::free_balance(asset_a, &user_3), 1000000000000000);
+		assert_eq!(Currency::free_balance(asset_b, &user_3), 1000000000000000);
+
+		assert_eq!(Currency::free_balance(asset_a, &user_4), 1000000000000000);
+		assert_eq!(Currency::free_balance(asset_b, &user_4), 1000000000000000);
+
+		// Check final pool balances
+		assert_eq!(Currency::free_balance(asset_a, &pair_account), 100000000000000);
+		assert_eq!(Currency::free_balance(asset_b, &pair_account), 200000000000000);
+
+		assert_eq!(Currency::free_balance(HDX, &user_4), 1000000000000000);
+		assert_eq!(Currency::free_balance(HDX, &user_2), 1000000000000000);
+		assert_eq!(Currency::free_balance(HDX, &user_3), 1000000000000000);
+
+		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 0);
+
+		expect_events(vec![
+			RawEvent::IntentionRegistered(
+				user_2,
+				asset_b,
+				asset_a,
+				5_000_000_000_000,
+				IntentionType::BUY,
+				user_2_sell_intention_id,
+				None,
+				1,
+			)
+			.into(),
+			RawEvent::IntentionRegistered(
+				user_3,
+				asset_b,
+				asset_a,
+				3_000_000_000_000,
+				IntentionType::SELL,
+				user_3_sell_intention_id,
+				None,
+				1,
+			)
+			.into(),
+			RawEvent::IntentionRegistered(
+				user_4,
+				asset_a,
+				asset_b,
+				10_000_000_000_000,
+				IntentionType::SELL,
+				user_4_sell_intention_id,
+				None,
+				1,
+			)
+			.into(),
+			RawEvent::AMMSellErrorEvent(
+				user_4,
+				asset_a,
+				asset_b,
+				IntentionType::SELL,
+				user_4_sell_intention_id,
+				DispatchError::Module {
+					index: 0,
+					error: 23,
+					message: None,
+				},
+				AMMFailureReason::Other,
+			)
+			.into(),
+			RawEvent::AMMBuyErrorEvent(
+				user_2,
+				asset_b,
+				asset_a,
+				IntentionType::BUY,
+				user_2_sell_intention_id,
+				DispatchError::Module {
+					index: 0,
+					error: 23,
+					message: None,
+				},
+				AMMFailureReason::Other,
+			)
+			.into(),
+			RawEvent::IntentionResolveErrorEvent(
+				user_3,
+				asset_b,
+				asset_a,
+				IntentionType::SELL,
+				user_3_sell_intention_id,
+				DispatchError::Module {
+					index: 0,
+					error: 23,
+					message: None,
+				},
+			)
+			.into(),
+		]);
+	});
+}
+
+#[test]
+fn simple_sell_sell() {
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let user_3 = CHARLIE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000;
+		let initial_price = Price::from(2);
+
+		let pair_account = AMMModule::get_pair_id(&asset_a, &asset_b);
+
+		initialize_pool(asset_a, asset_b, user_1, pool_amount, initial_price);
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_2),
+			asset_a,
+			asset_b,
+			2_000,
+			400,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_3),
+			asset_b,
+			asset_a,
+			1_000,
+			400,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		let user_2_sell_intention_id = generate_intention_id(&user_2, 0);
+		let user_3_sell_intention_id = generate_intention_id(&user_3, 1);
+
+		assert_eq!(Currency::free_balance(asset_a, &pair_account), 100000000);
+		assert_eq!(Currency::free_balance(asset_b, &pair_account), 200000000);
+
+		<Exchange as OnFinalize<u64>>::on_finalize(9);
+
+		assert_eq!(Currency::free_balance(asset_a, &user_2), 999999999998000);
+		assert_eq!(Currency::free_balance(asset_b, &user_2), 1000000000003992);
+
+		assert_eq!(Currency::free_balance(asset_a, &user_3), 1000000000000499);
+		assert_eq!(Currency::free_balance(asset_b, &user_3), 999999999999000);
+
+		assert_eq!(Currency::free_balance(asset_a, &pair_account), 100001501);
+		assert_eq!(Currency::free_balance(asset_b, &pair_account), 199997008);
+
+		expect_events(vec![
+			RawEvent::IntentionRegistered(
+				user_2,
+				asset_a,
+				asset_b,
+				2_000,
+				IntentionType::SELL,
+				user_2_sell_intention_id,
+				None,
+				1,
+			)
+			.into(),
+			RawEvent::IntentionRegistered(
+				user_3,
+				asset_b,
+				asset_a,
+				1_000,
+				IntentionType::SELL,
+				user_3_sell_intention_id,
+				None,
+				1,
+			)
+			.into(),
+			RawEvent::FundsReserved(user_2, asset_a, 500, user_2_sell_intention_id).into(),
+			RawEvent::FundsReserved(user_3, asset_b, 1000, user_3_sell_intention_id).into(),
+			RawEvent::IntentionResolvedDirectTrade(
+				user_2,
+				user_3,
+				user_2_sell_intention_id,
+				user_3_sell_intention_id,
+				500,
+				1000,
+				None,
+				None,
+				1,
+				1500,
+			)
+			.into(),
+			RawEvent::FundsUnreserved(user_2, asset_a, 500, user_2_sell_intention_id).into(),
+			RawEvent::FundsUnreserved(user_3, asset_b, 1000, user_3_sell_intention_id).into(),
+			RawEvent::IntentionResolvedDirectTradeFees(user_2, pair_account, asset_b, 2).into(),
+			RawEvent::IntentionResolvedDirectTradeFees(user_3, pair_account, asset_a, 1).into(),
+			TestEvent::amm(amm::RawEvent::Sell(2, 3000, 2000, 1500, 2994)),
+			RawEvent::IntentionResolvedAMMTrade(
+				user_2,
+				IntentionType::SELL,
+				user_2_sell_intention_id,
+				1500,
+				2994,
+				None,
+				1,
+			)
+			.into(),
+		]);
+	});
+}
+
+#[test]
+fn simple_buy_buy() {
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let user_3 = CHARLIE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000;
+		let initial_price = Price::from(2);
+
+		let pair_account = AMMModule::get_pair_id(&asset_a, &asset_b);
+
+		initialize_pool(asset_a, asset_b, user_1, pool_amount, initial_price);
+
+		assert_ok!(Exchange::buy(
+			Origin::signed(user_2),
+			asset_a,
+			asset_b,
+			2_000,
+			5000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		assert_ok!(Exchange::buy(
+			Origin::signed(user_3),
+			asset_b,
+			asset_a,
+			1_000,
+			5000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		let user_2_sell_intention_id = generate_intention_id(&user_2, 0);
+		let user_3_sell_intention_id = generate_intention_id(&user_3, 1);
+
+		assert_eq!(Currency::free_balance(asset_a, &pair_account), 100000000);
+		assert_eq!(Currency::free_balance(asset_b, &pair_account), 200000000);
+
+		<Exchange as OnFinalize<u64>>::on_finalize(9);
+
+		assert_eq!(Currency::free_balance(asset_a, &user_2), 1000000000002000);
+		assert_eq!(Currency::free_balance(asset_b, &user_2), 999999999995991);
+
+		assert_eq!(Currency::free_balance(asset_a, &user_3), 999999999999499);
+		assert_eq!(Currency::free_balance(asset_b, &user_3), 1000000000001000);
+
+		assert_eq!(Currency::free_balance(asset_a, &pair_account), 99998501);
+		assert_eq!(Currency::free_balance(asset_b, &pair_account), 200003009);
+
+		expect_events(vec![
+			RawEvent::IntentionRegistered(
+				user_2,
+				asset_a,
+				asset_b,
+				2_000,
+				IntentionType::BUY,
+				user_2_sell_intention_id,
+				None,
+				1,
+			)
+			.into(),
+			RawEvent::IntentionRegistered(
+				user_3,
+				asset_b,
+				asset_a,
+				1_000,
+				IntentionType::BUY,
+				user_3_sell_intention_id,
+				None,
+				1,
+			)
+			.into(),
+			TestEvent::amm(amm::RawEvent::Buy(2, 3000, 2000, 1500, 3007)),
+			RawEvent::IntentionResolvedAMMTrade(
+				user_2,
+				IntentionType::BUY,
+				user_2_sell_intention_id,
+				1500,
+				3007,
+				None,
+				1,
+			)
+			.into(),
+			RawEvent::FundsReserved(user_3, asset_a, 500, user_3_sell_intention_id).into(),
+			RawEvent::FundsReserved(user_2, asset_b, 1000, user_2_sell_intention_id).into(),
+			RawEvent::IntentionResolvedDirectTrade(
+				user_3,
+				user_2,
+				user_3_sell_intention_id,
+				user_2_sell_intention_id,
+				500,
+				1000,
+				None,
+				None,
+				1,
+				500,
+			)
+			.into(),
+			RawEvent::FundsUnreserved(user_3, asset_a, 500, user_3_sell_intention_id).into(),
+			RawEvent::FundsUnreserved(user_2, asset_b, 1000, user_2_sell_intention_id).into(),
+			RawEvent::IntentionResolvedDirectTradeFees(user_3, pair_account, asset_a, 1).into(),
+			RawEvent::IntentionResolvedDirectTradeFees(user_2, pair_account, asset_b, 2).into(),
+		]);
+	});
+}
+
+#[test]
+fn simple_sell_buy() {
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let user_3 = CHARLIE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000;
+		let initial_price = Price::from(2);
+
+		let pair_account = AMMModule::get_pair_id(&asset_a, &asset_b);
+
+		initialize_pool(asset_a, asset_b, user_1, pool_amount, initial_price);
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_2),
+			asset_a,
+			asset_b,
+			2_000,
+			400,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		assert_ok!(Exchange::buy(
+			Origin::signed(user_3),
+			asset_a,
+			asset_b,
+			1_000,
+			2_000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		let user_2_sell_intention_id = generate_intention_id(&user_2, 0);
+		let user_3_sell_intention_id = generate_intention_id(&user_3, 1);
+
+		assert_eq!(Currency::free_balance(asset_a, &pair_account), 100000000);
+		assert_eq!(Currency::free_balance(asset_b, &pair_account), 200000000);
+
+		<Exchange as OnFinalize<u64>>::on_finalize(9);
+
+		assert_eq!(Currency::free_balance(asset_a, &user_2), 999999999998000);
+		assert_eq!(Currency::free_balance(asset_b, &user_2), 1000000000003994);
+
+		assert_eq!(Currency::free_balance(asset_a, &user_3), 1000000000001000);
+		assert_eq!(Currency::free_balance(asset_b, &user_3), 999999999997996);
+
+		assert_eq!(Currency::free_balance(asset_a, &pair_account), 100001000);
+		assert_eq!(Currency::free_balance(asset_b, &pair_account), 199998010);
+
+		expect_events(vec![
+			RawEvent::IntentionRegistered(
+				user_2,
+				asset_a,
+				asset_b,
+				2_000,
+				IntentionType::SELL,
+				user_2_sell_intention_id,
+				None,
+				1,
+			)
+			.into(),
+			RawEvent::IntentionRegistered(
+				user_3,
+				asset_a,
+				asset_b,
+				1_000,
+				IntentionType::BUY,
+				user_3_sell_intention_id,
+				None,
+				1,
+			)
+			.into(),
+			RawEvent::FundsReserved(user_2, asset_a, 1000, user_2_sell_intention_id).into(),
+			RawEvent::FundsReserved(user_3, asset_b, 2000, user_3_sell_intention_id).into(),
+			RawEvent::IntentionResolvedDirectTrade(
+				user_2,
+				user_3,
+				user_2_sell_intention_id,
+				user_3_sell_intention_id,
+				1000,
+				2000,
+				None,
+				None,
+				1,
+				1000,
+			)
+			.into(),
+			RawEvent::FundsUnreserved(user_2, asset_a, 1000, user_2_sell_intention_id).into(),
+			RawEvent::FundsUnreserved(user_3, asset_b, 2000, user_3_sell_intention_id).into(),
+			RawEvent::IntentionResolvedDirectTradeFees(user_2, pair_account, asset_b, 2).into(),
+			RawEvent::IntentionResolvedDirectTradeFees(user_3, pair_account, asset_b, 4).into(),
+			TestEvent::amm(amm::RawEvent::Sell(2, 3000, 2000, 1000, 1996)),
+			RawEvent::IntentionResolvedAMMTrade(
+				user_2,
+				IntentionType::SELL,
+				user_2_sell_intention_id,
+				1000,
+				1996,
+				None,
+				1,
+			)
+			.into(),
+		]);
+	});
+}
+
+#[test]
+fn simple_buy_sell() {
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let user_3 = CHARLIE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000;
+		let initial_price = Price::from(2);
+
+		let pair_account = AMMModule::get_pair_id(&asset_a, &asset_b);
+
+		initialize_pool(asset_a, asset_b, user_1, pool_amount, initial_price);
+
+		assert_ok!(Exchange::buy(
+			Origin::signed(user_2),
+			asset_a,
+			asset_b,
+			2_000,
+			5000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_3),
+			asset_a,
+			asset_b,
+			1_000,
+			1500,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		let user_2_sell_intention_id = generate_intention_id(&user_2, 0);
+		let user_3_sell_intention_id = generate_intention_id(&user_3, 1);
+
+		assert_eq!(Currency::free_balance(asset_a, &pair_account), 100000000);
+		assert_eq!(Currency::free_balance(asset_b, &pair_account), 200000000);
+
+		<Exchange as OnFinalize<u64>>::on_finalize(9);
+
+		assert_eq!(Currency::free_balance(asset_a, &user_2), 1000000000002000);
+		assert_eq!(Currency::free_balance(asset_b, &user_2), 999999999995991);
+
+		assert_eq!(Currency::free_balance(asset_a, &user_3), 999999999999000);
+		assert_eq!(Currency::free_balance(asset_b, &user_3), 1000000000001998);
+
+		assert_eq!(Currency::free_balance(asset_a, &pair_account), 99999000);
+		assert_eq!(Currency::free_balance(asset_b, &pair_account), 200002011);
+
+		expect_events(vec![
+			RawEvent::IntentionRegistered(
+				user_2,
+				asset_a,
+				asset_b,
+				2_000,
+				IntentionType::BUY,
+				user_2_sell_intention_id,
+				None,
+				1,
+			)
+			.into(),
+			RawEvent::IntentionRegistered(
+				user_3,
+				asset_a,
+				asset_b,
+				1_000,
+				IntentionType::SELL,
+				user_3_sell_intention_id,
+				None,
+				1,
+			)
+			.into(),
+			TestEvent::amm(amm::RawEvent::Buy(user_2, 3000, 2000, 1000, 2005)),
+			RawEvent::IntentionResolvedAMMTrade(
+				user_2,
+				IntentionType::BUY,
+				user_2_sell_intention_id,
+				1000,
+				2005,
+				None,
+				1,
+			)
+			.into(),
+			RawEvent::FundsReserved(user_3, asset_a, 1000, user_3_sell_intention_id).into(),
+			RawEvent::FundsReserved(user_2, asset_b, 2000, user_2_sell_intention_id).into(),
+			RawEvent::IntentionResolvedDirectTrade(
+				user_3,
+				user_2,
+				user_3_sell_intention_id,
+				user_2_sell_intention_id,
+				1000,
+				2000,
+				None,
+				None,
+				1,
+				0,
+			)
+			.into(),
+			RawEvent::FundsUnreserved(user_3, asset_a, 1000, user_3_sell_intention_id).into(),
+			RawEvent::FundsUnreserved(user_2, asset_b, 2000, user_2_sell_intention_id).into(),
+			RawEvent::IntentionResolvedDirectTradeFees(user_3, pair_account, asset_b, 2).into(),
+			RawEvent::IntentionResolvedDirectTradeFees(user_2, pair_account, asset_b, 4).into(),
+		]);
+	});
+}
+
+#[test]
+fn single_sell_intention_test() {
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000_000_000;
+		let initial_price = Price::from(2);
+
+		let pair_account = AMMModule::get_pair_id(&asset_a, &asset_b);
+
+		initialize_pool(asset_a, asset_b, user_1, pool_amount, initial_price);
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_2),
+			asset_a,
+			asset_b,
+			2_000_000_000_000,
+			400_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		let user_2_sell_intention_id = generate_intention_id(&user_2, 0);
+
+		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 1);
+
+		// Finalize block
+		<Exchange as OnFinalize<u64>>::on_finalize(9);
+
+		// Check final account balances -> SEEMS LEGIT
+		assert_eq!(Currency::free_balance(asset_a, &user_2), 998_000_000_000_000);
+		assert_eq!(Currency::free_balance(asset_b, &user_2), 1003913878975647);
+
+		// Check final pool balances -> SEEMS LEGIT
+		assert_eq!(Currency::free_balance(asset_a, &pair_account), 102000000000000);
+		assert_eq!(Currency::free_balance(asset_b, &pair_account), 196086121024353);
+
+		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 0);
+
+		expect_events(vec![
+			RawEvent::IntentionRegistered(
+				user_2,
+				asset_a,
+				asset_b,
+				2_000_000_000_000,
+				IntentionType::SELL,
+				user_2_sell_intention_id,
+				None,
+				1,
+			)
+			.into(),
+			TestEvent::amm(amm::RawEvent::Sell(2, 3000, 2000, 2000000000000, 3913878975647)),
+			RawEvent::IntentionResolvedAMMTrade(
+				user_2,
+				IntentionType::SELL,
+				user_2_sell_intention_id,
+				2000000000000,
+				3913878975647,
+				None,
+				1,
+			)
+			.into(),
+		]);
+	});
+}
+
+#[test]
+fn single_buy_intention_test() {
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000_000_000;
+		let initial_price = Price::from(2);
+
+		let pair_account = AMMModule::get_pair_id(&asset_a, &asset_b);
+
+		initialize_pool(asset_a, asset_b, user_1, pool_amount, initial_price);
+
+		assert_ok!(Exchange::buy(
+			Origin::signed(user_2),
+			asset_a,
+			asset_b,
+			2_000_000_000_000,
+			15000_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		let user_2_sell_intention_id = generate_intention_id(&user_2, 0);
+
+		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 1);
+
+		// Finalize block
+		<Exchange as OnFinalize<u64>>::on_finalize(9);
+
+		// Check final account balances -> SEEMS LEGIT
+		assert_eq!(Currency::free_balance(asset_a, &user_2), 1002000000000000);
+		assert_eq!(Currency::free_balance(asset_b, &user_2), 995910037144373);
+
+		// Check final pool balances -> SEEMS LEGIT
+		assert_eq!(Currency::free_balance(asset_a, &pair_account), 98000000000000);
+		assert_eq!(Currency::free_balance(asset_b, &pair_account), 204089962855627);
+
+		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 0);
+
+		expect_events(vec![
+			RawEvent::IntentionRegistered(
+				user_2,
+				asset_a,
+				asset_b,
+				2_000_000_000_000,
+				IntentionType::BUY,
+				user_2_sell_intention_id,
+				None,
+				1,
+			)
+			.into(),
+			TestEvent::amm(amm::RawEvent::Buy(2, 3000, 2000, 2000000000000, 4089962855627)),
+			RawEvent::IntentionResolvedAMMTrade(
+				user_2,
+				IntentionType::BUY,
+				user_2_sell_intention_id,
+				2000000000000,
+				4089962855627,
+				None,
+				1,
+			)
+			.into(),
+		]);
+	});
+}
+
+#[test]
+fn buy_intention_should_receive_the_asset_it_asked_to_buy_not_the_asset_it_sold() {
+	// Regression test for the AMM buy path: `T::AMMPool::validate_buy`/`execute_buy` must be
+	// called with `(asset_buy, asset_sell)` in that order, otherwise a BUY intention would end up
+	// buying the asset it meant to sell.
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let asset_bought = ETH;
+		let asset_sold = DOT;
+		let pool_amount = 100_000_000_000_000;
+		let initial_price = Price::from(2);
+
+		initialize_pool(asset_bought, asset_sold, user_1, pool_amount, initial_price);
+
+		let bought_before = Currency::free_balance(asset_bought, &user_2);
+		let sold_before = Currency::free_balance(asset_sold, &user_2);
+
+		assert_ok!(Exchange::buy(
+			Origin::signed(user_2),
+			asset_bought,
+			asset_sold,
+			2_000_000_000_000,
+			15_000_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		<Exchange as OnFinalize<u64>>::on_finalize(9);
+
+		assert_eq!(
+			Currency::free_balance(asset_bought, &user_2),
+			bought_before + 2_000_000_000_000
+		);
+		assert!(Currency::free_balance(asset_sold, &user_2) < sold_before);
+	});
+}
+
+#[test]
+fn simple_sell_sell_with_error_should_not_pass() {
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let user_3 = CHARLIE;
+		let asset_a = ETH;
+		let asset_b = DOT;
 		let pool_amount = 100_000_000;
 		let initial_price = Price::from(2);
 
-		let pair_account = AMMModule::get_pair_id(&asset_a, &asset_b);
+		let pair_account = AMMModule::get_pair_id(&asset_a, &asset_b);
+
+		initialize_pool(asset_a, asset_b, user_1, pool_amount, initial_price);
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_2),
+			asset_a,
+			asset_b,
+			2_000,
+			5_000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		let user_2_sell_intention_id = generate_intention_id(&user_2, 0);
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_3),
+			asset_b,
+			asset_a,
+			1_000,
+			5_000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		let user_3_sell_intention_id = generate_intention_id(&user_3, 1);
+
+		assert_eq!(Currency::free_balance(asset_a, &pair_account), 100000000);
+		assert_eq!(Currency::free_balance(asset_b, &pair_account), 200000000);
+
+		<Exchange as OnFinalize<u64>>::on_finalize(9);
+
+		assert_eq!(Currency::free_balance(asset_a, &user_2), 1000000000000000);
+		assert_eq!(Currency::free_balance(asset_b, &user_2), 1000000000000000);
+
+		assert_eq!(Currency::free_balance(asset_a, &user_3), 1000000000000000);
+		assert_eq!(Currency::free_balance(asset_b, &user_3), 1000000000000000);
+
+		assert_eq!(Currency::free_balance(asset_a, &pair_account), 100000000);
+		assert_eq!(Currency::free_balance(asset_b, &pair_account), 200000000);
+
+		expect_events(vec![
+			RawEvent::IntentionRegistered(
+				user_2,
+				asset_a,
+				asset_b,
+				2_000,
+				IntentionType::SELL,
+				user_2_sell_intention_id,
+				None,
+				1,
+			)
+			.into(),
+			RawEvent::IntentionRegistered(
+				user_3,
+				asset_b,
+				asset_a,
+				1_000,
+				IntentionType::SELL,
+				user_3_sell_intention_id,
+				None,
+				1,
+			)
+			.into(),
+			RawEvent::AMMSellErrorEvent(
+				user_2,
+				asset_a,
+				asset_b,
+				IntentionType::SELL,
+				user_2_sell_intention_id,
+				DispatchError::Module {
+					index: 0,
+					error: 5,
+					message: None,
+				},
+				AMMFailureReason::TradeLimitExceeded,
+			)
+			.into(),
+			RawEvent::IntentionResolveErrorEvent(
+				user_3,
+				asset_b,
+				asset_a,
+				IntentionType::SELL,
+				user_3_sell_intention_id,
+				DispatchError::Module {
+					index: 0,
+					error: 5,
+					message: None,
+				},
+			)
+			.into(),
+		]);
+	});
+}
+
+#[test]
+fn amm_trade_within_oracle_deviation_should_succeed() {
+	new_test_ext().execute_with(|| {
+		let user = ALICE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000_000_000;
+		let initial_price = Price::from(2);
+
+		initialize_pool(asset_a, asset_b, user, pool_amount, initial_price);
+
+		let amount_sell = 1_000_000_000_000;
+		let expected_buy = AMMModule::validate_sell(&user, asset_a, asset_b, amount_sell, 0, false)
+			.unwrap()
+			.amount_out;
+
+		// Oracle agrees closely with the AMM - well within the 10% tolerance.
+		PriceOracleMock::set_price(Some(expected_buy));
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(user),
+			asset_a,
+			asset_b,
+			amount_sell,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		<Exchange as OnFinalize<u64>>::on_finalize(1);
+
+		let sell_intention_id = generate_intention_id(&user, 0);
+
+		expect_event(RawEvent::IntentionResolvedAMMTrade(
+			user,
+			IntentionType::SELL,
+			sell_intention_id,
+			amount_sell,
+			expected_buy,
+			None,
+			1,
+		));
+	});
+}
+
+#[test]
+fn amm_trade_beyond_oracle_deviation_should_be_rejected() {
+	new_test_ext().execute_with(|| {
+		let user = ALICE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000_000_000;
+		let initial_price = Price::from(2);
+
+		initialize_pool(asset_a, asset_b, user, pool_amount, initial_price);
+
+		let amount_sell = 1_000_000_000_000;
+		let expected_buy = AMMModule::get_spot_price_unchecked(asset_a, asset_b, amount_sell);
+
+		// Oracle wildly disagrees with the AMM price - well beyond the 10% tolerance.
+		PriceOracleMock::set_price(Some(expected_buy * 2));
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(user),
+			asset_a,
+			asset_b,
+			amount_sell,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		<Exchange as OnFinalize<u64>>::on_finalize(1);
+
+		let sell_intention_id = generate_intention_id(&user, 0);
+
+		expect_event(RawEvent::IntentionResolveErrorEvent(
+			user,
+			asset_a,
+			asset_b,
+			IntentionType::SELL,
+			sell_intention_id,
+			Error::<Test>::PriceDeviationTooLarge.into(),
+		));
+	});
+}
+
+#[test]
+fn amm_trade_within_price_impact_bound_should_succeed() {
+	new_test_ext().execute_with(|| {
+		let user = ALICE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000_000_000;
+		let initial_price = Price::from(2);
+
+		initialize_pool(asset_a, asset_b, user, pool_amount, initial_price);
+
+		// Selling 9% of the pool's reserve moves the price by roughly 8%, just inside the 10%
+		// impact bound.
+		let amount_sell = 9_000_000_000_000;
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(user),
+			asset_a,
+			asset_b,
+			amount_sell,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		<Exchange as OnFinalize<u64>>::on_finalize(1);
+
+		let sell_intention_id = generate_intention_id(&user, 0);
+
+		match last_event() {
+			TestEvent::exchange(RawEvent::IntentionResolvedAMMTrade(who, intention_type, id, sold, _bought, _, _)) => {
+				assert_eq!(who, user);
+				assert_eq!(intention_type, IntentionType::SELL);
+				assert_eq!(id, sell_intention_id);
+				assert_eq!(sold, amount_sell);
+			}
+			other => panic!("expected a successful AMM trade, got {:?}", other),
+		}
+	});
+}
+
+#[test]
+fn amm_trade_beyond_price_impact_bound_should_be_rejected() {
+	new_test_ext().execute_with(|| {
+		let user = ALICE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000_000_000;
+		let initial_price = Price::from(2);
+
+		initialize_pool(asset_a, asset_b, user, pool_amount, initial_price);
+
+		// Selling 15% of the pool's reserve moves the price by roughly 13%, well past the 10%
+		// impact bound.
+		let amount_sell = 15_000_000_000_000;
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(user),
+			asset_a,
+			asset_b,
+			amount_sell,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		<Exchange as OnFinalize<u64>>::on_finalize(1);
+
+		let sell_intention_id = generate_intention_id(&user, 0);
+
+		expect_event(RawEvent::IntentionResolveErrorEvent(
+			user,
+			asset_a,
+			asset_b,
+			IntentionType::SELL,
+			sell_intention_id,
+			Error::<Test>::PriceImpactTooHigh.into(),
+		));
+	});
+}
+
+#[test]
+fn amm_fallback_trade_should_deliver_proceeds_to_recipient() {
+	new_test_ext().execute_with(|| {
+		let user = ALICE;
+		let recipient = GEORGE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000_000_000;
+		let initial_price = Price::from(2);
+
+		initialize_pool(asset_a, asset_b, user, pool_amount, initial_price);
+
+		let user_asset_a_before = Currency::free_balance(asset_a, &user);
+		let user_asset_b_before = Currency::free_balance(asset_b, &user);
+		let recipient_asset_b_before = Currency::free_balance(asset_b, &recipient);
+
+		let amount_sell = 1_000_000_000_000;
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(user),
+			asset_a,
+			asset_b,
+			amount_sell,
+			0,
+			false,
+			Some(recipient),
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		<Exchange as OnFinalize<u64>>::on_finalize(1);
+
+		// The sold asset always leaves `who` ...
+		assert_eq!(
+			Currency::free_balance(asset_a, &user),
+			user_asset_a_before - amount_sell
+		);
+		// ... but the bought asset is delivered to `recipient`, not `who`.
+		assert_eq!(Currency::free_balance(asset_b, &user), user_asset_b_before);
+		assert!(Currency::free_balance(asset_b, &recipient) > recipient_asset_b_before);
+	});
+}
+
+#[test]
+fn a_sell_and_a_buy_of_the_same_pair_should_be_matched_directly_against_each_other() {
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let user_3 = CHARLIE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000_000_000;
+		let initial_price = Price::from(2);
+
+		initialize_pool(asset_a, asset_b, user_1, pool_amount, initial_price);
+
+		// user_2 sells asset_a for asset_b ...
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_2),
+			asset_a,
+			asset_b,
+			2_000_000_000_000,
+			20000000000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		// ... user_3 buys asset_a paying asset_b - the opposite direction of the same pair, so
+		// `on_finalize` must collect both of them into the same pair's matcher regardless of `sell`
+		// and `buy` appending under swapped `(asset_sell, asset_buy)` keys.
+		assert_ok!(Exchange::buy(
+			Origin::signed(user_3),
+			asset_a,
+			asset_b,
+			1_000_000_000_000,
+			4_000_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		<Exchange as OnFinalize<u64>>::on_finalize(9);
+
+		let direct_trades = system::Module::<Test>::events()
+			.into_iter()
+			.filter(|record| {
+				matches!(
+					record.event,
+					TestEvent::exchange(RawEvent::IntentionResolvedDirectTrade(..))
+				)
+			})
+			.count();
+
+		assert_eq!(
+			direct_trades, 1,
+			"the sell and the buy should have matched directly against each other"
+		);
+	});
+}
+
+#[test]
+fn on_finalize_should_conserve_total_value_across_direct_and_amm_settlement() {
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let user_3 = CHARLIE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000_000_000;
+		let initial_price = Price::from(2);
+
+		let pair_account = AMMModule::get_pair_id(&asset_a, &asset_b);
+
+		initialize_pool(asset_a, asset_b, user_1, pool_amount, initial_price);
+
+		// user_2's sell only partially matches user_3's buy directly - the remainder falls back to
+		// the AMM, so this exercises both settlement paths at once.
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_2),
+			asset_a,
+			asset_b,
+			2_000_000_000_000,
+			20000000000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		assert_ok!(Exchange::buy(
+			Origin::signed(user_3),
+			asset_a,
+			asset_b,
+			1_000_000_000_000,
+			4_000_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		let value_before = snapshot_balances(&[user_1, user_2, user_3, pair_account], &[asset_a, asset_b]);
+
+		<Exchange as OnFinalize<u64>>::on_finalize(9);
+
+		// Sanity check that settlement actually happened, rather than the conservation check below
+		// vacuously passing because nothing moved.
+		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 0);
+
+		assert_value_conserved(&value_before, &[user_1, user_2, user_3, pair_account]);
+	});
+}
+
+#[test]
+fn direct_trade_should_deliver_proceeds_to_matched_recipients() {
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let user_3 = CHARLIE;
+		let recipient_2 = DAVE;
+		let recipient_3 = FERDIE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000_000_000;
+		let initial_price = Price::from(2);
+
+		let pair_account = AMMModule::get_pair_id(&asset_a, &asset_b);
+
+		initialize_pool(asset_a, asset_b, user_1, pool_amount, initial_price);
+
+		// user_2 sells ETH for DOT, user_3 sells DOT for ETH - the amounts match exactly (see
+		// `sell_test_exact_match`), so the whole trade settles directly with no AMM leftover.
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_2),
+			asset_a,
+			asset_b,
+			1_000_000_000_000,
+			1_500_000_000_000,
+			false,
+			Some(recipient_2),
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_3),
+			asset_b,
+			asset_a,
+			2_000_000_000_000,
+			200_000_000_000,
+			false,
+			Some(recipient_3),
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		let value_before = snapshot_balances(
+			&[user_1, user_2, user_3, recipient_2, recipient_3, pair_account],
+			&[asset_a, asset_b],
+		);
+
+		<Exchange as OnFinalize<u64>>::on_finalize(9);
+
+		// The sold assets leave the payers, same as without a recipient ...
+		assert_eq!(Currency::free_balance(asset_a, &user_2), 999_000_000_000_000);
+		assert_eq!(Currency::free_balance(asset_b, &user_3), 998_000_000_000_000);
+		// ... the payers' bought-asset balances are untouched ...
+		assert_eq!(Currency::free_balance(asset_b, &user_2), ENDOWED_AMOUNT);
+		assert_eq!(Currency::free_balance(asset_a, &user_3), ENDOWED_AMOUNT);
+		// ... but the trade's proceeds land on the named recipients instead.
+		assert_eq!(
+			Currency::free_balance(asset_b, &recipient_2),
+			ENDOWED_AMOUNT + 1_996_000_000_000
+		);
+		assert_eq!(
+			Currency::free_balance(asset_a, &recipient_3),
+			ENDOWED_AMOUNT + 998_000_000_000
+		);
+
+		assert_value_conserved(
+			&value_before,
+			&[user_1, user_2, user_3, recipient_2, recipient_3, pair_account],
+		);
+	});
+}
+
+#[test]
+fn direct_trade_execute_should_roll_back_an_already_applied_transfer_when_a_later_one_fails() {
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let amount_from_a = 1_000_000_000_000;
+		let amount_from_b = 2_000_000_000_000;
+
+		let intention_a = crate::Intention::<Test> {
+			who: user_1,
+			asset_sell: asset_a,
+			asset_buy: asset_b,
+			amount_sell: amount_from_a,
+			amount_buy: amount_from_b,
+			trade_limit: 0,
+			discount: false,
+			sell_or_buy: IntentionType::SELL,
+			intention_id: generate_intention_id(&user_1, 0),
+			remaining_lifetime: 10,
+			recipient: None,
+			valid_until_timestamp: None,
+			reference: None,
+			allow_amm_fallback: true,
+			priority: 0,
+		};
+		let intention_b = crate::Intention::<Test> {
+			who: user_2,
+			asset_sell: asset_b,
+			asset_buy: asset_a,
+			amount_sell: amount_from_b,
+			amount_buy: amount_from_a,
+			trade_limit: 0,
+			discount: false,
+			sell_or_buy: IntentionType::SELL,
+			intention_id: generate_intention_id(&user_2, 1),
+			remaining_lifetime: 10,
+			recipient: None,
+			valid_until_timestamp: None,
+			reference: None,
+			allow_amm_fallback: true,
+			priority: 0,
+		};
+
+		let mut dt = crate::direct::DirectTradeData::<Test> {
+			intention_a: &intention_a,
+			intention_b: &intention_b,
+			amount_from_a,
+			amount_from_b,
+			transfers: Vec::new(),
+			remaining_amount: Zero::zero(),
+		};
+
+		assert!(dt.prepare(&user_1));
+
+		// The trade's first transfer moves `asset_a` from user_1 to user_2 - record user_2's
+		// balance right before `execute` so a later assertion can prove it never changed.
+		let user_2_asset_a_before_execute = Currency::free_balance(asset_a, &user_2);
+
+		// Push user_1's `asset_b` balance (the second transfer's recipient) to the maximum, so
+		// repatriating the second transfer's reserved `asset_b` into it overflows and fails -
+		// with nothing else in this test able to induce a `repatriate_reserved` failure through
+		// the public API alone.
+		assert_ok!(Currency::deposit(
+			asset_b,
+			&user_1,
+			Balance::MAX - Currency::free_balance(asset_b, &user_1),
+		));
+
+		assert!(!dt.execute());
+
+		// The first transfer must have been rolled back along with the failed second one -
+		// user_2 never actually received the `asset_a` that was reserved for it.
+		assert_eq!(Currency::free_balance(asset_a, &user_2), user_2_asset_a_before_execute);
+
+		// The failed transfer is reported so operators can see exactly which one it was and why.
+		match last_event() {
+			TestEvent::exchange(RawEvent::DirectTransferFailed(from, to, asset, amount, _)) => {
+				assert_eq!(from, user_2);
+				assert_eq!(to, user_1);
+				assert_eq!(asset, asset_b);
+				assert_eq!(amount, amount_from_b);
+			}
+			_ => panic!("expected a DirectTransferFailed event"),
+		}
+	});
+}
+
+fn direct_trade_data_for_fee_exempt_tests(
+	user_1: AccountId,
+	user_2: AccountId,
+	asset_a: AssetId,
+	asset_b: AssetId,
+	amount_from_a: Balance,
+	amount_from_b: Balance,
+) -> (crate::Intention<Test>, crate::Intention<Test>) {
+	let intention_a = crate::Intention::<Test> {
+		who: user_1,
+		asset_sell: asset_a,
+		asset_buy: asset_b,
+		amount_sell: amount_from_a,
+		amount_buy: amount_from_b,
+		trade_limit: 0,
+		discount: false,
+		sell_or_buy: IntentionType::SELL,
+		intention_id: generate_intention_id(&user_1, 0),
+		remaining_lifetime: 10,
+		recipient: None,
+		valid_until_timestamp: None,
+		reference: None,
+		allow_amm_fallback: true,
+		priority: 0,
+	};
+	let intention_b = crate::Intention::<Test> {
+		who: user_2,
+		asset_sell: asset_b,
+		asset_buy: asset_a,
+		amount_sell: amount_from_b,
+		amount_buy: amount_from_a,
+		trade_limit: 0,
+		discount: false,
+		sell_or_buy: IntentionType::SELL,
+		intention_id: generate_intention_id(&user_2, 1),
+		remaining_lifetime: 10,
+		recipient: None,
+		valid_until_timestamp: None,
+		reference: None,
+		allow_amm_fallback: true,
+		priority: 0,
+	};
+
+	(intention_a, intention_b)
+}
+
+#[test]
+fn direct_trade_prepare_should_charge_both_sides_fees_when_neither_is_fee_exempt() {
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let pool_account = CHARLIE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let amount_from_a = 1_000_000_000_000;
+		let amount_from_b = 2_000_000_000_000;
+
+		let (intention_a, intention_b) =
+			direct_trade_data_for_fee_exempt_tests(user_1, user_2, asset_a, asset_b, amount_from_a, amount_from_b);
+
+		let mut dt = crate::direct::DirectTradeData::<Test> {
+			intention_a: &intention_a,
+			intention_b: &intention_b,
+			amount_from_a,
+			amount_from_b,
+			transfers: Vec::new(),
+			remaining_amount: Zero::zero(),
+		};
+
+		assert!(dt.prepare(&pool_account));
+
+		let fee_transfers: Vec<_> = dt.transfers.iter().filter(|t| t.fee_transfer).collect();
+		assert_eq!(fee_transfers.len(), 2);
+		assert!(fee_transfers.iter().any(|t| t.from == &user_1));
+		assert!(fee_transfers.iter().any(|t| t.from == &user_2));
+	});
+}
+
+#[test]
+fn direct_trade_prepare_should_skip_the_fee_for_a_fee_exempt_side_only() {
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let pool_account = CHARLIE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let amount_from_a = 1_000_000_000_000;
+		let amount_from_b = 2_000_000_000_000;
+
+		FeeExempt::<Test>::insert(&user_1, true);
+
+		let (intention_a, intention_b) =
+			direct_trade_data_for_fee_exempt_tests(user_1, user_2, asset_a, asset_b, amount_from_a, amount_from_b);
+
+		let mut dt = crate::direct::DirectTradeData::<Test> {
+			intention_a: &intention_a,
+			intention_b: &intention_b,
+			amount_from_a,
+			amount_from_b,
+			transfers: Vec::new(),
+			remaining_amount: Zero::zero(),
+		};
+
+		assert!(dt.prepare(&pool_account));
+
+		let fee_transfers: Vec<_> = dt.transfers.iter().filter(|t| t.fee_transfer).collect();
+		assert_eq!(fee_transfers.len(), 1);
+		assert_eq!(fee_transfers[0].from, &user_2);
+	});
+}
+
+#[test]
+fn direct_trade_prepare_should_charge_no_fees_when_both_sides_are_fee_exempt() {
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let pool_account = CHARLIE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let amount_from_a = 1_000_000_000_000;
+		let amount_from_b = 2_000_000_000_000;
+
+		FeeExempt::<Test>::insert(&user_1, true);
+		FeeExempt::<Test>::insert(&user_2, true);
+
+		let (intention_a, intention_b) =
+			direct_trade_data_for_fee_exempt_tests(user_1, user_2, asset_a, asset_b, amount_from_a, amount_from_b);
+
+		let mut dt = crate::direct::DirectTradeData::<Test> {
+			intention_a: &intention_a,
+			intention_b: &intention_b,
+			amount_from_a,
+			amount_from_b,
+			transfers: Vec::new(),
+			remaining_amount: Zero::zero(),
+		};
+
+		assert!(dt.prepare(&pool_account));
+
+		assert_eq!(dt.transfers.iter().filter(|t| t.fee_transfer).count(), 0);
+	});
+}
+
+#[test]
+fn direct_trade_prepare_should_charge_the_discounted_fee_only_to_the_discounted_side() {
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let pool_account = CHARLIE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let amount_from_a = 1_000_000_000_000;
+		let amount_from_b = 2_000_000_000_000;
+
+		let (mut intention_a, intention_b) =
+			direct_trade_data_for_fee_exempt_tests(user_1, user_2, asset_a, asset_b, amount_from_a, amount_from_b);
+		intention_a.discount = true;
+
+		let mut dt = crate::direct::DirectTradeData::<Test> {
+			intention_a: &intention_a,
+			intention_b: &intention_b,
+			amount_from_a,
+			amount_from_b,
+			transfers: Vec::new(),
+			remaining_amount: Zero::zero(),
+		};
+
+		assert!(dt.prepare(&pool_account));
+
+		let fee_transfers: Vec<_> = dt.transfers.iter().filter(|t| t.fee_transfer).collect();
+		assert_eq!(fee_transfers.len(), 2);
+
+		// user_1 (discounted) pays the reduced rate on the amount it receives (amount_from_b).
+		let discounted_fee = amount_from_b.just_fee_round_up(Fee::discounted()).unwrap();
+		assert_eq!(
+			fee_transfers.iter().find(|t| t.from == &user_1).unwrap().amount,
+			discounted_fee
+		);
+
+		// user_2 (not discounted) still pays the standard rate on what it receives (amount_from_a).
+		let standard_fee = amount_from_a.just_fee_round_up(Fee::default()).unwrap();
+		assert_eq!(
+			fee_transfers.iter().find(|t| t.from == &user_2).unwrap().amount,
+			standard_fee
+		);
+
+		// The discounted rate really is lower - otherwise this test would pass for the wrong reason.
+		assert!(discounted_fee < amount_from_b.just_fee_round_up(Fee::default()).unwrap());
+	});
+}
+
+#[test]
+fn direct_trade_prepare_should_charge_the_discounted_fee_to_both_sides_when_both_are_discounted() {
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let pool_account = CHARLIE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let amount_from_a = 1_000_000_000_000;
+		let amount_from_b = 2_000_000_000_000;
+
+		let (mut intention_a, mut intention_b) =
+			direct_trade_data_for_fee_exempt_tests(user_1, user_2, asset_a, asset_b, amount_from_a, amount_from_b);
+		intention_a.discount = true;
+		intention_b.discount = true;
+
+		let mut dt = crate::direct::DirectTradeData::<Test> {
+			intention_a: &intention_a,
+			intention_b: &intention_b,
+			amount_from_a,
+			amount_from_b,
+			transfers: Vec::new(),
+			remaining_amount: Zero::zero(),
+		};
+
+		assert!(dt.prepare(&pool_account));
+
+		let fee_transfers: Vec<_> = dt.transfers.iter().filter(|t| t.fee_transfer).collect();
+		assert_eq!(fee_transfers.len(), 2);
+
+		// Both sides are discounted, so both pay the reduced rate on what they each receive.
+		let discounted_fee_on_b = amount_from_b.just_fee_round_up(Fee::discounted()).unwrap();
+		assert_eq!(
+			fee_transfers.iter().find(|t| t.from == &user_1).unwrap().amount,
+			discounted_fee_on_b
+		);
+		let discounted_fee_on_a = amount_from_a.just_fee_round_up(Fee::discounted()).unwrap();
+		assert_eq!(
+			fee_transfers.iter().find(|t| t.from == &user_2).unwrap().amount,
+			discounted_fee_on_a
+		);
+	});
+}
+
+#[test]
+fn direct_trade_prepare_should_charge_the_standard_fee_to_both_sides_when_neither_is_discounted() {
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let pool_account = CHARLIE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let amount_from_a = 1_000_000_000_000;
+		let amount_from_b = 2_000_000_000_000;
+
+		let (intention_a, intention_b) =
+			direct_trade_data_for_fee_exempt_tests(user_1, user_2, asset_a, asset_b, amount_from_a, amount_from_b);
+
+		let mut dt = crate::direct::DirectTradeData::<Test> {
+			intention_a: &intention_a,
+			intention_b: &intention_b,
+			amount_from_a,
+			amount_from_b,
+			transfers: Vec::new(),
+			remaining_amount: Zero::zero(),
+		};
+
+		assert!(dt.prepare(&pool_account));
+
+		let fee_transfers: Vec<_> = dt.transfers.iter().filter(|t| t.fee_transfer).collect();
+		assert_eq!(fee_transfers.len(), 2);
+
+		// Neither side is discounted, so both pay the standard rate on what they each receive.
+		let standard_fee_on_b = amount_from_b.just_fee_round_up(Fee::default()).unwrap();
+		assert_eq!(
+			fee_transfers.iter().find(|t| t.from == &user_1).unwrap().amount,
+			standard_fee_on_b
+		);
+		let standard_fee_on_a = amount_from_a.just_fee_round_up(Fee::default()).unwrap();
+		assert_eq!(
+			fee_transfers.iter().find(|t| t.from == &user_2).unwrap().amount,
+			standard_fee_on_a
+		);
+	});
+}
+
+#[test]
+fn direct_trade_prepare_should_charge_the_entire_dust_amount_as_fee_and_emit_dust_to_fee() {
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let pool_account = CHARLIE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let amount_from_a = 1_000_000_000_000;
+		// A single unit rounds `just_fee_round_up` up to 1 too - the fee would equal the entire
+		// amount it's based on, which is exactly the dust case `direct_trade_fee` saturates.
+		let amount_from_b = 1;
+
+		let (intention_a, intention_b) =
+			direct_trade_data_for_fee_exempt_tests(user_1, user_2, asset_a, asset_b, amount_from_a, amount_from_b);
+
+		let mut dt = crate::direct::DirectTradeData::<Test> {
+			intention_a: &intention_a,
+			intention_b: &intention_b,
+			amount_from_a,
+			amount_from_b,
+			transfers: Vec::new(),
+			remaining_amount: Zero::zero(),
+		};
+
+		assert!(dt.prepare(&pool_account));
+
+		let fee_transfers: Vec<_> = dt.transfers.iter().filter(|t| t.fee_transfer).collect();
+		assert_eq!(fee_transfers.len(), 2);
+
+		// user_1's fee is based on the dust `amount_from_b` - the whole amount was charged as fee.
+		assert_eq!(
+			fee_transfers.iter().find(|t| t.from == &user_1).unwrap().amount,
+			amount_from_b
+		);
+
+		// user_2's fee is based on the ordinary `amount_from_a` and isn't dust.
+		let standard_fee = amount_from_a.just_fee_round_up(Fee::default()).unwrap();
+		assert_eq!(
+			fee_transfers.iter().find(|t| t.from == &user_2).unwrap().amount,
+			standard_fee
+		);
+		assert!(standard_fee < amount_from_a);
+
+		match last_event() {
+			TestEvent::exchange(RawEvent::DustToFee(who, asset, amount, intention_id)) => {
+				assert_eq!(who, user_1);
+				assert_eq!(asset, asset_b);
+				assert_eq!(amount, amount_from_b);
+				assert_eq!(intention_id, intention_a.intention_id);
+			}
+			_ => panic!("expected a DustToFee event"),
+		}
+	});
+}
+
+#[test]
+fn direct_trade_prepare_should_fail_the_whole_trade_on_shortfall_by_default() {
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let pool_account = CHARLIE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		// More than user_1 actually holds of asset_a.
+		let amount_from_a = ENDOWED_AMOUNT + 1;
+		let amount_from_b = 2_000_000_000_000;
+
+		let (intention_a, intention_b) =
+			direct_trade_data_for_fee_exempt_tests(user_1, user_2, asset_a, asset_b, amount_from_a, amount_from_b);
+
+		let mut dt = crate::direct::DirectTradeData::<Test> {
+			intention_a: &intention_a,
+			intention_b: &intention_b,
+			amount_from_a,
+			amount_from_b,
+			transfers: Vec::new(),
+			remaining_amount: Zero::zero(),
+		};
+
+		assert!(!dt.prepare(&pool_account));
+
+		match last_event() {
+			TestEvent::exchange(RawEvent::InsufficientAssetBalanceEvent(who, asset, ..)) => {
+				assert_eq!(who, user_1);
+				assert_eq!(asset, asset_a);
+			}
+			_ => panic!("expected an InsufficientAssetBalanceEvent"),
+		}
+	});
+}
+
+#[test]
+fn direct_trade_prepare_should_shrink_to_the_available_balance_when_allow_partial_on_shortfall_is_set() {
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let pool_account = CHARLIE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let amount_from_a = ENDOWED_AMOUNT + 1;
+		let amount_from_b = 2_000_000_000_000;
+
+		AllowPartialOnShortfallMock::set(true);
+
+		let (intention_a, intention_b) =
+			direct_trade_data_for_fee_exempt_tests(user_1, user_2, asset_a, asset_b, amount_from_a, amount_from_b);
+
+		let mut dt = crate::direct::DirectTradeData::<Test> {
+			intention_a: &intention_a,
+			intention_b: &intention_b,
+			amount_from_a,
+			amount_from_b,
+			transfers: Vec::new(),
+			remaining_amount: Zero::zero(),
+		};
+
+		assert!(dt.prepare(&pool_account));
+
+		// Shrunk down to exactly what user_1 holds, instead of failing outright.
+		assert_eq!(dt.amount_from_a, ENDOWED_AMOUNT);
+
+		let partial_fill_record = system::Module::<Test>::events()
+			.into_iter()
+			.find(|record| {
+				matches!(
+					record.event,
+					TestEvent::exchange(RawEvent::IntentionResolvedDirectTradePartialFill(..))
+				)
+			})
+			.expect("IntentionResolvedDirectTradePartialFill event expected");
+
+		match partial_fill_record.event {
+			TestEvent::exchange(RawEvent::IntentionResolvedDirectTradePartialFill(
+				who,
+				asset,
+				_,
+				_,
+				requested,
+				available,
+			)) => {
+				assert_eq!(who, user_1);
+				assert_eq!(asset, asset_a);
+				assert_eq!(requested, amount_from_a);
+				assert_eq!(available, ENDOWED_AMOUNT);
+			}
+			_ => unreachable!(),
+		}
+
+		AllowPartialOnShortfallMock::set(false);
+	});
+}
+
+#[test]
+fn intention_should_be_retried_next_block_when_amm_trade_not_immediately_feasible() {
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000_000_000;
+		let initial_price = Price::from(2);
+
+		initialize_pool(asset_a, asset_b, user_1, pool_amount, initial_price);
+
+		// Selling more than a third of the pool's asset_a reserve breaches the max-in-ratio limit,
+		// so the AMM trade isn't feasible yet.
+		let amount_sell = 40_000_000_000_000;
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_2),
+			asset_a,
+			asset_b,
+			amount_sell,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		let sell_intention_id = generate_intention_id(&user_2, 0);
+
+		// Block 1: not feasible - carried forward instead of being dropped with an error.
+		<Exchange as OnFinalize<u64>>::on_finalize(1);
+
+		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 1);
+		assert_eq!(
+			Exchange::get_intentions((asset_a, asset_b))[0].remaining_lifetime,
+			DefaultIntentionLifetime::get() - 1
+		);
+
+		// Grow the pool so the same trade now stays within the max-in-ratio limit and within
+		// `MaxPriceImpact` (10% of the pool's asset_a reserve moves the price by roughly 9%).
+		assert_ok!(AMMModule::add_liquidity(
+			Origin::signed(user_1),
+			asset_a,
+			asset_b,
+			3 * pool_amount,
+			u128::MAX,
+		));
+
+		let expected_buy = AMMModule::validate_sell(&user_1, asset_a, asset_b, amount_sell, 0, false)
+			.unwrap()
+			.amount_out;
+
+		// Block 2: now feasible - resolved via AMM as normal.
+		<Exchange as OnFinalize<u64>>::on_finalize(2);
+
+		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 0);
+		expect_event(RawEvent::IntentionResolvedAMMTrade(
+			user_2,
+			IntentionType::SELL,
+			sell_intention_id,
+			amount_sell,
+			expected_buy,
+			None,
+			1,
+		));
+	});
+}
+
+#[test]
+fn intention_carried_forward_event_should_only_fire_for_an_intention_that_survives_on_finalize() {
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000_000_000;
+		let initial_price = Price::from(2);
+
+		initialize_pool(asset_a, asset_b, user_1, pool_amount, initial_price);
+
+		// Same infeasible-until-grown setup as
+		// `intention_should_be_retried_next_block_when_amm_trade_not_immediately_feasible`.
+		let amount_sell = 40_000_000_000_000;
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_2),
+			asset_a,
+			asset_b,
+			amount_sell,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		let sell_intention_id = generate_intention_id(&user_2, 0);
+
+		// Block 1: not feasible - the intention survives into the next block, so the event fires.
+		<Exchange as OnFinalize<u64>>::on_finalize(1);
+
+		expect_event(RawEvent::IntentionCarriedForward(
+			sell_intention_id,
+			DefaultIntentionLifetime::get() - 1,
+		));
+
+		assert_ok!(AMMModule::add_liquidity(
+			Origin::signed(user_1),
+			asset_a,
+			asset_b,
+			pool_amount,
+			u128::MAX,
+		));
+
+		// Block 2: resolved via AMM in the same block it's settled in - no carry-forward event.
+		<Exchange as OnFinalize<u64>>::on_finalize(2);
+
+		assert!(system::Module::<Test>::events()
+			.into_iter()
+			.all(|record| !matches!(record.event, TestEvent::exchange(RawEvent::IntentionCarriedForward(..)))));
+	});
+}
+
+#[test]
+fn intention_should_be_dropped_once_lifetime_is_exhausted() {
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000_000_000;
+		let initial_price = Price::from(2);
+
+		initialize_pool(asset_a, asset_b, user_1, pool_amount, initial_price);
+
+		// Breaches the max-in-ratio limit no matter how many times it's retried, since nothing
+		// ever changes the pool's liquidity in this test.
+		let amount_sell = 40_000_000_000_000;
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_2),
+			asset_a,
+			asset_b,
+			amount_sell,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		let sell_intention_id = generate_intention_id(&user_2, 0);
+
+		let lifetime = DefaultIntentionLifetime::get();
+
+		// The intention is carried forward once per block for as long as it has retries left...
+		for block in 1..=lifetime {
+			<Exchange as OnFinalize<u64>>::on_finalize(block);
+			assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 1);
+		}
+
+		// ...and dropped with the usual error event on the first attempt with none left.
+		<Exchange as OnFinalize<u64>>::on_finalize(lifetime + 1);
+
+		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 0);
+		expect_event(RawEvent::AMMSellErrorEvent(
+			user_2,
+			asset_a,
+			asset_b,
+			IntentionType::SELL,
+			sell_intention_id,
+			amm::Error::<Test>::MaxInRatioExceeded.into(),
+			AMMFailureReason::Other,
+		));
+	});
+}
+
+#[test]
+fn intention_should_expire_by_timestamp_before_its_block_lifetime_is_exhausted() {
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000_000_000;
+		let initial_price = Price::from(2);
+
+		initialize_pool(asset_a, asset_b, user_1, pool_amount, initial_price);
+
+		Timestamp::set_timestamp(1_000);
+
+		// Breaches the max-in-ratio limit no matter how many times it's retried, so it would
+		// otherwise survive for `DefaultIntentionLifetime` blocks - but its wall-clock deadline
+		// elapses before the first `on_finalize` runs, well within its block-based lifetime.
+		let amount_sell = 40_000_000_000_000;
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_2),
+			asset_a,
+			asset_b,
+			amount_sell,
+			0,
+			false,
+			None,
+			Some(1_500),
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		let sell_intention_id = generate_intention_id(&user_2, 0);
+
+		Timestamp::set_timestamp(1_500);
+
+		<Exchange as OnFinalize<u64>>::on_finalize(1);
+
+		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 0);
+		expect_event(RawEvent::AMMSellErrorEvent(
+			user_2,
+			asset_a,
+			asset_b,
+			IntentionType::SELL,
+			sell_intention_id,
+			amm::Error::<Test>::MaxInRatioExceeded.into(),
+			AMMFailureReason::Other,
+		));
+	});
+}
+
+#[test]
+fn sell_should_reject_an_already_elapsed_valid_until_timestamp() {
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000_000_000;
+		let initial_price = Price::from(2);
+
+		initialize_pool(asset_a, asset_b, user_1, pool_amount, initial_price);
+
+		Timestamp::set_timestamp(1_000);
+
+		assert_noop!(
+			Exchange::sell(
+				Origin::signed(user_2),
+				asset_a,
+				asset_b,
+				1_000_000_000_000,
+				0,
+				false,
+				None,
+				Some(1_000),
+				None,
+				true,
+				false,
+				0,
+				Price::from(1),
+			),
+			Error::<Test>::IntentionExpired.with_weight(<Test as Config>::WeightInfo::reject_intention())
+		);
+	});
+}
+
+#[test]
+fn sell_and_buy_should_be_rejected_while_exchange_is_paused() {
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000_000_000;
+		let initial_price = Price::from(2);
+
+		initialize_pool(asset_a, asset_b, user_1, pool_amount, initial_price);
+
+		assert_ok!(Exchange::pause(Origin::root()));
+
+		assert_noop!(
+			Exchange::sell(
+				Origin::signed(user_2),
+				asset_a,
+				asset_b,
+				1_000_000_000_000,
+				0,
+				false,
+				None,
+				None,
+				None,
+				true,
+				false,
+				0,
+				Price::from(1),
+			),
+			Error::<Test>::ExchangePaused.with_weight(<Test as Config>::WeightInfo::reject_intention())
+		);
+
+		assert_noop!(
+			Exchange::buy(
+				Origin::signed(user_2),
+				asset_a,
+				asset_b,
+				1_000_000_000_000,
+				u128::max_value(),
+				false,
+				None,
+				None,
+				None,
+				true,
+				false,
+				0,
+				Price::from(1),
+			),
+			Error::<Test>::ExchangePaused.with_weight(<Test as Config>::WeightInfo::reject_intention())
+		);
+	});
+}
+
+#[test]
+fn validate_sell_should_reject_the_same_preconditions_sell_would() {
+	// validate_sell and sell both delegate to validate_sell_registration, so any precondition
+	// that would make sell reject must make validate_sell reject too - these are exactly the
+	// checks the dry run had no way of predicting before that sharing was introduced.
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000_000_000;
+		let initial_price = Price::from(2);
+
+		initialize_pool(asset_a, asset_b, user_1, pool_amount, initial_price);
+
+		assert_ok!(Exchange::pause(Origin::root()));
+		assert_noop!(
+			Exchange::validate_sell(Origin::signed(user_1), asset_a, asset_b, 100, false),
+			Error::<Test>::ExchangePaused
+		);
+		assert_ok!(Exchange::resume(Origin::root()));
+
+		assert_noop!(
+			Exchange::validate_sell(Origin::signed(user_1), asset_a, asset_b, 0, false),
+			Error::<Test>::ZeroAmount
+		);
+
+		assert_ok!(Exchange::set_asset_min_trade_amount(
+			frame_system::RawOrigin::Root.into(),
+			asset_a,
+			1_000,
+		));
+		assert_noop!(
+			Exchange::validate_sell(Origin::signed(user_1), asset_a, asset_b, 999, false),
+			Error::<Test>::BelowMinTradeAmount
+		);
+	});
+}
+
+#[test]
+fn process_exchange_intentions_should_saturate_instead_of_overflowing_on_crafted_amounts() {
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let user_3 = CHARLIE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000_000_000;
+		let initial_price = Price::from(2);
+
+		initialize_pool(asset_a, asset_b, user_1, pool_amount, initial_price);
+
+		let pair_account = AMMModule::get_pair_id(&asset_a, &asset_b);
+
+		// A BUY intention's `amount_sell` isn't ratio-checked by `validate_buy` (only
+		// `amount_buy` is) - a genuinely crafted intention could carry an `amount_sell` far
+		// beyond anything a SELL intention could ever pass validation with, all the way up to
+		// `Balance::MAX`. That's the accumulation threshold the matcher below has to survive.
+		let intention_a = crate::Intention::<Test> {
+			who: user_1,
+			asset_sell: asset_b,
+			asset_buy: asset_a,
+			amount_sell: Balance::MAX,
+			amount_buy: 1_000_000_000,
+			trade_limit: Balance::MAX,
+			discount: false,
+			sell_or_buy: IntentionType::BUY,
+			intention_id: generate_intention_id(&user_1, 0),
+			remaining_lifetime: 10,
+			recipient: None,
+			valid_until_timestamp: None,
+			reference: None,
+			allow_amm_fallback: true,
+			priority: 0,
+		};
+
+		// Two counterparties whose amounts individually fit in `Balance` but whose sum doesn't -
+		// exactly the crafted input `total.saturating_add(..)` has to survive without panicking.
+		let make_b = |who: u64, c: u32| crate::Intention::<Test> {
+			who,
+			asset_sell: asset_a,
+			asset_buy: asset_b,
+			amount_sell: 1u128 << 127,
+			amount_buy: 1_000_000_000,
+			trade_limit: 0,
+			discount: false,
+			sell_or_buy: IntentionType::SELL,
+			intention_id: generate_intention_id(&who, c),
+			remaining_lifetime: 10,
+			recipient: None,
+			valid_until_timestamp: None,
+			reference: None,
+			allow_amm_fallback: true,
+			priority: 0,
+		};
+		let b_intentions = vec![make_b(user_2, 1), make_b(user_3, 2)];
+
+		// Must not panic - before this fix, accumulating both `1u128 << 127` counterparty amounts
+		// into `total` would overflow `Balance` by exactly `1`.
+		let (carried_forward, _matched_volume, _amm_volume) =
+			Exchange::process_exchange_intentions(&pair_account, &[intention_a], &b_intentions);
+
+		// Both counterparties were pulled into the same match group and nothing was left over in
+		// `b_copy` - saturating `total` doesn't corrupt that grouping into something nonsensical.
+		assert!(carried_forward.is_empty());
+	});
+}
+
+/// Build a bare `SELL` intention for `match_intentions` tests - no verification or settlement is
+/// involved, so the asset pair and `amount_buy` are placeholders and never inspected.
+fn make_match_intention(who: AccountId, c: u32, amount_sell: Balance, priority: u8) -> crate::Intention<Test> {
+	crate::Intention::<Test> {
+		who,
+		asset_sell: ETH,
+		asset_buy: DOT,
+		amount_sell,
+		amount_buy: 0,
+		trade_limit: 0,
+		discount: false,
+		sell_or_buy: IntentionType::SELL,
+		intention_id: generate_intention_id(&who, c),
+		remaining_lifetime: 10,
+		recipient: None,
+		valid_until_timestamp: None,
+		reference: None,
+		allow_amm_fallback: true,
+		priority,
+	}
+}
+
+#[test]
+fn match_intentions_should_greedily_fill_a_group_until_amount_sell_is_covered() {
+	new_test_ext().execute_with(|| {
+		let a = make_match_intention(ALICE, 0, 500, 0);
+		let b_400 = make_match_intention(BOB, 1, 400, 0);
+		let b_300 = make_match_intention(CHARLIE, 2, 300, 0);
+		let b_200 = make_match_intention(DAVE, 3, 200, 0);
+		let b_100 = make_match_intention(FERDIE, 4, 100, 0);
+
+		let groups = Exchange::match_intentions(
+			&[a.clone()],
+			&[b_100.clone(), b_200.clone(), b_300.clone(), b_400.clone()],
+		);
+
+		assert_eq!(groups.len(), 1);
+		let (matched_a, bvec) = &groups[0];
+		assert_eq!(matched_a.intention_id, a.intention_id);
+
+		// Largest counterparties are taken first - 400 alone isn't enough, but 400 + 300 is, so
+		// the two smaller ones are left out of the group entirely.
+		let matched_ids: Vec<_> = bvec.iter().map(|i| i.intention_id).collect();
+		assert_eq!(matched_ids, vec![b_400.intention_id, b_300.intention_id]);
+	});
+}
+
+#[test]
+fn match_intentions_should_skip_b_intentions_smaller_than_min_match_size() {
+	new_test_ext().execute_with(|| {
+		MinMatchSizeMock::set(100);
+
+		let a = make_match_intention(ALICE, 0, 650, 0);
+		let b_300_a = make_match_intention(BOB, 1, 300, 0);
+		let b_300_b = make_match_intention(CHARLIE, 2, 300, 0);
+		// Below `MinMatchSize` - skipped even though `a` still has room left to fill.
+		let b_50 = make_match_intention(DAVE, 3, 50, 0);
+
+		let groups = Exchange::match_intentions(&[a.clone()], &[b_50.clone(), b_300_a.clone(), b_300_b.clone()]);
+
+		assert_eq!(groups.len(), 1);
+		let (_, bvec) = &groups[0];
+		let matched_ids: Vec<_> = bvec.iter().map(|i| i.intention_id).collect();
+		assert_eq!(matched_ids, vec![b_300_a.intention_id, b_300_b.intention_id]);
+		assert!(!matched_ids.contains(&b_50.intention_id));
+	});
+}
+
+#[test]
+fn match_intentions_should_cap_a_groups_counterparty_count_at_max_counterparties_per_intention() {
+	new_test_ext().execute_with(|| {
+		MaxCounterpartiesPerIntentionMock::set(3);
+
+		// Total of every counterparty below is only 1050, well short of `a`'s 10_000 - without the
+		// cap, the greedy loop would happily pull in all five looking for coverage that never comes.
+		let a = make_match_intention(ALICE, 0, 10_000, 0);
+		let b_400 = make_match_intention(BOB, 1, 400, 0);
+		let b_300 = make_match_intention(CHARLIE, 2, 300, 0);
+		let b_200 = make_match_intention(DAVE, 3, 200, 0);
+		let b_100 = make_match_intention(FERDIE, 4, 100, 0);
+		let b_50 = make_match_intention(GEORGE, 5, 50, 0);
+
+		let groups = Exchange::match_intentions(
+			&[a.clone()],
+			&[b_50.clone(), b_100.clone(), b_200.clone(), b_300.clone(), b_400.clone()],
+		);
+
+		assert_eq!(groups.len(), 1);
+		let (_, bvec) = &groups[0];
+
+		// Largest counterparties are taken first - only the first `MaxCounterpartiesPerIntention`
+		// (3) make it into the group, leaving the two smallest to fall through to the AMM fallback.
+		let matched_ids: Vec<_> = bvec.iter().map(|i| i.intention_id).collect();
+		assert_eq!(
+			matched_ids,
+			vec![b_400.intention_id, b_300.intention_id, b_200.intention_id]
+		);
+	});
+}
+
+#[test]
+fn match_intentions_should_never_reuse_a_b_intention_across_two_groups() {
+	new_test_ext().execute_with(|| {
+		// Higher `priority` is matched first - see `matching_order`.
+		let a_first = make_match_intention(ALICE, 0, 100, 1);
+		let a_second = make_match_intention(BOB, 1, 100, 0);
+		let b_1 = make_match_intention(CHARLIE, 2, 100, 0);
+		let b_2 = make_match_intention(DAVE, 3, 100, 0);
+
+		let groups = Exchange::match_intentions(&[a_second.clone(), a_first.clone()], &[b_1.clone(), b_2.clone()]);
+
+		assert_eq!(groups.len(), 2);
+		assert_eq!(groups[0].0.intention_id, a_first.intention_id);
+		assert_eq!(groups[1].0.intention_id, a_second.intention_id);
+
+		assert_eq!(groups[0].1.len(), 1);
+		assert_eq!(groups[1].1.len(), 1);
+
+		// Every b intention is claimed exactly once, never by both groups.
+		assert_ne!(groups[0].1[0].intention_id, groups[1].1[0].intention_id);
+	});
+}
+
+#[test]
+fn match_intentions_should_return_an_empty_group_for_an_a_with_no_available_counterparties() {
+	new_test_ext().execute_with(|| {
+		let a = make_match_intention(ALICE, 0, 500, 0);
+
+		let groups = Exchange::match_intentions(&[a.clone()], &[]);
+
+		assert_eq!(groups.len(), 1);
+		assert_eq!(groups[0].0.intention_id, a.intention_id);
+		assert!(groups[0].1.is_empty());
+	});
+}
+
+#[test]
+fn compute_matches_should_predict_the_direct_counterparty_settlement_actually_picks() {
+	// Same setup as `partial_match_then_amm_fallback_should_emit_events_in_deterministic_order` -
+	// `compute_matches` and `on_finalize` both group via `match_intentions` from the same storage,
+	// so the counterparty it previews for user_2 is exactly the one settlement pairs it with.
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let user_3 = CHARLIE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000_000_000;
+		let initial_price = Price::from(2);
+
+		initialize_pool(asset_a, asset_b, user_1, pool_amount, initial_price);
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_2),
+			asset_a,
+			asset_b,
+			2_000_000_000_000,
+			300_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		let user_2_sell_intention_id = generate_intention_id(&user_2, 0);
+
+		assert_ok!(Exchange::buy(
+			Origin::signed(user_3),
+			asset_a,
+			asset_b,
+			1_000_000_000_000,
+			4_000_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		let user_3_sell_intention_id = generate_intention_id(&user_3, 1);
+
+		let plan = Exchange::compute_matches((asset_a, asset_b));
+		let preview = plan
+			.iter()
+			.find(|p| p.intention_id == user_2_sell_intention_id)
+			.expect("user_2's intention should be in the plan");
+
+		assert_eq!(preview.matched_against.len(), 1);
+		assert_eq!(preview.matched_against[0].0, user_3_sell_intention_id);
+		assert_eq!(preview.matched_against[0].1, user_3);
+
+		<Exchange as OnFinalize<u64>>::on_finalize(9);
+
+		let matched_pair = last_events(10).into_iter().find_map(|e| {
+			if let TestEvent::exchange(RawEvent::IntentionResolvedDirectTrade(a_who, b_who, a_id, b_id, ..)) = e {
+				Some((a_who, b_who, a_id, b_id))
+			} else {
+				None
+			}
+		});
+
+		assert_eq!(
+			matched_pair,
+			Some((user_2, user_3, user_2_sell_intention_id, user_3_sell_intention_id))
+		);
+	});
+}
+
+#[test]
+fn compute_matches_should_predict_the_exact_amm_leftover_for_an_intention_with_no_counterparty() {
+	// With no counterparty at all, `compute_matches`'s heuristic leftover (`amount_sell` minus
+	// whatever direct matches would absorb) is exact, since there is nothing to absorb -
+	// settlement routes the intention's full amount through the AMM.
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000_000_000;
+		let initial_price = Price::from(2);
+		let amount_sell = 2_000_000_000_000;
+
+		initialize_pool(asset_a, asset_b, user_1, pool_amount, initial_price);
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_2),
+			asset_a,
+			asset_b,
+			amount_sell,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		let user_2_sell_intention_id = generate_intention_id(&user_2, 0);
+
+		let plan = Exchange::compute_matches((asset_a, asset_b));
+		let preview = plan
+			.iter()
+			.find(|p| p.intention_id == user_2_sell_intention_id)
+			.expect("user_2's intention should be in the plan");
+
+		assert!(preview.matched_against.is_empty());
+		assert_eq!(preview.amm_leftover, amount_sell);
+		assert!(preview.amm_preview_price.is_some());
+
+		<Exchange as OnFinalize<u64>>::on_finalize(9);
+
+		let amm_amount = last_events(10).into_iter().find_map(|e| {
+			if let TestEvent::exchange(RawEvent::IntentionResolvedAMMTrade(who, _, id, amount, ..)) = e {
+				if who == user_2 && id == user_2_sell_intention_id {
+					Some(amount)
+				} else {
+					None
+				}
+			} else {
+				None
+			}
+		});
+
+		assert_eq!(amm_amount, Some(preview.amm_leftover));
+	});
+}
+
+#[test]
+fn register_sell_should_return_the_assigned_intention_id() {
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000_000_000;
+		let initial_price = Price::from(2);
+
+		initialize_pool(asset_a, asset_b, user_1, pool_amount, initial_price);
+
+		let amount_sell = 1_000_000_000_000;
+		let intention_id =
+			Exchange::register_sell(user_2, asset_a, asset_b, amount_sell, 0, false, None, None, None, true)
+				.expect("registration should succeed");
+
+		assert_eq!(intention_id, generate_intention_id(&user_2, 0));
+		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 1);
+	});
+}
+
+#[test]
+fn submit_intention_should_register_a_sell_or_buy_intention_from_another_pallet() {
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let user_3 = CHARLIE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000_000_000;
+
+		initialize_pool(asset_a, asset_b, user_1, pool_amount, Price::from(2));
+
+		let sell_amount = 1_000_000_000_000;
+		assert_ok!(MockCaller::submit(
+			Origin::signed(user_2),
+			asset_a,
+			asset_b,
+			sell_amount,
+			IntentionType::SELL,
+			false,
+		));
+
+		let sell_intention_id = MockCaller::last_intention_id().expect("sell intention should have been recorded");
+		assert_eq!(sell_intention_id, generate_intention_id(&user_2, 0));
+		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 1);
+
+		let buy_amount = 1_000_000_000_000;
+		assert_ok!(MockCaller::submit(
+			Origin::signed(user_3),
+			asset_a,
+			asset_b,
+			buy_amount,
+			IntentionType::BUY,
+			false,
+		));
+
+		let buy_intention_id = MockCaller::last_intention_id().expect("buy intention should have been recorded");
+		assert_ne!(buy_intention_id, sell_intention_id);
+		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 2);
+	});
+}
+
+#[test]
+fn matcher_should_skip_pairing_an_account_against_its_own_opposite_intention() {
+	// Same setup as `sell_test_standard`, except the SELL and the opposing BUY both belong to
+	// `self_trader` - the matcher would otherwise pair them directly against each other, which
+	// would just be a self-transfer that nets `self_trader` a fee-only loss.
+	new_test_ext().execute_with(|| {
+		let pool_owner = ALICE;
+		let self_trader = BOB;
+		let asset_a = ETH;
+		let asset_b = DOT;
+
+		initialize_pool(asset_a, asset_b, pool_owner, 100_000_000_000_000, Price::from(2));
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(self_trader),
+			asset_a,
+			asset_b,
+			2_000_000_000_000,
+			300_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		let sell_intention_id = generate_intention_id(&self_trader, 0);
+
+		assert_ok!(Exchange::buy(
+			Origin::signed(self_trader),
+			asset_a,
+			asset_b,
+			1_000_000_000_000,
+			4_000_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		let buy_intention_id = generate_intention_id(&self_trader, 1);
+
+		<Exchange as OnFinalize<u64>>::on_finalize(9);
+
+		assert!(system::Module::<Test>::events().into_iter().any(|record| record.event
+			== TestEvent::exchange(RawEvent::SelfMatchSkipped(
+				self_trader,
+				sell_intention_id,
+				buy_intention_id
+			))));
+
+		// Neither intention was direct-traded against the other.
+		assert!(!system::Module::<Test>::events().into_iter().any(|record| matches!(
+			record.event,
+			TestEvent::exchange(RawEvent::IntentionResolvedDirectTrade(..))
+		)));
+
+		// The SELL side still found its counterparty in the AMM instead of being left unmatched.
+		assert!(system::Module::<Test>::events().into_iter().any(|record| matches!(
+			record.event,
+			TestEvent::exchange(RawEvent::IntentionResolvedAMMTrade(..))
+		)));
+	});
+}
+
+#[test]
+fn already_queued_intentions_should_still_settle_in_on_finalize_while_paused() {
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000_000_000;
+		let initial_price = Price::from(2);
+
+		initialize_pool(asset_a, asset_b, user_1, pool_amount, initial_price);
+
+		let amount_sell = 1_000_000_000_000;
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_2),
+			asset_a,
+			asset_b,
+			amount_sell,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		assert_ok!(Exchange::pause(Origin::root()));
+
+		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 1);
+
+		<Exchange as OnFinalize<u64>>::on_finalize(1);
+
+		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 0);
+	});
+}
+
+#[test]
+fn reserved_funds_should_be_released_once_a_direct_trade_settles() {
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let user_3 = CHARLIE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000_000_000;
+		let initial_price = Price::from(2);
+
+		initialize_pool(asset_a, asset_b, user_1, pool_amount, initial_price);
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_2),
+			asset_a,
+			asset_b,
+			2_000_000_000_000,
+			20000000000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		let user_2_sell_intention_id = generate_intention_id(&user_2, 0);
+
+		assert_ok!(Exchange::buy(
+			Origin::signed(user_3),
+			asset_a,
+			asset_b,
+			1_000_000_000_000,
+			4_000_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		let user_3_sell_intention_id = generate_intention_id(&user_3, 1);
+
+		// Registering an intention doesn't reserve anything - reservation only happens once a
+		// direct trade is actually prepared during block finalization.
+		assert_eq!(Currency::reserved_balance(asset_a, &user_2), 0);
+		assert_eq!(Currency::reserved_balance(asset_b, &user_3), 0);
+
+		<Exchange as OnFinalize<u64>>::on_finalize(9);
+
+		// user_2's sell only partially matches user_3's buy directly - the remainder is routed
+		// through the AMM in the same block, so by the time finalization completes the amount
+		// reserved for the direct trade has already been released again.
+		assert_eq!(Currency::reserved_balance(asset_a, &user_2), 0);
+		assert_eq!(Currency::reserved_balance(asset_b, &user_3), 0);
+
+		expect_events(vec![
+			RawEvent::IntentionRegistered(
+				user_2,
+				asset_a,
+				asset_b,
+				2_000_000_000_000,
+				IntentionType::SELL,
+				user_2_sell_intention_id,
+				None,
+				1,
+			)
+			.into(),
+			RawEvent::IntentionRegistered(
+				user_3,
+				asset_a,
+				asset_b,
+				1_000_000_000_000,
+				IntentionType::BUY,
+				user_3_sell_intention_id,
+				None,
+				1,
+			)
+			.into(),
+			RawEvent::FundsReserved(user_2, asset_a, 1000000000000, user_2_sell_intention_id).into(),
+			RawEvent::FundsReserved(user_3, asset_b, 2000000000000, user_3_sell_intention_id).into(),
+			RawEvent::IntentionResolvedDirectTrade(
+				user_2,
+				user_3,
+				user_2_sell_intention_id,
+				user_3_sell_intention_id,
+				1000000000000,
+				2000000000000,
+				None,
+				None,
+				1,
+				1000000000000,
+			)
+			.into(),
+			RawEvent::FundsUnreserved(user_2, asset_a, 1000000000000, user_2_sell_intention_id).into(),
+			RawEvent::FundsUnreserved(user_3, asset_b, 2000000000000, user_3_sell_intention_id).into(),
+			RawEvent::IntentionResolvedDirectTradeFees(
+				user_2,
+				AMMModule::get_pair_id(&asset_a, &asset_b),
+				asset_b,
+				2000000000,
+			)
+			.into(),
+			RawEvent::IntentionResolvedDirectTradeFees(
+				user_3,
+				AMMModule::get_pair_id(&asset_a, &asset_b),
+				asset_b,
+				4000000000,
+			)
+			.into(),
+			TestEvent::amm(amm::RawEvent::Sell(user_2, 3000, 2000, 1000000000000, 1976336046259)),
+			RawEvent::IntentionResolvedAMMTrade(
+				user_2,
+				IntentionType::SELL,
+				user_2_sell_intention_id,
+				1000000000000,
+				1976336046259,
+				None,
+				1,
+			)
+			.into(),
+		]);
+	});
+}
+
+#[test]
+fn amm_settlement_should_never_touch_currency_level_reserves() {
+	// This pallet never calls `T::Currency::reserve` for an intention that settles purely via the
+	// AMM - reservation only happens transiently around a direct trade's prepare/execute (see
+	// `reserved_funds_should_be_released_once_a_direct_trade_settles`). What `Exchange::reserved_balance`
+	// reports for a queued intention is a logical view over `ExchangeAssetsIntentions`, not an
+	// actual currency reserve, so there is nothing to unreserve before `T::AMMPool::execute_sell`
+	// debits the free balance directly.
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000_000_000;
+		let initial_price = Price::from(2);
+		let amount_sell = 2_000_000_000_000;
+
+		initialize_pool(asset_a, asset_b, user_1, pool_amount, initial_price);
+
+		let free_before = Currency::free_balance(asset_a, &user_2);
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_2),
+			asset_a,
+			asset_b,
+			amount_sell,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		// Registered but not yet settled - logically reserved from the pallet's point of view,
+		// but no actual currency reserve exists.
+		assert_eq!(Exchange::reserved_balance(user_2, asset_a), amount_sell);
+		assert_eq!(Currency::reserved_balance(asset_a, &user_2), 0);
+
+		<Exchange as OnFinalize<u64>>::on_finalize(9);
+
+		// Settled fully via the AMM (no counterparty to match against) - the logical reservation
+		// is gone along with the intention, and the currency-level reserve was never non-zero.
+		assert_eq!(Exchange::reserved_balance(user_2, asset_a), 0);
+		assert_eq!(Currency::reserved_balance(asset_a, &user_2), 0);
+		assert_eq!(Currency::free_balance(asset_a, &user_2), free_before - amount_sell);
+	});
+}
+
+/// Minimal seeded xorshift PRNG - avoids pulling in an external fuzzing crate for this pallet.
+struct XorShiftRng(u64);
+
+impl XorShiftRng {
+	fn new(seed: u64) -> Self {
+		XorShiftRng(seed | 1)
+	}
+
+	fn next_u64(&mut self) -> u64 {
+		self.0 ^= self.0 << 13;
+		self.0 ^= self.0 >> 7;
+		self.0 ^= self.0 << 17;
+		self.0
+	}
+
+	fn next_amount(&mut self, max: u128) -> u128 {
+		1 + (self.next_u64() as u128) % max
+	}
+}
+
+#[test]
+fn matcher_invariants_hold_under_randomized_intentions() {
+	new_test_ext().execute_with(|| {
+		let users = [ALICE, BOB, CHARLIE, DAVE, FERDIE, GEORGE];
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000_000_000;
+
+		initialize_pool(asset_a, asset_b, ALICE, pool_amount, Price::from(2));
+
+		let pair_account = AMMModule::get_pair_id(&asset_a, &asset_b);
+		let mut rng = XorShiftRng::new(0xDEAD_BEEF_CAFE_F00D);
+
+		for i in 0..300u64 {
+			let total_before: u128 = users
+				.iter()
+				.map(|u| Currency::free_balance(asset_a, u) + Currency::free_balance(asset_b, u))
+				.chain(core::iter::once(
+					Currency::free_balance(asset_a, &pair_account) + Currency::free_balance(asset_b, &pair_account),
+				))
+				.sum();
+
+			// Register a handful of random sell intentions - amounts kept well within each user's
+			// balance and the pool's liquidity so trades don't spuriously fail validation.
+			for user in users.iter() {
+				if rng.next_u64() % 2 == 0 {
+					let (sell, buy) = if rng.next_u64() % 2 == 0 {
+						(asset_a, asset_b)
+					} else {
+						(asset_b, asset_a)
+					};
+					let amount = rng.next_amount(1_000_000_000);
+					let _ = Exchange::sell(
+						Origin::signed(*user),
+						sell,
+						buy,
+						amount,
+						0,
+						false,
+						None,
+						None,
+						None,
+						true,
+						false,
+						0,
+						Price::from(1),
+					);
+				}
+			}
+
+			<Exchange as OnFinalize<u64>>::on_finalize(i);
+
+			let total_after: u128 = users
+				.iter()
+				.map(|u| Currency::free_balance(asset_a, u) + Currency::free_balance(asset_b, u))
+				.chain(core::iter::once(
+					Currency::free_balance(asset_a, &pair_account) + Currency::free_balance(asset_b, &pair_account),
+				))
+				.sum();
+
+			// No trade path (direct or AMM) may create or destroy value - only move it between
+			// users and the pair account.
+			assert_eq!(
+				total_before, total_after,
+				"value was created or destroyed at iteration {}",
+				i
+			);
+
+			for user in users.iter() {
+				assert!(Currency::free_balance(asset_a, user) <= ENDOWED_AMOUNT * 2);
+				assert!(Currency::free_balance(asset_b, user) <= ENDOWED_AMOUNT * 2);
+			}
+		}
+	});
+}
+
+#[test]
+fn remove_liquidity_should_purge_queued_intentions_when_pool_is_destroyed() {
+	new_test_ext().execute_with(|| {
+		let user = ALICE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000_000_000;
+		let sell_amount = 1_000_000_000_000;
+
+		initialize_pool(asset_a, asset_b, user, pool_amount, Price::from(2));
+
+		let balance_before = Currency::free_balance(asset_a, &user);
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(user),
+			asset_a,
+			asset_b,
+			sell_amount,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		let intention_id = generate_intention_id_for_pair(&user, 0, asset_a, asset_b);
+
+		let pair_account = AMMModule::get_pair_id(&asset_a, &asset_b);
+		let share_token = AMMModule::share_token(pair_account);
+		let all_shares = Currency::free_balance(share_token, &user);
+
+		// Fully withdraw liquidity - this destroys the pool and purges the queued intention
+		// above, before it ever reaches settlement.
+		assert_ok!(AMMModule::remove_liquidity(
+			Origin::signed(user),
+			asset_a,
+			asset_b,
+			all_shares
+		));
+		assert!(!AMMModule::exists(asset_a, asset_b));
+
+		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 0);
+		assert!(Exchange::get_intentions((asset_b, asset_a)).is_empty());
+		assert_eq!(Exchange::intention_status(intention_id), Some(Status::Failed));
+
+		// The amount set aside for the purged sell is back with its owner.
+		assert_eq!(Currency::free_balance(asset_a, &user), balance_before);
+
+		let events = system::Module::<Test>::events();
+		assert!(events.iter().any(
+			|e| e.event == TestEvent::exchange(RawEvent::FundsUnreserved(user, asset_a, sell_amount, intention_id))
+		));
+
+		// Nothing left queued for the pair, so `on_finalize` has nothing to report on it.
+		<Exchange as OnFinalize<u64>>::on_finalize(1);
+		let events = system::Module::<Test>::events();
+		assert!(!events
+			.iter()
+			.any(|e| matches!(e.event, TestEvent::exchange(RawEvent::PoolRemovedBeforeSettlement(..)))));
+	});
+}
+
+#[test]
+fn canonical_pair_should_return_the_same_key_regardless_of_argument_order() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(canonical_pair(HDX, DOT), canonical_pair(DOT, HDX));
+		assert_eq!(canonical_pair(HDX, DOT), (HDX, DOT));
+
+		assert_eq!(canonical_pair(ETH, DOT), canonical_pair(DOT, ETH));
+		assert_eq!(canonical_pair(ETH, DOT), (DOT, ETH));
+
+		assert_eq!(canonical_pair(ETH, HDX), canonical_pair(HDX, ETH));
+		assert_eq!(canonical_pair(ETH, HDX), (HDX, ETH));
+
+		// Same asset on both sides is its own canonical form either way.
+		assert_eq!(canonical_pair(DOT, DOT), (DOT, DOT));
+	});
+}
+
+#[test]
+fn on_finalize_should_settle_pairs_in_a_fixed_order_regardless_of_insertion_sequence() {
+	new_test_ext().execute_with(|| {
+		let user = ALICE;
+		let pool_amount = 100_000_000_000_000;
+
+		// (DOT, ETH) sorts after (HDX, ETH) - set it up first so storage insertion order is the
+		// reverse of the expected settlement order.
+		initialize_pool(DOT, ETH, user, pool_amount, Price::from(2));
+		initialize_pool(HDX, ETH, user, pool_amount, Price::from(2));
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(user),
+			DOT,
+			ETH,
+			1_000_000_000_000,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		assert_ok!(Exchange::sell(
+			Origin::signed(user),
+			HDX,
+			ETH,
+			1_000_000_000_000,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		<Exchange as OnFinalize<u64>>::on_finalize(1);
+
+		// Neither pair has a direct counterparty, so both fall back to an AMM trade - settled in
+		// ascending `(asset_1, asset_2)` order, (HDX, ETH) before (DOT, ETH), even though (DOT,
+		// ETH) was registered first.
+		let hdx_eth_intention_id = generate_intention_id_for_pair(&user, 0, HDX, ETH);
+		let dot_eth_intention_id = generate_intention_id_for_pair(&user, 0, DOT, ETH);
+
+		let resolved_order: Vec<_> = system::Module::<Test>::events()
+			.into_iter()
+			.filter_map(|e| match e.event {
+				TestEvent::exchange(RawEvent::IntentionResolvedAMMTrade(_, _, intention_id, _, _, _, _)) => {
+					Some(intention_id)
+				}
+				_ => None,
+			})
+			.collect();
+
+		assert_eq!(resolved_order, vec![hdx_eth_intention_id, dot_eth_intention_id]);
+	});
+}
+
+#[test]
+fn get_intentions_page_should_paginate_across_all_pairs() {
+	new_test_ext().execute_with(|| {
+		let user = ALICE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000_000_000;
+
+		initialize_pool(asset_a, asset_b, user, pool_amount, Price::from(2));
+
+		for _ in 0..5 {
+			assert_ok!(Exchange::sell(
+				Origin::signed(user),
+				asset_a,
+				asset_b,
+				1_000_000_000,
+				0,
+				false,
+				None,
+				None,
+				None,
+				true,
+				false,
+				0,
+				Price::from(1),
+			));
+		}
+
+		// A page size of 0 always yields nothing, regardless of how much is registered.
+		assert!(Exchange::get_intentions_page(0, 0).is_empty());
+
+		// Two full pages of 2, then a partial page of 1.
+		assert_eq!(Exchange::get_intentions_page(0, 2).len(), 2);
+		assert_eq!(Exchange::get_intentions_page(1, 2).len(), 2);
+		assert_eq!(Exchange::get_intentions_page(2, 2).len(), 1);
+
+		// Past the end, there's nothing left to read.
+		assert!(Exchange::get_intentions_page(3, 2).is_empty());
+
+		// A single page big enough to hold everything returns the lot.
+		assert_eq!(Exchange::get_intentions_page(0, 10).len(), 5);
+	});
+}
+
+#[test]
+fn settle_offchain_match_should_settle_an_exact_match_and_remove_it_from_storage() {
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let user_3 = CHARLIE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000_000_000;
+
+		initialize_pool(asset_a, asset_b, user_1, pool_amount, Price::from(2));
+
+		// Same setup as `sell_test_exact_match` - the amounts match exactly.
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_2),
+			asset_a,
+			asset_b,
+			1_000_000_000_000,
+			1_500_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_3),
+			asset_b,
+			asset_a,
+			2_000_000_000_000,
+			200_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		let intention_2 = generate_intention_id(&user_2, 0);
+		let intention_3 = generate_intention_id(&user_3, 1);
+
+		let pair = (cmp::min(asset_a, asset_b), cmp::max(asset_a, asset_b));
+
+		assert_eq!(Exchange::get_intentions_page(0, 10).len(), 2);
+
+		assert_ok!(Exchange::settle_offchain_match(
+			frame_system::RawOrigin::None.into(),
+			pair,
+			intention_2,
+			intention_3,
+		));
+
+		expect_event(RawEvent::IntentionsSettledOffchain(intention_2, intention_3));
+
+		// Settled intentions are removed so `on_finalize` doesn't try to match them again.
+		assert!(Exchange::get_intentions_page(0, 10).is_empty());
+
+		// ... and the trade actually happened - same deltas as `sell_test_exact_match`.
+		assert_eq!(Currency::free_balance(asset_a, &user_2), 999_000_000_000_000);
+		assert_eq!(Currency::free_balance(asset_b, &user_3), 998_000_000_000_000);
+		assert_eq!(
+			Currency::free_balance(asset_b, &user_2),
+			ENDOWED_AMOUNT + 1_996_000_000_000
+		);
+		assert_eq!(
+			Currency::free_balance(asset_a, &user_3),
+			ENDOWED_AMOUNT + 998_000_000_000
+		);
+	});
+}
+
+#[test]
+fn settle_offchain_match_should_reject_intentions_that_are_not_an_exact_match() {
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let user_3 = CHARLIE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000_000_000;
+
+		initialize_pool(asset_a, asset_b, user_1, pool_amount, Price::from(2));
+
+		// Both sell in the same direction - never a match, however matched by amount.
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_2),
+			asset_a,
+			asset_b,
+			1_000_000_000_000,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_3),
+			asset_a,
+			asset_b,
+			1_000_000_000_000,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		let intention_2 = generate_intention_id(&user_2, 0);
+		let intention_3 = generate_intention_id(&user_3, 1);
+
+		let pair = (cmp::min(asset_a, asset_b), cmp::max(asset_a, asset_b));
+
+		assert_noop!(
+			Exchange::settle_offchain_match(frame_system::RawOrigin::None.into(), pair, intention_2, intention_3),
+			Error::<Test>::IntentionsNotMatched
+		);
+	});
+}
+
+#[test]
+fn settle_offchain_match_should_reject_signed_origin() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			Exchange::settle_offchain_match(
+				Origin::signed(ALICE),
+				(ETH, DOT),
+				Default::default(),
+				Default::default(),
+			),
+			DispatchError::BadOrigin
+		);
+	});
+}
+
+#[test]
+fn set_min_pool_liquidity_should_require_root_origin() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			Exchange::set_min_pool_liquidity(Origin::signed(ALICE), ETH, DOT, 100),
+			DispatchError::BadOrigin
+		);
+	});
+}
+
+#[test]
+fn set_min_pool_liquidity_should_store_value_keyed_by_sorted_pair() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(Exchange::set_min_pool_liquidity(
+			frame_system::RawOrigin::Root.into(),
+			ETH,
+			DOT,
+			123_456,
+		));
+
+		expect_event(RawEvent::MinPoolLiquiditySet(
+			cmp::min(ETH, DOT),
+			cmp::max(ETH, DOT),
+			123_456,
+		));
+
+		assert_eq!(
+			Exchange::get_min_liquidity((cmp::min(ETH, DOT), cmp::max(ETH, DOT))),
+			123_456
+		);
+		assert_eq!(Exchange::get_min_liquidity((cmp::max(ETH, DOT), cmp::min(ETH, DOT))), 0);
+	});
+}
+
+#[test]
+fn set_asset_min_trade_amount_should_require_root_origin() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			Exchange::set_asset_min_trade_amount(Origin::signed(ALICE), ETH, 1_000_000),
+			DispatchError::BadOrigin
+		);
+	});
+}
+
+#[test]
+fn sell_should_reject_an_amount_below_the_assets_custom_minimum() {
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let asset_a = ETH;
+		let asset_b = DOT;
+
+		initialize_pool(asset_a, asset_b, user_1, 100_000_000_000_000, Price::from(2));
+
+		// The global `MinTradingLimit` alone would let this through - `sell` doesn't enforce it
+		// directly - but a custom override for `asset_a` makes it stricter.
+		let global_limit = MinTradingLimit::get();
+		let custom_minimum = global_limit + 1_000_000;
+		assert_ok!(Exchange::set_asset_min_trade_amount(
+			frame_system::RawOrigin::Root.into(),
+			asset_a,
+			custom_minimum,
+		));
+		expect_event(RawEvent::AssetMinTradeAmountSet(asset_a, custom_minimum));
+		assert_eq!(Exchange::min_trade_amount(asset_a), custom_minimum);
+
+		assert_noop!(
+			Exchange::sell(
+				Origin::signed(user_2),
+				asset_a,
+				asset_b,
+				custom_minimum - 1,
+				0,
+				false,
+				None,
+				None,
+				None,
+				true,
+				false,
+				0,
+				Price::from(1),
+			),
+			Error::<Test>::BelowMinTradeAmount
+		);
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_2),
+			asset_a,
+			asset_b,
+			custom_minimum,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		// The override is per-asset - selling `asset_b` (with no override of its own) is
+		// unaffected and still falls back to the (unenforced-in-`sell`) global limit.
+		assert_eq!(Exchange::min_trade_amount(asset_b), global_limit);
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_1),
+			asset_b,
+			asset_a,
+			1,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+	});
+}
+
+#[test]
+fn sell_should_work_when_pool_liquidity_is_at_or_above_configured_minimum() {
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000_000_000;
+
+		initialize_pool(asset_a, asset_b, user_1, pool_amount, Price::from(2));
+
+		// asset_a's reserve in the pair account is exactly `pool_amount` - set the minimum to
+		// that, i.e. right at the boundary.
+		assert_ok!(Exchange::set_min_pool_liquidity(
+			frame_system::RawOrigin::Root.into(),
+			asset_a,
+			asset_b,
+			pool_amount,
+		));
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_2),
+			asset_a,
+			asset_b,
+			1_000_000_000_000,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+	});
+}
+
+#[test]
+fn sell_should_fail_when_pool_liquidity_is_below_configured_minimum() {
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000_000_000;
+
+		initialize_pool(asset_a, asset_b, user_1, pool_amount, Price::from(2));
+
+		// One more than asset_a's actual reserve in the pair account.
+		assert_ok!(Exchange::set_min_pool_liquidity(
+			frame_system::RawOrigin::Root.into(),
+			asset_a,
+			asset_b,
+			pool_amount + 1,
+		));
+
+		assert_noop!(
+			Exchange::sell(
+				Origin::signed(user_2),
+				asset_a,
+				asset_b,
+				1_000_000_000_000,
+				0,
+				false,
+				None,
+				None,
+				None,
+				true,
+				false,
+				0,
+				Price::from(1),
+			),
+			Error::<Test>::PoolLiquidityBelowMinimum
+		);
+	});
+}
+
+#[test]
+fn buy_should_fail_when_pool_liquidity_is_below_configured_minimum() {
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000_000_000;
+
+		initialize_pool(asset_a, asset_b, user_1, pool_amount, Price::from(2));
+
+		assert_ok!(Exchange::set_min_pool_liquidity(
+			frame_system::RawOrigin::Root.into(),
+			asset_a,
+			asset_b,
+			pool_amount + 1,
+		));
+
+		assert_noop!(
+			Exchange::buy(
+				Origin::signed(user_2),
+				asset_a,
+				asset_b,
+				1_000_000_000_000,
+				3_000_000_000_000,
+				false,
+				None,
+				None,
+				None,
+				true,
+				false,
+				0,
+				Price::from(1),
+			),
+			Error::<Test>::PoolLiquidityBelowMinimum
+		);
+	});
+}
+
+#[test]
+fn resolve_single_intention_should_partially_fill_when_enabled_and_full_amount_fails() {
+	new_test_ext().execute_with(|| {
+		EnablePartialAMMFillMock::set(true);
+
+		let user = ALICE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000_000_000;
+
+		initialize_pool(asset_a, asset_b, user, pool_amount, Price::from(2));
+
+		// `MAX_IN_RATIO` caps a single AMM sell at a third of the pair's reserve of the asset
+		// being sold - selling the whole pool amount at once is rejected outright.
+		let intention = Intention::<Test> {
+			who: user,
+			asset_sell: asset_a,
+			asset_buy: asset_b,
+			amount_sell: pool_amount,
+			amount_buy: 0,
+			trade_limit: 0,
+			discount: false,
+			sell_or_buy: IntentionType::SELL,
+			intention_id: generate_intention_id(&user, 0),
+			remaining_lifetime: DefaultIntentionLifetime::get(),
+			recipient: None,
+			valid_until_timestamp: None,
+			reference: None,
+			allow_amm_fallback: true,
+			priority: 0,
+		};
+
+		let balance_before = Currency::free_balance(asset_a, &user);
+
+		Exchange::resolve_single_intention(&intention);
+
+		let events = last_events(1);
+		match &events[0] {
+			TestEvent::exchange(RawEvent::IntentionResolvedAMMTradePartialFill(who, kind, id, amount_sold, _, _)) => {
+				assert_eq!(*who, user);
+				assert_eq!(*kind, IntentionType::SELL);
+				assert_eq!(*id, intention.intention_id);
+				assert!(
+					*amount_sold < pool_amount,
+					"partial fill must trade less than the full amount"
+				);
+			}
+			other => panic!("expected a partial fill event, got {:?}", other),
+		}
+
+		assert!(Currency::free_balance(asset_a, &user) < balance_before);
+	});
+}
+
+#[test]
+fn resolve_single_intention_should_drop_with_error_event_when_partial_fill_disabled() {
+	new_test_ext().execute_with(|| {
+		EnablePartialAMMFillMock::set(false);
+
+		let user = ALICE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000_000_000;
+
+		initialize_pool(asset_a, asset_b, user, pool_amount, Price::from(2));
+
+		let intention = Intention::<Test> {
+			who: user,
+			asset_sell: asset_a,
+			asset_buy: asset_b,
+			amount_sell: pool_amount,
+			amount_buy: 0,
+			trade_limit: 0,
+			discount: false,
+			sell_or_buy: IntentionType::SELL,
+			intention_id: generate_intention_id(&user, 0),
+			remaining_lifetime: DefaultIntentionLifetime::get(),
+			recipient: None,
+			valid_until_timestamp: None,
+			reference: None,
+			allow_amm_fallback: true,
+			priority: 0,
+		};
+
+		let balance_before = Currency::free_balance(asset_a, &user);
+
+		Exchange::resolve_single_intention(&intention);
+
+		match last_event() {
+			TestEvent::exchange(RawEvent::IntentionResolveErrorEvent(who, sell, buy, kind, id, _)) => {
+				assert_eq!(who, user);
+				assert_eq!(sell, asset_a);
+				assert_eq!(buy, asset_b);
+				assert_eq!(kind, IntentionType::SELL);
+				assert_eq!(id, intention.intention_id);
+			}
+			other => panic!("expected an intention resolve error event, got {:?}", other),
+		}
+
+		assert_eq!(Currency::free_balance(asset_a, &user), balance_before);
+	});
+}
+
+#[test]
+fn resolve_single_intention_or_carry_forward_should_route_through_native_asset_when_no_direct_pool_exists() {
+	new_test_ext().execute_with(|| {
+		EnableRoutingMock::set(true);
+
+		let user = ALICE;
+		let pool_amount = 100_000_000_000_000;
+
+		// No DOT/ETH pool exists, but both assets have a pool with the native asset (HDX).
+		initialize_pool(DOT, HDX, user, pool_amount, Price::from(2));
+		initialize_pool(HDX, ETH, user, pool_amount, Price::from(2));
+
+		let intention = Intention::<Test> {
+			who: user,
+			asset_sell: DOT,
+			asset_buy: ETH,
+			amount_sell: 1_000_000_000_000,
+			amount_buy: 0,
+			trade_limit: 0,
+			discount: false,
+			sell_or_buy: IntentionType::SELL,
+			intention_id: generate_intention_id(&user, 0),
+			remaining_lifetime: DefaultIntentionLifetime::get(),
+			recipient: None,
+			valid_until_timestamp: None,
+			reference: None,
+			allow_amm_fallback: true,
+			priority: 0,
+		};
+
+		let dot_before = Currency::free_balance(DOT, &user);
+		let eth_before = Currency::free_balance(ETH, &user);
+
+		assert!(Exchange::resolve_single_intention_or_carry_forward(&intention)
+			.0
+			.is_none());
+
+		match last_event() {
+			TestEvent::exchange(RawEvent::IntentionResolvedAMMTrade(
+				who,
+				kind,
+				id,
+				amount_sold,
+				amount_bought,
+				_,
+				_,
+			)) => {
+				assert_eq!(who, user);
+				assert_eq!(kind, IntentionType::SELL);
+				assert_eq!(id, intention.intention_id);
+				assert_eq!(amount_sold, intention.amount_sell);
+				assert!(amount_bought > 0);
+			}
+			other => panic!("expected an AMM trade resolved event, got {:?}", other),
+		}
+
+		assert_eq!(Currency::free_balance(DOT, &user), dot_before - intention.amount_sell);
+		assert!(Currency::free_balance(ETH, &user) > eth_before);
+	});
+}
+
+#[test]
+fn resolve_single_intention_or_carry_forward_should_not_route_when_disabled() {
+	new_test_ext().execute_with(|| {
+		EnableRoutingMock::set(false);
+
+		let user = ALICE;
+		let pool_amount = 100_000_000_000_000;
+
+		initialize_pool(DOT, HDX, user, pool_amount, Price::from(2));
+		initialize_pool(HDX, ETH, user, pool_amount, Price::from(2));
+
+		let intention = Intention::<Test> {
+			who: user,
+			asset_sell: DOT,
+			asset_buy: ETH,
+			amount_sell: 1_000_000_000_000,
+			amount_buy: 0,
+			trade_limit: 0,
+			discount: false,
+			sell_or_buy: IntentionType::SELL,
+			intention_id: generate_intention_id(&user, 0),
+			remaining_lifetime: Zero::zero(),
+			recipient: None,
+			valid_until_timestamp: None,
+			reference: None,
+			allow_amm_fallback: true,
+			priority: 0,
+		};
+
+		let dot_before = Currency::free_balance(DOT, &user);
+
+		assert!(Exchange::resolve_single_intention_or_carry_forward(&intention)
+			.0
+			.is_none());
+
+		match last_event() {
+			TestEvent::exchange(RawEvent::IntentionResolveErrorEvent(who, sell, buy, kind, id, _)) => {
+				assert_eq!(who, user);
+				assert_eq!(sell, DOT);
+				assert_eq!(buy, ETH);
+				assert_eq!(kind, IntentionType::SELL);
+				assert_eq!(id, intention.intention_id);
+			}
+			other => panic!("expected an intention resolve error event, got {:?}", other),
+		}
+
+		assert_eq!(Currency::free_balance(DOT, &user), dot_before);
+	});
+}
+
+#[test]
+fn resolve_single_intention_or_carry_forward_should_not_route_when_an_intermediate_pool_is_missing() {
+	new_test_ext().execute_with(|| {
+		EnableRoutingMock::set(true);
+
+		let user = ALICE;
+		let pool_amount = 100_000_000_000_000;
+
+		// Only the first leg's pool exists - DOT/HDX - HDX/ETH does not.
+		initialize_pool(DOT, HDX, user, pool_amount, Price::from(2));
+
+		let intention = Intention::<Test> {
+			who: user,
+			asset_sell: DOT,
+			asset_buy: ETH,
+			amount_sell: 1_000_000_000_000,
+			amount_buy: 0,
+			trade_limit: 0,
+			discount: false,
+			sell_or_buy: IntentionType::SELL,
+			intention_id: generate_intention_id(&user, 0),
+			remaining_lifetime: Zero::zero(),
+			recipient: None,
+			valid_until_timestamp: None,
+			reference: None,
+			allow_amm_fallback: true,
+			priority: 0,
+		};
+
+		let dot_before = Currency::free_balance(DOT, &user);
+		let hdx_before = Currency::free_balance(HDX, &user);
+
+		assert!(Exchange::resolve_single_intention_or_carry_forward(&intention)
+			.0
+			.is_none());
+
+		match last_event() {
+			TestEvent::exchange(RawEvent::IntentionResolveErrorEvent(who, sell, buy, kind, id, _)) => {
+				assert_eq!(who, user);
+				assert_eq!(sell, DOT);
+				assert_eq!(buy, ETH);
+				assert_eq!(kind, IntentionType::SELL);
+				assert_eq!(id, intention.intention_id);
+			}
+			other => panic!("expected an intention resolve error event, got {:?}", other),
+		}
+
+		// The second leg (HDX -> ETH) is validated before either leg executes, so finding it has
+		// no pool aborts routing without ever touching the user's balance - unlike a stranded
+		// partial route, the intention is dropped via the normal error path with funds untouched.
+		assert_eq!(Currency::free_balance(DOT, &user), dot_before);
+		assert_eq!(Currency::free_balance(HDX, &user), hdx_before);
+	});
+}
+
+#[test]
+fn sell_should_set_intention_status_to_pending() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(AMMModule::create_pool(
+			Origin::signed(ALICE),
+			HDX,
+			ETH,
+			200_000_000_000_000,
+			Price::from(2)
+		));
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(ALICE),
+			HDX,
+			ETH,
+			1_000_000_000_000,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		let intention_id = Exchange::get_intentions((HDX, ETH))[0].intention_id;
+		assert_eq!(Exchange::intention_status(intention_id), Some(Status::Pending));
+	});
+}
+
+#[test]
+fn resolve_single_intention_should_set_status_to_filled_on_a_full_amm_fill() {
+	new_test_ext().execute_with(|| {
+		let user = ALICE;
+		let pool_amount = 100_000_000_000_000;
+
+		initialize_pool(HDX, ETH, user, pool_amount, Price::from(2));
+
+		let intention = Intention::<Test> {
+			who: user,
+			asset_sell: HDX,
+			asset_buy: ETH,
+			amount_sell: 1_000_000_000_000,
+			amount_buy: 0,
+			trade_limit: 0,
+			discount: false,
+			sell_or_buy: IntentionType::SELL,
+			intention_id: generate_intention_id(&user, 0),
+			remaining_lifetime: DefaultIntentionLifetime::get(),
+			recipient: None,
+			valid_until_timestamp: None,
+			reference: None,
+			allow_amm_fallback: true,
+			priority: 0,
+		};
+
+		IntentionStatus::<Test>::insert(intention.intention_id, Status::Pending);
+
+		Exchange::resolve_single_intention(&intention);
+
+		assert_eq!(Exchange::intention_status(intention.intention_id), Some(Status::Filled));
+	});
+}
+
+#[test]
+fn resolve_single_intention_should_set_status_to_partially_filled_when_partial_fill_applies() {
+	new_test_ext().execute_with(|| {
+		EnablePartialAMMFillMock::set(true);
+
+		let user = ALICE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000_000_000;
+
+		initialize_pool(asset_a, asset_b, user, pool_amount, Price::from(2));
+
+		let intention = Intention::<Test> {
+			who: user,
+			asset_sell: asset_a,
+			asset_buy: asset_b,
+			amount_sell: pool_amount,
+			amount_buy: 0,
+			trade_limit: 0,
+			discount: false,
+			sell_or_buy: IntentionType::SELL,
+			intention_id: generate_intention_id(&user, 0),
+			remaining_lifetime: DefaultIntentionLifetime::get(),
+			recipient: None,
+			valid_until_timestamp: None,
+			reference: None,
+			allow_amm_fallback: true,
+			priority: 0,
+		};
+
+		IntentionStatus::<Test>::insert(intention.intention_id, Status::Pending);
+
+		Exchange::resolve_single_intention(&intention);
+
+		assert_eq!(
+			Exchange::intention_status(intention.intention_id),
+			Some(Status::PartiallyFilled)
+		);
+	});
+}
+
+#[test]
+fn resolve_single_intention_should_set_status_to_failed_when_it_cannot_be_filled_at_all() {
+	new_test_ext().execute_with(|| {
+		EnablePartialAMMFillMock::set(false);
+
+		let user = ALICE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000_000_000;
+
+		initialize_pool(asset_a, asset_b, user, pool_amount, Price::from(2));
+
+		let intention = Intention::<Test> {
+			who: user,
+			asset_sell: asset_a,
+			asset_buy: asset_b,
+			amount_sell: pool_amount,
+			amount_buy: 0,
+			trade_limit: 0,
+			discount: false,
+			sell_or_buy: IntentionType::SELL,
+			intention_id: generate_intention_id(&user, 0),
+			remaining_lifetime: DefaultIntentionLifetime::get(),
+			recipient: None,
+			valid_until_timestamp: None,
+			reference: None,
+			allow_amm_fallback: true,
+			priority: 0,
+		};
+
+		IntentionStatus::<Test>::insert(intention.intention_id, Status::Pending);
+
+		Exchange::resolve_single_intention(&intention);
+
+		assert_eq!(Exchange::intention_status(intention.intention_id), Some(Status::Failed));
+	});
+}
+
+#[test]
+fn resolve_single_intention_or_carry_forward_should_set_status_to_amm_routed() {
+	new_test_ext().execute_with(|| {
+		EnableRoutingMock::set(true);
+
+		let user = ALICE;
+		let pool_amount = 100_000_000_000_000;
+
+		initialize_pool(DOT, HDX, user, pool_amount, Price::from(2));
+		initialize_pool(HDX, ETH, user, pool_amount, Price::from(2));
+
+		let intention = Intention::<Test> {
+			who: user,
+			asset_sell: DOT,
+			asset_buy: ETH,
+			amount_sell: 1_000_000_000_000,
+			amount_buy: 0,
+			trade_limit: 0,
+			discount: false,
+			sell_or_buy: IntentionType::SELL,
+			intention_id: generate_intention_id(&user, 0),
+			remaining_lifetime: DefaultIntentionLifetime::get(),
+			recipient: None,
+			valid_until_timestamp: None,
+			reference: None,
+			allow_amm_fallback: true,
+			priority: 0,
+		};
+
+		IntentionStatus::<Test>::insert(intention.intention_id, Status::Pending);
+
+		assert!(Exchange::resolve_single_intention_or_carry_forward(&intention)
+			.0
+			.is_none());
+
+		assert_eq!(
+			Exchange::intention_status(intention.intention_id),
+			Some(Status::AMMRouted)
+		);
+	});
+}
+
+#[test]
+fn intention_status_should_be_cleared_at_the_start_of_the_next_blocks_on_finalize() {
+	new_test_ext().execute_with(|| {
+		let user = ALICE;
+		let pool_amount = 100_000_000_000_000;
+
+		initialize_pool(HDX, ETH, user, pool_amount, Price::from(2));
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(user),
+			HDX,
+			ETH,
+			1_000_000_000_000,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		let intention_id = Exchange::get_intentions((HDX, ETH))[0].intention_id;
+
+		<Exchange as OnFinalize<u64>>::on_finalize(1);
+		assert_eq!(Exchange::intention_status(intention_id), Some(Status::Filled));
+
+		System::set_block_number(2);
+		<Exchange as OnFinalize<u64>>::on_finalize(2);
+		assert_eq!(Exchange::intention_status(intention_id), None);
+	});
+}
+
+#[test]
+fn intention_events_should_carry_a_block_number_to_disambiguate_replays_across_reorgs() {
+	new_test_ext().execute_with(|| {
+		let user = ALICE;
+		let pool_amount = 100_000_000_000_000;
+
+		initialize_pool(HDX, ETH, user, pool_amount, Price::from(2));
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(user),
+			HDX,
+			ETH,
+			1_000_000_000_000,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		let first_intention_id = Exchange::get_intentions((HDX, ETH))[0].intention_id;
+
+		match last_event() {
+			TestEvent::exchange(RawEvent::IntentionRegistered(_, _, _, _, _, id, _, block)) => {
+				assert_eq!(id, first_intention_id);
+				assert_eq!(block, 1);
+			}
+			_ => panic!("expected an IntentionRegistered event"),
+		}
+
+		<Exchange as OnFinalize<u64>>::on_finalize(1);
+		System::set_block_number(2);
+
+		// Replay the exact same extrinsic in a new block - as could happen after a re-org rolls
+		// `Nonce` back and a validator re-executes it.
+		assert_ok!(Exchange::sell(
+			Origin::signed(user),
+			HDX,
+			ETH,
+			1_000_000_000_000,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		let second_intention_id = Exchange::get_intentions((HDX, ETH))[0].intention_id;
+
+		match last_event() {
+			TestEvent::exchange(RawEvent::IntentionRegistered(_, _, _, _, _, id, _, block)) => {
+				assert_eq!(id, second_intention_id);
+				assert_eq!(block, 2);
+			}
+			_ => panic!("expected an IntentionRegistered event"),
+		}
+
+		assert_ne!((1u64, first_intention_id), (2u64, second_intention_id));
+	});
+}
+
+#[test]
+fn intention_status_should_be_none_for_an_unknown_intention_id() {
+	new_test_ext().execute_with(|| {
+		let unknown_id = generate_intention_id(&ALICE, 999);
+		assert_eq!(Exchange::intention_status(unknown_id), None);
+	});
+}
+
+#[test]
+fn pair_account_should_match_the_account_used_by_on_finalize() {
+	new_test_ext().execute_with(|| {
+		let user = ALICE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000_000_000;
+
+		initialize_pool(asset_a, asset_b, user, pool_amount, Price::from(2));
+
+		// `on_finalize` derives the pair account via `T::AMMPool::get_pair_id` - `pair_account`
+		// exists purely to expose that same derivation to external tools.
+		assert_eq!(
+			Exchange::pair_account(asset_a, asset_b),
+			AMMModule::get_pair_id(&asset_a, &asset_b)
+		);
+		assert_eq!(
+			Exchange::pair_account(asset_a, asset_b),
+			Exchange::pair_account(asset_b, asset_a)
+		);
+	});
+}
+
+#[test]
+fn spot_price_should_reflect_the_pools_reserve_ratio() {
+	new_test_ext().execute_with(|| {
+		let user = ALICE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+
+		// A 1:2 initial price means the pool holds twice as much `asset_b` as `asset_a`.
+		initialize_pool(asset_a, asset_b, user, 100_000_000_000_000, Price::from(2));
+
+		assert_eq!(Exchange::spot_price(asset_a, asset_b), Some(2));
+		assert_eq!(Exchange::spot_price(asset_b, asset_a), Some(0));
+	});
+}
+
+#[test]
+fn spot_price_should_be_none_when_no_pool_exists() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(Exchange::spot_price(ETH, DOT), None);
+	});
+}
+
+#[test]
+fn quote_buy_should_reflect_the_pools_reserve_ratio() {
+	new_test_ext().execute_with(|| {
+		let user = ALICE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+
+		// A 1:2 initial price means the pool holds twice as much `asset_b` as `asset_a` - buying
+		// 1 unit of `asset_b` only costs half a unit of `asset_a`, which truncates to `0`.
+		initialize_pool(asset_a, asset_b, user, 100_000_000_000_000, Price::from(2));
+
+		assert_eq!(Exchange::quote_buy(asset_b, asset_a, 1), Some(0));
+		assert_eq!(Exchange::quote_buy(asset_a, asset_b, 1), Some(2));
+	});
+}
+
+#[test]
+fn quote_buy_should_match_the_amm_pools_input_required_for_a_larger_output() {
+	new_test_ext().execute_with(|| {
+		let user = ALICE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+
+		initialize_pool(asset_a, asset_b, user, 100_000_000_000_000, Price::from(2));
+
+		let amount_out = 10_000_000_000;
+		let expected_sell = AMMModule::get_spot_price_unchecked(asset_b, asset_a, amount_out);
+		assert!(expected_sell > 0);
+
+		assert_eq!(Exchange::quote_buy(asset_b, asset_a, amount_out), Some(expected_sell));
+	});
+}
+
+#[test]
+fn quote_buy_should_be_none_when_no_pool_exists() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(Exchange::quote_buy(ETH, DOT, 1_000), None);
+	});
+}
+
+#[test]
+fn freeze_asset_should_require_root_origin() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			Exchange::freeze_asset(Origin::signed(ALICE), ETH),
+			DispatchError::BadOrigin
+		);
+	});
+}
+
+#[test]
+fn thaw_asset_should_require_root_origin() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			Exchange::thaw_asset(Origin::signed(ALICE), ETH),
+			DispatchError::BadOrigin
+		);
+	});
+}
+
+#[test]
+fn freeze_asset_then_thaw_asset_should_toggle_frozen_state() {
+	new_test_ext().execute_with(|| {
+		assert!(!Exchange::is_asset_frozen(ETH));
+
+		assert_ok!(Exchange::freeze_asset(frame_system::RawOrigin::Root.into(), ETH));
+		expect_event(RawEvent::AssetFrozen(ETH));
+		assert!(Exchange::is_asset_frozen(ETH));
+
+		assert_ok!(Exchange::thaw_asset(frame_system::RawOrigin::Root.into(), ETH));
+		expect_event(RawEvent::AssetThawed(ETH));
+		assert!(!Exchange::is_asset_frozen(ETH));
+	});
+}
+
+#[test]
+fn sell_should_fail_when_sold_asset_is_frozen() {
+	new_test_ext().execute_with(|| {
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000_000_000;
+
+		initialize_pool(asset_a, asset_b, ALICE, pool_amount, Price::from(2));
+
+		assert_ok!(Exchange::freeze_asset(frame_system::RawOrigin::Root.into(), asset_a));
+
+		assert_noop!(
+			Exchange::sell(
+				Origin::signed(BOB),
+				asset_a,
+				asset_b,
+				1_000_000_000_000,
+				0,
+				false,
+				None,
+				None,
+				None,
+				true,
+				false,
+				0,
+				Price::from(1),
+			),
+			Error::<Test>::AssetFrozen.with_weight(<Test as Config>::WeightInfo::reject_intention())
+		);
+	});
+}
+
+#[test]
+fn buy_should_fail_when_bought_asset_is_frozen() {
+	new_test_ext().execute_with(|| {
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000_000_000;
+
+		initialize_pool(asset_a, asset_b, ALICE, pool_amount, Price::from(2));
+
+		assert_ok!(Exchange::freeze_asset(frame_system::RawOrigin::Root.into(), asset_a));
+
+		// `asset_buy` (the first asset argument to `buy`) is the one frozen here.
+		assert_noop!(
+			Exchange::buy(
+				Origin::signed(BOB),
+				asset_a,
+				asset_b,
+				1_000_000_000_000,
+				u128::MAX,
+				false,
+				None,
+				None,
+				None,
+				true,
+				false,
+				0,
+				Price::from(1),
+			),
+			Error::<Test>::AssetFrozen.with_weight(<Test as Config>::WeightInfo::reject_intention())
+		);
+	});
+}
+
+#[test]
+fn sell_should_reject_an_unknown_asset() {
+	// No pool needs to be set up at all - an asset with zero issuance is rejected before
+	// `TokenPoolNotFound` is even checked.
+	new_test_ext().execute_with(|| {
+		let unknown_asset = 9999;
+
+		assert_noop!(
+			Exchange::sell(
+				Origin::signed(ALICE),
+				HDX,
+				unknown_asset,
+				1_000_000_000_000,
+				0,
+				false,
+				None,
+				None,
+				None,
+				true,
+				false,
+				0,
+				Price::from(1),
+			),
+			Error::<Test>::UnknownAsset.with_weight(<Test as Config>::WeightInfo::reject_intention())
+		);
+	});
+}
+
+#[test]
+fn buy_should_reject_an_unknown_asset() {
+	new_test_ext().execute_with(|| {
+		let unknown_asset = 9999;
+
+		assert_noop!(
+			Exchange::buy(
+				Origin::signed(ALICE),
+				unknown_asset,
+				HDX,
+				1_000_000_000_000,
+				u128::MAX,
+				false,
+				None,
+				None,
+				None,
+				true,
+				false,
+				0,
+				Price::from(1),
+			),
+			Error::<Test>::UnknownAsset.with_weight(<Test as Config>::WeightInfo::reject_intention())
+		);
+	});
+}
+
+#[test]
+fn on_finalize_should_skip_pair_and_carry_intentions_forward_when_one_leg_is_frozen() {
+	new_test_ext().execute_with(|| {
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000_000_000;
+
+		initialize_pool(asset_a, asset_b, ALICE, pool_amount, Price::from(2));
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(BOB),
+			asset_a,
+			asset_b,
+			1_000_000_000_000,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		// Freeze after registering the intention, so it's already sitting in storage when
+		// `on_finalize` runs - `on_finalize` should skip it rather than settle it.
+		assert_ok!(Exchange::freeze_asset(frame_system::RawOrigin::Root.into(), asset_b));
+
+		let balance_before = Currency::free_balance(asset_a, &BOB);
+
+		<Exchange as OnFinalize<u64>>::on_finalize(9);
+
+		assert_eq!(Currency::free_balance(asset_a, &BOB), balance_before);
+		assert_eq!(
+			Exchange::get_intentions_count((cmp::min(asset_a, asset_b), cmp::max(asset_a, asset_b))),
+			1
+		);
+
+		// Thawing and finalizing again should settle the carried-forward intention normally.
+		assert_ok!(Exchange::thaw_asset(frame_system::RawOrigin::Root.into(), asset_b));
+
+		<Exchange as OnFinalize<u64>>::on_finalize(10);
+
+		assert_ne!(Currency::free_balance(asset_a, &BOB), balance_before);
+	});
+}
+
+#[test]
+fn collected_fees_should_accumulate_across_multiple_direct_trades() {
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let user_3 = CHARLIE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000_000_000;
+		let pair = (cmp::min(asset_a, asset_b), cmp::max(asset_a, asset_b));
+
+		initialize_pool(asset_a, asset_b, user_1, pool_amount, Price::from(2));
+
+		assert_eq!(Exchange::get_collected_fees(pair), 0);
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_2),
+			asset_a,
+			asset_b,
+			2_000_000_000_000,
+			20000000000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		assert_ok!(Exchange::buy(
+			Origin::signed(user_3),
+			asset_a,
+			asset_b,
+			1_000_000_000_000,
+			4_000_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		<Exchange as OnFinalize<u64>>::on_finalize(9);
+
+		// Two `IntentionResolvedDirectTradeFees` events fire for this direct trade, 2_000_000_000
+		// and 4_000_000_000 of `asset_b` respectively - both to the pair account.
+		let fees_after_first_round = Exchange::get_collected_fees(pair);
+		assert_eq!(fees_after_first_round, 6_000_000_000);
+		assert_eq!(Exchange::collected_fees(asset_a, asset_b), fees_after_first_round);
+		assert_eq!(Exchange::collected_fees(asset_b, asset_a), fees_after_first_round);
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_2),
+			asset_a,
+			asset_b,
+			2_000_000_000_000,
+			20000000000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		assert_ok!(Exchange::buy(
+			Origin::signed(user_3),
+			asset_a,
+			asset_b,
+			1_000_000_000_000,
+			4_000_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		<Exchange as OnFinalize<u64>>::on_finalize(10);
+
+		// Cumulative, never reset - the second round's fees are added on top of the first.
+		assert_eq!(Exchange::get_collected_fees(pair), fees_after_first_round * 2);
+	});
+}
+
+#[test]
+fn asset_volume_should_accumulate_for_both_assets_of_a_matched_pair() {
+	// Same mixed scenario as sell_test_standard: half of user_2's sell matches user_3's buy
+	// directly, the other half falls through to the AMM. Each settled leg (both direct legs, plus
+	// the AMM fill) adds to AssetVolume for both assets it touches, so a direct match counts twice
+	// per side - once from each leg's own perspective.
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let user_3 = CHARLIE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000_000_000;
+		let initial_price = Price::from(2);
+
+		initialize_pool(asset_a, asset_b, user_1, pool_amount, initial_price);
+
+		assert_eq!(Exchange::asset_volume(asset_a), 0);
+		assert_eq!(Exchange::asset_volume(asset_b), 0);
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_2),
+			asset_a,
+			asset_b,
+			2_000_000_000_000,
+			300_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		assert_ok!(Exchange::buy(
+			Origin::signed(user_3),
+			asset_a,
+			asset_b,
+			1_000_000_000_000,
+			4_000_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		<Exchange as OnFinalize<u64>>::on_finalize(9);
+
+		// Direct trade: 1_000_000_000_000 of asset_a for 2_000_000_000_000 of asset_b, recorded
+		// once per leg (so twice per asset). AMM fallback: user_2's remaining 1_000_000_000_000 of
+		// asset_a for 1_976_336_046_259 of asset_b, recorded once.
+		assert_eq!(Exchange::asset_volume(asset_a), 3_000_000_000_000);
+		assert_eq!(Exchange::asset_volume(asset_b), 5_976_336_046_259);
+	});
+}
+
+#[test]
+fn fee_collected_events_should_sum_to_the_configured_fee_rate() {
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let user_3 = CHARLIE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000_000_000;
+		let pair_account = AMMModule::get_pair_id(&asset_a, &asset_b);
+		let pair = (cmp::min(asset_a, asset_b), cmp::max(asset_a, asset_b));
+
+		initialize_pool(asset_a, asset_b, user_1, pool_amount, Price::from(2));
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_2),
+			asset_a,
+			asset_b,
+			2_000_000_000_000,
+			20000000000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		assert_ok!(Exchange::buy(
+			Origin::signed(user_3),
+			asset_a,
+			asset_b,
+			1_000_000_000_000,
+			4_000_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		<Exchange as OnFinalize<u64>>::on_finalize(9);
+
+		let total_fees_collected: Balance = system::Module::<Test>::events()
+			.into_iter()
+			.filter_map(|record| match record.event {
+				TestEvent::exchange(RawEvent::FeeCollected(asset, amount, to)) => {
+					assert!(asset == asset_a || asset == asset_b);
+					assert_eq!(to, pair_account);
+					Some(amount)
+				}
+				_ => None,
+			})
+			.sum();
+
+		// Matches the trade sizes from `collected_fees_should_accumulate_across_multiple_direct_trades` -
+		// a 1_000_000_000_000 and a 2_000_000_000_000 direct-trade leg, each charged `ExchangeFeeRate`.
+		let expected_fees = 1_000_000_000_000u128.just_fee(Fee::default()).unwrap()
+			+ 2_000_000_000_000u128.just_fee(Fee::default()).unwrap();
+
+		assert_eq!(total_fees_collected, expected_fees);
+		assert_eq!(total_fees_collected, Exchange::get_collected_fees(pair));
+	});
+}
+
+#[test]
+fn just_fee_round_up_should_never_leak_value_in_either_direction() {
+	// `just_fee_round_up` is what `DirectTradeData::prepare` charges on top of a trade's main
+	// transfer, so for every gross amount the fee it reports plus what's left over for the
+	// trader must reconstruct the gross exactly, and the fee itself must never be less than the
+	// exact, unrounded fee - otherwise the pool is short-changed by truncation.
+	let fee = Fee::default();
+	for gross in (0..2_000_000u128).step_by(997) {
+		let rounded_up = gross.just_fee_round_up(fee).unwrap();
+		let net = gross.checked_sub(rounded_up).unwrap();
+
+		assert_eq!(net + rounded_up, gross);
+		assert!(
+			rounded_up as u128 * fee.denominator as u128 >= gross as u128 * fee.numerator as u128,
+			"rounded-up fee for {} must not be smaller than the exact fee",
+			gross
+		);
+		// Rounding up should never overshoot the exact fee by more than the smallest unit.
+		assert!(rounded_up == gross.just_fee(fee).unwrap() || rounded_up == gross.just_fee(fee).unwrap() + 1);
+	}
+}
+
+#[test]
+fn calculate_fee_should_floor_a_tiny_amounts_fee_up_to_min_fee() {
+	new_test_ext().execute_with(|| {
+		MinFeeMock::set(100);
+
+		// `ExchangeFeeRate` on `1_000` rounds up to a fee well below the floor - without the clamp
+		// this trade would settle far cheaper than `MinFee` is meant to guarantee.
+		let unclamped = 1_000u128.just_fee_round_up(Fee::default()).unwrap();
+		assert!(unclamped < 100);
+		assert_eq!(Exchange::calculate_fee(1_000, Fee::default()), Some(100));
+	});
+}
+
+#[test]
+fn calculate_fee_should_cap_the_floored_fee_at_the_amount_itself_for_dust() {
+	new_test_ext().execute_with(|| {
+		// `MinFee` set higher than the trade itself - the floor must never push the fee past
+		// `amount`, or a dust-sized trade would be charged more than it's worth.
+		MinFeeMock::set(100);
+
+		assert_eq!(Exchange::calculate_fee(40, Fee::default()), Some(40));
+	});
+}
+
+#[test]
+fn calculate_fee_should_cap_a_huge_amounts_fee_at_max_fee() {
+	new_test_ext().execute_with(|| {
+		MaxFeeMock::set(500);
+
+		// `ExchangeFeeRate` on a trade this large would ordinarily charge well above the ceiling.
+		let unclamped = 10_000_000_000_000u128.just_fee_round_up(Fee::default()).unwrap();
+		assert!(unclamped > 500);
+		assert_eq!(Exchange::calculate_fee(10_000_000_000_000, Fee::default()), Some(500));
+	});
+}
+
+#[test]
+fn on_idle_should_only_clean_pairs_that_fit_the_remaining_weight() {
+	new_test_ext().execute_with(|| {
+		let expired = |asset_sell: u32, asset_buy: u32, id: u32| Intention::<Test> {
+			who: ALICE,
+			asset_sell,
+			asset_buy,
+			amount_sell: 1_000,
+			amount_buy: 1_000,
+			trade_limit: 0,
+			discount: false,
+			sell_or_buy: IntentionType::SELL,
+			intention_id: generate_intention_id_for_pair(&ALICE, id, asset_sell, asset_buy),
+			remaining_lifetime: Zero::zero(),
+			recipient: None,
+			valid_until_timestamp: None,
+			reference: None,
+			allow_amm_fallback: true,
+			priority: 0,
+		};
+
+		<ExchangeAssetsIntentions<Test>>::insert((HDX, DOT), vec![expired(HDX, DOT, 0)]);
+		ExchangeAssetsIntentionCount::insert((cmp::min(HDX, DOT), cmp::max(HDX, DOT)), 1);
+
+		<ExchangeAssetsIntentions<Test>>::insert((DOT, ETH), vec![expired(DOT, ETH, 0)]);
+		ExchangeAssetsIntentionCount::insert((cmp::min(DOT, ETH), cmp::max(DOT, ETH)), 1);
+
+		let check_cost = <Test as Config>::WeightInfo::on_idle_intention_check();
+
+		// Only enough weight for one pair's single intention - the other pair must be left
+		// completely untouched for a future `on_idle` call or `on_finalize` to handle.
+		let consumed = Exchange::clean_expired_intentions(check_cost);
+
+		assert_eq!(consumed, check_cost);
+
+		let hdx_dot_cleaned = Exchange::get_intentions((HDX, DOT)).is_empty();
+		let dot_eth_cleaned = Exchange::get_intentions((DOT, ETH)).is_empty();
+
+		// Pairs are visited in ascending `(asset_sell, asset_buy)` order, so `(HDX, DOT)` is
+		// cleaned before `(DOT, ETH)`.
+		assert!(hdx_dot_cleaned);
+		assert!(!dot_eth_cleaned);
+		assert_eq!(
+			Exchange::get_intentions_count((cmp::min(HDX, DOT), cmp::max(HDX, DOT))),
+			0
+		);
+		assert_eq!(
+			Exchange::get_intentions_count((cmp::min(DOT, ETH), cmp::max(DOT, ETH))),
+			1
+		);
+
+		let events = system::Module::<Test>::events();
+		assert!(events
+			.iter()
+			.any(|e| matches!(e.event, TestEvent::exchange(RawEvent::AMMSellErrorEvent(..)))));
+
+		// The remaining pair is cleaned on a later call once there's enough weight for it too.
+		let consumed = Exchange::clean_expired_intentions(check_cost);
+		assert_eq!(consumed, check_cost);
+		assert!(Exchange::get_intentions((DOT, ETH)).is_empty());
+	});
+}
+
+fn dummy_sell_intention(
+	who: <Test as system::Config>::AccountId,
+	asset_sell: u32,
+	asset_buy: u32,
+	amount_sell: u128,
+	trade_limit: u128,
+) -> Intention<Test> {
+	Intention::<Test> {
+		who,
+		asset_sell,
+		asset_buy,
+		amount_sell,
+		amount_buy: 0,
+		trade_limit,
+		discount: false,
+		sell_or_buy: IntentionType::SELL,
+		intention_id: generate_intention_id_for_pair(&who, 0, asset_sell, asset_buy),
+		remaining_lifetime: DefaultIntentionLifetime::get(),
+		recipient: None,
+		valid_until_timestamp: None,
+		reference: None,
+		allow_amm_fallback: true,
+		priority: 0,
+	}
+}
+
+#[test]
+fn classify_amm_failure_should_report_pool_missing() {
+	new_test_ext().execute_with(|| {
+		let intention = dummy_sell_intention(ALICE, HDX, DOT, 1_000, 0);
+
+		assert_eq!(
+			Exchange::classify_amm_failure(&intention),
+			AMMFailureReason::PoolMissing
+		);
+	});
+}
+
+#[test]
+fn classify_amm_failure_should_report_trade_limit_exceeded() {
+	new_test_ext().execute_with(|| {
+		let asset_a = ETH;
+		let asset_b = DOT;
+
+		initialize_pool(asset_a, asset_b, ALICE, 100_000_000, Price::from(2));
+
+		// A trade limit no amount of pool liquidity could ever satisfy for this trade size.
+		let intention = dummy_sell_intention(BOB, asset_a, asset_b, 2_000, 1_000_000_000_000);
+
+		assert_eq!(
+			Exchange::classify_amm_failure(&intention),
+			AMMFailureReason::TradeLimitExceeded
+		);
+	});
+}
+
+#[test]
+fn classify_amm_failure_should_report_other_when_limit_is_not_the_cause() {
+	new_test_ext().execute_with(|| {
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000_000_000;
+
+		initialize_pool(asset_a, asset_b, ALICE, pool_amount, Price::from(2));
+
+		// Breaches the max-in-ratio limit regardless of `trade_limit` - relaxing it to `0`
+		// wouldn't help, so this isn't a trade-limit rejection.
+		let intention = dummy_sell_intention(BOB, asset_a, asset_b, 40_000_000_000_000, 0);
+
+		assert_eq!(Exchange::classify_amm_failure(&intention), AMMFailureReason::Other);
+	});
+}
+
+#[test]
+fn replace_intention_should_increase_a_pending_sell_amount() {
+	new_test_ext().execute_with(|| {
+		let user = ALICE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+
+		initialize_pool(asset_a, asset_b, user, 100_000_000_000_000, Price::from(2));
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(user),
+			asset_a,
+			asset_b,
+			1_000_000_000_000,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		let intention_id = generate_intention_id_for_pair(&user, 0, asset_a, asset_b);
+
+		let new_amount = 2_000_000_000_000;
+		assert_ok!(Exchange::replace_intention(
+			Origin::signed(user),
+			asset_a,
+			asset_b,
+			intention_id,
+			new_amount,
+		));
+
+		let intentions = Exchange::get_intentions((asset_a, asset_b));
+		assert_eq!(intentions.len(), 1);
+		assert_eq!(intentions[0].intention_id, intention_id);
+		assert_eq!(intentions[0].amount_sell, new_amount);
+		assert_eq!(
+			Exchange::get_intentions_count((cmp::min(asset_a, asset_b), cmp::max(asset_a, asset_b))),
+			1
+		);
+
+		expect_event(RawEvent::IntentionReplaced(
+			user,
+			asset_a,
+			asset_b,
+			intention_id,
+			new_amount,
+		));
+	});
+}
+
+#[test]
+fn replace_intention_should_decrease_a_pending_sell_amount() {
+	new_test_ext().execute_with(|| {
+		let user = ALICE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+
+		initialize_pool(asset_a, asset_b, user, 100_000_000_000_000, Price::from(2));
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(user),
+			asset_a,
+			asset_b,
+			2_000_000_000_000,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		let intention_id = generate_intention_id_for_pair(&user, 0, asset_a, asset_b);
+
+		let new_amount = 1_000_000_000_000;
+		assert_ok!(Exchange::replace_intention(
+			Origin::signed(user),
+			asset_a,
+			asset_b,
+			intention_id,
+			new_amount,
+		));
+
+		let intentions = Exchange::get_intentions((asset_a, asset_b));
+		assert_eq!(intentions[0].amount_sell, new_amount);
+	});
+}
+
+#[test]
+fn replace_intention_should_fail_when_caller_is_not_the_owner() {
+	new_test_ext().execute_with(|| {
+		let owner = ALICE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+
+		initialize_pool(asset_a, asset_b, owner, 100_000_000_000_000, Price::from(2));
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(owner),
+			asset_a,
+			asset_b,
+			1_000_000_000_000,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		let intention_id = generate_intention_id_for_pair(&owner, 0, asset_a, asset_b);
+
+		assert_noop!(
+			Exchange::replace_intention(Origin::signed(BOB), asset_a, asset_b, intention_id, 500_000_000_000),
+			Error::<Test>::NotIntentionOwner
+		);
+	});
+}
+
+#[test]
+fn replace_intention_should_fail_when_new_amount_exceeds_free_balance() {
+	new_test_ext().execute_with(|| {
+		let user = ALICE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+
+		initialize_pool(asset_a, asset_b, user, 100_000_000_000_000, Price::from(2));
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(user),
+			asset_a,
+			asset_b,
+			1_000_000_000_000,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		let intention_id = generate_intention_id_for_pair(&user, 0, asset_a, asset_b);
+
+		let too_much = Currency::free_balance(asset_a, &user) + 1;
+		assert_noop!(
+			Exchange::replace_intention(Origin::signed(user), asset_a, asset_b, intention_id, too_much),
+			Error::<Test>::InsufficientAssetBalance
+		);
+	});
+}
+
+#[test]
+fn replace_intention_should_fail_when_intention_not_found() {
+	new_test_ext().execute_with(|| {
+		let user = ALICE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+
+		initialize_pool(asset_a, asset_b, user, 100_000_000_000_000, Price::from(2));
+
+		let bogus_id = generate_intention_id_for_pair(&user, 999, asset_a, asset_b);
+
+		assert_noop!(
+			Exchange::replace_intention(Origin::signed(user), asset_a, asset_b, bogus_id, 1_000_000_000_000),
+			Error::<Test>::IntentionNotFound
+		);
+	});
+}
+
+#[test]
+fn cancel_pair_should_only_clear_the_callers_orders_on_the_targeted_pair() {
+	new_test_ext().execute_with(|| {
+		let maker = ALICE;
+		let user = BOB;
+		let other_trader = CHARLIE;
+
+		initialize_pool(ETH, DOT, maker, 100_000_000_000_000, Price::from(2));
+		initialize_pool(HDX, DOT, maker, 100_000_000_000_000, Price::from(1));
+
+		// user's two orders on ETH/DOT (one on each side) - these should be cancelled.
+		assert_ok!(Exchange::sell(
+			Origin::signed(user),
+			ETH,
+			DOT,
+			1_000_000_000_000,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		assert_ok!(Exchange::sell(
+			Origin::signed(user),
+			DOT,
+			ETH,
+			1_000_000_000_000,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		// other_trader's order on the same pair - must be left alone.
+		assert_ok!(Exchange::sell(
+			Origin::signed(other_trader),
+			ETH,
+			DOT,
+			500_000_000_000,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		// user's order on an unrelated pair - must be left alone.
+		assert_ok!(Exchange::sell(
+			Origin::signed(user),
+			HDX,
+			DOT,
+			1_000_000_000_000,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		let eth_dot_pair = (cmp::min(ETH, DOT), cmp::max(ETH, DOT));
+		let hdx_dot_pair = (cmp::min(HDX, DOT), cmp::max(HDX, DOT));
+		assert_eq!(Exchange::get_intentions_count(eth_dot_pair), 3);
+		assert_eq!(Exchange::get_intentions_count(hdx_dot_pair), 1);
+		assert_eq!(TotalIntentions::get(), 4);
+
+		assert_ok!(Exchange::cancel_pair(Origin::signed(user), ETH, DOT));
+
+		// user's two ETH/DOT orders are gone ...
+		assert!(Exchange::get_intentions((ETH, DOT)).is_empty());
+		assert!(Exchange::get_intentions((DOT, ETH)).iter().all(|i| i.who != user));
+		// ... other_trader's order on the same pair survives ...
+		assert_eq!(
+			Exchange::get_intentions((ETH, DOT)).len() + Exchange::get_intentions((DOT, ETH)).len(),
+			1
+		);
+		assert_eq!(Exchange::get_intentions_count(eth_dot_pair), 1);
+		// ... and user's order on the unrelated pair is untouched.
+		assert_eq!(Exchange::get_intentions_count(hdx_dot_pair), 1);
+		assert_eq!(TotalIntentions::get(), 2);
+
+		expect_event(RawEvent::IntentionsCancelledForPair(user, ETH, DOT, 2));
+	});
+}
+
+#[test]
+fn cancel_pair_should_be_a_no_op_when_the_caller_has_no_orders_on_that_pair() {
+	new_test_ext().execute_with(|| {
+		let maker = ALICE;
+		let user = BOB;
+
+		initialize_pool(ETH, DOT, maker, 100_000_000_000_000, Price::from(2));
+		assert_ok!(Exchange::sell(
+			Origin::signed(maker),
+			ETH,
+			DOT,
+			1_000_000_000_000,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		assert_ok!(Exchange::cancel_pair(Origin::signed(user), ETH, DOT));
+
+		assert_eq!(Exchange::get_intentions((ETH, DOT)).len(), 1);
+		expect_event(RawEvent::IntentionsCancelledForPair(user, ETH, DOT, 0));
+	});
+}
+
+#[test]
+fn reserved_balance_should_sum_open_intentions_selling_the_same_asset_across_pairs() {
+	new_test_ext().execute_with(|| {
+		let maker = ALICE;
+		let user = BOB;
+
+		initialize_pool(ETH, DOT, maker, 100_000_000_000_000, Price::from(2));
+		initialize_pool(HDX, ETH, maker, 100_000_000_000_000, Price::from(1));
+
+		// user sells ETH on two different pairs - both amounts should be counted.
+		assert_ok!(Exchange::sell(
+			Origin::signed(user),
+			ETH,
+			DOT,
+			1_000_000_000_000,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		assert_ok!(Exchange::sell(
+			Origin::signed(user),
+			ETH,
+			HDX,
+			2_000_000_000_000,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		// user sells DOT too - shouldn't be counted towards their reserved ETH.
+		assert_ok!(Exchange::sell(
+			Origin::signed(user),
+			DOT,
+			ETH,
+			500_000_000_000,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		// another trader selling ETH shouldn't be counted towards user's reserved balance either.
+		assert_ok!(Exchange::sell(
+			Origin::signed(maker),
+			ETH,
+			DOT,
+			300_000_000_000,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		assert_eq!(
+			Exchange::reserved_balance(user, ETH),
+			1_000_000_000_000 + 2_000_000_000_000
+		);
+		assert_eq!(Exchange::reserved_balance(user, DOT), 500_000_000_000);
+		assert_eq!(Exchange::reserved_balance(user, HDX), 0);
+	});
+}
+
+#[test]
+fn active_pairs_should_list_every_pair_with_a_nonzero_queued_intention_count() {
+	new_test_ext().execute_with(|| {
+		let maker = ALICE;
+		let user = BOB;
+
+		initialize_pool(ETH, DOT, maker, 100_000_000_000_000, Price::from(2));
+		initialize_pool(HDX, ETH, maker, 100_000_000_000_000, Price::from(1));
+		initialize_pool(HDX, DOT, maker, 100_000_000_000_000, Price::from(1));
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(user),
+			ETH,
+			DOT,
+			1_000_000_000_000,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		assert_ok!(Exchange::sell(
+			Origin::signed(user),
+			HDX,
+			ETH,
+			2_000_000_000_000,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		assert_ok!(Exchange::sell(
+			Origin::signed(maker),
+			ETH,
+			DOT,
+			300_000_000_000,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		// HDX/DOT has a pool but nothing queued against it - shouldn't show up.
+
+		let mut active_pairs = Exchange::active_pairs();
+		active_pairs.sort();
+
+		let mut expected: Vec<_> = vec![
+			(
+				cmp::min(ETH, DOT),
+				cmp::max(ETH, DOT),
+				Exchange::get_intentions_count((cmp::min(ETH, DOT), cmp::max(ETH, DOT))),
+			),
+			(
+				cmp::min(HDX, ETH),
+				cmp::max(HDX, ETH),
+				Exchange::get_intentions_count((cmp::min(HDX, ETH), cmp::max(HDX, ETH))),
+			),
+		];
+		expected.sort();
+
+		assert_eq!(active_pairs, expected);
+		assert_eq!(
+			Exchange::get_intentions_count((cmp::min(ETH, DOT), cmp::max(ETH, DOT))),
+			2
+		);
+		assert_eq!(
+			Exchange::get_intentions_count((cmp::min(HDX, ETH), cmp::max(HDX, ETH))),
+			1
+		);
+		assert!(!active_pairs
+			.iter()
+			.any(|(a, b, _)| (*a, *b) == (cmp::min(HDX, DOT), cmp::max(HDX, DOT))));
+	});
+}
+
+#[test]
+fn counterparty_below_min_match_size_should_be_amm_routed_instead_of_matched() {
+	// user_2's ETH sell is large enough to match user_3's tiny DOT sell in full, but with
+	// `MinMatchSize` set above user_3's amount, the grouping step must leave user_3 out of the
+	// match entirely - both sides then settle via the AMM fallback instead of a direct trade.
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let user_3 = CHARLIE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+
+		MinMatchSizeMock::set(500);
+
+		initialize_pool(asset_a, asset_b, user_1, 100_000_000_000_000, Price::from(2));
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_2),
+			asset_a,
+			asset_b,
+			1_000,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_3),
+			asset_b,
+			asset_a,
+			100,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		<Exchange as OnFinalize<u64>>::on_finalize(9);
+
+		assert!(!system::Module::<Test>::events().into_iter().any(|record| matches!(
+			record.event,
+			TestEvent::exchange(RawEvent::IntentionResolvedDirectTrade(..))
+		)));
+
+		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 0);
+
+		MinMatchSizeMock::set(0);
+	});
+}
+
+#[test]
+fn on_trade_handler_should_fire_for_both_the_direct_match_and_the_amm_fallback_leg() {
+	// Same scenario as `sell_test_standard`: user_2's SELL only partially matches user_3's BUY
+	// directly, so user_2's leftover is routed through the AMM - `OnTradeHandler` must be notified
+	// once for each of the three settled legs (both sides of the direct match, plus the AMM leg).
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let user_3 = CHARLIE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+
+		initialize_pool(asset_a, asset_b, user_1, 100_000_000_000_000, Price::from(2));
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_2),
+			asset_a,
+			asset_b,
+			2_000_000_000_000,
+			300_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		assert_ok!(Exchange::buy(
+			Origin::signed(user_3),
+			asset_a,
+			asset_b,
+			1_000_000_000_000,
+			4_000_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		OnTradeHandlerMock::reset();
+
+		<Exchange as OnFinalize<u64>>::on_finalize(9);
+
+		assert_eq!(
+			OnTradeHandlerMock::calls(),
+			vec![
+				(user_2, asset_a, asset_b, 1_000_000_000_000, 2_000_000_000_000),
+				(user_3, asset_b, asset_a, 2_000_000_000_000, 1_000_000_000_000),
+				(user_2, asset_a, asset_b, 1_000_000_000_000, 1_974_336_046_259),
+			]
+		);
+	});
+}
+
+#[test]
+fn set_pair_max_slippage_should_require_root() {
+	new_test_ext().execute_with(|| {
+		assert_noop!(
+			Exchange::set_pair_max_slippage(Origin::signed(ALICE), HDX, ETH, Permill::from_percent(1)),
+			DispatchError::BadOrigin
+		);
+	});
+}
+
+#[test]
+fn sell_should_apply_pair_default_slippage_when_caller_gives_no_limit() {
+	new_test_ext().execute_with(|| {
+		initialize_pool(HDX, ETH, ALICE, 100_000_000_000_000, Price::from(2));
+
+		assert_ok!(Exchange::set_pair_max_slippage(
+			Origin::root(),
+			HDX,
+			ETH,
+			Permill::from_percent(1)
+		));
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(BOB),
+			HDX,
+			ETH,
+			1_000_000_000_000,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		let intention = &Exchange::get_intentions((HDX, ETH))[0];
+		let expected_trade_limit = intention
+			.amount_buy
+			.saturating_sub(Permill::from_percent(1).mul_ceil(intention.amount_buy));
+		assert_eq!(intention.trade_limit, expected_trade_limit);
+	});
+}
+
+#[test]
+fn sell_should_use_the_stricter_of_caller_and_pair_default_slippage() {
+	new_test_ext().execute_with(|| {
+		initialize_pool(HDX, ETH, ALICE, 100_000_000_000_000, Price::from(2));
+
+		assert_ok!(Exchange::set_pair_max_slippage(
+			Origin::root(),
+			HDX,
+			ETH,
+			Permill::from_percent(1)
+		));
+
+		// A looser caller-provided min_bought than the pair default is overridden by the default.
+		assert_ok!(Exchange::sell(
+			Origin::signed(BOB),
+			HDX,
+			ETH,
+			1_000_000_000_000,
+			1,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		let looser_intention = &Exchange::get_intentions((HDX, ETH))[0];
+		let default_min_bought = looser_intention
+			.amount_buy
+			.saturating_sub(Permill::from_percent(1).mul_ceil(looser_intention.amount_buy));
+		assert_eq!(looser_intention.trade_limit, default_min_bought);
+
+		// A stricter caller-provided min_bought than the pair default is left untouched.
+		let stricter_min_bought = default_min_bought + 1;
+		assert_ok!(Exchange::sell(
+			Origin::signed(CHARLIE),
+			HDX,
+			ETH,
+			1_000_000_000_000,
+			stricter_min_bought,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		let stricter_intention = &Exchange::get_intentions((HDX, ETH))[1];
+		assert_eq!(stricter_intention.trade_limit, stricter_min_bought);
+	});
+}
+
+#[test]
+fn buy_should_apply_pair_default_slippage_when_caller_gives_no_limit() {
+	new_test_ext().execute_with(|| {
+		initialize_pool(HDX, ETH, ALICE, 100_000_000_000_000, Price::from(2));
+
+		assert_ok!(Exchange::set_pair_max_slippage(
+			Origin::root(),
+			HDX,
+			ETH,
+			Permill::from_percent(1)
+		));
+
+		assert_ok!(Exchange::buy(
+			Origin::signed(BOB),
+			ETH,
+			HDX,
+			1_000_000_000_000,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		let intention = &Exchange::get_intentions((HDX, ETH))[0];
+		let expected_trade_limit = intention
+			.amount_sell
+			.saturating_add(Permill::from_percent(1).mul_ceil(intention.amount_sell));
+		assert_eq!(intention.trade_limit, expected_trade_limit);
+	});
+}
+
+#[test]
+fn last_block_fills_should_record_both_the_direct_match_and_the_amm_fallback_leg() {
+	// Same mixed batch as `on_trade_handler_should_fire_for_both_the_direct_match_and_the_amm_fallback_leg`:
+	// user_2's SELL only partially matches user_3's BUY directly, so user_2's leftover is routed
+	// through the AMM - `last_block_fills` must end up with one direct-trade record per side of the
+	// direct match, plus one AMM record for the leftover.
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let user_3 = CHARLIE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+
+		initialize_pool(asset_a, asset_b, user_1, 100_000_000_000_000, Price::from(2));
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_2),
+			asset_a,
+			asset_b,
+			2_000_000_000_000,
+			300_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		assert_ok!(Exchange::buy(
+			Origin::signed(user_3),
+			asset_a,
+			asset_b,
+			1_000_000_000_000,
+			4_000_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		<Exchange as OnFinalize<u64>>::on_finalize(9);
+
+		let fills = Exchange::last_block_fills();
+		assert_eq!(fills.iter().filter(|fill| fill.direct).count(), 2);
+		assert_eq!(fills.iter().filter(|fill| !fill.direct).count(), 1);
+
+		let amm_fill = fills.iter().find(|fill| !fill.direct).unwrap();
+		assert_eq!(amm_fill.asset_sell, asset_a);
+		assert_eq!(amm_fill.asset_buy, asset_b);
+		assert_eq!(amm_fill.amount, 1_000_000_000_000);
+
+		<Exchange as OnInitialize<u64>>::on_initialize(10);
+		assert_eq!(Exchange::last_block_fills(), vec![]);
+	});
+}
+
+#[test]
+fn fairness_report_should_show_zero_deviation_when_equal_orders_are_matched_fairly() {
+	// user_2 and user_3 register the exact same size SELL order back to back against the same
+	// untouched pool - each quotes the same `amount_buy`, so both should be matched into
+	// counterparty's group at the exact same realized price, regardless of which one happened to
+	// be registered first.
+	new_test_ext().execute_with(|| {
+		let pool_owner = FERDIE;
+		let counterparty = DAVE;
+		let user_2 = BOB;
+		let user_3 = CHARLIE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+
+		initialize_pool(asset_a, asset_b, pool_owner, 100_000_000_000_000, Price::from(2));
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_2),
+			asset_b,
+			asset_a,
+			2_000_000_000_000,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_3),
+			asset_b,
+			asset_a,
+			2_000_000_000_000,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		assert_ok!(Exchange::sell(
+			Origin::signed(counterparty),
+			asset_a,
+			asset_b,
+			10_000_000_000_000,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		<Exchange as OnFinalize<u64>>::on_finalize(9);
+
+		let fills = Exchange::last_block_fills();
+
+		// user_2's and user_3's own legs - both sold the same amount of `asset_b`.
+		let seller_legs: Vec<_> = fills
+			.iter()
+			.filter(|fill| fill.asset_sell == asset_b && fill.amount == 2_000_000_000_000)
+			.collect();
+		assert_eq!(seller_legs.len(), 2);
+		assert_eq!(seller_legs[0].price, seller_legs[1].price);
+
+		// counterparty's two legs against each of them - both sold the same amount of `asset_a`,
+		// distinct from its own separate AMM-routed leftover leg.
+		let counterparty_legs: Vec<_> = fills.iter().filter(|fill| fill.asset_sell == asset_a && fill.direct).collect();
+		assert_eq!(counterparty_legs.len(), 2);
+		assert_eq!(counterparty_legs[0].amount, counterparty_legs[1].amount);
+		assert_eq!(counterparty_legs[0].price, counterparty_legs[1].price);
+
+		expect_event(RawEvent::FairnessReport(
+			asset_b,
+			asset_a,
+			2_000_000_000_000,
+			seller_legs[0].price,
+			0,
+		));
+		expect_event(RawEvent::FairnessReport(
+			asset_a,
+			asset_b,
+			counterparty_legs[0].amount,
+			counterparty_legs[0].price,
+			0,
+		));
+	});
+}
+
+#[test]
+fn account_settlements_should_record_every_filled_order_for_an_account() {
+	// Same exact-match shape as `sell_test_exact_match`, run once against ETH/DOT and once
+	// against HDX/DOT so `trader` ends the block with two independently filled orders.
+	new_test_ext().execute_with(|| {
+		let pool_owner = FERDIE;
+		let trader = ALICE;
+		let counterparty_1 = BOB;
+		let counterparty_2 = CHARLIE;
+		let pool_amount = 100_000_000_000_000;
+		let initial_price = Price::from(2);
+
+		initialize_pool(ETH, DOT, pool_owner, pool_amount, initial_price);
+		initialize_pool(HDX, DOT, pool_owner, pool_amount, initial_price);
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(trader),
+			ETH,
+			DOT,
+			1_000_000_000_000,
+			1_500_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		let order_1_id = generate_intention_id_for_pair(&trader, 0, ETH, DOT);
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(counterparty_1),
+			DOT,
+			ETH,
+			2_000_000_000_000,
+			200_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(trader),
+			HDX,
+			DOT,
+			1_000_000_000_000,
+			1_500_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		let order_2_id = generate_intention_id_for_pair(&trader, 0, HDX, DOT);
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(counterparty_2),
+			DOT,
+			HDX,
+			2_000_000_000_000,
+			200_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		<Exchange as OnFinalize<u64>>::on_finalize(9);
+
+		let records = Exchange::account_settlements(trader);
+		assert_eq!(records.len(), 2);
+
+		let ids: Vec<_> = records.iter().map(|record| record.intention_id).collect();
+		assert!(ids.contains(&order_1_id));
+		assert!(ids.contains(&order_2_id));
+
+		for record in &records {
+			assert_eq!(record.filled_amount, 1_000_000_000_000);
+			assert_eq!(record.counterparty_count, 1);
+			assert_eq!(record.amm_portion, 0);
+			assert!(record.fee_paid > 0);
+		}
+
+		// Neither counterparty is `trader` - each settled exactly one order of their own too.
+		assert_eq!(Exchange::account_settlements(counterparty_1).len(), 1);
+		assert_eq!(Exchange::account_settlements(counterparty_2).len(), 1);
+
+		<Exchange as OnInitialize<u64>>::on_initialize(10);
+		assert!(Exchange::account_settlements(trader).is_empty());
+	});
+}
+
+#[test]
+fn reserved_balance_should_saturate_instead_of_overflowing_on_many_large_intentions() {
+	new_test_ext().execute_with(|| {
+		let intention = |amount_sell: Balance, id: u32| Intention::<Test> {
+			who: ALICE,
+			asset_sell: ETH,
+			asset_buy: DOT,
+			amount_sell,
+			amount_buy: amount_sell,
+			trade_limit: 0,
+			discount: false,
+			sell_or_buy: IntentionType::SELL,
+			intention_id: generate_intention_id_for_pair(&ALICE, id, ETH, DOT),
+			remaining_lifetime: Zero::zero(),
+			recipient: None,
+			valid_until_timestamp: None,
+			reference: None,
+			allow_amm_fallback: true,
+			priority: 0,
+		};
+
+		// Three intentions whose amounts alone don't overflow `Balance::MAX`, but whose sum does -
+		// the aggregation must saturate at `Balance::MAX` rather than wrapping around to a small
+		// (or zero) reported reserved balance.
+		<ExchangeAssetsIntentions<Test>>::insert(
+			(ETH, DOT),
+			vec![
+				intention(Balance::MAX / 2, 0),
+				intention(Balance::MAX / 2, 1),
+				intention(Balance::MAX / 2, 2),
+			],
+		);
+
+		assert_eq!(Exchange::reserved_balance(ALICE, ETH), Balance::MAX);
+	});
+}
+
+#[test]
+fn pool_reserves_just_below_min_pool_reserve_should_leave_intentions_unsettled() {
+	// The pool's ETH reserve sits one unit below `MinPoolReserve` - `on_finalize` must skip
+	// matching or AMM-routing this pair entirely, carrying the intention forward and reporting
+	// `PoolReservesTooLow` instead of settling it against unstable near-empty reserves.
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000_000_000;
+
+		initialize_pool(asset_a, asset_b, user_1, pool_amount, Price::from(2));
+
+		MinPoolReserveMock::set(pool_amount + 1);
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_2),
+			asset_a,
+			asset_b,
+			1_000_000_000_000,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		let pair = (cmp::min(asset_a, asset_b), cmp::max(asset_a, asset_b));
+		assert_eq!(Exchange::get_intentions_count(pair), 1);
+
+		<Exchange as OnFinalize<u64>>::on_finalize(9);
+
+		expect_event(RawEvent::PoolReservesTooLow(pair.0, pair.1));
+
+		// The intention is still queued, untouched, for a future block to retry.
+		assert_eq!(Exchange::get_intentions_count(pair), 1);
+		assert_eq!(
+			Currency::free_balance(asset_a, &user_2),
+			ENDOWED_AMOUNT - 1_000_000_000_000
+		);
+
+		assert!(!system::Module::<Test>::events().into_iter().any(|record| matches!(
+			record.event,
+			TestEvent::exchange(RawEvent::IntentionResolvedAMMTrade(..))
+				| TestEvent::exchange(RawEvent::IntentionResolvedDirectTrade(..))
+		)));
+
+		MinPoolReserveMock::set(0);
+	});
+}
+
+#[test]
+fn intention_registered_and_resolved_events_should_be_indexed_by_intention_id_and_pair() {
+	// Both `IntentionRegistered` and the resolution event it leads to should carry the
+	// intention's id and a topic for its (normalized) asset pair, so a light client can
+	// subscribe to either without scanning every event this pallet emits.
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let user_3 = CHARLIE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000_000_000;
+
+		initialize_pool(asset_a, asset_b, user_1, pool_amount, Price::from(2));
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_2),
+			asset_a,
+			asset_b,
+			2_000_000_000_000,
+			300_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		let user_2_sell_intention_id = generate_intention_id(&user_2, 0);
+
+		let pair_topic = (cmp::min(asset_a, asset_b), cmp::max(asset_a, asset_b))
+			.using_encoded(<Test as system::Config>::Hashing::hash);
+
+		let registered_record = system::Module::<Test>::events()
+			.into_iter()
+			.find(|record| matches!(record.event, TestEvent::exchange(RawEvent::IntentionRegistered(..))))
+			.expect("IntentionRegistered event expected");
+		assert_eq!(registered_record.topics, vec![user_2_sell_intention_id, pair_topic]);
+
+		assert_ok!(Exchange::buy(
+			Origin::signed(user_3),
+			asset_a,
+			asset_b,
+			1_000_000_000_000,
+			4_000_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		<Exchange as OnFinalize<u64>>::on_finalize(9);
+
+		let resolved_record = system::Module::<Test>::events()
+			.into_iter()
+			.find(|record| {
+				matches!(
+					record.event,
+					TestEvent::exchange(RawEvent::IntentionResolvedAMMTrade(..))
+				)
+			})
+			.expect("IntentionResolvedAMMTrade event expected");
+		assert_eq!(resolved_record.topics, vec![user_2_sell_intention_id, pair_topic]);
+	});
+}
+
+#[test]
+fn direct_trade_fee_should_convert_to_native_asset_when_collect_fees_in_native_is_enabled() {
+	// Same direct SELL/SELL match as `sell_test_exact_match`, but with `CollectFeesInNative` on
+	// and pools between HDX and both traded assets available - each fee leg should arrive at the
+	// pool account already swapped into HDX instead of staying in the asset it was paid in.
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let user_3 = CHARLIE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000_000_000;
+		let initial_price = Price::from(2);
+
+		initialize_pool(HDX, asset_a, user_1, 1_000_000_000_000_000, Price::from(1));
+		initialize_pool(HDX, asset_b, user_1, 1_000_000_000_000_000, Price::from(1));
+		initialize_pool(asset_a, asset_b, user_1, pool_amount, initial_price);
+
+		CollectFeesInNativeMock::set(true);
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_2),
+			asset_a,
+			asset_b,
+			1_000_000_000_000,
+			1_500_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_3),
+			asset_b,
+			asset_a,
+			2_000_000_000_000,
+			200_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		<Exchange as OnFinalize<u64>>::on_finalize(9);
+
+		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 0);
+
+		// The raw fee amounts are still what's tallied as collected, regardless of what they end
+		// up converted to - only the asset they're paid out in changes.
+		assert_eq!(
+			Exchange::get_collected_fees((asset_a, asset_b)),
+			4_000_000_000 + 2_000_000_000
+		);
+
+		let fee_collected_events: Vec<_> = system::Module::<Test>::events()
+			.into_iter()
+			.filter_map(|record| match record.event {
+				TestEvent::exchange(RawEvent::FeeCollected(asset, amount, to)) => Some((asset, amount, to)),
+				_ => None,
+			})
+			.collect();
+
+		assert_eq!(fee_collected_events.len(), 2);
+		for (asset, amount, _) in &fee_collected_events {
+			assert_eq!(*asset, HDX);
+			assert!(*amount > 0);
+		}
+
+		CollectFeesInNativeMock::set(false);
+	});
+}
+
+#[test]
+fn direct_trade_fee_should_stay_in_kind_when_no_native_pool_exists() {
+	// Same scenario as above, but with `CollectFeesInNative` on and no pool between HDX and
+	// either traded asset - the conversion isn't possible, so fees fall back to being collected
+	// in the asset they were paid in, exactly as if the setting were off.
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let user_3 = CHARLIE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000_000_000;
+		let initial_price = Price::from(2);
+
+		let pair_account = AMMModule::get_pair_id(&asset_a, &asset_b);
+
+		initialize_pool(asset_a, asset_b, user_1, pool_amount, initial_price);
+
+		CollectFeesInNativeMock::set(true);
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_2),
+			asset_a,
+			asset_b,
+			1_000_000_000_000,
+			1_500_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		let user_2_sell_intention_id = generate_intention_id(&user_2, 0);
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(user_3),
+			asset_b,
+			asset_a,
+			2_000_000_000_000,
+			200_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		let user_3_sell_intention_id = generate_intention_id(&user_3, 1);
+
+		<Exchange as OnFinalize<u64>>::on_finalize(9);
+
+		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 0);
+
+		expect_events(vec![
+			RawEvent::FundsUnreserved(user_2, asset_a, 1000000000000, user_2_sell_intention_id).into(),
+			RawEvent::FundsUnreserved(user_3, asset_b, 2000000000000, user_3_sell_intention_id).into(),
+			RawEvent::IntentionResolvedDirectTradeFees(user_2, pair_account, asset_b, 4000000000).into(),
+			RawEvent::FeeCollected(asset_b, 4000000000, pair_account).into(),
+			RawEvent::IntentionResolvedDirectTradeFees(user_3, pair_account, asset_a, 2000000000).into(),
+			RawEvent::FeeCollected(asset_a, 2000000000, pair_account).into(),
+		]);
+
+		CollectFeesInNativeMock::set(false);
+	});
+}
+
+#[test]
+fn sell_matched_against_three_buys_should_emit_monotonically_decreasing_remaining_amount() {
+	new_test_ext().execute_with(|| {
+		let user_1 = ALICE;
+		let user_2 = BOB;
+		let user_3 = CHARLIE;
+		let user_4 = DAVE;
+		let user_5 = FERDIE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+
+		let pool_amount = 100_000_000_000_000;
+		let initial_price = Price::from(2);
 
 		initialize_pool(asset_a, asset_b, user_1, pool_amount, initial_price);
 
+		// user_2 sells far more asset_a than the three buyers below want combined, so all three
+		// match directly against it in one pass and the rest falls back to the AMM.
 		assert_ok!(Exchange::sell(
 			Origin::signed(user_2),
 			asset_a,
 			asset_b,
-			2_000,
-			400,
+			20_000_000_000_000,
+			200_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
 			false,
+			0,
+			Price::from(1),
 		));
-		assert_ok!(Exchange::sell(
+
+		// Registered in increasing size, but the matcher visits buyers largest-first, so user_3
+		// is matched first and user_5 last.
+		assert_ok!(Exchange::buy(
 			Origin::signed(user_3),
+			asset_a,
 			asset_b,
+			3_000_000_000_000,
+			20_000_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		assert_ok!(Exchange::buy(
+			Origin::signed(user_4),
 			asset_a,
-			1_000,
-			400,
+			asset_b,
+			2_000_000_000_000,
+			20_000_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
 			false,
+			0,
+			Price::from(1),
+		));
+		assert_ok!(Exchange::buy(
+			Origin::signed(user_5),
+			asset_a,
+			asset_b,
+			1_000_000_000_000,
+			20_000_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
 		));
-		let user_2_sell_intention_id = generate_intention_id(&user_2, 0);
-		let user_3_sell_intention_id = generate_intention_id(&user_3, 1);
-
-		assert_eq!(Currency::free_balance(asset_a, &pair_account), 100000000);
-		assert_eq!(Currency::free_balance(asset_b, &pair_account), 200000000);
 
 		<Exchange as OnFinalize<u64>>::on_finalize(9);
 
-		assert_eq!(Currency::free_balance(asset_a, &user_2), 999999999998000);
-		assert_eq!(Currency::free_balance(asset_b, &user_2), 1000000000003992);
+		let remaining_amounts: Vec<Balance> = system::Module::<Test>::events()
+			.into_iter()
+			.filter_map(|record| match record.event {
+				TestEvent::exchange(RawEvent::IntentionResolvedDirectTrade(
+					who_a,
+					_,
+					_,
+					_,
+					_,
+					_,
+					_,
+					_,
+					_,
+					remaining,
+				)) if who_a == user_2 => Some(remaining),
+				_ => None,
+			})
+			.collect();
+
+		assert_eq!(
+			remaining_amounts,
+			vec![17_000_000_000_000, 15_000_000_000_000, 14_000_000_000_000]
+		);
 
-		assert_eq!(Currency::free_balance(asset_a, &user_3), 1000000000000499);
-		assert_eq!(Currency::free_balance(asset_b, &user_3), 999999999999000);
+		assert!(
+			remaining_amounts.windows(2).all(|w| w[0] > w[1]),
+			"remaining amount should strictly decrease across successive matches"
+		);
+	});
+}
 
-		assert_eq!(Currency::free_balance(asset_a, &pair_account), 100001501);
-		assert_eq!(Currency::free_balance(asset_b, &pair_account), 199997008);
+#[test]
+fn price_proximity_matching_should_reduce_amm_routed_volume_for_the_same_intentions() {
+	// Three counterparties are built by hand (rather than via `sell`/`buy` extrinsics) so their
+	// `amount_buy` can be set independently of `amount_sell` - giving each an implied price that
+	// diverges from the pool's spot price by a different, deliberately chosen amount, which is
+	// what `sort_by_price_proximity` actually orders by.
+	let run = |price_proximity_matching: bool| -> Balance {
+		let mut amm_volume = Zero::zero();
+
+		new_test_ext().execute_with(|| {
+			let main = ALICE;
+			let close = BOB;
+			let mid = CHARLIE;
+			let far = DAVE;
+
+			initialize_pool(ETH, DOT, main, 100_000_000_000_000, Price::from(2));
+
+			let counterparty = |who: u64, id: u32, amount_sell: Balance, amount_buy: Balance| Intention::<Test> {
+				who,
+				asset_sell: DOT,
+				asset_buy: ETH,
+				amount_sell,
+				amount_buy,
+				trade_limit: 0,
+				discount: false,
+				sell_or_buy: IntentionType::SELL,
+				intention_id: generate_intention_id_for_pair(&who, id, DOT, ETH),
+				remaining_lifetime: DefaultIntentionLifetime::get(),
+				recipient: None,
+				valid_until_timestamp: None,
+				reference: None,
+				allow_amm_fallback: true,
+				priority: 0,
+			};
+
+			// `close` sits right on the pool's spot price (0.5 ETH per DOT); `mid` and `far` sit
+			// increasingly further away from it.
+			let intentions = vec![
+				counterparty(close, 0, 1_000_000_000_000, 500_000_000_000),
+				counterparty(mid, 0, 2_000_000_000_000, 2_000_000_000_000),
+				counterparty(far, 0, 2_500_000_000_000, 4_900_000_000_000),
+			];
+
+			<ExchangeAssetsIntentions<Test>>::insert((DOT, ETH), intentions);
+			ExchangeAssetsIntentionCount::insert((cmp::min(DOT, ETH), cmp::max(DOT, ETH)), 4);
+
+			<ExchangeAssetsIntentions<Test>>::insert(
+				(ETH, DOT),
+				vec![Intention::<Test> {
+					who: main,
+					asset_sell: ETH,
+					asset_buy: DOT,
+					amount_sell: 5_000_000_000_000,
+					amount_buy: 3_200_000_000_000,
+					trade_limit: 0,
+					discount: false,
+					sell_or_buy: IntentionType::SELL,
+					intention_id: generate_intention_id_for_pair(&main, 0, ETH, DOT),
+					remaining_lifetime: DefaultIntentionLifetime::get(),
+					recipient: None,
+					valid_until_timestamp: None,
+					reference: None,
+					allow_amm_fallback: true,
+					priority: 0,
+				}],
+			);
+
+			PriceProximityMatchingMock::set(price_proximity_matching);
+
+			<Exchange as OnFinalize<u64>>::on_finalize(1);
+
+			if let TestEvent::exchange(RawEvent::BlockSettlementSummary(matched, amm)) = last_event() {
+				// The direct-trade portion is the same either way - `main`'s whole sell amount is
+				// always fully absorbed by some combination of direct matches, only the split
+				// between counterparties changes. Only AMM routing should move.
+				assert_eq!(matched, 5_000_000_000_000);
+				amm_volume = amm;
+			} else {
+				panic!("expected a BlockSettlementSummary event");
+			}
+
+			PriceProximityMatchingMock::set(false);
+		});
+
+		amm_volume
+	};
 
-		expect_events(vec![
-			RawEvent::IntentionRegistered(
-				user_2,
+	let amm_volume_amount_only = run(false);
+	let amm_volume_price_proximity = run(true);
+
+	assert!(amm_volume_amount_only > 0);
+	assert!(amm_volume_price_proximity > 0);
+	assert_ne!(
+		amm_volume_amount_only, amm_volume_price_proximity,
+		"matching the counterparty closest to spot first should route a different amount through the AMM"
+	);
+}
+
+#[test]
+fn max_events_per_block_should_cap_resolution_events_without_affecting_settlement_totals() {
+	new_test_ext().execute_with(|| {
+		let seller = ALICE;
+		let buyer = BOB;
+		let asset_a = ETH;
+		let asset_b = DOT;
+
+		let pool_amount = 100_000_000_000_000;
+		let initial_price = Price::from(2);
+
+		initialize_pool(asset_a, asset_b, seller, pool_amount, initial_price);
+
+		// Far more than the twelve buyers below want combined, so every one of them matches
+		// directly and the untouched remainder falls back to the AMM.
+		assert_ok!(Exchange::sell(
+			Origin::signed(seller),
+			asset_a,
+			asset_b,
+			20_000_000_000_000,
+			200_000_000_000,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		const BUY_COUNT: usize = 12;
+		const AMOUNT_BUY: Balance = 500_000_000_000;
+
+		for _ in 0..BUY_COUNT {
+			assert_ok!(Exchange::buy(
+				Origin::signed(buyer),
 				asset_a,
 				asset_b,
-				2_000,
-				IntentionType::SELL,
-				user_2_sell_intention_id,
-			)
-			.into(),
-			RawEvent::IntentionRegistered(
-				user_3,
-				asset_b,
-				asset_a,
-				1_000,
-				IntentionType::SELL,
-				user_3_sell_intention_id,
-			)
-			.into(),
-			RawEvent::IntentionResolvedDirectTrade(
-				user_2,
-				user_3,
-				user_2_sell_intention_id,
-				user_3_sell_intention_id,
-				500,
-				1000,
-			)
-			.into(),
-			RawEvent::IntentionResolvedDirectTradeFees(user_2, pair_account, asset_b, 2).into(),
-			RawEvent::IntentionResolvedDirectTradeFees(user_3, pair_account, asset_a, 1).into(),
-			TestEvent::amm(amm::RawEvent::Sell(2, 3000, 2000, 1500, 2994)),
-			RawEvent::IntentionResolvedAMMTrade(user_2, IntentionType::SELL, user_2_sell_intention_id, 1500, 2994)
-				.into(),
+				AMOUNT_BUY,
+				20_000_000_000_000,
+				false,
+				None,
+				None,
+				None,
+				true,
+				false,
+				0,
+				Price::from(1),
+			));
+		}
+
+		MaxEventsPerBlockMock::set(5);
+
+		<Exchange as OnFinalize<u64>>::on_finalize(9);
+
+		let resolution_event_count = system::Module::<Test>::events()
+			.into_iter()
+			.filter(|record| {
+				matches!(
+					record.event,
+					TestEvent::exchange(RawEvent::IntentionResolvedDirectTrade(..))
+						| TestEvent::exchange(RawEvent::IntentionResolvedAMMTrade(..))
+						| TestEvent::exchange(RawEvent::IntentionResolvedAMMTradePartialFill(..))
+				)
+			})
+			.count();
+
+		assert_eq!(
+			resolution_event_count, 5,
+			"resolution events should be capped at MaxEventsPerBlock even though thirteen resolutions happened"
+		);
+
+		match last_event() {
+			TestEvent::exchange(RawEvent::BlockSettlementSummary(matched, amm)) => {
+				// Every buyer is fully consumed directly (scenario 1) with no rounding, so the
+				// matched total is exactly the sum of what was asked for - unaffected by whether
+				// its event was actually emitted.
+				assert_eq!(matched, AMOUNT_BUY * BUY_COUNT as Balance);
+				assert!(
+					amm > 0,
+					"the seller's untouched remainder should still be routed through the AMM"
+				);
+			}
+			_ => panic!("expected a BlockSettlementSummary event"),
+		}
+
+		MaxEventsPerBlockMock::set(u32::MAX);
+	});
+}
+
+#[test]
+fn next_intention_id_should_match_the_id_in_the_subsequent_registration_event() {
+	new_test_ext().execute_with(|| {
+		let user = ALICE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let pool_amount = 100_000_000_000_000;
+
+		initialize_pool(asset_a, asset_b, user, pool_amount, Price::from(2));
+
+		let predicted = Exchange::get_next_intention_id(&user, asset_a, asset_b);
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(user),
+			asset_a,
+			asset_b,
+			1_000_000_000_000,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		match last_event() {
+			TestEvent::exchange(RawEvent::IntentionRegistered(_, _, _, _, _, intention_id, _, _)) => {
+				assert_eq!(intention_id, predicted);
+			}
+			_ => panic!("expected an IntentionRegistered event"),
+		}
+
+		// The counter has advanced, so the same call now predicts a different id.
+		assert_ne!(Exchange::get_next_intention_id(&user, asset_a, asset_b), predicted);
+	});
+}
+
+#[test]
+fn cancel_pair_should_waive_the_cancellation_fee_for_a_same_block_order() {
+	new_test_ext().execute_with(|| {
+		let user = ALICE;
+
+		CancellationFeeMock::set(100_000_000);
+
+		let intention_id = generate_intention_id_for_pair(&user, 0, ETH, DOT);
+		let intention = Intention::<Test> {
+			who: user,
+			asset_sell: ETH,
+			asset_buy: DOT,
+			amount_sell: 1_000_000_000_000,
+			amount_buy: 500_000_000_000,
+			trade_limit: 0,
+			discount: false,
+			sell_or_buy: IntentionType::SELL,
+			intention_id,
+			remaining_lifetime: DefaultIntentionLifetime::get(),
+			recipient: None,
+			valid_until_timestamp: None,
+			reference: None,
+			allow_amm_fallback: true,
+			priority: 0,
+		};
+
+		<ExchangeAssetsIntentions<Test>>::insert((ETH, DOT), vec![intention]);
+		ExchangeAssetsIntentionCount::insert((cmp::min(ETH, DOT), cmp::max(ETH, DOT)), 1);
+
+		let balance_before = Currency::free_balance(ETH, &user);
+
+		assert_ok!(Exchange::cancel_pair(Origin::signed(user), ETH, DOT));
+
+		assert!(system::Module::<Test>::events()
+			.into_iter()
+			.all(|record| !matches!(record.event, TestEvent::exchange(RawEvent::CancellationFeeCharged(..)))));
+		assert_eq!(Currency::free_balance(ETH, &user), balance_before);
+
+		CancellationFeeMock::set(0);
+	});
+}
+
+#[test]
+fn cancel_pair_should_charge_the_cancellation_fee_for_a_carried_forward_order() {
+	new_test_ext().execute_with(|| {
+		let user = ALICE;
+		let fee = 100_000_000;
+
+		CancellationFeeMock::set(fee);
+
+		let intention_id = generate_intention_id_for_pair(&user, 0, ETH, DOT);
+		let intention = Intention::<Test> {
+			who: user,
+			asset_sell: ETH,
+			asset_buy: DOT,
+			amount_sell: 1_000_000_000_000,
+			amount_buy: 500_000_000_000,
+			trade_limit: 0,
+			discount: false,
+			sell_or_buy: IntentionType::SELL,
+			intention_id,
+			// One block short of a fresh registration's lifetime - as if it had already been
+			// carried forward once by `on_finalize`.
+			remaining_lifetime: DefaultIntentionLifetime::get() - 1,
+			recipient: None,
+			valid_until_timestamp: None,
+			reference: None,
+			allow_amm_fallback: true,
+			priority: 0,
+		};
+
+		<ExchangeAssetsIntentions<Test>>::insert((ETH, DOT), vec![intention]);
+		ExchangeAssetsIntentionCount::insert((cmp::min(ETH, DOT), cmp::max(ETH, DOT)), 1);
+
+		let pair_account = Exchange::pair_account(ETH, DOT);
+		let balance_before = Currency::free_balance(ETH, &user);
+
+		assert_ok!(Exchange::cancel_pair(Origin::signed(user), ETH, DOT));
+
+		expect_events(vec![
+			RawEvent::CancellationFeeCharged(user, ETH, fee, intention_id).into(),
+			RawEvent::FundsUnreserved(user, ETH, 1_000_000_000_000, intention_id).into(),
+			RawEvent::IntentionsCancelledForPair(user, ETH, DOT, 1).into(),
+		]);
+		assert_eq!(Currency::free_balance(ETH, &user), balance_before - fee);
+		assert_eq!(Currency::free_balance(ETH, &pair_account), fee);
+
+		CancellationFeeMock::set(0);
+	});
+}
+
+#[test]
+#[should_panic(expected = "value was created or destroyed")]
+fn conservation_of_value_assertion_should_trip_when_a_transfer_leaks_value_out_of_the_settling_set() {
+	new_test_ext().execute_with(|| {
+		let user = ALICE;
+		let outsider = GEORGE;
+
+		let intention_id = generate_intention_id_for_pair(&user, 0, ETH, DOT);
+		let intention = Intention::<Test> {
+			who: user,
+			asset_sell: ETH,
+			asset_buy: DOT,
+			amount_sell: 1_000_000_000_000,
+			amount_buy: 500_000_000_000,
+			trade_limit: 0,
+			discount: false,
+			sell_or_buy: IntentionType::SELL,
+			intention_id,
+			remaining_lifetime: DefaultIntentionLifetime::get(),
+			recipient: None,
+			valid_until_timestamp: None,
+			reference: None,
+			allow_amm_fallback: true,
+			priority: 0,
+		};
+
+		let pair_account = Exchange::pair_account(ETH, DOT);
+		let asset_a_sells = vec![intention];
+		let asset_b_sells: Vec<Intention<Test>> = Vec::new();
+
+		let before = Exchange::total_settlement_balance(ETH, &pair_account, &asset_a_sells, &asset_b_sells);
+
+		// Simulate the failure mode a broken transfer amount inside `process_exchange_intentions`
+		// would produce - `user` debited, but the amount credited to an account outside the
+		// settling set instead of `pair_account`.
+		assert_ok!(Currency::transfer(ETH, &user, &outsider, 1_000_000_000));
+
+		let after = Exchange::total_settlement_balance(ETH, &pair_account, &asset_a_sells, &asset_b_sells);
+
+		// The exact check `on_finalize` wires in around `process_exchange_intentions` - exercised
+		// directly here since triggering it via `on_finalize` itself would require shipping a real
+		// regression into the settlement path just to prove the assertion catches it.
+		debug_assert_eq!(
+			before,
+			after,
+			"settling ({:?}, {:?}) changed the total balance held by its participants and pair account - value was created or destroyed",
+			ETH,
+			DOT,
+		);
+	});
+}
+
+/// Cancels three of `user`'s own queued ETH-sell intentions for (ETH, DOT), with `netted`
+/// controlling `NetSettlementTransfers`, and returns the resulting ETH balances plus how many
+/// `CancellationFeeCharged` events were emitted - used to compare the netted and per-transfer
+/// paths against each other.
+fn cancel_three_intentions_and_collect_result(netted: bool) -> (Balance, Balance, usize) {
+	let mut result = (0, 0, 0);
+
+	new_test_ext().execute_with(|| {
+		let user = ALICE;
+		let fee = 100_000_000;
+
+		CancellationFeeMock::set(fee);
+		NetSettlementTransfersMock::set(netted);
+
+		let pair_account = Exchange::pair_account(ETH, DOT);
+
+		let intentions: Vec<_> = (0..3u32)
+			.map(|c| Intention::<Test> {
+				who: user,
+				asset_sell: ETH,
+				asset_buy: DOT,
+				amount_sell: 1_000_000_000_000,
+				amount_buy: 500_000_000_000,
+				trade_limit: 0,
+				discount: false,
+				sell_or_buy: IntentionType::SELL,
+				intention_id: generate_intention_id_for_pair(&user, c, ETH, DOT),
+				// One block short of a fresh registration's lifetime, so the fee isn't waived.
+				remaining_lifetime: DefaultIntentionLifetime::get() - 1,
+				recipient: None,
+				valid_until_timestamp: None,
+				reference: None,
+				allow_amm_fallback: true,
+				priority: 0,
+			})
+			.collect();
+
+		<ExchangeAssetsIntentions<Test>>::insert((ETH, DOT), intentions);
+		ExchangeAssetsIntentionCount::insert((cmp::min(ETH, DOT), cmp::max(ETH, DOT)), 3);
+
+		assert_ok!(Exchange::cancel_pair(Origin::signed(user), ETH, DOT));
+
+		let charged_events = system::Module::<Test>::events()
+			.into_iter()
+			.filter(|record| matches!(record.event, TestEvent::exchange(RawEvent::CancellationFeeCharged(..))))
+			.count();
+
+		result = (
+			Currency::free_balance(ETH, &user),
+			Currency::free_balance(ETH, &pair_account),
+			charged_events,
+		);
+
+		CancellationFeeMock::set(0);
+		NetSettlementTransfersMock::set(false);
+	});
+
+	result
+}
+
+#[test]
+fn cancel_pair_netted_fee_settlement_should_match_the_per_transfer_result() {
+	assert_eq!(
+		cancel_three_intentions_and_collect_result(false),
+		cancel_three_intentions_and_collect_result(true),
+	);
+}
+
+#[test]
+fn on_finalize_should_short_circuit_without_touching_storage_when_nothing_is_queued() {
+	// `TotalIntentions` is the one counter `on_finalize` checks before doing any settlement
+	// work at all - plant a stale `ExchangeAssetsIntentionCount` entry it would otherwise
+	// iterate and clear, without touching `TotalIntentions` itself, so the entry surviving
+	// `on_finalize` untouched proves the short-circuit fired before it was ever looked at.
+	new_test_ext().execute_with(|| {
+		assert_eq!(TotalIntentions::get(), 0);
+		ExchangeAssetsIntentionCount::insert((DOT, ETH), 3);
+
+		<Exchange as OnFinalize<u64>>::on_finalize(1);
+
+		expect_events(vec![]);
+		assert_eq!(Exchange::get_intentions_count((DOT, ETH)), 3);
+	});
+}
+
+#[test]
+fn on_finalize_should_unreserve_and_fail_intentions_queued_for_a_pair_with_no_pool() {
+	// In this tree, pools only ever disappear via `remove_liquidity`, which already purges the
+	// pair's queued intentions synchronously - `on_finalize` never actually sees a pool-less pair
+	// with anything left queued for it. This test drives the defensive fallback branch directly,
+	// the same way the `cancel_pair` tests above insert an intention straight into storage rather
+	// than relying on a code path that can't reach it in practice.
+	new_test_ext().execute_with(|| {
+		let user = ALICE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let sell_amount = 1_000_000_000_000;
+
+		let intention_id = generate_intention_id_for_pair(&user, 0, asset_a, asset_b);
+		let intention = Intention::<Test> {
+			who: user,
+			asset_sell: asset_a,
+			asset_buy: asset_b,
+			amount_sell: sell_amount,
+			amount_buy: 0,
+			trade_limit: 0,
+			discount: false,
+			sell_or_buy: IntentionType::SELL,
+			intention_id,
+			remaining_lifetime: DefaultIntentionLifetime::get(),
+			recipient: None,
+			valid_until_timestamp: None,
+			reference: None,
+			allow_amm_fallback: true,
+			priority: 0,
+		};
+
+		assert_ok!(Currency::reserve(asset_a, &user, sell_amount));
+		<ExchangeAssetsIntentions<Test>>::insert((asset_a, asset_b), vec![intention]);
+		ExchangeAssetsIntentionCount::insert((cmp::min(asset_a, asset_b), cmp::max(asset_a, asset_b)), 1);
+
+		assert!(!AMMModule::exists(asset_a, asset_b));
+
+		let balance_before = Currency::free_balance(asset_a, &user);
+
+		<Exchange as OnFinalize<u64>>::on_finalize(1);
+
+		expect_events(vec![
+			RawEvent::PoolRemovedBeforeSettlement(cmp::min(asset_a, asset_b), cmp::max(asset_a, asset_b)).into(),
+			RawEvent::IntentionUnmatched(user, asset_a, sell_amount, intention_id).into(),
+		]);
+
+		assert_eq!(Exchange::intention_status(intention_id), Some(Status::Failed));
+		assert_eq!(Currency::free_balance(asset_a, &user), balance_before + sell_amount);
+		assert_eq!(Currency::reserved_balance(asset_a, &user), 0);
+
+		// Nothing carried forward - the pair is gone entirely.
+		assert_eq!(
+			Exchange::get_intentions_count((cmp::min(asset_a, asset_b), cmp::max(asset_a, asset_b))),
+			0
+		);
+	});
+}
+
+#[test]
+fn on_finalize_should_unreserve_and_fail_intentions_queued_for_a_pair_with_a_removed_asset() {
+	// Unlike a removed pool, `T::Currency` losing all issuance of one of a pair's assets isn't
+	// something this pallet can prevent synchronously - the pool itself keeps reporting `exists`,
+	// so the settlement loop has to notice it the same way it notices a vanished pool.
+	new_test_ext().execute_with(|| {
+		let user = ALICE;
+		let asset_a = ETH;
+		let asset_b = 8888;
+		let pool_amount = 200_000_000_000_000;
+		let price = Price::from(2);
+		let sell_amount = 1_000_000_000_000;
+
+		let amount_b = price.saturating_mul_int(pool_amount);
+		assert_ok!(Currency::deposit(asset_b, &user, amount_b));
+		assert_ok!(AMMModule::create_pool(
+			Origin::signed(user),
+			asset_a,
+			asset_b,
+			pool_amount,
+			price
+		));
+
+		let pair_account = AMMModule::get_pair_id(&asset_a, &asset_b);
+		assert!(AMMModule::exists(asset_a, asset_b));
+
+		let intention_id = generate_intention_id_for_pair(&user, 0, asset_a, asset_b);
+		let intention = Intention::<Test> {
+			who: user,
+			asset_sell: asset_a,
+			asset_buy: asset_b,
+			amount_sell: sell_amount,
+			amount_buy: 0,
+			trade_limit: 0,
+			discount: false,
+			sell_or_buy: IntentionType::SELL,
+			intention_id,
+			remaining_lifetime: DefaultIntentionLifetime::get(),
+			recipient: None,
+			valid_until_timestamp: None,
+			reference: None,
+			allow_amm_fallback: true,
+			priority: 0,
+		};
+
+		assert_ok!(Currency::reserve(asset_a, &user, sell_amount));
+		<ExchangeAssetsIntentions<Test>>::insert((asset_a, asset_b), vec![intention]);
+		ExchangeAssetsIntentionCount::insert((cmp::min(asset_a, asset_b), cmp::max(asset_a, asset_b)), 1);
+
+		// The pool's entire holding of `asset_b` was the only issuance there ever was - draining it
+		// takes `total_issuance(asset_b)` to zero without touching `AMMModule::exists`.
+		assert_ok!(Currency::withdraw(asset_b, &pair_account, amount_b));
+		assert!(Currency::total_issuance(asset_b).is_zero());
+		assert!(AMMModule::exists(asset_a, asset_b));
+
+		let balance_before = Currency::free_balance(asset_a, &user);
+
+		<Exchange as OnFinalize<u64>>::on_finalize(1);
+
+		expect_events(vec![
+			RawEvent::AssetRemovedBeforeSettlement(cmp::min(asset_a, asset_b), cmp::max(asset_a, asset_b)).into(),
+			RawEvent::IntentionUnmatched(user, asset_a, sell_amount, intention_id).into(),
 		]);
+
+		assert_eq!(Exchange::intention_status(intention_id), Some(Status::Failed));
+		assert_eq!(Currency::free_balance(asset_a, &user), balance_before + sell_amount);
+		assert_eq!(Currency::reserved_balance(asset_a, &user), 0);
+
+		// Nothing carried forward - there's nothing left to eventually settle against.
+		assert_eq!(
+			Exchange::get_intentions_count((cmp::min(asset_a, asset_b), cmp::max(asset_a, asset_b))),
+			0
+		);
+	});
+}
+
+#[test]
+fn resolve_matched_intentions_should_go_straight_to_amm_when_matched_is_empty() {
+	new_test_ext().execute_with(|| {
+		let asset_a = ETH;
+		let asset_b = DOT;
+		let user = ALICE;
+		let sell_amount = 1_000_000_000_000;
+
+		initialize_pool(asset_a, asset_b, user, 100_000_000_000_000, Price::from(2));
+
+		let intention = dummy_sell_intention(user, asset_a, asset_b, sell_amount, 0);
+		assert_ok!(Currency::reserve(asset_a, &user, sell_amount));
+
+		let pair_account = Exchange::pair_account(asset_a, asset_b);
+		let (matched_volume, amm_volume) = Exchange::resolve_matched_intentions(&pair_account, &intention, &[]);
+
+		assert_eq!(matched_volume, 0);
+		assert!(amm_volume > 0);
+		assert!(system::Module::<Test>::events().into_iter().any(|record| matches!(
+			record.event,
+			TestEvent::exchange(RawEvent::IntentionResolvedAMMTrade(..))
+		)));
 	});
 }
 
 #[test]
-fn simple_buy_buy() {
+fn resolve_matched_intentions_should_unreserve_when_matched_is_empty_and_amm_fallback_is_disallowed() {
 	new_test_ext().execute_with(|| {
-		let user_1 = ALICE;
-		let user_2 = BOB;
-		let user_3 = CHARLIE;
 		let asset_a = ETH;
 		let asset_b = DOT;
-		let pool_amount = 100_000_000;
-		let initial_price = Price::from(2);
+		let user = ALICE;
+		let sell_amount = 1_000_000_000_000;
 
-		let pair_account = AMMModule::get_pair_id(&asset_a, &asset_b);
+		initialize_pool(asset_a, asset_b, user, 100_000_000_000_000, Price::from(2));
 
-		initialize_pool(asset_a, asset_b, user_1, pool_amount, initial_price);
+		let mut intention = dummy_sell_intention(user, asset_a, asset_b, sell_amount, 0);
+		intention.allow_amm_fallback = false;
+		assert_ok!(Currency::reserve(asset_a, &user, sell_amount));
 
-		assert_ok!(Exchange::buy(
-			Origin::signed(user_2),
+		let pair_account = Exchange::pair_account(asset_a, asset_b);
+		let (matched_volume, amm_volume) = Exchange::resolve_matched_intentions(&pair_account, &intention, &[]);
+
+		assert_eq!(matched_volume, 0);
+		assert_eq!(amm_volume, 0);
+		assert_eq!(Currency::reserved_balance(asset_a, &user), 0);
+		expect_event(RawEvent::IntentionUnmatched(
+			user,
+			asset_a,
+			sell_amount,
+			intention.intention_id,
+		));
+	});
+}
+
+#[test]
+fn set_intention_priority_should_raise_a_pending_intentions_priority() {
+	new_test_ext().execute_with(|| {
+		let user = ALICE;
+		let asset_a = ETH;
+		let asset_b = DOT;
+
+		initialize_pool(asset_a, asset_b, user, 100_000_000_000_000, Price::from(2));
+
+		assert_ok!(Exchange::sell(
+			Origin::signed(user),
 			asset_a,
 			asset_b,
-			2_000,
-			5000,
+			1_000_000_000_000,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
 			false,
+			0,
+			Price::from(1),
 		));
-		assert_ok!(Exchange::buy(
-			Origin::signed(user_3),
-			asset_b,
+		let intention_id = generate_intention_id_for_pair(&user, 0, asset_a, asset_b);
+
+		assert_ok!(Exchange::set_intention_priority(
+			Origin::signed(user),
 			asset_a,
-			1_000,
-			5000,
-			false,
+			asset_b,
+			intention_id,
+			5,
 		));
-		let user_2_sell_intention_id = generate_intention_id(&user_2, 0);
-		let user_3_sell_intention_id = generate_intention_id(&user_3, 1);
 
-		assert_eq!(Currency::free_balance(asset_a, &pair_account), 100000000);
-		assert_eq!(Currency::free_balance(asset_b, &pair_account), 200000000);
+		let intentions = Exchange::get_intentions((asset_a, asset_b));
+		assert_eq!(intentions[0].priority, 5);
 
-		<Exchange as OnFinalize<u64>>::on_finalize(9);
+		expect_event(RawEvent::IntentionPrioritySet(user, asset_a, asset_b, intention_id, 5));
+	});
+}
 
-		assert_eq!(Currency::free_balance(asset_a, &user_2), 1000000000002000);
-		assert_eq!(Currency::free_balance(asset_b, &user_2), 999999999995991);
+#[test]
+fn set_intention_priority_should_fail_when_caller_is_not_the_owner() {
+	new_test_ext().execute_with(|| {
+		let owner = ALICE;
+		let asset_a = ETH;
+		let asset_b = DOT;
 
-		assert_eq!(Currency::free_balance(asset_a, &user_3), 999999999999499);
-		assert_eq!(Currency::free_balance(asset_b, &user_3), 1000000000001000);
+		initialize_pool(asset_a, asset_b, owner, 100_000_000_000_000, Price::from(2));
 
-		assert_eq!(Currency::free_balance(asset_a, &pair_account), 99998501);
-		assert_eq!(Currency::free_balance(asset_b, &pair_account), 200003009);
+		assert_ok!(Exchange::sell(
+			Origin::signed(owner),
+			asset_a,
+			asset_b,
+			1_000_000_000_000,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		let intention_id = generate_intention_id_for_pair(&owner, 0, asset_a, asset_b);
 
-		expect_events(vec![
-			RawEvent::IntentionRegistered(
-				user_2,
-				asset_a,
-				asset_b,
-				2_000,
-				IntentionType::BUY,
-				user_2_sell_intention_id,
-			)
-			.into(),
-			RawEvent::IntentionRegistered(
-				user_3,
-				asset_b,
-				asset_a,
-				1_000,
-				IntentionType::BUY,
-				user_3_sell_intention_id,
-			)
-			.into(),
-			TestEvent::amm(amm::RawEvent::Buy(2, 3000, 2000, 1500, 3007)),
-			RawEvent::IntentionResolvedAMMTrade(user_2, IntentionType::BUY, user_2_sell_intention_id, 1500, 3007)
-				.into(),
-			RawEvent::IntentionResolvedDirectTrade(
-				user_3,
-				user_2,
-				user_3_sell_intention_id,
-				user_2_sell_intention_id,
-				500,
-				1000,
-			)
-			.into(),
-			RawEvent::IntentionResolvedDirectTradeFees(user_3, pair_account, asset_a, 1).into(),
-			RawEvent::IntentionResolvedDirectTradeFees(user_2, pair_account, asset_b, 2).into(),
-		]);
+		assert_noop!(
+			Exchange::set_intention_priority(Origin::signed(BOB), asset_a, asset_b, intention_id, 5),
+			Error::<Test>::NotIntentionOwner
+		);
 	});
 }
 
 #[test]
-fn simple_sell_buy() {
+fn set_intention_priority_should_fail_when_not_raising_the_priority() {
 	new_test_ext().execute_with(|| {
-		let user_1 = ALICE;
-		let user_2 = BOB;
-		let user_3 = CHARLIE;
+		let user = ALICE;
 		let asset_a = ETH;
 		let asset_b = DOT;
-		let pool_amount = 100_000_000;
-		let initial_price = Price::from(2);
-
-		let pair_account = AMMModule::get_pair_id(&asset_a, &asset_b);
 
-		initialize_pool(asset_a, asset_b, user_1, pool_amount, initial_price);
+		initialize_pool(asset_a, asset_b, user, 100_000_000_000_000, Price::from(2));
 
 		assert_ok!(Exchange::sell(
-			Origin::signed(user_2),
+			Origin::signed(user),
 			asset_a,
 			asset_b,
-			2_000,
-			400,
+			1_000_000_000_000,
+			0,
 			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
 		));
-		assert_ok!(Exchange::buy(
-			Origin::signed(user_3),
+		let intention_id = generate_intention_id_for_pair(&user, 0, asset_a, asset_b);
+
+		assert_ok!(Exchange::set_intention_priority(
+			Origin::signed(user),
 			asset_a,
 			asset_b,
-			1_000,
-			2_000,
-			false,
+			intention_id,
+			3,
 		));
 
-		let user_2_sell_intention_id = generate_intention_id(&user_2, 0);
-		let user_3_sell_intention_id = generate_intention_id(&user_3, 1);
+		assert_noop!(
+			Exchange::set_intention_priority(Origin::signed(user), asset_a, asset_b, intention_id, 3),
+			Error::<Test>::PriorityNotIncreased
+		);
+	});
+}
 
-		assert_eq!(Currency::free_balance(asset_a, &pair_account), 100000000);
-		assert_eq!(Currency::free_balance(asset_b, &pair_account), 200000000);
+#[test]
+fn set_intention_priority_should_charge_the_priority_fee_to_the_pair_account() {
+	new_test_ext().execute_with(|| {
+		let user = ALICE;
+		let asset_a = ETH;
+		let asset_b = DOT;
 
-		<Exchange as OnFinalize<u64>>::on_finalize(9);
+		initialize_pool(asset_a, asset_b, user, 100_000_000_000_000, Price::from(2));
 
-		assert_eq!(Currency::free_balance(asset_a, &user_2), 999999999998000);
-		assert_eq!(Currency::free_balance(asset_b, &user_2), 1000000000003994);
+		assert_ok!(Exchange::sell(
+			Origin::signed(user),
+			asset_a,
+			asset_b,
+			1_000_000_000_000,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		let intention_id = generate_intention_id_for_pair(&user, 0, asset_a, asset_b);
 
-		assert_eq!(Currency::free_balance(asset_a, &user_3), 1000000000001000);
-		assert_eq!(Currency::free_balance(asset_b, &user_3), 999999999997996);
+		let pair_account = Exchange::pair_account(asset_a, asset_b);
+		let fee = 1_000_000;
+		PriorityFeeMock::set(fee);
 
-		assert_eq!(Currency::free_balance(asset_a, &pair_account), 100001000);
-		assert_eq!(Currency::free_balance(asset_b, &pair_account), 199998010);
+		let balance_before = Currency::free_balance(asset_a, &user);
+		let pair_balance_before = Currency::free_balance(asset_a, &pair_account);
 
-		expect_events(vec![
-			RawEvent::IntentionRegistered(
-				user_2,
-				asset_a,
-				asset_b,
-				2_000,
-				IntentionType::SELL,
-				user_2_sell_intention_id,
-			)
-			.into(),
-			RawEvent::IntentionRegistered(
-				user_3,
-				asset_a,
-				asset_b,
-				1_000,
-				IntentionType::BUY,
-				user_3_sell_intention_id,
-			)
-			.into(),
-			RawEvent::IntentionResolvedDirectTrade(
-				user_2,
-				user_3,
-				user_2_sell_intention_id,
-				user_3_sell_intention_id,
-				1000,
-				2000,
-			)
-			.into(),
-			RawEvent::IntentionResolvedDirectTradeFees(user_2, pair_account, asset_b, 2).into(),
-			RawEvent::IntentionResolvedDirectTradeFees(user_3, pair_account, asset_b, 4).into(),
-			TestEvent::amm(amm::RawEvent::Sell(2, 3000, 2000, 1000, 1996)),
-			RawEvent::IntentionResolvedAMMTrade(user_2, IntentionType::SELL, user_2_sell_intention_id, 1000, 1996)
-				.into(),
-		]);
+		assert_ok!(Exchange::set_intention_priority(
+			Origin::signed(user),
+			asset_a,
+			asset_b,
+			intention_id,
+			1,
+		));
+
+		assert_eq!(Currency::free_balance(asset_a, &user), balance_before - fee);
+		assert_eq!(
+			Currency::free_balance(asset_a, &pair_account),
+			pair_balance_before + fee
+		);
+
+		PriorityFeeMock::set(0);
 	});
 }
 
 #[test]
-fn simple_buy_sell() {
+fn amend_limit_price_should_update_a_pending_intentions_trade_limit_and_affect_subsequent_matching() {
 	new_test_ext().execute_with(|| {
-		let user_1 = ALICE;
-		let user_2 = BOB;
-		let user_3 = CHARLIE;
+		let user = ALICE;
 		let asset_a = ETH;
 		let asset_b = DOT;
-		let pool_amount = 100_000_000;
-		let initial_price = Price::from(2);
-
-		let pair_account = AMMModule::get_pair_id(&asset_a, &asset_b);
 
-		initialize_pool(asset_a, asset_b, user_1, pool_amount, initial_price);
+		initialize_pool(asset_a, asset_b, user, 100_000_000_000_000, Price::from(2));
 
-		assert_ok!(Exchange::buy(
-			Origin::signed(user_2),
-			asset_a,
-			asset_b,
-			2_000,
-			5000,
-			false,
-		));
 		assert_ok!(Exchange::sell(
-			Origin::signed(user_3),
+			Origin::signed(user),
 			asset_a,
 			asset_b,
-			1_000,
-			1500,
+			1_000_000_000_000,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
 			false,
+			0,
+			Price::from(1),
 		));
+		let intention_id = generate_intention_id_for_pair(&user, 0, asset_a, asset_b);
 
-		let user_2_sell_intention_id = generate_intention_id(&user_2, 0);
-		let user_3_sell_intention_id = generate_intention_id(&user_3, 1);
-
-		assert_eq!(Currency::free_balance(asset_a, &pair_account), 100000000);
-		assert_eq!(Currency::free_balance(asset_b, &pair_account), 200000000);
-
-		<Exchange as OnFinalize<u64>>::on_finalize(9);
+		// A trade limit of `0` accepts whatever the AMM currently offers.
+		let intention = Exchange::get_intentions((asset_a, asset_b))[0].clone();
+		assert_ok!(Exchange::validate_intention(&intention));
 
-		assert_eq!(Currency::free_balance(asset_a, &user_2), 1000000000002000);
-		assert_eq!(Currency::free_balance(asset_b, &user_2), 999999999995991);
+		let unreachable_limit = 1_000_000_000_000_000_000;
+		assert_ok!(Exchange::amend_limit_price(
+			Origin::signed(user),
+			asset_a,
+			asset_b,
+			intention_id,
+			unreachable_limit,
+		));
 
-		assert_eq!(Currency::free_balance(asset_a, &user_3), 999999999999000);
-		assert_eq!(Currency::free_balance(asset_b, &user_3), 1000000000001998);
+		let intentions = Exchange::get_intentions((asset_a, asset_b));
+		assert_eq!(intentions[0].trade_limit, unreachable_limit);
+		assert_eq!(intentions[0].amount_sell, 1_000_000_000_000);
+		assert_eq!(intentions[0].priority, 0);
 
-		assert_eq!(Currency::free_balance(asset_a, &pair_account), 99999000);
-		assert_eq!(Currency::free_balance(asset_b, &pair_account), 200002011);
+		// No amount of pool liquidity could satisfy the amended limit - the same check an
+		// `on_finalize` AMM fallback would run against this intention if it were still queued
+		// next block.
+		assert!(Exchange::validate_intention(&intentions[0]).is_err());
 
-		expect_events(vec![
-			RawEvent::IntentionRegistered(
-				user_2,
-				asset_a,
-				asset_b,
-				2_000,
-				IntentionType::BUY,
-				user_2_sell_intention_id,
-			)
-			.into(),
-			RawEvent::IntentionRegistered(
-				user_3,
-				asset_a,
-				asset_b,
-				1_000,
-				IntentionType::SELL,
-				user_3_sell_intention_id,
-			)
-			.into(),
-			TestEvent::amm(amm::RawEvent::Buy(user_2, 3000, 2000, 1000, 2005)),
-			RawEvent::IntentionResolvedAMMTrade(user_2, IntentionType::BUY, user_2_sell_intention_id, 1000, 2005)
-				.into(),
-			RawEvent::IntentionResolvedDirectTrade(
-				user_3,
-				user_2,
-				user_3_sell_intention_id,
-				user_2_sell_intention_id,
-				1000,
-				2000,
-			)
-			.into(),
-			RawEvent::IntentionResolvedDirectTradeFees(user_3, pair_account, asset_b, 2).into(),
-			RawEvent::IntentionResolvedDirectTradeFees(user_2, pair_account, asset_b, 4).into(),
-		]);
+		expect_event(RawEvent::IntentionLimitPriceAmended(
+			user,
+			asset_a,
+			asset_b,
+			intention_id,
+			unreachable_limit,
+		));
 	});
 }
 
 #[test]
-fn single_sell_intention_test() {
+fn amend_limit_price_should_fail_when_caller_is_not_the_owner() {
 	new_test_ext().execute_with(|| {
-		let user_1 = ALICE;
-		let user_2 = BOB;
+		let owner = ALICE;
 		let asset_a = ETH;
 		let asset_b = DOT;
-		let pool_amount = 100_000_000_000_000;
-		let initial_price = Price::from(2);
 
-		let pair_account = AMMModule::get_pair_id(&asset_a, &asset_b);
-
-		initialize_pool(asset_a, asset_b, user_1, pool_amount, initial_price);
+		initialize_pool(asset_a, asset_b, owner, 100_000_000_000_000, Price::from(2));
 
 		assert_ok!(Exchange::sell(
-			Origin::signed(user_2),
+			Origin::signed(owner),
 			asset_a,
 			asset_b,
-			2_000_000_000_000,
-			400_000_000_000,
+			1_000_000_000_000,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
 			false,
+			0,
+			Price::from(1),
 		));
-		let user_2_sell_intention_id = generate_intention_id(&user_2, 0);
-
-		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 1);
+		let intention_id = generate_intention_id_for_pair(&owner, 0, asset_a, asset_b);
 
-		// Finalize block
-		<Exchange as OnFinalize<u64>>::on_finalize(9);
+		assert_noop!(
+			Exchange::amend_limit_price(Origin::signed(BOB), asset_a, asset_b, intention_id, 1),
+			Error::<Test>::NotIntentionOwner
+		);
+	});
+}
 
-		// Check final account balances -> SEEMS LEGIT
-		assert_eq!(Currency::free_balance(asset_a, &user_2), 998_000_000_000_000);
-		assert_eq!(Currency::free_balance(asset_b, &user_2), 1003913878975647);
+#[test]
+fn amend_limit_price_should_fail_when_intention_not_found() {
+	new_test_ext().execute_with(|| {
+		let user = ALICE;
+		let asset_a = ETH;
+		let asset_b = DOT;
 
-		// Check final pool balances -> SEEMS LEGIT
-		assert_eq!(Currency::free_balance(asset_a, &pair_account), 102000000000000);
-		assert_eq!(Currency::free_balance(asset_b, &pair_account), 196086121024353);
+		initialize_pool(asset_a, asset_b, user, 100_000_000_000_000, Price::from(2));
 
-		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 0);
+		let bogus_id = generate_intention_id_for_pair(&user, 99, asset_a, asset_b);
 
-		expect_events(vec![
-			RawEvent::IntentionRegistered(
-				user_2,
-				asset_a,
-				asset_b,
-				2_000_000_000_000,
-				IntentionType::SELL,
-				user_2_sell_intention_id,
-			)
-			.into(),
-			TestEvent::amm(amm::RawEvent::Sell(2, 3000, 2000, 2000000000000, 3913878975647)),
-			RawEvent::IntentionResolvedAMMTrade(
-				user_2,
-				IntentionType::SELL,
-				user_2_sell_intention_id,
-				2000000000000,
-				3913878975647,
-			)
-			.into(),
-		]);
+		assert_noop!(
+			Exchange::amend_limit_price(Origin::signed(user), asset_a, asset_b, bogus_id, 1),
+			Error::<Test>::IntentionNotFound
+		);
 	});
 }
 
 #[test]
-fn single_buy_intention_test() {
+fn high_priority_small_order_should_be_matched_before_a_larger_low_priority_order() {
 	new_test_ext().execute_with(|| {
-		let user_1 = ALICE;
-		let user_2 = BOB;
-		let asset_a = ETH;
-		let asset_b = DOT;
-		let pool_amount = 100_000_000_000_000;
-		let initial_price = Price::from(2);
+		let main = ALICE;
+		let high_priority = BOB;
+		let low_priority = CHARLIE;
+
+		initialize_pool(ETH, DOT, main, 100_000_000_000_000, Price::from(2));
+
+		let counterparty = |who: u64, amount_sell: Balance, amount_buy: Balance, priority: u8| Intention::<Test> {
+			who,
+			asset_sell: DOT,
+			asset_buy: ETH,
+			amount_sell,
+			amount_buy,
+			trade_limit: 0,
+			discount: false,
+			sell_or_buy: IntentionType::SELL,
+			intention_id: generate_intention_id_for_pair(&who, 0, DOT, ETH),
+			remaining_lifetime: DefaultIntentionLifetime::get(),
+			recipient: None,
+			valid_until_timestamp: None,
+			reference: None,
+			allow_amm_fallback: true,
+			priority,
+		};
+
+		// `low_priority` sells ten times as much as `high_priority` - an amount-only sort would
+		// pull it into the match bucket first, but `high_priority`'s elevated `priority` must win.
+		let intentions = vec![
+			counterparty(low_priority, 10_000_000_000_000, 20_000_000_000_000, 0),
+			counterparty(high_priority, 1_000_000_000_000, 2_000_000_000_000, 5),
+		];
+
+		<ExchangeAssetsIntentions<Test>>::insert((DOT, ETH), intentions);
+		ExchangeAssetsIntentionCount::insert((cmp::min(DOT, ETH), cmp::max(DOT, ETH)), 3);
+
+		<ExchangeAssetsIntentions<Test>>::insert(
+			(ETH, DOT),
+			vec![Intention::<Test> {
+				who: main,
+				asset_sell: ETH,
+				asset_buy: DOT,
+				amount_sell: 1_000_000_000_000,
+				amount_buy: 2_000_000_000_000,
+				trade_limit: 0,
+				discount: false,
+				sell_or_buy: IntentionType::SELL,
+				intention_id: generate_intention_id_for_pair(&main, 0, ETH, DOT),
+				remaining_lifetime: DefaultIntentionLifetime::get(),
+				recipient: None,
+				valid_until_timestamp: None,
+				reference: None,
+				allow_amm_fallback: true,
+				priority: 0,
+			}],
+		);
 
-		let pair_account = AMMModule::get_pair_id(&asset_a, &asset_b);
+		<Exchange as OnFinalize<u64>>::on_finalize(1);
+
+		let matched_against = system::Module::<Test>::events().into_iter().find_map(|record| {
+			if let TestEvent::exchange(RawEvent::IntentionResolvedDirectTrade(_, b_who, _, _, _, _, _, _, _, _)) =
+				record.event
+			{
+				Some(b_who)
+			} else {
+				None
+			}
+		});
+
+		assert_eq!(
+			matched_against,
+			Some(high_priority),
+			"the high-priority counterparty should be matched even though it sells far less"
+		);
+	});
+}
 
-		initialize_pool(asset_a, asset_b, user_1, pool_amount, initial_price);
+#[test]
+fn constant_metadata_should_expose_the_configured_constants() {
+	let metadata = Exchange::module_constants_metadata();
+
+	let decode_value = |name: &str| -> Vec<u8> {
+		let constant = metadata
+			.iter()
+			.find(|c| c.name == DecodeDifferent::Encode(name))
+			.unwrap_or_else(|| panic!("{} should be present in module constants metadata", name));
+
+		match constant.value {
+			DecodeDifferent::Encode(bytes) => bytes.to_vec(),
+			DecodeDifferent::Decoded(ref bytes) => bytes.clone(),
+		}
+	};
 
-		assert_ok!(Exchange::buy(
-			Origin::signed(user_2),
-			asset_a,
-			asset_b,
-			2_000_000_000_000,
-			15000_000_000_000,
+	assert_eq!(Balance::decode(&mut &decode_value("MinTradingLimit")[..]).unwrap(), 1_000);
+	assert_eq!(AssetId::decode(&mut &decode_value("NativeAssetId")[..]).unwrap(), HDX);
+	assert_eq!(Balance::decode(&mut &decode_value("CancellationFee")[..]).unwrap(), 0);
+	assert_eq!(u32::decode(&mut &decode_value("MaxEventsPerBlock")[..]).unwrap(), u32::MAX);
+}
+
+#[test]
+fn signed_extension_should_reject_a_sell_naming_a_pair_with_no_pool() {
+	new_test_ext().execute_with(|| {
+		// No pool was ever created for (HDX, DOT) in this test, and `create_if_missing` is `false`.
+		let call = TestCall::Exchange(Call::sell(
+			HDX,
+			DOT,
+			1_000,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
 			false,
+			0,
+			Price::from(1),
 		));
 
-		let user_2_sell_intention_id = generate_intention_id(&user_2, 0);
+		let result = RejectObviouslyInvalidExchangeCall::<Test>::new().validate(&ALICE, &call, &Default::default(), 0);
 
-		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 1);
+		assert_eq!(result, Err(InvalidTransaction::Custom(2).into()));
+	});
+}
 
-		// Finalize block
-		<Exchange as OnFinalize<u64>>::on_finalize(9);
+#[test]
+fn signed_extension_should_reject_a_zero_amount_sell() {
+	new_test_ext().execute_with(|| {
+		initialize_pool(HDX, DOT, ALICE, 100_000_000_000_000, Price::from(2));
 
-		// Check final account balances -> SEEMS LEGIT
-		assert_eq!(Currency::free_balance(asset_a, &user_2), 1002000000000000);
-		assert_eq!(Currency::free_balance(asset_b, &user_2), 995910037144373);
+		let call = TestCall::Exchange(Call::sell(
+			HDX,
+			DOT,
+			0,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
 
-		// Check final pool balances -> SEEMS LEGIT
-		assert_eq!(Currency::free_balance(asset_a, &pair_account), 98000000000000);
-		assert_eq!(Currency::free_balance(asset_b, &pair_account), 204089962855627);
+		let result = RejectObviouslyInvalidExchangeCall::<Test>::new().validate(&ALICE, &call, &Default::default(), 0);
 
-		assert_eq!(Exchange::get_intentions_count((asset_b, asset_a)), 0);
+		assert_eq!(result, Err(InvalidTransaction::Custom(1).into()));
+	});
+}
 
-		expect_events(vec![
-			RawEvent::IntentionRegistered(
-				user_2,
-				asset_a,
-				asset_b,
-				2_000_000_000_000,
-				IntentionType::BUY,
-				user_2_sell_intention_id,
-			)
-			.into(),
-			TestEvent::amm(amm::RawEvent::Buy(2, 3000, 2000, 2000000000000, 4089962855627)),
-			RawEvent::IntentionResolvedAMMTrade(
-				user_2,
-				IntentionType::BUY,
-				user_2_sell_intention_id,
-				2000000000000,
-				4089962855627,
-			)
-			.into(),
-		]);
+#[test]
+fn signed_extension_should_accept_a_sell_against_an_existing_pool() {
+	new_test_ext().execute_with(|| {
+		initialize_pool(HDX, DOT, ALICE, 100_000_000_000_000, Price::from(2));
+
+		let call = TestCall::Exchange(Call::sell(
+			HDX,
+			DOT,
+			1_000,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+
+		let result = RejectObviouslyInvalidExchangeCall::<Test>::new().validate(&ALICE, &call, &Default::default(), 0);
+
+		assert!(result.is_ok());
 	});
 }
 
 #[test]
-fn simple_sell_sell_with_error_should_not_pass() {
+fn signed_extension_should_accept_a_sell_with_no_pool_when_create_if_missing_is_set() {
 	new_test_ext().execute_with(|| {
-		let user_1 = ALICE;
-		let user_2 = BOB;
-		let user_3 = CHARLIE;
-		let asset_a = ETH;
-		let asset_b = DOT;
-		let pool_amount = 100_000_000;
-		let initial_price = Price::from(2);
+		// `create_if_missing` is `true`, so the missing (HDX, DOT) pool is not a reason to reject
+		// up front - `sell` will create it itself before registering the intention.
+		let call = TestCall::Exchange(Call::sell(
+			HDX,
+			DOT,
+			1_000,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			true,
+			0,
+			Price::from(1),
+		));
 
-		let pair_account = AMMModule::get_pair_id(&asset_a, &asset_b);
+		let result = RejectObviouslyInvalidExchangeCall::<Test>::new().validate(&ALICE, &call, &Default::default(), 0);
 
-		initialize_pool(asset_a, asset_b, user_1, pool_amount, initial_price);
+		assert!(result.is_ok());
+	});
+}
 
+#[test]
+fn intention_receipt_should_differ_when_the_same_intention_id_is_replayed_after_a_reorg() {
+	new_test_ext().execute_with(|| {
+		let asset_a = HDX;
+		let asset_b = DOT;
+		initialize_pool(asset_a, asset_b, ALICE, 100_000_000_000_000, Price::from(2));
+
+		let pair = (cmp::min(asset_a, asset_b), cmp::max(asset_a, asset_b));
+		let intention_count_before = ExchangeAssetsIntentionCount::get(pair);
+
+		let last_receipt = || -> IntentionReceipt<crate::IntentionId<Test>> {
+			system::Module::<Test>::events()
+				.into_iter()
+				.find_map(|record| {
+					if let TestEvent::exchange(RawEvent::IntentionReceiptIssued(receipt)) = record.event {
+						Some(receipt)
+					} else {
+						None
+					}
+				})
+				.expect("IntentionReceiptIssued should have been deposited")
+		};
+
+		// Original block 5, built on parent hash `[1; 32]`.
+		System::initialize(&5, &[1u8; 32].into(), &Default::default(), &Default::default(), InitKind::Full);
 		assert_ok!(Exchange::sell(
-			Origin::signed(user_2),
+			Origin::signed(ALICE),
 			asset_a,
 			asset_b,
-			2_000,
-			5_000,
+			1_000_000_000_000,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
 			false,
+			0,
+			Price::from(1),
 		));
+		let receipt_a = last_receipt();
 
-		let user_2_sell_intention_id = generate_intention_id(&user_2, 0);
-
+		// Block 5 is reverted by a re-org - its storage changes, including the nonce this `sell`
+		// just bumped, are rolled back with it, so the replacement block 5 starts from exactly the
+		// same state, just with a different parent.
+		ExchangeAssetsIntentionCount::insert(pair, intention_count_before);
+		System::initialize(&5, &[2u8; 32].into(), &Default::default(), &Default::default(), InitKind::Full);
 		assert_ok!(Exchange::sell(
-			Origin::signed(user_3),
-			asset_b,
+			Origin::signed(ALICE),
 			asset_a,
-			1_000,
-			5_000,
+			asset_b,
+			1_000_000_000_000,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
 			false,
+			0,
+			Price::from(1),
 		));
+		let receipt_b = last_receipt();
 
-		let user_3_sell_intention_id = generate_intention_id(&user_3, 1);
+		// Both executions land on the exact same `IntentionId` - block number alone doesn't
+		// distinguish the fork they actually happened on.
+		assert_eq!(receipt_a.intention_id, receipt_b.intention_id);
+		// Their receipts still differ, since each was issued against a different parent hash.
+		assert_ne!(receipt_a.block_hash_prefix, receipt_b.block_hash_prefix);
+	});
+}
 
-		assert_eq!(Currency::free_balance(asset_a, &pair_account), 100000000);
-		assert_eq!(Currency::free_balance(asset_b, &pair_account), 200000000);
+#[test]
+fn sell_should_reject_new_intention_once_pending_intentions_bytes_budget_is_reached() {
+	// Two intentions of different shapes - the second carries every optional field the first
+	// leaves empty - fill `PendingIntentionsBytes` up to a budget capped at exactly their
+	// combined encoded size, leaving no room for a third intention of any size.
+	new_test_ext().execute_with(|| {
+		assert_ok!(AMMModule::create_pool(Origin::signed(ALICE), HDX, ETH, 200_000_000, Price::from(2)));
 
-		<Exchange as OnFinalize<u64>>::on_finalize(9);
+		assert_eq!(Exchange::pending_intentions_bytes(), 0);
 
-		assert_eq!(Currency::free_balance(asset_a, &user_2), 1000000000000000);
-		assert_eq!(Currency::free_balance(asset_b, &user_2), 1000000000000000);
+		assert_ok!(Exchange::sell(
+			Origin::signed(BOB),
+			HDX,
+			ETH,
+			1_000,
+			0,
+			false,
+			None,
+			None,
+			None,
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		let bytes_after_first = Exchange::pending_intentions_bytes();
+		assert!(bytes_after_first > 0);
 
-		assert_eq!(Currency::free_balance(asset_a, &user_3), 1000000000000000);
-		assert_eq!(Currency::free_balance(asset_b, &user_3), 1000000000000000);
+		assert_ok!(Exchange::sell(
+			Origin::signed(CHARLIE),
+			HDX,
+			ETH,
+			1_000,
+			0,
+			false,
+			Some(DAVE),
+			Some(1_000_000),
+			Some([7u8; 32]),
+			true,
+			false,
+			0,
+			Price::from(1),
+		));
+		let bytes_after_second = Exchange::pending_intentions_bytes();
+		assert!(bytes_after_second > bytes_after_first);
 
-		assert_eq!(Currency::free_balance(asset_a, &pair_account), 100000000);
-		assert_eq!(Currency::free_balance(asset_b, &pair_account), 200000000);
+		MaxIntentionsBytesMock::set(bytes_after_second);
 
-		expect_events(vec![
-			RawEvent::IntentionRegistered(
-				user_2,
-				asset_a,
-				asset_b,
-				2_000,
-				IntentionType::SELL,
-				user_2_sell_intention_id,
-			)
-			.into(),
-			RawEvent::IntentionRegistered(
-				user_3,
-				asset_b,
-				asset_a,
+		assert_noop!(
+			Exchange::sell(
+				Origin::signed(FERDIE),
+				HDX,
+				ETH,
 				1_000,
-				IntentionType::SELL,
-				user_3_sell_intention_id,
-			)
-			.into(),
-			RawEvent::AMMSellErrorEvent(
-				user_2,
-				asset_a,
-				asset_b,
-				IntentionType::SELL,
-				user_2_sell_intention_id,
-				DispatchError::Module {
-					index: 0,
-					error: 5,
-					message: None,
-				},
-			)
-			.into(),
-			RawEvent::IntentionResolveErrorEvent(
-				user_3,
-				asset_b,
-				asset_a,
-				IntentionType::SELL,
-				user_3_sell_intention_id,
-				DispatchError::Module {
-					index: 0,
-					error: 5,
-					message: None,
-				},
-			)
-			.into(),
-		]);
+				0,
+				false,
+				None,
+				None,
+				None,
+				true,
+				false,
+				0,
+				Price::from(1),
+			),
+			Error::<Test>::IntentionStorageBudgetExceeded.with_weight(<Test as Config>::WeightInfo::reject_intention())
+		);
+		assert_eq!(Exchange::pending_intentions_bytes(), bytes_after_second);
+
+		<Exchange as OnFinalize<u64>>::on_finalize(9);
+
+		// Both intentions settled fully against the deep pool and nothing carried forward, so
+		// the budget is reclaimed for the next block.
+		assert_eq!(Exchange::pending_intentions_bytes(), 0);
 	});
 }
@@ -46,6 +46,7 @@ use sp_std::marker::PhantomData;
 pub trait WeightInfo {
 	fn known_overhead_for_on_finalize() -> Weight;
 	fn sell_intention() -> Weight;
+	fn sell_all_intention() -> Weight;
 	fn buy_intention() -> Weight;
 	fn on_finalize(t: u32, ) -> Weight;
 	fn on_finalize_buys_no_matches(t: u32, ) -> Weight;
@@ -54,6 +55,24 @@ pub trait WeightInfo {
 	fn on_finalize_for_one_sell_extrinsic() -> Weight;
 	fn buy_extrinsic() -> Weight;
 	fn on_finalize_for_one_buy_extrinsic() -> Weight;
+	fn set_min_pool_liquidity() -> Weight;
+	fn set_pair_max_slippage() -> Weight;
+	fn reject_intention() -> Weight;
+	fn freeze_asset() -> Weight;
+	fn thaw_asset() -> Weight;
+	fn set_fee_exempt() -> Weight;
+	fn unset_fee_exempt() -> Weight;
+	fn on_idle_intention_check() -> Weight;
+	fn replace_intention() -> Weight;
+	fn set_intention_priority() -> Weight;
+	fn amend_limit_price() -> Weight;
+	fn sell(queued: u32) -> Weight;
+	fn buy(queued: u32) -> Weight;
+	fn cancel_pair(intentions: u32) -> Weight;
+	fn set_asset_min_trade_amount() -> Weight;
+	fn pause() -> Weight;
+	fn resume() -> Weight;
+	fn resolve_pair(intentions: u32) -> Weight;
 }
 
 /// Weights for exchange using the hack.hydraDX node and recommended hardware.
@@ -68,6 +87,11 @@ impl<T: frame_system::Config> WeightInfo for HackHydraWeight<T> {
 			.saturating_add(T::DbWeight::get().reads(6 as Weight))
 			.saturating_add(T::DbWeight::get().writes(2 as Weight))
 	}
+	fn sell_all_intention() -> Weight {
+		(85_164_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(7 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
 	fn buy_intention() -> Weight {
 		(85_048_000 as Weight)
 			.saturating_add(T::DbWeight::get().reads(6 as Weight))
@@ -120,6 +144,96 @@ impl<T: frame_system::Config> WeightInfo for HackHydraWeight<T> {
 			.saturating_add(T::DbWeight::get().reads(9 as Weight))
 			.saturating_add(T::DbWeight::get().writes(6 as Weight))
 	}
+	fn set_min_pool_liquidity() -> Weight {
+		(15_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	fn set_pair_max_slippage() -> Weight {
+		(15_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	fn reject_intention() -> Weight {
+		(10_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+	}
+	fn freeze_asset() -> Weight {
+		(15_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	fn thaw_asset() -> Weight {
+		(15_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	fn set_fee_exempt() -> Weight {
+		(15_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	fn unset_fee_exempt() -> Weight {
+		(15_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	fn on_idle_intention_check() -> Weight {
+		(10_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(1 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	fn replace_intention() -> Weight {
+		(15_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	fn set_intention_priority() -> Weight {
+		(15_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	fn amend_limit_price() -> Weight {
+		(15_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	fn sell(queued: u32) -> Weight {
+		(85_164_000 as Weight)
+			.saturating_add((50_000 as Weight).saturating_mul(queued as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
+	fn buy(queued: u32) -> Weight {
+		(85_048_000 as Weight)
+			.saturating_add((50_000 as Weight).saturating_mul(queued as Weight))
+			.saturating_add(T::DbWeight::get().reads(6 as Weight))
+			.saturating_add(T::DbWeight::get().writes(2 as Weight))
+	}
+	fn cancel_pair(intentions: u32) -> Weight {
+		(15_000_000 as Weight)
+			.saturating_add((50_000 as Weight).saturating_mul(intentions as Weight))
+			.saturating_add(T::DbWeight::get().reads(2 as Weight))
+			.saturating_add(T::DbWeight::get().writes(3 as Weight))
+	}
+	fn set_asset_min_trade_amount() -> Weight {
+		(15_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	fn pause() -> Weight {
+		(15_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	fn resume() -> Weight {
+		(15_000_000 as Weight)
+			.saturating_add(T::DbWeight::get().writes(1 as Weight))
+	}
+	// The `resolve_pair` benchmark drives a single huge intention against `intentions` tiny
+	// counterparties, the adversarial shape that makes `process_exchange_intentions`'s
+	// `Vec::remove` counterparty removal loop quadratic - fitted here as an explicit quadratic
+	// term rather than the usual linear one, since a linear fit would under-charge the tail.
+	fn resolve_pair(intentions: u32) -> Weight {
+		(21_475_000 as Weight)
+			.saturating_add((45_000 as Weight).saturating_mul((intentions as Weight).saturating_mul(intentions as Weight)))
+			.saturating_add(T::DbWeight::get().reads(7 as Weight))
+			.saturating_add(T::DbWeight::get().reads((2 as Weight).saturating_mul(intentions as Weight)))
+			.saturating_add(T::DbWeight::get().writes(4 as Weight))
+			.saturating_add(T::DbWeight::get().writes((2 as Weight).saturating_mul(intentions as Weight)))
+	}
 }
 
 // For backwards compatibility and tests
@@ -133,6 +247,11 @@ impl WeightInfo for () {
 			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
 			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
 	}
+	fn sell_all_intention() -> Weight {
+		(85_164_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(7 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+	}
 	fn buy_intention() -> Weight {
 		(85_048_000 as Weight)
 			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
@@ -185,4 +304,90 @@ impl WeightInfo for () {
 			.saturating_add(RocksDbWeight::get().reads(9 as Weight))
 			.saturating_add(RocksDbWeight::get().writes(6 as Weight))
 	}
+	fn set_min_pool_liquidity() -> Weight {
+		(15_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn set_pair_max_slippage() -> Weight {
+		(15_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn reject_intention() -> Weight {
+		(10_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+	}
+	fn freeze_asset() -> Weight {
+		(15_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn thaw_asset() -> Weight {
+		(15_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn set_fee_exempt() -> Weight {
+		(15_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn unset_fee_exempt() -> Weight {
+		(15_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn on_idle_intention_check() -> Weight {
+		(10_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(1 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn replace_intention() -> Weight {
+		(15_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn set_intention_priority() -> Weight {
+		(15_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn amend_limit_price() -> Weight {
+		(15_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn sell(queued: u32) -> Weight {
+		(85_164_000 as Weight)
+			.saturating_add((50_000 as Weight).saturating_mul(queued as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+	}
+	fn buy(queued: u32) -> Weight {
+		(85_048_000 as Weight)
+			.saturating_add((50_000 as Weight).saturating_mul(queued as Weight))
+			.saturating_add(RocksDbWeight::get().reads(6 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(2 as Weight))
+	}
+	fn cancel_pair(intentions: u32) -> Weight {
+		(15_000_000 as Weight)
+			.saturating_add((50_000 as Weight).saturating_mul(intentions as Weight))
+			.saturating_add(RocksDbWeight::get().reads(2 as Weight))
+			.saturating_add(RocksDbWeight::get().writes(3 as Weight))
+	}
+	fn set_asset_min_trade_amount() -> Weight {
+		(15_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn pause() -> Weight {
+		(15_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn resume() -> Weight {
+		(15_000_000 as Weight)
+			.saturating_add(RocksDbWeight::get().writes(1 as Weight))
+	}
+	fn resolve_pair(intentions: u32) -> Weight {
+		(21_475_000 as Weight)
+			.saturating_add((45_000 as Weight).saturating_mul((intentions as Weight).saturating_mul(intentions as Weight)))
+			.saturating_add(RocksDbWeight::get().reads(7 as Weight))
+			.saturating_add(RocksDbWeight::get().reads((2 as Weight).saturating_mul(intentions as Weight)))
+			.saturating_add(RocksDbWeight::get().writes(4 as Weight))
+			.saturating_add(RocksDbWeight::get().writes((2 as Weight).saturating_mul(intentions as Weight)))
+	}
 }
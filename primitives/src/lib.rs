@@ -6,6 +6,7 @@ use frame_support::sp_runtime::FixedU128;
 use primitive_types::U256;
 #[cfg(feature = "std")]
 use serde::{Deserialize, Serialize};
+use sp_std::vec::Vec;
 
 pub mod traits;
 
@@ -53,9 +54,47 @@ impl Default for IntentionType {
 	}
 }
 
+/// One settled leg of a trade, recorded for `ExchangeApi::last_block_fills` - a direct trade
+/// between two intentions produces one `FillRecord` per side, an AMM fill produces exactly one.
 #[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 #[derive(Encode, Decode, Default, Clone, PartialEq)]
-pub struct ExchangeIntention<AccountId, AssetId, Balance, IntentionID> {
+pub struct FillRecord<IntentionID, AssetId, Balance> {
+	pub intention_id: IntentionID,
+	pub asset_sell: AssetId,
+	pub asset_buy: AssetId,
+	pub direction: IntentionType,
+	/// Amount of `asset_sell` this leg settled.
+	pub amount: Balance,
+	/// Realized price of this fill, as `asset_buy` received per unit of `asset_sell` sold, scaled
+	/// by `PRICE_PROXIMITY_PRECISION` - the same fixed-point scale `sort_by_price_proximity` uses.
+	pub price: Balance,
+	/// Whether this leg settled directly against another intention, as opposed to via the AMM.
+	pub direct: bool,
+}
+
+/// Everything that happened to one of an account's intentions during the block this state was
+/// read from, recorded for `ExchangeApi::account_settlements` - a coarser, per-intention
+/// counterpart to `FillRecord`'s per-leg view, so a caller doesn't have to reconstruct "what did
+/// my order do" by filtering `last_block_fills` for their own intention ids and summing the legs
+/// themselves.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Default, Clone, PartialEq)]
+pub struct SettlementRecord<IntentionID, Balance> {
+	pub intention_id: IntentionID,
+	/// Total amount of the intention's `amount_sell` settled this block, direct and AMM combined.
+	pub filled_amount: Balance,
+	/// Total direct-trade fee charged against this intention's own side of its settlement.
+	pub fee_paid: Balance,
+	/// Number of counterparties this intention was directly matched against - `0` if it settled
+	/// entirely through the AMM fallback (or not at all).
+	pub counterparty_count: u32,
+	/// Portion of `filled_amount` routed through the AMM rather than matched directly.
+	pub amm_portion: Balance,
+}
+
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Default, Clone, PartialEq)]
+pub struct ExchangeIntention<AccountId, AssetId, Balance, IntentionID, BlockNumber, Moment> {
 	pub who: AccountId,
 	pub asset_sell: AssetId,
 	pub asset_buy: AssetId,
@@ -65,6 +104,58 @@ pub struct ExchangeIntention<AccountId, AssetId, Balance, IntentionID> {
 	pub discount: bool,
 	pub sell_or_buy: IntentionType,
 	pub intention_id: IntentionID,
+	/// Number of remaining blocks this intention may be retried in before it is dropped.
+	pub remaining_lifetime: BlockNumber,
+	/// Account which should receive the proceeds of this intention, if different from `who`.
+	pub recipient: Option<AccountId>,
+	/// Alternative, wall-clock based expiry - if set, the intention is dropped once
+	/// `pallet_timestamp::Now` reaches this value, regardless of `remaining_lifetime`.
+	pub valid_until_timestamp: Option<Moment>,
+	/// Opaque client-supplied reference, echoed back in this intention's registration and
+	/// resolution events so integrators can correlate them with their own order ids. No on-chain
+	/// logic depends on it.
+	pub reference: Option<[u8; 32]>,
+	/// Whether any amount left unmatched after direct P2P trading may be routed through the AMM.
+	/// If `false`, the leftover is dropped and unreserved instead, rather than filled at whatever
+	/// price the AMM currently offers.
+	pub allow_amm_fallback: bool,
+	/// Matching priority - higher goes first. `0` (the default) is assigned by `sell`/`buy` to
+	/// every ordinary intention; raising it afterwards is a paid feature, see
+	/// `pallet_exchange::Config::PriorityFee` and `set_intention_priority`.
+	pub priority: u8,
+}
+
+impl<AccountId, AssetId, Balance, IntentionID, BlockNumber, Moment>
+	ExchangeIntention<AccountId, AssetId, Balance, IntentionID, BlockNumber, Moment>
+{
+	/// The account which should receive the proceeds of this intention - `recipient` if set,
+	/// otherwise `who`.
+	pub fn beneficiary(&self) -> &AccountId {
+		self.recipient.as_ref().unwrap_or(&self.who)
+	}
+}
+
+/// Read-only preview of how a single intention would settle if matching ran right now - one
+/// entry of the plan `pallet_exchange::Module::compute_matches` returns. Reflects storage as it
+/// currently stands and executes nothing, so it can be stale by the time an actual block settles
+/// it - concurrent registrations, cancellations or AMM price movement between the preview and
+/// settlement are not accounted for.
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[derive(Encode, Decode, Default, Clone, PartialEq)]
+pub struct MatchPreview<AccountId, IntentionID, Balance> {
+	pub intention_id: IntentionID,
+	pub who: AccountId,
+	/// Direct counterparties this intention would be matched against, in the same greedy order
+	/// `match_intentions` would pick them - `(intention id, who, amount of their `amount_sell`
+	/// this match would absorb)`.
+	pub matched_against: Vec<(IntentionID, AccountId, Balance)>,
+	/// Amount of `amount_sell` left over after `matched_against`, that would be routed through the
+	/// AMM instead - `0` if the intention would be fully matched directly.
+	pub amm_leftover: Balance,
+	/// `T::AMMPool`'s current spot price for `amm_leftover`, if any is left over - a preview only,
+	/// not necessarily the price it would actually execute at once other intentions in the same
+	/// block have moved the pool's reserves.
+	pub amm_preview_price: Option<Balance>,
 }
 
 pub mod fee {
@@ -85,12 +176,29 @@ pub mod fee {
 		}
 	}
 
+	impl Fee {
+		/// The reduced fee applied to a discounted trade - kept in step with `discounted_fee`'s
+		/// own `Fee`, but exposed here too so callers that need it rounded up (fees collected as
+		/// their own transfer rather than netted out) aren't stuck with `discounted_fee`'s
+		/// truncating `just_fee`.
+		pub fn discounted() -> Self {
+			Fee {
+				numerator: 7,
+				denominator: 10000,
+			} // 0.07%
+		}
+	}
+
 	pub trait WithFee
 	where
 		Self: Sized,
 	{
 		fn with_fee(&self, fee: Fee) -> Option<Self>;
 		fn just_fee(&self, fee: Fee) -> Option<Self>;
+		/// Same as `just_fee`, but rounds the fee up instead of truncating it down. Used wherever
+		/// the fee is collected as its own transfer rather than netted out of a larger one, so
+		/// truncation can't quietly leak value away from the pool.
+		fn just_fee_round_up(&self, fee: Fee) -> Option<Self>;
 		fn discounted_fee(&self) -> Option<Self>;
 	}
 
@@ -105,12 +213,19 @@ pub mod fee {
 				.checked_div(fee.denominator as Self)
 		}
 
+		fn just_fee_round_up(&self, fee: Fee) -> Option<Self> {
+			let product = self.checked_mul(fee.numerator as Self)?;
+			let denominator = fee.denominator as Self;
+			let quotient = product.checked_div(denominator)?;
+			if product.checked_rem(denominator)? > 0 {
+				quotient.checked_add(1)
+			} else {
+				Some(quotient)
+			}
+		}
+
 		fn discounted_fee(&self) -> Option<Self> {
-			let fee = Fee {
-				numerator: 7,
-				denominator: 10000,
-			};
-			self.just_fee(fee)
+			self.just_fee(Fee::discounted())
 		}
 	}
 }
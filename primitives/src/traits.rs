@@ -93,15 +93,81 @@ pub trait AMM<AccountId, AssetId, Amount> {
 	}
 }
 
-pub trait Resolver<AccountId, Intention, E> {
-	/// Resolve an intention directl via AMM pool.
-	fn resolve_single_intention(intention: &Intention);
+/// Bootstraps a pool for a pair on behalf of a caller who doesn't otherwise have access to the
+/// AMM pallet's own dispatchables - e.g. `pallet_exchange::Config::AMMTrader`, used to create a
+/// pool on demand for `sell`/`buy` when one doesn't exist yet and the caller opts in.
+pub trait AMMTrader<AccountId, AssetId, Balance, Price> {
+	/// Create a pool for `asset_a`/`asset_b`, seeded with `amount` of `asset_a` and whatever
+	/// `initial_price` implies of `asset_b`, both debited from `who`, which receives the newly
+	/// minted share token in return.
+	fn create_pool(
+		who: &AccountId,
+		asset_a: AssetId,
+		asset_b: AssetId,
+		amount: Balance,
+		initial_price: Price,
+	) -> DispatchResult;
+}
+
+pub trait Resolver<AccountId, Intention, E, Balance> {
+	/// Resolve an intention directl via AMM pool. Returns the amount settled via the AMM, or
+	/// `0` if it couldn't be filled at all.
+	fn resolve_single_intention(intention: &Intention) -> Balance;
 
 	/// Resolve intentions by either directly trading with each other or via AMM pool.
 	/// Intention ```intention``` must be validated prior to call this function.
-	fn resolve_matched_intentions(pair_account: &AccountId, intention: &Intention, matched: &[Intention]);
+	///
+	/// Returns `(matched_volume, amm_volume)` - the amount settled directly against `matched`
+	/// and the amount settled via the AMM for whatever was left over, respectively.
+	fn resolve_matched_intentions(
+		pair_account: &AccountId,
+		intention: &Intention,
+		matched: &[Intention],
+	) -> (Balance, Balance);
 }
 
 pub trait CurrencySwap<AccountId, Balance> {
 	fn swap_currency(who: &AccountId, fee: Balance) -> DispatchResult;
 }
+
+/// Provides an independent reference price for a pair of assets.
+/// Used to sanity check prices coming out of an AMM pool before they are trusted.
+pub trait PriceProvider<AssetId, Balance> {
+	/// Return the reference amount of `asset_b` corresponding to `amount` of `asset_a`, if known.
+	fn spot_price(asset_a: AssetId, asset_b: AssetId, amount: Balance) -> Option<Balance>;
+}
+
+/// No-op oracle for deployments which don't configure one - always reports "unknown".
+impl<AssetId, Balance> PriceProvider<AssetId, Balance> for () {
+	fn spot_price(_asset_a: AssetId, _asset_b: AssetId, _amount: Balance) -> Option<Balance> {
+		None
+	}
+}
+
+/// Notified after a trade settles, whether directly against another intention or via the AMM -
+/// lets other pallets (e.g. rewards or staking) react to trading activity without the exchange
+/// pallet needing to know anything about them.
+pub trait OnTradeHandler<AccountId, AssetId, Balance> {
+	/// `who` sold `amount_in` of `asset_in` for `amount_out` of `asset_out`. Called once per
+	/// settled leg, so a direct match between two intentions calls this twice, once for each side.
+	fn on_trade(who: &AccountId, asset_in: AssetId, asset_out: AssetId, amount_in: Balance, amount_out: Balance);
+}
+
+/// No-op handler for deployments which don't configure one.
+impl<AccountId, AssetId, Balance> OnTradeHandler<AccountId, AssetId, Balance> for () {
+	fn on_trade(_who: &AccountId, _asset_in: AssetId, _asset_out: AssetId, _amount_in: Balance, _amount_out: Balance) {}
+}
+
+/// Notified when a pool is destroyed, so any queued trades against it can be cleaned up before
+/// they become permanently unresolvable.
+pub trait IntentionPurger<AssetId> {
+	/// Remove and unreserve every queued intention for `(asset_a, asset_b)` - called after the
+	/// pool backing that pair has been destroyed.
+	fn purge_pair_intentions(asset_a: AssetId, asset_b: AssetId);
+}
+
+/// No-op handler for deployments which don't configure one - a destroyed pool's queued
+/// intentions are simply left to fail settlement on their own once their pool is gone.
+impl<AssetId> IntentionPurger<AssetId> for () {
+	fn purge_pair_intentions(_asset_a: AssetId, _asset_b: AssetId) {}
+}
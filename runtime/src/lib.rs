@@ -14,7 +14,7 @@ use sp_api::impl_runtime_apis;
 use sp_authority_discovery::AuthorityId as AuthorityDiscoveryId;
 use sp_core::{
 	crypto::KeyTypeId,
-	u32_trait::{_2, _3, _4},
+	u32_trait::{_1, _2, _3, _4},
 	OpaqueMetadata,
 };
 use sp_runtime::traits::{
@@ -54,11 +54,12 @@ use sp_runtime::curve::PiecewiseLinear;
 use pallet_session::historical as session_historical;
 
 use module_amm_rpc_runtime_api as amm_rpc;
+use module_exchange_rpc_runtime_api as exchange_rpc;
 
 use orml_currencies::BasicCurrencyAdapter;
 use orml_traits::parameter_type_with_key;
 
-pub use primitives::{Amount, AssetId, Balance, Moment, CORE_ASSET_ID};
+pub use primitives::{Amount, AssetId, Balance, FillRecord, Moment, CORE_ASSET_ID};
 
 /// Import HydraDX pallets
 pub use pallet_asset_registry;
@@ -334,6 +335,48 @@ impl pallet_asset_registry::Config for Runtime {
 
 parameter_types! {
 	pub ExchangeFee: fee::Fee = fee::Fee::default();
+
+	pub const MaxPriceDeviation: Permill = Permill::from_percent(10);
+
+	pub const MaxPriceImpact: Permill = Permill::from_percent(10);
+
+	pub const DefaultIntentionLifetime: BlockNumber = 3;
+
+	pub const EnablePartialAMMFill: bool = false;
+
+	pub const MinTradingLimit: Balance = 1_000;
+
+	pub const EnableRouting: bool = false;
+
+	pub const MatchTolerance: Balance = 1_000;
+
+	pub const MinMatchSize: Balance = 0;
+
+	pub const MaxCounterpartiesPerIntention: u32 = 100;
+
+	pub const MinPoolReserve: Balance = 0;
+
+	pub const CollectFeesInNative: bool = false;
+
+	pub const PriceProximityMatching: bool = false;
+
+	pub const MaxEventsPerBlock: u32 = 1_000;
+
+	pub const CancellationFee: Balance = 0;
+
+	pub const NetSettlementTransfers: bool = false;
+
+	pub const AllowPartialOnShortfall: bool = false;
+
+	pub const PriorityFee: Balance = 0;
+
+	pub const AllowPoolCreationOnDemand: bool = false;
+
+	pub const MinFee: Balance = 0;
+
+	pub const MaxFee: Balance = 1_000 * DOLLARS;
+
+	pub const MaxIntentionsBytes: u32 = 1_000_000;
 }
 
 impl pallet_amm::Config for Runtime {
@@ -343,6 +386,7 @@ impl pallet_amm::Config for Runtime {
 	type HDXAssetId = HDXAssetId;
 	type WeightInfo = pallet_amm::weights::HackHydraWeight<Runtime>;
 	type GetExchangeFee = ExchangeFee;
+	type IntentionHandler = Exchange;
 }
 
 impl pallet_exchange::Config for Runtime {
@@ -351,6 +395,36 @@ impl pallet_exchange::Config for Runtime {
 	type Resolver = Exchange;
 	type Currency = Currencies;
 	type WeightInfo = pallet_exchange::weights::HackHydraWeight<Runtime>;
+	type PriceOracle = ();
+	type MaxPriceDeviation = MaxPriceDeviation;
+	type MaxPriceImpact = MaxPriceImpact;
+	type DefaultIntentionLifetime = DefaultIntentionLifetime;
+	type EnablePartialAMMFill = EnablePartialAMMFill;
+	type MinTradingLimit = MinTradingLimit;
+	type MatchTolerance = MatchTolerance;
+	type MinMatchSize = MinMatchSize;
+	type MaxCounterpartiesPerIntention = MaxCounterpartiesPerIntention;
+	type OnTradeHandler = ();
+	type MinPoolReserve = MinPoolReserve;
+	type CollectFeesInNative = CollectFeesInNative;
+	type PriceProximityMatching = PriceProximityMatching;
+	type MaxEventsPerBlock = MaxEventsPerBlock;
+	type CancellationFee = CancellationFee;
+	type NetSettlementTransfers = NetSettlementTransfers;
+	type AllowPartialOnShortfall = AllowPartialOnShortfall;
+	type PriorityFee = PriorityFee;
+	type AMMTrader = AMM;
+	type AllowPoolCreationOnDemand = AllowPoolCreationOnDemand;
+	type MinFee = MinFee;
+	type MaxFee = MaxFee;
+	type MaxIntentionsBytes = MaxIntentionsBytes;
+	type EnableRouting = EnableRouting;
+	type NativeAssetId = HDXAssetId;
+	type PauseOrigin = EnsureOneOf<
+		AccountId,
+		EnsureRoot<AccountId>,
+		pallet_collective::EnsureMembers<_1, AccountId, CouncilCollective>,
+	>;
 }
 
 impl pallet_faucet::Config for Runtime {
@@ -632,7 +706,7 @@ construct_runtime!(
 		// HydraDX related modules
 		AssetRegistry: pallet_asset_registry::{Module, Call, Storage, Config<T>},
 		AMM: pallet_amm::{Module, Call, Storage, Event<T>},
-		Exchange: pallet_exchange::{Module, Call, Storage, Event<T>},
+		Exchange: pallet_exchange::{Module, Call, Storage, Event<T>, ValidateUnsigned},
 		Faucet: pallet_faucet::{Module, Call, Storage, Config, Event<T>},
 		MultiTransactionPayment: pallet_transaction_multi_payment::{Module, Call, Storage, Event<T>},
 	}
@@ -657,6 +731,7 @@ pub type SignedExtra = (
 	frame_system::CheckNonce<Runtime>,
 	frame_system::CheckWeight<Runtime>,
 	pallet_transaction_payment::ChargeTransactionPayment<Runtime>,
+	pallet_exchange::signed_extension::RejectObviouslyInvalidExchangeCall<Runtime>,
 );
 /// Unchecked extrinsic type as expected by this runtime.
 pub type UncheckedExtrinsic = generic::UncheckedExtrinsic<Address, Call, Signature, SignedExtra>;
@@ -892,6 +967,64 @@ impl_runtime_apis! {
 
 	}
 
+	impl exchange_rpc::ExchangeApi<
+		Block,
+		AccountId,
+		AssetId,
+		Balance,
+		Hash,
+		pallet_exchange::Status,
+		BlockNumber,
+	> for Runtime {
+		fn pair_account(asset_a: AssetId, asset_b: AssetId) -> AccountId {
+			Exchange::pair_account(asset_a, asset_b)
+		}
+
+		fn collected_fees(asset_a: AssetId, asset_b: AssetId) -> Balance {
+			Exchange::collected_fees(asset_a, asset_b)
+		}
+
+		fn reserved_balance(who: AccountId, asset: AssetId) -> Balance {
+			Exchange::reserved_balance(who, asset)
+		}
+
+		fn intention_status(intention_id: Hash) -> Option<pallet_exchange::Status> {
+			Exchange::intention_status(intention_id)
+		}
+
+		fn spot_price(asset_a: AssetId, asset_b: AssetId) -> Option<Balance> {
+			Exchange::spot_price(asset_a, asset_b)
+		}
+
+		fn quote_buy(asset_buy: AssetId, asset_sell: AssetId, amount_out: Balance) -> Option<Balance> {
+			Exchange::quote_buy(asset_buy, asset_sell, amount_out)
+		}
+
+		fn last_price(asset_a: AssetId, asset_b: AssetId) -> Option<(Balance, BlockNumber)> {
+			Exchange::last_price(asset_a, asset_b)
+		}
+
+		fn next_intention_id(who: AccountId, asset_sell: AssetId, asset_buy: AssetId) -> Hash {
+			Exchange::get_next_intention_id(&who, asset_sell, asset_buy)
+		}
+
+		fn active_pairs() -> Vec<(AssetId, AssetId, u32)> {
+			Exchange::active_pairs()
+		}
+
+		fn last_block_fills() -> Vec<FillRecord<Hash, AssetId, Balance>> {
+			Exchange::last_block_fills()
+		}
+
+		fn asset_volume(asset: AssetId) -> Balance {
+			Exchange::asset_volume(asset)
+		}
+
+		fn account_settlements(who: AccountId) -> Vec<primitives::SettlementRecord<Hash, Balance>> {
+			Exchange::account_settlements(who)
+		}
+	}
+
 	#[cfg(feature = "runtime-benchmarks")]
 	impl frame_benchmarking::Benchmark<Block> for Runtime {
 		fn dispatch_benchmark(